@@ -15,9 +15,21 @@ impl AppState {
         let mac = Arc::new(MacService::new()?);
         let hotkey = Arc::new(HotkeyService::new()?);
         hotkey.initialize(app)?;
+        // schtasks 查询较慢，放到后台，不拖慢启动
+        let verifier = mac.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = verifier.verify_auto_restore_task() {
+                log::warn!("校验开机自动还原任务失败: {}", err);
+            }
+        });
         Ok(Self { hotkey, mac })
     }
 
+    /// Explicit teardown on app exit; managed state isn't guaranteed to drop
+    pub fn shutdown(&self, app: &AppHandle) {
+        self.hotkey.shutdown(app);
+    }
+
     pub fn hotkey(&self) -> Arc<HotkeyService> {
         self.hotkey.clone()
     }
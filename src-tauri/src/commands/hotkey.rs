@@ -4,7 +4,13 @@ use tauri::{command, AppHandle};
 
 use crate::app_state::AppState;
 use crate::error::{AppError, AppResult};
-use crate::services::hotkey::{HotkeyConfig, HotkeyStatus};
+use crate::services::hotkey::keymap::{self, KeyGroup};
+use crate::services::hotkey::process::{self, ProcessInfo};
+use crate::services::hotkey::window::WindowFilter;
+use crate::services::hotkey::{
+    HotkeyConfig, HotkeyStatus, InterceptionDevice, ListenerHealth, ListenerStats, MacroInfo,
+    RunnerTransition, SavedConfigInfo, SessionLogInfo,
+};
 
 #[cfg(target_os = "windows")]
 use crate::services::hotkey::window::WindowInfo;
@@ -23,39 +29,233 @@ pub fn get_hotkey_status(state: tauri::State<AppState>) -> HotkeyStatus {
     state.hotkey().get_status()
 }
 
-/// Save hotkey configuration and register shortcuts
+/// 查询开始/结束热键是否仍在生效，用于提示用户重新注册
+#[command]
+pub fn get_listener_health(app: AppHandle, state: tauri::State<AppState>) -> ListenerHealth {
+    log::debug!("Command: get_listener_health");
+    state.hotkey().listener_health(&app)
+}
+
+/// 自上次注册以来各热键收到/丢弃的按下次数，用于排查"按键不灵"
+#[command]
+pub fn get_listener_stats(state: tauri::State<AppState>) -> ListenerStats {
+    log::debug!("Command: get_listener_stats");
+    state.hotkey().listener_stats()
+}
+
+/// Save hotkey configuration and register shortcuts; reports how the key
+/// labels were resolved
 #[command]
 pub fn save_hotkey_config(
     app: AppHandle,
     state: tauri::State<AppState>,
     config: HotkeyConfig,
-) -> AppResult<HotkeyConfig> {
+) -> AppResult<SavedConfigInfo> {
     log::debug!("Command: save_hotkey_config({:?})", config);
     state.hotkey().save_config(&app, config)
 }
 
+/// Change only the start/stop hotkeys (omitted = unchanged) on the saved
+/// config, without round-tripping the whole config
+#[command]
+pub fn update_hotkeys(
+    app: AppHandle,
+    state: tauri::State<AppState>,
+    start: Option<String>,
+    stop: Option<String>,
+) -> AppResult<SavedConfigInfo> {
+    log::debug!("Command: update_hotkeys({:?}, {:?})", start, stop);
+    state.hotkey().update_hotkeys(&app, start, stop)
+}
+
+/// 按当前配置重新注册开始/结束热键（不重新保存配置）
+#[command]
+pub fn reregister_hotkeys(
+    app: AppHandle,
+    state: tauri::State<AppState>,
+) -> AppResult<HotkeyStatus> {
+    log::debug!("Command: reregister_hotkeys");
+    let service = state.hotkey();
+    service.reregister(&app)?;
+    Ok(service.get_status())
+}
+
+/// 暂停/恢复热键捕获（不改配置）：暂停时开始/结束键照常输入，紧急停止仍有效
+#[command]
+pub fn set_listener_enabled(
+    app: AppHandle,
+    state: tauri::State<AppState>,
+    enabled: bool,
+) -> AppResult<HotkeyStatus> {
+    log::debug!("Command: set_listener_enabled({})", enabled);
+    let service = state.hotkey();
+    service.set_listener_enabled(&app, enabled)?;
+    Ok(service.get_status())
+}
+
+/// 清除状态里的错误提示（前端关闭错误横幅），不影响配置与注册
+#[command]
+pub fn clear_hotkey_error(app: AppHandle, state: tauri::State<AppState>) -> HotkeyStatus {
+    log::debug!("Command: clear_hotkey_error");
+    let service = state.hotkey();
+    service.update_status(&app, |status| status.last_error = None);
+    service.get_status()
+}
+
+/// Start the hotkey automation task, same as pressing the start hotkey
+#[command]
+pub fn start_hotkey_task(
+    app: AppHandle,
+    state: tauri::State<AppState>,
+) -> AppResult<RunnerTransition> {
+    log::debug!("Command: start_hotkey_task");
+    state.hotkey().start_runner(&app)
+}
+
 /// Stop the running hotkey automation task
 #[command]
-pub fn stop_hotkey_task(app: AppHandle, state: tauri::State<AppState>) {
+pub fn stop_hotkey_task(app: AppHandle, state: tauri::State<AppState>) -> RunnerTransition {
     log::debug!("Command: stop_hotkey_task");
-    state.hotkey().stop_runner(&app);
+    state.hotkey().stop_runner(&app)
+}
+
+/// 按当前配置发送一次触发按键，用于开跑前确认游戏能收到按键
+#[command]
+pub fn test_trigger_key(state: tauri::State<AppState>) -> AppResult<()> {
+    log::debug!("Command: test_trigger_key");
+    state.hotkey().test_trigger_key()
+}
+
+/// 向指定窗口发送一次按键（PostMessage），确认游戏能收到窗口模式的按键
+#[command]
+pub fn test_window_send(state: tauri::State<AppState>, hwnd: u64, key: String) -> AppResult<()> {
+    log::debug!("Command: test_window_send(0x{:X}, {})", hwnd, key);
+    state.hotkey().test_window_send(hwnd, &key)
+}
+
+/// 列出已打开的 Interception 键盘设备及其硬件 ID，用于选择注入设备（仅 Windows）
+#[command]
+pub fn list_interception_devices(
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<InterceptionDevice>> {
+    log::debug!("Command: list_interception_devices");
+    state.hotkey().list_interception_devices()
+}
+
+/// 开始录制按键宏 `name`（仅 Windows）
+#[command]
+pub fn start_macro_record(state: tauri::State<AppState>, name: String) -> AppResult<()> {
+    log::debug!("Command: start_macro_record({})", name);
+    state.hotkey().start_macro_record(&name)
+}
+
+/// 停止录制并把录到的按键保存为开始时给出的宏名称
+#[command]
+pub fn stop_macro_record(state: tauri::State<AppState>) -> AppResult<MacroInfo> {
+    log::debug!("Command: stop_macro_record");
+    state.hotkey().stop_macro_record()
+}
+
+/// 列出已保存的按键宏
+#[command]
+pub fn list_macros(state: tauri::State<AppState>) -> AppResult<Vec<MacroInfo>> {
+    log::debug!("Command: list_macros");
+    state.hotkey().list_macros()
+}
+
+/// 删除按键宏；返回文件是否存在
+#[command]
+pub fn delete_macro(state: tauri::State<AppState>, name: String) -> AppResult<bool> {
+    log::debug!("Command: delete_macro({})", name);
+    state.hotkey().delete_macro(&name)
+}
+
+/// 列出自动按键会话日志（最新在前）
+#[command]
+pub fn list_session_logs(state: tauri::State<AppState>) -> AppResult<Vec<SessionLogInfo>> {
+    log::debug!("Command: list_session_logs");
+    state.hotkey().list_session_logs()
+}
+
+/// 把全部会话日志汇总为 CSV（开始时间、时长、发送次数、APM、发送方式）；
+/// 返回导出的会话数
+#[command]
+pub async fn export_session_stats(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> AppResult<usize> {
+    log::debug!("Command: export_session_stats({})", path);
+    let hotkey = state.hotkey();
+    tauri::async_runtime::spawn_blocking(move || {
+        hotkey.export_session_stats(std::path::Path::new(path.trim()))
+    })
+    .await
+    .map_err(|e| AppError::Hotkey(format!("后台任务执行失败: {e}")))?
+}
+
+/// 在系统文件管理器中打开会话日志目录（不存在则先创建）
+#[command]
+pub fn open_session_log_dir(state: tauri::State<AppState>) -> AppResult<()> {
+    log::debug!("Command: open_session_log_dir");
+    let dir = state.hotkey().session_log_dir();
+    std::fs::create_dir_all(&dir)?;
+    super::open_folder(&dir.to_string_lossy())
+}
+
+/// JSON Schema of `HotkeyConfig` (wire format) for external config tooling
+#[command]
+pub fn hotkey_config_schema() -> String {
+    log::debug!("Command: hotkey_config_schema");
+    crate::services::hotkey::config_schema()
+}
+
+/// Trigger-key labels the backend accepts, grouped for the picker
+/// (letters / digits / function / numpad / special, plus user overrides)
+#[command]
+pub fn supported_trigger_keys() -> Vec<KeyGroup> {
+    log::debug!("Command: supported_trigger_keys");
+    keymap::supported_key_groups()
 }
 
 /// 获取可见窗口列表（仅 Windows）
 #[cfg(target_os = "windows")]
 #[command]
-pub fn list_windows(filter: Option<String>) -> AppResult<Vec<WindowInfo>> {
+pub fn list_windows(filter: Option<WindowFilter>) -> AppResult<Vec<WindowInfo>> {
     log::debug!("Command: list_windows(filter={:?})", filter);
-    crate::services::hotkey::window::enumerate_windows(filter.as_deref())
+    crate::services::hotkey::window::enumerate_windows(&filter.unwrap_or_default())
 }
 
 /// 获取可见窗口列表（非 Windows 平台）
 #[cfg(not(target_os = "windows"))]
 #[command]
-pub fn list_windows(_filter: Option<String>) -> AppResult<Vec<()>> {
+pub fn list_windows(_filter: Option<WindowFilter>) -> AppResult<Vec<()>> {
     Ok(vec![])
 }
 
+/// 按窗口类名查找第一个可见窗口（仅 Windows），供界面自动选中游戏窗口
+#[cfg(target_os = "windows")]
+#[command]
+pub fn find_window_by_class(class_name: String) -> AppResult<Option<WindowInfo>> {
+    log::debug!("Command: find_window_by_class(class_name={})", class_name);
+    crate::services::hotkey::window::find_window_by_class(&class_name, None)
+}
+
+/// 按窗口类名查找窗口（非 Windows 平台）
+#[cfg(not(target_os = "windows"))]
+#[command]
+pub fn find_window_by_class(_class_name: String) -> AppResult<Option<()>> {
+    Ok(None)
+}
+
+/// 按可执行文件名列出正在运行的游戏进程（仅 Windows），多开时比窗口标题可靠
+#[command]
+pub async fn list_game_processes(names: Vec<String>) -> AppResult<Vec<ProcessInfo>> {
+    log::debug!("Command: list_game_processes(names={:?})", names);
+    tauri::async_runtime::spawn_blocking(move || process::list_game_processes(&names))
+        .await
+        .map_err(|e| AppError::Hotkey(format!("后台任务执行失败: {e}")))?
+}
+
 /// 检查窗口是否仍然有效
 #[cfg(target_os = "windows")]
 #[command]
@@ -1,27 +1,90 @@
 //! Keyboard configuration commands
 
 use std::process::Command;
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
 
 use crate::error::{validate_path_not_empty, AppError, AppResult};
-use crate::services::keyboard::{CopyParams, FileEntry, KeyboardService};
+use crate::services::game_dirs;
+use crate::services::keyboard::{
+    CopyOutcome, CopyParams, CopyPreview, DirDiff, FileEntry, KeyboardService, ListOptions,
+    RoleEntry, TargetCopyResult,
+};
 use crate::services::plugin_data::{PluginDataService, PluginSyncReport};
 
 /// List directory contents for keyboard configuration
+/// (`options` omitted = current client layout)
 ///
 /// async + spawn_blocking：同步命令会在主线程执行，递归磁盘 IO 会卡 UI
 #[command]
-pub async fn list_directory_contents(path: String) -> AppResult<Vec<FileEntry>> {
-    log::debug!("Command: list_directory_contents({})", path);
+pub async fn list_directory_contents(
+    path: String,
+    options: Option<ListOptions>,
+) -> AppResult<Vec<FileEntry>> {
+    log::debug!("Command: list_directory_contents({}, {:?})", path, options);
     validate_path_not_empty(&path, "path")?;
-    tauri::async_runtime::spawn_blocking(move || KeyboardService::list_directory_contents(&path))
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        KeyboardService::list_directory_contents(&path, &options)
+    })
+    .await
+    .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Flat list of every role under `path` (same `options` as list_directory_contents)
+#[command]
+pub async fn list_roles(path: String, options: Option<ListOptions>) -> AppResult<Vec<RoleEntry>> {
+    log::debug!("Command: list_roles({}, {:?})", path, options);
+    validate_path_not_empty(&path, "path")?;
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::list_roles(&path, &options))
+        .await
+        .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Candidate userdata dirs of local JX3 installs, for the directory picker.
+/// Empty when nothing is found, and always empty outside Windows
+#[command]
+pub async fn detect_jx3_keybind_dirs() -> AppResult<Vec<String>> {
+    log::debug!("Command: detect_jx3_keybind_dirs");
+    tauri::async_runtime::spawn_blocking(game_dirs::detect_keybind_dirs)
+        .await
+        .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))
+}
+
+/// 键位复制进度事件名（前端 listen 此事件驱动进度条）
+pub const KEYBOARD_COPY_PROGRESS_EVENT: &str = "keyboard://copy-progress";
+
+/// Dry-run preview of a keyboard copy (counts only, nothing is modified)
+#[command]
+pub async fn preview_copy(params: CopyParams) -> AppResult<CopyPreview> {
+    log::debug!(
+        "Command: preview_copy({} -> {})",
+        params.source_path,
+        params.target_path
+    );
+    validate_path_not_empty(&params.source_path, "source_path")?;
+    validate_path_not_empty(&params.target_path, "target_path")?;
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::preview_copy(&params))
+        .await
+        .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// File-level diff of two keyboard config dirs (only-in-a / only-in-b /
+/// content differs), so the UI can show what a copy would actually change
+#[command]
+pub async fn diff_keyboard_dirs(a: String, b: String) -> AppResult<DirDiff> {
+    log::debug!("Command: diff_keyboard_dirs({} <-> {})", a, b);
+    validate_path_not_empty(&a, "a")?;
+    validate_path_not_empty(&b, "b")?;
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::diff_dirs(&a, &b))
         .await
         .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
 }
 
-/// Copy keyboard configuration from source to target
+/// Copy keyboard configuration from source to target, emitting per-file
+/// progress; the outcome records whether it replaced or merged
 #[command]
-pub async fn cp_source_to_target(params: CopyParams) -> AppResult<bool> {
+pub async fn cp_source_to_target(app: AppHandle, params: CopyParams) -> AppResult<CopyOutcome> {
     log::debug!(
         "Command: cp_source_to_target({} -> {})",
         params.source_path,
@@ -29,7 +92,148 @@ pub async fn cp_source_to_target(params: CopyParams) -> AppResult<bool> {
     );
     validate_path_not_empty(&params.source_path, "source_path")?;
     validate_path_not_empty(&params.target_path, "target_path")?;
-    tauri::async_runtime::spawn_blocking(move || KeyboardService::copy_source_to_target(&params))
+    tauri::async_runtime::spawn_blocking(move || {
+        KeyboardService::copy_source_to_target(&params, &|p| {
+            let _ = app.emit(KEYBOARD_COPY_PROGRESS_EVENT, p);
+        })
+    })
+    .await
+    .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Copy one role into the same account/server/role location under
+/// `target_base`, returning the target role path
+#[command]
+pub async fn copy_role(
+    app: AppHandle,
+    source_role_path: String,
+    target_base: String,
+    verify: Option<bool>,
+) -> AppResult<String> {
+    log::debug!(
+        "Command: copy_role({} -> {}, verify={:?})",
+        source_role_path,
+        target_base,
+        verify
+    );
+    validate_path_not_empty(&source_role_path, "source_role_path")?;
+    validate_path_not_empty(&target_base, "target_base")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        KeyboardService::copy_role(
+            &source_role_path,
+            &target_base,
+            verify.unwrap_or(false),
+            &|p| {
+                let _ = app.emit(KEYBOARD_COPY_PROGRESS_EVENT, p);
+            },
+        )
+    })
+    .await
+    .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Copy one source into several targets, returning a result per target.
+/// `stop_on_error` (default false) ends the run at the first failed target;
+/// `verify` (default false) checks each copy before it replaces the target.
+/// Progress events are emitted per target, restarting from 0 for each.
+#[command]
+pub async fn cp_source_to_targets(
+    app: AppHandle,
+    source_path: String,
+    target_paths: Vec<String>,
+    stop_on_error: Option<bool>,
+    verify: Option<bool>,
+) -> AppResult<Vec<TargetCopyResult>> {
+    log::debug!(
+        "Command: cp_source_to_targets({} -> {:?}, stop_on_error={:?}, verify={:?})",
+        source_path,
+        target_paths,
+        stop_on_error,
+        verify
+    );
+    validate_path_not_empty(&source_path, "source_path")?;
+    for target_path in &target_paths {
+        validate_path_not_empty(target_path, "target_paths")?;
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        KeyboardService::copy_source_to_targets(
+            &source_path,
+            &target_paths,
+            stop_on_error.unwrap_or(false),
+            verify.unwrap_or(false),
+            &|p| {
+                let _ = app.emit(KEYBOARD_COPY_PROGRESS_EVENT, p);
+            },
+        )
+    })
+    .await
+    .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Copy only the selected subtrees (ids from list_directory_contents, listed
+/// with the same `options`; omitted = current client layout)
+#[command]
+pub async fn copy_selected_entries(
+    params: CopyParams,
+    selected_ids: Vec<u64>,
+    options: Option<ListOptions>,
+) -> AppResult<Vec<String>> {
+    log::debug!(
+        "Command: copy_selected_entries({} -> {}, {} 项, {:?})",
+        params.source_path,
+        params.target_path,
+        selected_ids.len(),
+        options
+    );
+    validate_path_not_empty(&params.source_path, "source_path")?;
+    validate_path_not_empty(&params.target_path, "target_path")?;
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        KeyboardService::copy_selected_entries(&params, &selected_ids, &options)
+    })
+    .await
+    .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// List backups kept by keyboard copies for `target_path`, newest first
+#[command]
+pub async fn list_keyboard_backups(target_path: String) -> AppResult<Vec<String>> {
+    log::debug!("Command: list_keyboard_backups({})", target_path);
+    validate_path_not_empty(&target_path, "target_path")?;
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::list_backups(&target_path))
+        .await
+        .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Roll a role's keybinds back to a backup (current content is backed up first)
+#[command]
+pub async fn restore_keyboard_backup(path: String) -> AppResult<()> {
+    log::debug!("Command: restore_keyboard_backup({})", path);
+    validate_path_not_empty(&path, "path")?;
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::restore_backup(&path))
+        .await
+        .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Zip a keyboard config dir into `out` for sharing; returns the file count
+#[command]
+pub async fn export_keyboard_zip(source: String, out: String) -> AppResult<usize> {
+    log::debug!("Command: export_keyboard_zip({} -> {})", source, out);
+    validate_path_not_empty(&source, "source")?;
+    validate_path_not_empty(&out, "out")?;
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::export_zip(&source, &out))
+        .await
+        .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
+}
+
+/// Extract a shared keyboard zip into `target` (old content is backed up);
+/// returns the file count
+#[command]
+pub async fn import_keyboard_zip(archive: String, target: String) -> AppResult<usize> {
+    log::debug!("Command: import_keyboard_zip({} -> {})", archive, target);
+    validate_path_not_empty(&archive, "archive")?;
+    validate_path_not_empty(&target, "target")?;
+    tauri::async_runtime::spawn_blocking(move || KeyboardService::import_zip(&archive, &target))
         .await
         .map_err(|e| AppError::Keyboard(format!("后台任务执行失败: {e}")))?
 }
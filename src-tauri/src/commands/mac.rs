@@ -3,42 +3,172 @@
 //! async + spawn_blocking：PowerShell/schtasks 调用耗时数百毫秒到十几秒
 //! （改 MAC 含网卡重启与回读验证），不能阻塞主线程
 
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
 
 use crate::app_state::AppState;
 use crate::error::{AppError, AppResult};
-use crate::services::mac::MacInfo;
+use crate::services::mac::{
+    AdapterInfo, MacCase, MacChangeResult, MacFormat, MacHistoryEntry, MacInfo, MacStatus,
+    RestoreTrigger,
+};
 
-/// Get the primary adapter's MAC info
+/// 网卡重启阶段事件名（disabling → waiting → enabling → up/failed）
+pub const MAC_ADAPTER_RESTART_EVENT: &str = "mac://adapter-restart";
+
+/// Get the selected (or auto-picked) adapter's MAC info. `format`/`case`
+/// pick the output style (default `XX:XX:XX:XX:XX:XX`)
+#[command]
+pub async fn get_mac_info(
+    state: tauri::State<'_, AppState>,
+    format: Option<MacFormat>,
+    case: Option<MacCase>,
+) -> AppResult<MacInfo> {
+    log::debug!("Command: get_mac_info({:?}, {:?})", format, case);
+    let mac = state.mac();
+    let info = tauri::async_runtime::spawn_blocking(move || mac.get_mac_info())
+        .await
+        .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))??;
+    Ok(info.rendered(format.unwrap_or_default(), case.unwrap_or_default()))
+}
+
+/// Current vs. original MAC of the selected adapter (is a spoof active?).
+/// `format`/`case` pick the output style (default `XX:XX:XX:XX:XX:XX`)
 #[command]
-pub async fn get_mac_info(state: tauri::State<'_, AppState>) -> AppResult<MacInfo> {
-    log::debug!("Command: get_mac_info");
+pub async fn get_mac_status(
+    state: tauri::State<'_, AppState>,
+    format: Option<MacFormat>,
+    case: Option<MacCase>,
+) -> AppResult<MacStatus> {
+    log::debug!("Command: get_mac_status({:?}, {:?})", format, case);
+    let mac = state.mac();
+    let status = tauri::async_runtime::spawn_blocking(move || mac.get_mac_status())
+        .await
+        .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))??;
+    Ok(status.rendered(format.unwrap_or_default(), case.unwrap_or_default()))
+}
+
+/// List every physical adapter, flagging the persisted selection
+#[command]
+pub async fn list_network_adapters(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<AdapterInfo>> {
+    log::debug!("Command: list_network_adapters");
     let mac = state.mac();
-    tauri::async_runtime::spawn_blocking(move || mac.get_mac_info())
+    tauri::async_runtime::spawn_blocking(move || mac.list_adapters())
         .await
         .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
 }
 
-/// Change the MAC address to a random value, verified against the driver
+/// Persist the adapter to operate on (None = back to auto-pick)
 #[command]
-pub async fn randomize_mac_address(state: tauri::State<'_, AppState>) -> AppResult<MacInfo> {
-    log::debug!("Command: randomize_mac_address");
+pub fn select_network_adapter(
+    state: tauri::State<AppState>,
+    adapter_guid: Option<String>,
+) -> AppResult<Option<String>> {
+    log::debug!("Command: select_network_adapter({:?})", adapter_guid);
+    state.mac().select_adapter(adapter_guid.as_deref())
+}
+
+/// Change the MAC address to a random value, verified against the driver.
+/// An explicit `adapter_guid` becomes the persisted selection; `soft_apply`
+/// skips the adapter restart (takes effect on reconnect/reboot).
+#[command]
+pub async fn randomize_mac_address(
+    state: tauri::State<'_, AppState>,
+    adapter_guid: Option<String>,
+    soft_apply: Option<bool>,
+) -> AppResult<MacChangeResult> {
+    log::debug!(
+        "Command: randomize_mac_address({:?}, soft_apply={:?})",
+        adapter_guid,
+        soft_apply
+    );
     let mac = state.mac();
-    tauri::async_runtime::spawn_blocking(move || mac.randomize_mac_address())
+    let soft_apply = soft_apply.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        mac.randomize_mac_address(adapter_guid.as_deref(), soft_apply)
+    })
+    .await
+    .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
+}
+
+/// Restart an adapter (the given one, else the selection) in stages, emitting
+/// `mac://adapter-restart` per stage, and verify it came back `Up`
+#[command]
+pub async fn restart_network_adapter(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    adapter_guid: Option<String>,
+) -> AppResult<MacInfo> {
+    log::debug!("Command: restart_network_adapter({:?})", adapter_guid);
+    let mac = state.mac();
+    tauri::async_runtime::spawn_blocking(move || {
+        mac.restart_adapter(adapter_guid.as_deref(), &|p| {
+            let _ = app.emit(MAC_ADAPTER_RESTART_EVENT, p);
+        })
+    })
+    .await
+    .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
+}
+
+/// Restore the original MAC address (only `adapter_guid` if given, else all adapters)
+#[command]
+pub async fn restore_mac_cmd(
+    state: tauri::State<'_, AppState>,
+    adapter_guid: Option<String>,
+) -> AppResult<MacInfo> {
+    log::debug!("Command: restore_mac_cmd({:?})", adapter_guid);
+    let mac = state.mac();
+    tauri::async_runtime::spawn_blocking(move || mac.restore_mac_address(adapter_guid.as_deref()))
         .await
         .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
 }
 
-/// Restore the original MAC address
+/// History of applied MACs, most recent first
+#[command]
+pub fn get_mac_history(state: tauri::State<AppState>) -> AppResult<Vec<MacHistoryEntry>> {
+    log::debug!("Command: get_mac_history");
+    state.mac().get_mac_history()
+}
+
+/// Re-apply a MAC from the history (`index` 0 = most recent), verified against the driver
+/// unless `soft_apply`
 #[command]
-pub async fn restore_mac_cmd(state: tauri::State<'_, AppState>) -> AppResult<MacInfo> {
-    log::debug!("Command: restore_mac_cmd");
+pub async fn apply_mac_from_history(
+    state: tauri::State<'_, AppState>,
+    index: usize,
+    soft_apply: Option<bool>,
+) -> AppResult<MacChangeResult> {
+    log::debug!(
+        "Command: apply_mac_from_history({}, soft_apply={:?})",
+        index,
+        soft_apply
+    );
     let mac = state.mac();
-    tauri::async_runtime::spawn_blocking(move || mac.restore_mac_address())
+    let soft_apply = soft_apply.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || mac.apply_mac_from_history(index, soft_apply))
         .await
         .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
 }
 
+/// Generate a random MAC for preview (optionally keeping a vendor OUI prefix)
+#[command]
+pub fn generate_random_mac_cmd(
+    state: tauri::State<AppState>,
+    oui: Option<String>,
+) -> AppResult<String> {
+    log::debug!("Command: generate_random_mac_cmd({:?})", oui);
+    state.mac().generate_random_mac(oui.as_deref())
+}
+
+/// Validate a MAC as the user types, without touching any adapter; returns
+/// the canonical `XX:XX:XX:XX:XX:XX` form
+#[command]
+pub fn validate_mac(state: tauri::State<AppState>, mac: String) -> AppResult<String> {
+    log::debug!("Command: validate_mac({})", mac);
+    state.mac().validate_mac(&mac)
+}
+
 /// Get the auto-restore on reboot setting (scheduled task existence)
 #[command]
 pub async fn get_auto_restore_setting(state: tauri::State<'_, AppState>) -> AppResult<bool> {
@@ -49,15 +179,59 @@ pub async fn get_auto_restore_setting(state: tauri::State<'_, AppState>) -> AppR
         .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
 }
 
-/// Set the auto-restore on reboot setting
+/// Re-check the auto-restore task against the current executable (repairs drift)
+#[command]
+pub async fn verify_auto_restore_task(state: tauri::State<'_, AppState>) -> AppResult<bool> {
+    log::debug!("Command: verify_auto_restore_task");
+    let mac = state.mac();
+    tauri::async_runtime::spawn_blocking(move || mac.verify_auto_restore_task())
+        .await
+        .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
+}
+
+/// Persisted trigger of the auto-restore task (logon or startup)
+#[command]
+pub fn get_auto_restore_trigger(state: tauri::State<AppState>) -> RestoreTrigger {
+    log::debug!("Command: get_auto_restore_trigger");
+    state.mac().restore_trigger()
+}
+
+/// Set the auto-restore on reboot setting. `trigger` picks logon vs. startup
+/// and is remembered; omitted keeps the previous choice (default: logon)
 #[command]
 pub async fn set_auto_restore_setting(
     state: tauri::State<'_, AppState>,
     auto_restore: bool,
+    trigger: Option<RestoreTrigger>,
 ) -> AppResult<()> {
-    log::debug!("Command: set_auto_restore_setting({})", auto_restore);
+    log::debug!(
+        "Command: set_auto_restore_setting({}, trigger={:?})",
+        auto_restore,
+        trigger
+    );
     let mac = state.mac();
-    tauri::async_runtime::spawn_blocking(move || mac.set_auto_restore_setting(auto_restore))
+    tauri::async_runtime::spawn_blocking(move || {
+        mac.set_auto_restore_setting(auto_restore, trigger)
+    })
+    .await
+    .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
+}
+
+/// Re-launch the app elevated after a `PermissionDenied` MAC failure, then
+/// exit this instance. Returns `false` (and keeps running) off Windows
+#[command]
+pub async fn relaunch_as_admin(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<bool> {
+    log::debug!("Command: relaunch_as_admin");
+    let mac = state.mac();
+    // UAC 提示期间 ShellExecuteW 一直阻塞
+    let relaunched = tauri::async_runtime::spawn_blocking(move || mac.relaunch_as_admin())
         .await
-        .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))?
+        .map_err(|e| AppError::Command(format!("后台任务执行失败: {e}")))??;
+    if relaunched {
+        app.exit(0);
+    }
+    Ok(relaunched)
 }
@@ -2,8 +2,10 @@ mod cloud;
 mod hotkey;
 mod keyboard;
 mod mac;
+mod settings;
 
 pub use cloud::*;
 pub use hotkey::*;
 pub use keyboard::*;
 pub use mac::*;
+pub use settings::*;
@@ -0,0 +1,171 @@
+//! Application-wide settings commands
+
+use std::path::PathBuf;
+
+use tauri::{command, AppHandle, Manager};
+
+use crate::app_state::AppState;
+use crate::error::{AppError, AppResult};
+use crate::fs_util::app_config_dir;
+use crate::services::diagnostics::{self, Diagnostics};
+use crate::services::hotkey::{CleanupReport, CONFIG_FILE_NAME};
+use crate::services::settings::{self as app_settings, AppSettings, LogLevel, ResetSummary};
+
+/// Load app settings (defaults when the file doesn't exist yet)
+#[command]
+pub async fn get_settings() -> AppResult<AppSettings> {
+    log::debug!("Command: get_settings");
+    tauri::async_runtime::spawn_blocking(app_settings::load_settings)
+        .await
+        .map_err(|e| AppError::Config(format!("后台任务执行失败: {e}")))?
+}
+
+/// Validate and persist app settings
+#[command]
+pub async fn save_settings(settings: AppSettings) -> AppResult<()> {
+    log::debug!("Command: save_settings({:?})", settings);
+    tauri::async_runtime::spawn_blocking(move || {
+        app_settings::save_settings(&settings)?;
+        app_settings::apply_runtime(&settings);
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Config(format!("后台任务执行失败: {e}")))?
+}
+
+/// Change the log level at runtime and persist it (error/warn/info/debug/trace)
+#[command]
+pub async fn set_log_level(level: String) -> AppResult<()> {
+    log::debug!("Command: set_log_level({})", level);
+    let level: LogLevel = level.parse()?;
+    tauri::async_runtime::spawn_blocking(move || app_settings::set_log_level(level))
+        .await
+        .map_err(|e| AppError::Config(format!("后台任务执行失败: {e}")))?
+}
+
+/// Reset every config file to defaults for troubleshooting: stops the hotkey
+/// runner, deletes the config files and the auto-restore task, reloads the
+/// default hotkey config and emits a fresh status. Idempotent.
+#[command]
+pub async fn reset_all_config(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ResetSummary> {
+    log::debug!("Command: reset_all_config");
+    let hotkey = state.hotkey();
+    let mac = state.mac();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut summary = ResetSummary::default();
+        if hotkey.reset_config(&app)? {
+            summary.removed_files.push(CONFIG_FILE_NAME.to_string());
+        }
+        summary.removed_files.extend(mac.reset_config()?);
+        if app_settings::remove_settings()? {
+            summary.removed_files.push("settings.json".to_string());
+        }
+        if mac.get_auto_restore_setting()? {
+            mac.set_auto_restore_setting(false, None)?;
+            summary.auto_restore_task_removed = true;
+        }
+        log::info!("已重置全部配置: {:?}", summary);
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| AppError::Config(format!("后台任务执行失败: {e}")))?
+}
+
+/// Delete old hotkey session logs, keyboard `.bak-*` backup dirs under
+/// `keyboard_base_path` (the userdata dir; omitted = skipped) and
+/// corrupt-config backups: keeps the newest `keep_sessions` / `keep_backups`
+/// (backups per backed-up directory) and drops anything older than
+/// `max_age_days` (0 = no age limit). Active config files and role
+/// directories are never touched.
+#[command]
+pub async fn cleanup_app_data(
+    state: tauri::State<'_, AppState>,
+    keyboard_base_path: Option<String>,
+    keep_sessions: usize,
+    keep_backups: usize,
+    max_age_days: u64,
+) -> AppResult<CleanupReport> {
+    log::debug!(
+        "Command: cleanup_app_data({:?}, {}, {}, {})",
+        keyboard_base_path,
+        keep_sessions,
+        keep_backups,
+        max_age_days
+    );
+    let keyboard_base = keyboard_base_path
+        .map(|path| PathBuf::from(path.trim()))
+        .filter(|path| !path.as_os_str().is_empty());
+    if let Some(base) = &keyboard_base {
+        if !base.is_dir() {
+            return Err(AppError::validation(
+                "keyboard_base_path",
+                format!("键位目录不存在: {}", base.display()),
+            ));
+        }
+    }
+    let hotkey = state.hotkey();
+    tauri::async_runtime::spawn_blocking(move || {
+        hotkey.cleanup_app_data(
+            keyboard_base.as_deref(),
+            keep_sessions,
+            keep_backups,
+            max_age_days,
+        )
+    })
+    .await
+    .map_err(|e| AppError::Config(format!("后台任务执行失败: {e}")))?
+}
+
+/// Environment readiness report for support (OS, elevation, driver, config
+/// dir, adapter, hotkey config). Sub-checks fail soft inside the report
+#[command]
+pub async fn diagnostics(state: tauri::State<'_, AppState>) -> AppResult<Diagnostics> {
+    log::debug!("Command: diagnostics");
+    let hotkey = state.hotkey();
+    let mac = state.mac();
+    tauri::async_runtime::spawn_blocking(move || diagnostics::collect(&hotkey, &mac))
+        .await
+        .map_err(|e| AppError::Config(format!("后台任务执行失败: {e}")))
+}
+
+/// Whether the app runs elevated (admin/root), so the UI can offer a restart
+/// as admin before MAC/driver operations instead of failing midway. True
+/// where elevation can't be told, so nothing is gated needlessly
+#[command]
+pub fn is_elevated() -> bool {
+    log::debug!("Command: is_elevated");
+    diagnostics::is_elevated().unwrap_or(true)
+}
+
+/// Directory `tauri_plugin_log` writes to (`TargetKind::LogDir`)
+fn log_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    app.path()
+        .app_log_dir()
+        .map_err(|e| AppError::Message(format!("无法获取日志目录: {e}")))
+}
+
+/// 配置目录路径（`JX3_TOOLS_CONFIG_DIR` 可覆盖），方便用户查找、备份配置
+#[command]
+pub fn get_config_dir() -> AppResult<String> {
+    log::debug!("Command: get_config_dir");
+    Ok(app_config_dir()?.to_string_lossy().into_owned())
+}
+
+/// 日志目录路径，供界面展示（反馈问题时附上日志）
+#[command]
+pub fn get_log_dir_path(app: AppHandle) -> AppResult<String> {
+    log::debug!("Command: get_log_dir_path");
+    Ok(log_dir(&app)?.to_string_lossy().into_owned())
+}
+
+/// 在系统文件管理器中打开日志目录（不存在则先创建）
+#[command]
+pub fn open_log_dir(app: AppHandle) -> AppResult<()> {
+    log::debug!("Command: open_log_dir");
+    let dir = log_dir(&app)?;
+    std::fs::create_dir_all(&dir)?;
+    super::open_folder(&dir.to_string_lossy())
+}
@@ -43,6 +43,11 @@ pub enum AppError {
 
     #[error("权限不足: {0}。请以管理员身份运行程序")]
     PermissionDenied(String),
+
+    /// 按键驱动（Interception）未安装/未生效。消息里也带上 code 前缀：
+    /// 写进 `HotkeyStatus::last_error` 的只有字符串，前端据此引导安装驱动
+    #[error("[DRIVER_MISSING] {0}")]
+    DriverMissing(String),
 }
 
 impl AppError {
@@ -74,6 +79,11 @@ impl AppError {
         AppError::PermissionDenied(action.into())
     }
 
+    /// Create a driver missing error
+    pub fn driver_missing<T: Into<String>>(detail: T) -> Self {
+        AppError::DriverMissing(detail.into())
+    }
+
     /// Get error code for frontend handling
     pub fn code(&self) -> &'static str {
         match self {
@@ -89,13 +99,14 @@ impl AppError {
             AppError::Validation { .. } => "VALIDATION_ERROR",
             AppError::PlatformNotSupported(_) => "PLATFORM_NOT_SUPPORTED",
             AppError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AppError::DriverMissing(_) => "DRIVER_MISSING",
         }
     }
 }
 
-/// Structured error response for frontend
+/// Structured error response for frontend; this is what an `AppError`
+/// looks like after crossing IPC
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct ErrorResponse {
     pub code: String,
     pub message: String,
@@ -115,7 +126,7 @@ impl Serialize for AppError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        ErrorResponse::from(self).serialize(serializer)
     }
 }
 
@@ -133,3 +144,24 @@ pub fn validate_path_not_empty(path: &str, field_name: &str) -> AppResult<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_alongside_message() {
+        let json = serde_json::to_value(AppError::validation("macAddress", "格式不正确")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "VALIDATION_ERROR",
+                "message": "验证失败: macAddress - 格式不正确",
+            })
+        );
+
+        let json = serde_json::to_value(AppError::driver_missing("未检测到驱动")).unwrap();
+        assert_eq!(json["code"], "DRIVER_MISSING");
+        assert_eq!(json["message"], "[DRIVER_MISSING] 未检测到驱动");
+    }
+}
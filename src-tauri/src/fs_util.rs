@@ -0,0 +1,227 @@
+//! Crash-safe file persistence shared by every config/state file.
+//!
+//! `fs::write` truncates the target before writing, so a force-quit mid-write
+//! leaves half a JSON document behind. `write_atomic` writes a sibling temp
+//! file, syncs it, then renames it over the target: readers see either the old
+//! file or the new one, never a mix. The temp file lives in the same directory
+//! because a rename is only atomic within one filesystem.
+//!
+//! It also decides where those files live: `app_config_dir` (roaming) and
+//! `app_local_dir` (machine-specific), both redirected by `CONFIG_DIR_ENV`
+//! for portable installs.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::{AppError, AppResult};
+
+/// Env var that moves every config/state file into one directory (portable installs)
+pub const CONFIG_DIR_ENV: &str = "JX3_TOOLS_CONFIG_DIR";
+
+const APP_DIR_NAME: &str = "jx3-tools";
+
+/// `$JX3_TOOLS_CONFIG_DIR`, else `dirs::config_dir()/jx3-tools`. Not created here
+pub fn app_config_dir() -> AppResult<PathBuf> {
+    app_dir(std::env::var_os(CONFIG_DIR_ENV), dirs::config_dir())
+        .ok_or_else(|| AppError::Config("无法获取配置目录".into()))
+}
+
+/// `$JX3_TOOLS_CONFIG_DIR`, else `dirs::data_local_dir()/jx3-tools`, for files
+/// that must not roam to another machine. Not created here
+pub fn app_local_dir() -> AppResult<PathBuf> {
+    app_dir(std::env::var_os(CONFIG_DIR_ENV), dirs::data_local_dir())
+        .ok_or_else(|| AppError::Config("无法获取本机数据目录".into()))
+}
+
+/// A non-empty override wins over `<base>/jx3-tools`
+fn app_dir(override_dir: Option<OsString>, base: Option<PathBuf>) -> Option<PathBuf> {
+    match override_dir.filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => base.map(|base| base.join(APP_DIR_NAME)),
+    }
+}
+
+/// Replace `path` with `contents` atomically
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+fn write_atomic_with<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut File) -> io::Result<()>,
+{
+    let tmp = temp_path(path)?;
+    let result = File::create(&tmp).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    // 目标在 Windows 上也会被替换（MoveFileEx + REPLACE_EXISTING）
+    let result = result.and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Delete `path`, treating an already-missing file as success; returns whether
+/// a file was actually removed
+pub fn remove_if_exists(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Move `old` to `new` unless `new` already exists or `old` is missing;
+/// returns whether a file was moved. Across filesystems the rename fails, so
+/// the contents are rewritten atomically at `new` and `old` is removed after.
+pub fn migrate_file(old: &Path, new: &Path) -> io::Result<bool> {
+    if old == new || new.exists() || !old.is_file() {
+        return Ok(false);
+    }
+    if let Some(parent) = new.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(old, new).is_err() {
+        write_atomic(new, &fs::read(old)?)?;
+        fs::remove_file(old)?;
+    }
+    Ok(true)
+}
+
+/// Numbers the temp files of this process, so concurrent writers of the same
+/// file (two threads saving one config) never share a temp file
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// `.<name>.tmp-<pid>-<n>` next to `path`, unique per call
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("不是文件路径: {}", path.display()),
+        )
+    })?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    Ok(path.with_file_name(tmp_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jx3-atomic-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn config_dir_override_is_honored() {
+        let base = Some(PathBuf::from("/home/u/.config"));
+        assert_eq!(
+            app_dir(None, base.clone()),
+            Some(PathBuf::from("/home/u/.config/jx3-tools"))
+        );
+        assert_eq!(
+            app_dir(Some("".into()), base.clone()),
+            Some(PathBuf::from("/home/u/.config/jx3-tools"))
+        );
+        assert_eq!(
+            app_dir(Some("D:/jx3-tools/data".into()), base),
+            Some(PathBuf::from("D:/jx3-tools/data"))
+        );
+        assert_eq!(app_dir(None, None), None);
+        assert_eq!(
+            app_dir(Some("D:/jx3-tools/data".into()), None),
+            Some(PathBuf::from("D:/jx3-tools/data"))
+        );
+    }
+
+    #[test]
+    fn replaces_existing_file() {
+        let dir = test_dir("replace");
+        let path = dir.join("config.json");
+        write_atomic(&path, b"{\"a\":1}").unwrap();
+        write_atomic(&path, b"{\"a\":2}").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"{\"a\":2}");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn concurrent_writers_use_separate_temp_files() {
+        let path = Path::new("/data/config.json");
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.to_path_buf();
+                std::thread::spawn(move || temp_path(&path).unwrap())
+            })
+            .collect();
+        let names: std::collections::HashSet<PathBuf> = writers
+            .into_iter()
+            .map(|writer| writer.join().unwrap())
+            .collect();
+        assert_eq!(names.len(), 8);
+        assert!(names.iter().all(|tmp| tmp.parent() == path.parent()));
+    }
+
+    #[test]
+    fn remove_if_exists_is_idempotent() {
+        let dir = test_dir("remove");
+        let path = dir.join("config.json");
+        write_atomic(&path, b"{}").unwrap();
+        assert!(remove_if_exists(&path).unwrap());
+        assert!(!remove_if_exists(&path).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn migrate_moves_once_and_never_overwrites() {
+        let dir = test_dir("migrate");
+        let old = dir.join("roaming/state.json");
+        let new = dir.join("local/state.json");
+        // 旧文件不存在时什么也不做
+        assert!(!migrate_file(&old, &new).unwrap());
+
+        fs::create_dir_all(old.parent().unwrap()).unwrap();
+        fs::write(&old, b"old").unwrap();
+        assert!(migrate_file(&old, &new).unwrap());
+        assert!(!old.exists());
+        assert_eq!(fs::read(&new).unwrap(), b"old");
+
+        // 新位置已有文件时保留新文件，旧文件原样留着
+        fs::write(&old, b"stale").unwrap();
+        assert!(!migrate_file(&old, &new).unwrap());
+        assert_eq!(fs::read(&new).unwrap(), b"old");
+        assert!(!migrate_file(&new, &new).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn interrupted_write_keeps_previous_file() {
+        let dir = test_dir("partial");
+        let path = dir.join("config.json");
+        write_atomic(&path, b"{\"good\":true}").unwrap();
+
+        // 写到一半失败，模拟进程被强制结束
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"{\"go")?;
+            Err(io::Error::new(io::ErrorKind::Interrupted, "killed"))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"{\"good\":true}");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
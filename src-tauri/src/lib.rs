@@ -1,7 +1,10 @@
 mod app_state;
 mod commands;
 mod error;
+mod fs_util;
 mod services;
+#[cfg(target_os = "windows")]
+mod win_registry;
 
 use app_state::AppState;
 use commands::*;
@@ -9,13 +12,17 @@ use tauri::Manager;
 use tauri_plugin_log::{Target, TargetKind};
 
 pub use error::AppError;
-pub use services::hotkey::HOTKEY_STATUS_EVENT;
+pub use services::hotkey::{
+    HOTKEY_HEARTBEAT_EVENT, HOTKEY_STARTED_EVENT, HOTKEY_STATUS_EVENT, HOTKEY_STOPPED_EVENT,
+};
 pub use services::mac::MacService;
 
-/// Restore MAC address (called from main.rs for CLI)
-pub fn restore_mac_address() -> error::AppResult<()> {
+/// Restore MAC address (called from main.rs for CLI). `adapter_guid` comes
+/// from the at-startup task, which runs as SYSTEM and can't read the user's
+/// adapter choice; None restores every physical adapter
+pub fn restore_mac_address(adapter_guid: Option<&str>) -> error::AppResult<()> {
     let service = MacService::new()?;
-    service.restore_mac_address().map(|_| ())
+    service.restore_mac_address(adapter_guid).map(|_| ())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -36,8 +43,9 @@ pub fn run() {
     tauri::Builder::default()
         .device_event_filter(tauri::DeviceEventFilter::Never)
         .plugin(
+            // 以 Trace 构建，实际级别由 setup 中的 log::set_max_level 决定（可运行时调整）
             tauri_plugin_log::Builder::default()
-                .level(log::LevelFilter::Info)
+                .level(log::LevelFilter::Trace)
                 .targets([
                     Target::new(TargetKind::LogDir { file_name: None }),
                     Target::new(TargetKind::Stdout),
@@ -47,6 +55,8 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
+            let settings = services::settings::load_settings().unwrap_or_default();
+            services::settings::apply_runtime(&settings);
             let state = match AppState::initialize(&app.handle()) {
                 Ok(state) => state,
                 Err(err) => {
@@ -60,13 +70,35 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // MAC address commands
             get_mac_info,
+            get_mac_status,
+            list_network_adapters,
+            select_network_adapter,
             randomize_mac_address,
             restore_mac_cmd,
+            restart_network_adapter,
+            generate_random_mac_cmd,
+            validate_mac,
+            get_mac_history,
+            apply_mac_from_history,
             get_auto_restore_setting,
+            get_auto_restore_trigger,
             set_auto_restore_setting,
+            relaunch_as_admin,
+            verify_auto_restore_task,
             // Keyboard commands
             list_directory_contents,
+            list_roles,
+            detect_jx3_keybind_dirs,
+            preview_copy,
+            diff_keyboard_dirs,
             cp_source_to_target,
+            cp_source_to_targets,
+            copy_role,
+            copy_selected_entries,
+            list_keyboard_backups,
+            restore_keyboard_backup,
+            export_keyboard_zip,
+            import_keyboard_zip,
             sync_plugin_config,
             open_folder,
             // Cloud sync commands
@@ -76,21 +108,62 @@ pub fn run() {
             cloud_upload_all,
             cloud_list_roles,
             cloud_download_role,
+            // Settings commands
+            get_settings,
+            save_settings,
+            reset_all_config,
+            cleanup_app_data,
+            get_config_dir,
+            diagnostics,
+            is_elevated,
+            get_log_dir_path,
+            open_log_dir,
+            set_log_level,
             // Hotkey commands
             get_hotkey_config,
             get_hotkey_status,
+            get_listener_health,
+            get_listener_stats,
             save_hotkey_config,
+            update_hotkeys,
+            reregister_hotkeys,
+            set_listener_enabled,
+            clear_hotkey_error,
+            supported_trigger_keys,
+            hotkey_config_schema,
+            start_hotkey_task,
             stop_hotkey_task,
+            test_trigger_key,
+            test_window_send,
+            list_interception_devices,
+            start_macro_record,
+            stop_macro_record,
+            list_macros,
+            delete_macro,
+            list_session_logs,
+            export_session_stats,
+            open_session_log_dir,
             list_windows,
+            find_window_by_class,
+            list_game_processes,
             check_window_valid,
             install_hotkey_driver,
             uninstall_hotkey_driver,
             remove_mouse_filter,
         ])
-        .run(tauri::generate_context!())
+        .build(tauri::generate_context!())
         .unwrap_or_else(|err| {
             log::error!("Tauri 应用运行失败: {}", err);
             eprintln!("Tauri 应用运行失败: {}", err);
             std::process::exit(1);
+        })
+        .run(|app, event| {
+            // 退出前显式停止热键任务、注销热键：托管状态不保证及时 drop，
+            // 任务停在按住阶段时按键会一直保持按下
+            if let tauri::RunEvent::Exit = event {
+                if let Some(state) = app.try_state::<AppState>() {
+                    state.shutdown(app);
+                }
+            }
         });
 }
@@ -8,9 +8,9 @@ fn main() {
     // 检查命令行参数
     let args: Vec<String> = env::args().collect();
 
-    // 如果有--restore-mac参数，则执行还原MAC地址操作
+    // 如果有--restore-mac参数，则执行还原MAC地址操作（可选的第二个参数为网卡 GUID）
     if args.len() > 1 && args[1] == "--restore-mac" {
-        match app_lib::restore_mac_address() {
+        match app_lib::restore_mac_address(args.get(2).map(String::as_str)) {
             Ok(_) => {
                 println!("MAC地址已成功还原");
                 return;
@@ -1,5 +1,5 @@
 //! 云同步账号配置持久化：服务器地址 + 账号 + 应用密码，存
-//! `dirs::config_dir()/jx3-tools/cloud_config.json`（与 hotkey_config.json 同目录）。
+//! `app_config_dir()/cloud_config.json`（与 hotkey_config.json 同目录）。
 //! 应用密码是网盘侧可单独吊销的第三方授权密码，不是网盘登录密码；
 //! 明文落盘是当前取舍（目录受用户档案保护），后续可换 DPAPI。
 
@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 
 use super::webdav::normalize_base_url;
 use crate::error::{AppError, AppResult};
+use crate::fs_util::{app_config_dir, write_atomic};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -31,10 +32,7 @@ pub fn validate(config: &CloudConfig) -> AppResult<()> {
 }
 
 fn config_path() -> AppResult<PathBuf> {
-    let dir = dirs::config_dir()
-        .ok_or_else(|| AppError::Cloud("无法定位系统配置目录".into()))?
-        .join("jx3-tools");
-    Ok(dir.join("cloud_config.json"))
+    Ok(app_config_dir()?.join("cloud_config.json"))
 }
 
 pub fn load_config() -> AppResult<Option<CloudConfig>> {
@@ -60,7 +58,7 @@ fn save_to(config: &CloudConfig, path: &Path) -> AppResult<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    fs::write(path, serde_json::to_vec_pretty(config)?)?;
+    write_atomic(path, &serde_json::to_vec_pretty(config)?)?;
     Ok(())
 }
 
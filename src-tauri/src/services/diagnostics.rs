@@ -0,0 +1,202 @@
+//! 环境自检：用户反馈问题前，把支持人员需要的信息（系统、权限、按键驱动、
+//! 配置目录、网卡、热键配置）汇总成一份，可整体复制。每项检查各自报告成败，
+//! 单项失败只记在该项里，不会让整个自检报错。
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{AppError, AppResult};
+use crate::fs_util::app_config_dir;
+use crate::services::hotkey::{HotkeyConfig, HotkeyService, HotkeyStatus};
+use crate::services::mac::{MacInfo, MacService};
+
+/// 写入探测用的临时文件名（以 . 开头，写完即删）
+const WRITE_PROBE_FILE: &str = ".write-probe";
+
+/// Outcome of one sub-check: `value` on success, `error` otherwise
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check<T> {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<T> From<AppResult<T>> for Check<T> {
+    fn from(result: AppResult<T>) -> Self {
+        match result {
+            Ok(value) => Self {
+                ok: true,
+                value: Some(value),
+                error: None,
+            },
+            Err(err) => Self {
+                ok: false,
+                value: None,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Environment readiness report returned by the `diagnostics` command
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub app_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// 是否以管理员/root 运行；无法判断的平台为 None
+    pub elevated: Option<bool>,
+    /// 热键状态，含 Interception 驱动是否就绪（driverReady / driverState）
+    pub hotkey_status: HotkeyStatus,
+    /// 配置目录路径；目录不可创建或不可写时报错（错误信息含路径）
+    pub config_dir: Check<String>,
+    /// 当前选中（或自动选择）网卡的 MAC 概况
+    pub adapter: Check<MacInfo>,
+    pub hotkey_config: HotkeyConfig,
+}
+
+/// Run every check. Never fails as a whole; see `Check` for per-item errors.
+/// The adapter query may run PowerShell, so call this off the main thread
+pub fn collect(hotkey: &HotkeyService, mac: &MacService) -> Diagnostics {
+    let diagnostics = Diagnostics {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        elevated: is_elevated(),
+        hotkey_status: hotkey.get_status(),
+        config_dir: config_dir_check().into(),
+        adapter: mac.get_mac_info().into(),
+        hotkey_config: hotkey.get_config(),
+    };
+    log::info!(
+        "环境自检完成: {} {}，管理员={:?}，驱动就绪={}，配置目录可用={}，网卡可读={}",
+        diagnostics.os,
+        diagnostics.arch,
+        diagnostics.elevated,
+        diagnostics.hotkey_status.driver_ready,
+        diagnostics.config_dir.ok,
+        diagnostics.adapter.ok
+    );
+    diagnostics
+}
+
+/// Resolve the config dir and prove it is writable; returns its path
+fn config_dir_check() -> AppResult<String> {
+    let dir = app_config_dir()?;
+    probe_writable(&dir)
+        .map_err(|e| AppError::Config(format!("配置目录不可写 {}: {e}", dir.display())))?;
+    Ok(dir.to_string_lossy().into_owned())
+}
+
+/// Create `dir` if needed, then write and remove a probe file in it
+fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(WRITE_PROBE_FILE);
+    fs::write(&probe, b"ok")?;
+    fs::remove_file(&probe)
+}
+
+/// Whether this process runs elevated (Windows: the token is elevated;
+/// Linux: effective UID 0); None where it can't be told
+#[cfg(target_os = "windows")]
+pub fn is_elevated() -> Option<bool> {
+    use std::ffi::c_void;
+
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    // 看令牌是否已提权，而不是"是否管理员组成员"：UAC 下管理员账户未提权运行时
+    // 照样改不了网卡和驱动
+    let mut token = HANDLE::default();
+    unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }.ok()?;
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut len = 0u32;
+    let queried = unsafe {
+        GetTokenInformation(
+            token,
+            TokenElevation,
+            Some((&mut elevation as *mut TOKEN_ELEVATION).cast::<c_void>()),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        )
+    };
+    unsafe {
+        let _ = CloseHandle(token);
+    }
+    queried.ok()?;
+    Some(elevation.TokenIsElevated != 0)
+}
+
+/// 有效 UID 为 0 即 root（读 /proc，免引入 libc）
+#[cfg(target_os = "linux")]
+pub fn is_elevated() -> Option<bool> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    effective_uid(&status).map(|uid| uid == 0)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn is_elevated() -> Option<bool> {
+    None
+}
+
+/// `Uid:\t<real>\t<effective>\t...` 行中的有效 UID
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn effective_uid(proc_status: &str) -> Option<u32> {
+    proc_status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_carry_either_value_or_error() {
+        let ok: Check<u8> = Ok(7).into();
+        assert_eq!(
+            serde_json::to_value(&ok).unwrap(),
+            serde_json::json!({ "ok": true, "value": 7 })
+        );
+        let failed: Check<u8> = Err(AppError::Config("坏了".into())).into();
+        assert!(!failed.ok);
+        assert!(failed.value.is_none());
+        assert!(failed.error.unwrap().contains("坏了"));
+    }
+
+    #[test]
+    fn write_probe_leaves_nothing_behind() {
+        let dir = std::env::temp_dir()
+            .join(format!("jx3-diag-{}", std::process::id()))
+            .join("nested");
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+        probe_writable(&dir).unwrap();
+        assert!(dir.is_dir());
+        assert!(!dir.join(WRITE_PROBE_FILE).exists());
+        // 目标是文件而非目录：报错而不是 panic
+        let file = dir.join("file");
+        fs::write(&file, "x").unwrap();
+        assert!(probe_writable(&file).is_err());
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn effective_uid_is_the_second_uid_field() {
+        let status = "Name:\tjx3\nUid:\t1000\t0\t0\t0\nGid:\t1000\t1000\t1000\t1000\n";
+        assert_eq!(effective_uid(status), Some(0));
+        assert_eq!(effective_uid("Name:\tjx3\n"), None);
+    }
+}
@@ -0,0 +1,245 @@
+//! 自动查找剑网3 键位目录（游戏 `bin` 下的 userdata，如
+//! `SeasunGame/Game/JX3/bin/userdata`），省得用户在很深的目录里手动翻找。
+//! 候选根目录：注册表卸载项里剑网3/西山居的安装位置、各盘符下启动器的默认安装
+//! 目录、Program Files 下名字像剑网3/西山居的目录。每个根下有限深度地找名为
+//! userdata 的目录，其下至少有一个角色（与 `list_roles` 同一布局）才算命中。
+//! 仅 Windows；其他平台没有候选根，结果为空列表。
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::services::keyboard::{KeyboardService, ListOptions};
+
+/// 从候选根往下找 userdata 的最大深度（`SeasunGame/Game/JX3/bin/userdata` 为 4，
+/// 带版本目录的 `bin/zhcn_hd/userdata` 再深一层）
+const MAX_SEARCH_DEPTH: usize = 6;
+
+/// 一次查找最多进入的目录数，防止根目录选得太宽时扫遍整个磁盘
+const MAX_VISITED_DIRS: usize = 20_000;
+
+/// 目录名 / 注册表 DisplayName 中出现这些片段（不区分大小写）才当作游戏相关
+const NAME_MARKERS: &[&str] = &["jx3", "剑网3", "剑侠情缘", "seasun", "西山居", "kingsoft"];
+
+/// 各盘符根下启动器常见的默认安装目录
+const DEFAULT_ROOT_NAMES: &[&str] = &["SeasunGame", "Seasun", "JX3", "剑网3", "西山居"];
+
+/// Candidate keybind (userdata) dirs of local JX3 installs, best effort:
+/// unreadable locations are skipped, so this never fails. Walks the disk,
+/// call it off the main thread
+pub fn detect_keybind_dirs() -> Vec<String> {
+    let dirs = keybind_dirs_under(&candidate_roots());
+    log::info!("自动查找键位目录: 找到 {} 个", dirs.len());
+    dirs
+}
+
+/// Userdata dirs with at least one role under any of `roots`, deduplicated
+/// (case-insensitively, as Windows paths compare) in discovery order
+fn keybind_dirs_under(roots: &[PathBuf]) -> Vec<String> {
+    let mut budget = MAX_VISITED_DIRS;
+    let mut found = Vec::new();
+    for root in roots {
+        find_userdata_dirs(root, MAX_SEARCH_DEPTH, &mut budget, &mut found);
+    }
+
+    let mut dirs: Vec<String> = Vec::new();
+    for dir in found {
+        let path = dir.to_string_lossy().into_owned();
+        if dirs.iter().any(|seen| seen.eq_ignore_ascii_case(&path)) {
+            continue;
+        }
+        if has_roles(&path) {
+            dirs.push(path);
+        }
+    }
+    dirs
+}
+
+/// Collect dirs named `userdata` under `dir` down to `depth` levels, without
+/// descending into them. Hidden dirs and symlinks are skipped; each visited
+/// dir costs one unit of `budget`
+fn find_userdata_dirs(dir: &Path, depth: usize, budget: &mut usize, out: &mut Vec<PathBuf>) {
+    if depth == 0 || *budget == 0 {
+        return;
+    }
+    *budget -= 1;
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if !file_type.is_dir() || file_type.is_symlink() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.eq_ignore_ascii_case("userdata") {
+            out.push(entry.path());
+        } else {
+            find_userdata_dirs(&entry.path(), depth - 1, budget, out);
+        }
+    }
+}
+
+/// Whether `userdata` holds at least one role in the keybind layout
+fn has_roles(userdata: &str) -> bool {
+    KeyboardService::list_roles(userdata, &ListOptions::default())
+        .is_ok_and(|roles| !roles.is_empty())
+}
+
+/// Whether a dir or program name looks like JX3 / the Seasun launcher
+fn is_game_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    NAME_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// Existing dirs that may contain a JX3 install, most specific first
+#[cfg(target_os = "windows")]
+fn candidate_roots() -> Vec<PathBuf> {
+    let mut roots = registry::install_locations();
+
+    for letter in b'C'..=b'Z' {
+        let drive = PathBuf::from(format!("{}:\\", letter as char));
+        if !drive.is_dir() {
+            continue;
+        }
+        roots.extend(DEFAULT_ROOT_NAMES.iter().map(|name| drive.join(name)));
+    }
+
+    for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+        let Some(program_files) = std::env::var_os(var) else {
+            continue;
+        };
+        let Ok(entries) = fs::read_dir(program_files) else {
+            continue;
+        };
+        roots.extend(
+            entries
+                .flatten()
+                .filter(|entry| is_game_name(&entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path()),
+        );
+    }
+
+    roots.retain(|root| root.is_dir());
+    roots
+}
+
+/// 非 Windows 平台没有剑网3 客户端
+#[cfg(not(target_os = "windows"))]
+fn candidate_roots() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+mod registry {
+    use std::path::PathBuf;
+
+    use windows::Win32::System::Registry::{HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+
+    use super::is_game_name;
+    use crate::win_registry::RegKey;
+
+    const UNINSTALL_KEYS: &[(HKEY, &str)] = &[
+        (
+            HKEY_LOCAL_MACHINE,
+            r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
+        ),
+        (
+            HKEY_LOCAL_MACHINE,
+            r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
+        ),
+        (
+            HKEY_CURRENT_USER,
+            r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
+        ),
+    ];
+
+    /// `InstallLocation` of every uninstall entry whose `DisplayName` looks
+    /// like JX3 or the Seasun launcher
+    pub fn install_locations() -> Vec<PathBuf> {
+        let mut locations = Vec::new();
+        for &(hive, path) in UNINSTALL_KEYS {
+            let Ok(key) = RegKey::open_read(hive, path) else {
+                continue;
+            };
+            for subkey in key.subkeys() {
+                let is_game = key
+                    .read_sz(&subkey, "DisplayName")
+                    .is_some_and(|n| is_game_name(&n));
+                if !is_game {
+                    continue;
+                }
+                if let Some(location) = key
+                    .read_sz(&subkey, "InstallLocation")
+                    .map(|location| location.trim().trim_matches('"').to_string())
+                    .filter(|location| !location.is_empty())
+                {
+                    locations.push(PathBuf::from(location));
+                }
+            }
+        }
+        locations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("jx3-game-dirs-{label}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn game_names_match_case_insensitively() {
+        assert!(is_game_name("SeasunGame"));
+        assert!(is_game_name("剑网3 缘起"));
+        assert!(is_game_name("JX3Client"));
+        assert!(!is_game_name("Steam"));
+    }
+
+    #[test]
+    fn userdata_search_respects_depth_and_does_not_descend() {
+        let root = temp_dir("search");
+        fs::create_dir_all(root.join("Game/JX3/bin/userdata/userdata")).unwrap();
+        fs::create_dir_all(root.join("a/b/c/d/e/f/userdata")).unwrap();
+        fs::create_dir_all(root.join(".hidden/userdata")).unwrap();
+
+        let mut found = Vec::new();
+        find_userdata_dirs(&root, MAX_SEARCH_DEPTH, &mut 100, &mut found);
+        assert_eq!(found, [root.join("Game/JX3/bin/userdata")]);
+
+        // 预算用完即停
+        let mut found = Vec::new();
+        find_userdata_dirs(&root, MAX_SEARCH_DEPTH, &mut 2, &mut found);
+        assert!(found.is_empty());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn only_userdata_with_roles_is_reported_once() {
+        let root = temp_dir("roles");
+        let with_role = root.join("SeasunGame/Game/JX3/bin/userdata");
+        fs::create_dir_all(with_role.join("账号/电信区/梦江南/角色A")).unwrap();
+        fs::create_dir_all(root.join("Other/bin/userdata/账号")).unwrap();
+
+        let roots = [root.join("SeasunGame"), root.clone()];
+        assert_eq!(
+            keybind_dirs_under(&roots),
+            [with_role.to_string_lossy().into_owned()]
+        );
+        assert!(keybind_dirs_under(&[root.join("missing")]).is_empty());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,251 @@
+//! 数据清理：会话日志（`sessions/<毫秒时间戳>.jsonl`）、键位复制留下的旧目标备份
+//! （键位目录里的 `.<角色>.bak-<毫秒时间戳>` 目录）与损坏配置的备份
+//! （`hotkey_config.json.corrupt-<秒时间戳>`）只增不减，长期使用会越积越多。
+//! 这里按"保留最新 N 个 + 超过 N 天的删除"清理它们。时间取自名字，只认这几种命名，
+//! hotkey_config.json、settings.json 等在用的配置和角色目录本身永远不会被匹配到。
+
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::config::CORRUPT_BACKUP_MARKER;
+use super::session_log::sessions_dir;
+use crate::error::AppResult;
+use crate::services::keyboard::KeyboardService;
+
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// What `cleanup` removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReport {
+    pub removed_sessions: usize,
+    /// 删除的键位目录备份与损坏配置备份
+    pub removed_backups: usize,
+    /// 删除文件的总字节数（备份目录按其中全部文件计）
+    pub bytes_reclaimed: u64,
+}
+
+/// Delete session logs under `config_dir` beyond the newest `keep_sessions`,
+/// and backups beyond the newest `keep_backups`, plus any older than
+/// `max_age_days` (0 = no age limit). Backups are counted per backed-up
+/// directory for the `.bak-*` dirs in the `keyboard_base` userdata tree, and
+/// as one group for the corrupt-config backups in `config_dir`
+pub fn cleanup(
+    config_dir: &Path,
+    keyboard_base: Option<&Path>,
+    keep_sessions: usize,
+    keep_backups: usize,
+    max_age_days: u64,
+) -> AppResult<CleanupReport> {
+    let cutoff_ms =
+        (max_age_days > 0).then(|| now_millis().saturating_sub(max_age_days * MS_PER_DAY));
+    let mut report = CleanupReport::default();
+
+    let sessions = stamped_files(&sessions_dir(config_dir), session_stamp_ms)?;
+    for path in expired(sessions, keep_sessions, cutoff_ms) {
+        report.bytes_reclaimed += remove_file(&path)?;
+        report.removed_sessions += 1;
+    }
+
+    let backups = stamped_files(config_dir, backup_stamp_ms)?;
+    for path in expired(backups, keep_backups, cutoff_ms) {
+        report.bytes_reclaimed += remove_file(&path)?;
+        report.removed_backups += 1;
+    }
+
+    if let Some(base) = keyboard_base {
+        for group in KeyboardService::backup_groups(base)? {
+            let group = group
+                .into_iter()
+                .map(|(ms, path)| (u64::try_from(ms).unwrap_or(u64::MAX), path))
+                .collect();
+            for path in expired(group, keep_backups, cutoff_ms) {
+                report.bytes_reclaimed += remove_dir(&path)?;
+                report.removed_backups += 1;
+            }
+        }
+    }
+
+    log::info!("已清理配置目录: {:?}", report);
+    Ok(report)
+}
+
+/// `<ms>.jsonl` → ms
+fn session_stamp_ms(name: &str) -> Option<u64> {
+    name.strip_suffix(".jsonl")?.parse().ok()
+}
+
+/// `<file>.corrupt-<secs>` → ms
+fn backup_stamp_ms(name: &str) -> Option<u64> {
+    let (file, secs) = name.rsplit_once(CORRUPT_BACKUP_MARKER)?;
+    if file.is_empty() {
+        return None;
+    }
+    secs.parse::<u64>().ok()?.checked_mul(1000)
+}
+
+/// Regular files in `dir` whose name `stamp` recognizes, with that stamp
+fn stamped_files(dir: &Path, stamp: fn(&str) -> Option<u64>) -> AppResult<Vec<(u64, PathBuf)>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        if let Some(ms) = entry.file_name().to_str().and_then(stamp) {
+            files.push((ms, entry.path()));
+        }
+    }
+    Ok(files)
+}
+
+/// Files to delete: everything past the newest `keep`, and anything stamped
+/// before `cutoff_ms`
+fn expired(mut files: Vec<(u64, PathBuf)>, keep: usize, cutoff_ms: Option<u64>) -> Vec<PathBuf> {
+    files.sort_by_key(|(ms, _)| Reverse(*ms));
+    files
+        .into_iter()
+        .enumerate()
+        .filter(|(rank, (ms, _))| *rank >= keep || cutoff_ms.is_some_and(|cutoff| *ms < cutoff))
+        .map(|(_, (_, path))| path)
+        .collect()
+}
+
+/// Remove `path`, returning its size
+fn remove_file(path: &Path) -> AppResult<u64> {
+    let bytes = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    fs::remove_file(path)?;
+    Ok(bytes)
+}
+
+/// Remove the directory tree at `path`, returning the size of its files
+fn remove_dir(path: &Path) -> AppResult<u64> {
+    let bytes = KeyboardService::dir_size(path).unwrap_or(0);
+    fs::remove_dir_all(path)?;
+    Ok(bytes)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_are_read_from_file_names() {
+        assert_eq!(
+            session_stamp_ms("1700000000000.jsonl"),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(session_stamp_ms("notes.jsonl"), None);
+        assert_eq!(session_stamp_ms("1700000000000.csv"), None);
+        assert_eq!(
+            backup_stamp_ms("hotkey_config.json.corrupt-1700000000"),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(backup_stamp_ms("hotkey_config.json"), None);
+        assert_eq!(backup_stamp_ms(".corrupt-1"), None);
+        assert_eq!(backup_stamp_ms("settings.json.corrupt-x"), None);
+    }
+
+    #[test]
+    fn expired_keeps_newest_and_drops_old() {
+        let files = |stamps: &[u64]| -> Vec<(u64, PathBuf)> {
+            stamps
+                .iter()
+                .map(|ms| (*ms, PathBuf::from(format!("{ms}"))))
+                .collect()
+        };
+        let names = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(names(expired(files(&[1, 3, 2]), 2, None)), ["1"]);
+        assert!(expired(files(&[1, 2]), 5, None).is_empty());
+        // 在保留数量内但超龄的也删
+        assert_eq!(
+            names(expired(files(&[10, 20, 30]), 5, Some(25))),
+            ["20", "10"]
+        );
+        assert_eq!(names(expired(files(&[10, 20]), 0, None)), ["20", "10"]);
+    }
+
+    #[test]
+    fn cleanup_only_touches_sessions_and_backups() {
+        let dir = std::env::temp_dir().join(format!("jx3-cleanup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let sessions = sessions_dir(&dir);
+        fs::create_dir_all(&sessions).unwrap();
+        for ms in [1_000u64, 2_000, 3_000] {
+            fs::write(sessions.join(format!("{ms}.jsonl")), "{}\n").unwrap();
+        }
+        fs::write(sessions.join("readme.txt"), "x").unwrap();
+        for secs in [1u64, 2] {
+            fs::write(dir.join(format!("hotkey_config.json.corrupt-{secs}")), "{").unwrap();
+        }
+        fs::write(dir.join("hotkey_config.json"), "{}").unwrap();
+        fs::write(dir.join("settings.json"), "{}").unwrap();
+
+        let report = cleanup(&dir, None, 1, 0, 0).unwrap();
+        assert_eq!(report.removed_sessions, 2);
+        assert_eq!(report.removed_backups, 2);
+        assert_eq!(report.bytes_reclaimed, 2 * 3 + 2);
+        assert!(sessions.join("3000.jsonl").exists());
+        assert!(sessions.join("readme.txt").exists());
+        assert!(dir.join("hotkey_config.json").exists());
+        assert!(dir.join("settings.json").exists());
+
+        // 时间戳都在 1970 年：按天数清理时一个不留
+        let report = cleanup(&dir, None, 10, 10, 30).unwrap();
+        assert_eq!(report.removed_sessions, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cleanup_prunes_keyboard_backups_per_role() {
+        let dir = std::env::temp_dir().join(format!("jx3-cleanup-bak-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config_dir = dir.join("config");
+        fs::create_dir_all(&config_dir).unwrap();
+        let server = dir.join("userdata/acc/zone/server");
+        fs::create_dir_all(server.join("roleA")).unwrap();
+        fs::write(server.join("roleA/keys.ini"), "live").unwrap();
+        for (role, ms) in [("roleA", 1u64), ("roleA", 2), ("roleA", 3), ("roleB", 1)] {
+            let bak = server.join(format!(".{role}.bak-{ms}"));
+            fs::create_dir_all(bak.join("sub")).unwrap();
+            fs::write(bak.join("sub/keys.ini"), "old").unwrap();
+        }
+
+        let base = dir.join("userdata");
+        let report = cleanup(&config_dir, Some(&base), 10, 1, 0).unwrap();
+        assert_eq!(report.removed_backups, 2);
+        assert_eq!(report.bytes_reclaimed, 2 * 3);
+        assert!(server.join(".roleA.bak-3").exists());
+        assert!(!server.join(".roleA.bak-2").exists());
+        assert!(!server.join(".roleA.bak-1").exists());
+        assert!(server.join(".roleB.bak-1").exists(), "每个角色各自计数");
+        assert!(server.join("roleA/keys.ini").exists());
+
+        // 时间戳都在 1970 年：按天数清理时一个不留，角色目录不动
+        let report = cleanup(&config_dir, Some(&base), 10, 10, 30).unwrap();
+        assert_eq!(report.removed_backups, 2);
+        assert!(server.join("roleA/keys.ini").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,57 +1,196 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
 
 use crate::error::{AppError, AppResult};
-use super::keymap;
-use super::types::{HotkeyConfig, KeyMode};
+use crate::fs_util::{app_config_dir, write_atomic};
+use tauri_plugin_global_shortcut::Shortcut;
+
+use super::types::{BurstConfig, HotkeyConfig, KeyMode, TriggerAction};
+use super::{keymap, macros};
 
 pub const CONFIG_FILE_NAME: &str = "hotkey_config.json";
+/// User key table merged over the built-in one, next to the hotkey config
+pub const KEY_OVERRIDES_FILE_NAME: &str = "key_overrides.json";
+/// Corrupt configs are kept as `<file>.corrupt-<unix seconds>`
+pub const CORRUPT_BACKUP_MARKER: &str = ".corrupt-";
+
+/// One entry of `key_overrides.json`, e.g.
+/// `{ "F13": { "scancode": 100, "vk": 124 } }`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct KeyOverride {
+    scancode: u16,
+    /// 虚拟键码，窗口模式需要；缺省 0 表示仅全局模式可用
+    #[serde(default)]
+    vk: u16,
+    #[serde(default)]
+    extended: bool,
+}
 
 /// Ensure the app config directory exists and return its path
 pub fn ensure_app_config_dir() -> AppResult<PathBuf> {
-    let mut base = dirs::config_dir()
-        .ok_or_else(|| AppError::Config("无法获取配置目录".into()))?;
-    base.push("jx3-tools");
+    let base = app_config_dir()?;
     fs::create_dir_all(&base)?;
     Ok(base)
 }
 
-/// Load config from disk
-pub fn load_config(config_path: &PathBuf) -> AppResult<HotkeyConfig> {
-    if !config_path.exists() {
-        return Ok(HotkeyConfig::default());
+/// What `load_config_or_reset` found on disk
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: HotkeyConfig,
+    /// Shown to the user via `last_error`: the file was reset or is unreadable
+    pub notice: Option<String>,
+    /// The file exists but couldn't be read (permissions, sharing lock…); it
+    /// must not be overwritten with the defaults used in its place
+    pub unreadable: bool,
+}
+
+impl LoadedConfig {
+    fn defaults(notice: Option<String>) -> Self {
+        Self {
+            config: HotkeyConfig::default(),
+            notice,
+            unreadable: false,
+        }
     }
-    let content = fs::read_to_string(config_path)?;
-    let config = serde_json::from_str::<HotkeyConfig>(&content)?;
-    Ok(config)
+}
+
+/// Load config from disk, falling back to defaults when the file can't be used.
+///
+/// A file that fails to parse, or isn't UTF-8, is moved aside to
+/// `hotkey_config.json.corrupt-<unix seconds>` so the user's data survives the
+/// next save; the returned notice says where it went. Any other read error
+/// leaves the file alone and marks it `unreadable`.
+pub fn load_config_or_reset(config_path: &PathBuf) -> LoadedConfig {
+    let parsed = match fs::read_to_string(config_path) {
+        Ok(content) => serde_json::from_str::<HotkeyConfig>(&content).map_err(|e| e.to_string()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return LoadedConfig::defaults(None)
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::InvalidData => Err(err.to_string()),
+        Err(err) => {
+            log::warn!("读取热键配置失败，本次使用默认配置且不覆盖原文件: {err}");
+            return LoadedConfig {
+                unreadable: true,
+                ..LoadedConfig::defaults(Some(format!(
+                    "读取热键配置失败（{err}），本次使用默认配置；为保护原文件，修改不会保存，请检查文件权限或占用后重启工具"
+                )))
+            };
+        }
+    };
+    let err = match parsed {
+        Ok(config) => {
+            return LoadedConfig {
+                config,
+                ..LoadedConfig::defaults(None)
+            }
+        }
+        Err(err) => err,
+    };
+
+    log::warn!("热键配置文件损坏（{err}），已重置为默认配置");
+    let notice = match backup_corrupt_config(config_path) {
+        Ok(backup) => format!(
+            "热键配置文件损坏，已重置为默认配置（原文件备份为 {}）",
+            backup.display()
+        ),
+        Err(backup_err) => {
+            log::warn!("备份损坏的热键配置失败: {backup_err}");
+            "热键配置文件损坏，已重置为默认配置".to_string()
+        }
+    };
+    LoadedConfig::defaults(Some(notice))
+}
+
+/// Load `key_overrides.json`; a missing file is an empty table. Any invalid
+/// entry rejects the whole file so a typo can't half-apply.
+pub fn load_key_overrides(path: &Path) -> AppResult<BTreeMap<String, keymap::KeyDef>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err.into()),
+    };
+    parse_key_overrides(&content)
+}
+
+fn parse_key_overrides(content: &str) -> AppResult<BTreeMap<String, keymap::KeyDef>> {
+    let raw: BTreeMap<String, KeyOverride> = serde_json::from_str(content)
+        .map_err(|e| AppError::Config(format!("按键覆盖表解析失败: {e}")))?;
+    let mut overrides = BTreeMap::new();
+    for (label, entry) in raw {
+        let label = label.trim();
+        if label.is_empty() || label.contains('+') {
+            return Err(AppError::Config(format!(
+                "按键覆盖表中的按键名无效: {label:?}"
+            )));
+        }
+        keymap::validate_scancode(entry.scancode)
+            .map_err(|e| AppError::Config(format!("按键覆盖表 {label}: {e}")))?;
+        if entry.vk > 0xFE {
+            return Err(AppError::Config(format!(
+                "按键覆盖表 {label}: 虚拟键码超出范围 0x{:X}",
+                entry.vk
+            )));
+        }
+        overrides.insert(
+            label.to_string(),
+            keymap::KeyDef {
+                scancode: entry.scancode,
+                vk: entry.vk,
+                extended: entry.extended,
+            },
+        );
+    }
+    Ok(overrides)
+}
+
+fn backup_corrupt_config(config_path: &Path) -> AppResult<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut name = config_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!("{CORRUPT_BACKUP_MARKER}{secs}"));
+    let backup = config_path.with_file_name(name);
+    fs::rename(config_path, &backup)?;
+    Ok(backup)
 }
 
 /// Save config to disk
 pub fn save_config(config_path: &PathBuf, config: &HotkeyConfig) -> AppResult<()> {
     let data = serde_json::to_string_pretty(config)?;
-    fs::write(config_path, data)?;
+    write_atomic(config_path, data.as_bytes())?;
     Ok(())
 }
 
+/// JSON Schema of `HotkeyConfig` as it appears on the wire and in
+/// hotkey_config.json (camelCase, serde defaults included), for external
+/// editors and config generators
+pub fn config_schema() -> String {
+    // RootSchema 由 schemars 生成，序列化不会失败
+    serde_json::to_string_pretty(&schemars::schema_for!(HotkeyConfig)).unwrap_or_default()
+}
+
 /// Validate config before saving
 pub fn validate_config(config: &HotkeyConfig) -> AppResult<()> {
-    if config.trigger_key.trim().is_empty() {
-        return Err(AppError::Hotkey("触发按键不能为空".into()));
-    }
-    if config.interval_ms < 20 {
-        return Err(AppError::Hotkey("触发频率不能低于 20 毫秒".into()));
-    }
-    if config.interval_ms > 60000 {
-        return Err(AppError::Hotkey("触发频率不能高于 60000 毫秒".into()));
-    }
+    validate_trigger_action(config)?;
+    validate_interval(config.interval_ms)?;
+    validate_burst(config.burst.as_ref())?;
+    validate_skip_chance(config.skip_chance)?;
+    validate_hold(config.hold_ms, config.shortest_period_ms())?;
+    validate_press_gap(config.press_gap_ms, config.shortest_period_ms())?;
+    validate_max_duration(config.max_duration_secs)?;
+    validate_start_delay(config.start_delay_ms)?;
     if config.start_hotkey.trim().is_empty() {
         return Err(AppError::Hotkey("开始热键不能为空".into()));
     }
     if config.stop_hotkey.trim().is_empty() {
         return Err(AppError::Hotkey("结束热键不能为空".into()));
     }
-    keymap::resolve_key(&config.trigger_key)
-        .map_err(|e| AppError::Hotkey(format!("触发按键格式无效: {e}")))?;
     let start = keymap::parse_shortcut(&config.start_hotkey)
         .map_err(|e| AppError::Hotkey(format!("开始热键格式无效: {e}")))?;
     let stop = keymap::parse_shortcut(&config.stop_hotkey)
@@ -61,38 +200,751 @@ pub fn validate_config(config: &HotkeyConfig) -> AppResult<()> {
         return Err(AppError::Hotkey("开始与结束热键不能相同".into()));
     }
 
-    // 触发按键被模拟按下时会命中同名热键，必须与开始/结束热键错开
-    if let Ok(trigger) = keymap::parse_shortcut(&config.trigger_key) {
-        if trigger == start || trigger == stop {
-            return Err(AppError::Hotkey("触发按键不能与开始/结束热键相同".into()));
-        }
+    if config.emergency_stop_hotkey.trim().is_empty() {
+        return Err(AppError::Hotkey("紧急停止热键不能为空".into()));
+    }
+    let emergency = keymap::parse_shortcut(&config.emergency_stop_hotkey)
+        .map_err(|e| AppError::Hotkey(format!("紧急停止热键格式无效: {e}")))?;
+    if emergency == start || emergency == stop {
+        return Err(AppError::Hotkey(
+            "紧急停止热键不能与开始/结束热键相同".into(),
+        ));
+    }
+
+    if config.trigger_action == TriggerAction::Key {
+        validate_trigger_conflicts(
+            &config.trigger_key,
+            &[
+                ("开始热键", start),
+                ("结束热键", stop),
+                ("紧急停止热键", emergency),
+            ],
+        )?;
     }
 
+    validate_restrict_to_process(config.restrict_to_process.as_deref())?;
+    validate_stop_on_focus_loss(config)?;
+    validate_hold_modifier(
+        config,
+        &[
+            ("结束热键", &config.stop_hotkey, stop),
+            ("紧急停止热键", &config.emergency_stop_hotkey, emergency),
+        ],
+    )?;
+    validate_device_filter(config.device_filter)?;
+
     // 窗口模式验证
     if config.key_mode == KeyMode::Window {
         #[cfg(not(target_os = "windows"))]
         return Err(AppError::Hotkey("窗口模式仅支持 Windows".into()));
 
         #[cfg(target_os = "windows")]
-        if config.target_window.is_none() {
-            return Err(AppError::Hotkey("窗口模式需要选择目标窗口".into()));
+        if config.target_windows.is_empty() {
+            return Err(AppError::Hotkey("窗口模式需要至少选择一个目标窗口".into()));
         }
     }
 
     Ok(())
 }
 
+/// `restrict_to_process` is a bare image name (no path), Windows only
+fn validate_restrict_to_process(process: Option<&str>) -> AppResult<()> {
+    let Some(process) = process else {
+        return Ok(());
+    };
+    let process = process.trim();
+    if process.is_empty() {
+        return Err(AppError::Hotkey("限定进程名不能为空".into()));
+    }
+    if process.contains(['/', '\\']) {
+        return Err(AppError::Hotkey(format!(
+            "限定进程只填进程名，不要带路径: {process}"
+        )));
+    }
+    if cfg!(not(target_os = "windows")) {
+        return Err(AppError::Hotkey("限定前台进程仅支持 Windows".into()));
+    }
+    Ok(())
+}
+
+/// `stop_on_focus_loss` needs a process to watch, Windows only
+fn validate_stop_on_focus_loss(config: &HotkeyConfig) -> AppResult<()> {
+    if !config.stop_on_focus_loss {
+        return Ok(());
+    }
+    if config.focus_processes().is_empty() {
+        return Err(AppError::Hotkey(
+            "失焦自动停止需要设置限定进程，或在窗口模式下选择目标窗口".into(),
+        ));
+    }
+    if cfg!(not(target_os = "windows")) {
+        return Err(AppError::Hotkey("失焦自动停止仅支持 Windows".into()));
+    }
+    Ok(())
+}
+
+/// `hold_modifier` is a bare modifier key, global mode only (window mode
+/// posts messages per press and has nothing to keep held). System-reserved
+/// keys (Win) are refused as for triggers: held down, every tap would fire a
+/// system shortcut. The OS matches hotkey modifiers exactly, so while the
+/// modifier is held each of `stop_hotkeys` (name, label, shortcut) has to
+/// include it, or pressing it arrives as another combo and the run can't be
+/// stopped from the keyboard
+fn validate_hold_modifier(
+    config: &HotkeyConfig,
+    stop_hotkeys: &[(&str, &str, Shortcut)],
+) -> AppResult<()> {
+    let Some(label) = config.hold_modifier.as_deref() else {
+        return Ok(());
+    };
+    let flag = keymap::modifier_flag(label)
+        .filter(|_| keymap::is_modifier_label(label))
+        .ok_or_else(|| AppError::Hotkey(format!("常按修饰键只能是 Ctrl/Shift/Alt: {label}")))?;
+    let key = keymap::resolve_key(label)?;
+    if let Some(reserved) = keymap::reserved_key(key) {
+        return Err(AppError::Hotkey(format!(
+            "{} 是系统保留按键（{}），不能常按",
+            label.trim(),
+            reserved.name
+        )));
+    }
+    if config.key_mode != KeyMode::Global {
+        return Err(AppError::Hotkey("常按修饰键仅支持全局模式".into()));
+    }
+    let label = label.trim();
+    for (name, hotkey, shortcut) in stop_hotkeys {
+        if !shortcut.mods.contains(flag) {
+            return Err(AppError::Hotkey(format!(
+                "常按 {label} 期间{name} {} 会被识别为 {label}+{}、无法触发，请把{name}改为含 {label} 的组合键",
+                hotkey.trim(),
+                hotkey.trim()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `device_filter` is an Interception keyboard device index, Windows only
+fn validate_device_filter(device_filter: Option<i32>) -> AppResult<()> {
+    let Some(index) = device_filter else {
+        return Ok(());
+    };
+    if !(0..=HotkeyConfig::MAX_DEVICE_INDEX).contains(&index) {
+        return Err(AppError::Hotkey(format!(
+            "注入设备序号必须在 0 到 {} 之间，当前为 {index}",
+            HotkeyConfig::MAX_DEVICE_INDEX
+        )));
+    }
+    if cfg!(not(target_os = "windows")) {
+        return Err(AppError::Hotkey("限定注入设备仅支持 Windows".into()));
+    }
+    Ok(())
+}
+
+/// Key actions need a resolvable trigger key. Mouse clicks ignore it, but a
+/// lone modifier there would read as Ctrl+click etc., which isn't supported
+fn validate_trigger_action(config: &HotkeyConfig) -> AppResult<()> {
+    match &config.trigger_action {
+        TriggerAction::Key => {
+            if config.trigger_key.trim().is_empty() {
+                return Err(AppError::Hotkey("触发按键不能为空".into()));
+            }
+            let key = keymap::resolve_key(&config.trigger_key)
+                .map_err(|e| AppError::Hotkey(format!("触发按键格式无效: {e}")))?;
+            keymap::ensure_not_reserved(&config.trigger_key, key)?;
+            // SC: 字面量与未填 vk 的覆盖项没有虚拟键码，PostMessage 发不出去
+            if key.vk == 0 && config.key_mode == KeyMode::Window {
+                return Err(AppError::Hotkey(format!(
+                    "触发按键 {} 没有虚拟键码，只能用于全局模式",
+                    config.trigger_key.trim()
+                )));
+            }
+            #[cfg(target_os = "macos")]
+            ensure_mac_keycode(&config.trigger_key, key)?;
+        }
+        TriggerAction::MouseClick { .. } => {
+            if keymap::is_modifier_label(&config.trigger_key) {
+                return Err(AppError::Hotkey(
+                    "鼠标点击不支持与修饰键组合，请清空触发按键".into(),
+                ));
+            }
+        }
+        // 宏是否存在在启动时检查：可以先保存配置再录制
+        TriggerAction::Macro { name } => {
+            macros::validate_name(name)?;
+        }
+    }
+    Ok(())
+}
+
+/// macOS simulates keys by CGKeyCode, derived from the VK: keys without one
+/// (`SC:` literals with vk 0, ScrollLock, F21–F24) can't be pressed there
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn ensure_mac_keycode(label: &str, key: keymap::KeyDef) -> AppResult<()> {
+    if keymap::mac_keycode(key).is_none() {
+        return Err(AppError::Hotkey(format!(
+            "触发按键 {} 在 macOS 上没有对应的按键，无法模拟",
+            label.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// The trigger key must differ from every control hotkey: the listener
+/// swallows a registered shortcut, so the game would never see the trigger and
+/// each simulated press would hit the start/stop handler instead
+fn validate_trigger_conflicts(trigger_key: &str, hotkeys: &[(&str, Shortcut)]) -> AppResult<()> {
+    // 无法解析为热键的触发按键不可能与热键冲突
+    let Ok(trigger) = keymap::parse_shortcut(trigger_key) else {
+        return Ok(());
+    };
+    if let Some((name, _)) = hotkeys.iter().find(|(_, hotkey)| *hotkey == trigger) {
+        return Err(AppError::Hotkey(format!(
+            "触发按键 {} 与{name}相同，请更换其中之一",
+            trigger_key.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Interval must lie within `[MIN_INTERVAL_MS, MAX_INTERVAL_MS]`
+fn validate_interval(interval_ms: u64) -> AppResult<()> {
+    if interval_ms < HotkeyConfig::MIN_INTERVAL_MS {
+        return Err(AppError::Hotkey(format!(
+            "触发频率不能低于 {} 毫秒",
+            HotkeyConfig::MIN_INTERVAL_MS
+        )));
+    }
+    if interval_ms > HotkeyConfig::MAX_INTERVAL_MS {
+        return Err(AppError::Hotkey(format!(
+            "触发频率不能高于 {} 毫秒，当前为 {interval_ms} 毫秒",
+            HotkeyConfig::MAX_INTERVAL_MS
+        )));
+    }
+    Ok(())
+}
+
+/// Burst spacing follows the same bounds as the interval, with at least one
+/// press per burst
+fn validate_burst(burst: Option<&BurstConfig>) -> AppResult<()> {
+    let Some(burst) = burst else {
+        return Ok(());
+    };
+    if burst.count == 0 {
+        return Err(AppError::Hotkey("连发次数至少为 1".into()));
+    }
+    for (name, value) in [
+        ("连发间隔", burst.burst_gap_ms),
+        ("连发停顿", burst.pause_ms),
+    ] {
+        if value < HotkeyConfig::MIN_INTERVAL_MS {
+            return Err(AppError::Hotkey(format!(
+                "{name}不能低于 {} 毫秒",
+                HotkeyConfig::MIN_INTERVAL_MS
+            )));
+        }
+        if value > HotkeyConfig::MAX_INTERVAL_MS {
+            return Err(AppError::Hotkey(format!(
+                "{name}不能高于 {} 毫秒，当前为 {value} 毫秒",
+                HotkeyConfig::MAX_INTERVAL_MS
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Skip probability must lie within `[0.0, 1.0]` (NaN is rejected too)
+fn validate_skip_chance(skip_chance: f32) -> AppResult<()> {
+    if !(0.0..=1.0).contains(&skip_chance) {
+        return Err(AppError::Hotkey(format!(
+            "跳过概率必须在 0 到 1 之间，当前为 {skip_chance}"
+        )));
+    }
+    Ok(())
+}
+
+/// Hold time must stay below the shortest period (see
+/// `HotkeyConfig::shortest_period_ms`), or presses would run back to back
+fn validate_hold(hold_ms: u64, period_ms: u64) -> AppResult<()> {
+    if hold_ms >= period_ms {
+        return Err(AppError::Hotkey(format!(
+            "按住时长必须小于触发周期（{period_ms} 毫秒）"
+        )));
+    }
+    Ok(())
+}
+
+/// Press gap must be at least `MIN_PRESS_GAP_MS` and below the shortest period
+fn validate_press_gap(press_gap_ms: u64, period_ms: u64) -> AppResult<()> {
+    if press_gap_ms < HotkeyConfig::MIN_PRESS_GAP_MS {
+        return Err(AppError::Hotkey(format!(
+            "点按间隔不能低于 {} 毫秒",
+            HotkeyConfig::MIN_PRESS_GAP_MS
+        )));
+    }
+    if press_gap_ms >= period_ms {
+        return Err(AppError::Hotkey(format!(
+            "点按间隔必须小于触发周期（{period_ms} 毫秒）"
+        )));
+    }
+    Ok(())
+}
+
+/// Max run duration, if set, must be at least `MIN_DURATION_SECS`
+fn validate_max_duration(max_duration_secs: Option<u64>) -> AppResult<()> {
+    match max_duration_secs {
+        Some(secs) if secs < HotkeyConfig::MIN_DURATION_SECS => Err(AppError::Hotkey(format!(
+            "最长运行时长不能低于 {} 秒",
+            HotkeyConfig::MIN_DURATION_SECS
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Warm-up delay is capped at `MAX_START_DELAY_MS`
+fn validate_start_delay(start_delay_ms: u64) -> AppResult<()> {
+    if start_delay_ms > HotkeyConfig::MAX_START_DELAY_MS {
+        return Err(AppError::Hotkey(format!(
+            "开始延迟不能超过 {} 毫秒",
+            HotkeyConfig::MAX_START_DELAY_MS
+        )));
+    }
+    Ok(())
+}
+
 /// Validate config at runtime (before starting runner)
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "macos"))]
 pub fn validate_runtime_config(config: &HotkeyConfig) -> AppResult<()> {
-    if config.trigger_key.trim().is_empty() {
+    if config.trigger_action == TriggerAction::Key && config.trigger_key.trim().is_empty() {
         return Err(AppError::Hotkey("触发按键未设置".into()));
     }
-    if config.interval_ms < 20 {
-        return Err(AppError::Hotkey("触发频率不能低于 20 毫秒".into()));
+    validate_interval(config.interval_ms)?;
+    validate_burst(config.burst.as_ref())?;
+    validate_skip_chance(config.skip_chance)?;
+    validate_hold(config.hold_ms, config.shortest_period_ms())?;
+    validate_press_gap(config.press_gap_ms, config.shortest_period_ms())?;
+    validate_max_duration(config.max_duration_secs)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::hotkey::types::DEFAULT_EMERGENCY_STOP_HOTKEY;
+    use crate::services::hotkey::MouseButton;
+
+    fn config_with_interval(interval_ms: u64) -> HotkeyConfig {
+        HotkeyConfig {
+            trigger_key: "A".into(),
+            interval_ms,
+            ..HotkeyConfig::default()
+        }
     }
-    if config.interval_ms > 60000 {
-        return Err(AppError::Hotkey("触发频率不能高于 60000 毫秒".into()));
+
+    #[test]
+    fn corrupt_config_is_backed_up_and_reset() {
+        let dir = std::env::temp_dir().join(format!("jx3-hotkey-corrupt-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+
+        let loaded = load_config_or_reset(&path);
+        assert!(loaded.notice.is_none());
+        assert!(!loaded.unreadable);
+
+        let saved = HotkeyConfig {
+            trigger_key: "F1".into(),
+            ..HotkeyConfig::default()
+        };
+        fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+        let loaded = load_config_or_reset(&path);
+        assert_eq!(loaded.config.trigger_key, "F1");
+        assert!(loaded.notice.is_none());
+
+        fs::write(&path, "{\"triggerKey\": ").unwrap();
+        let loaded = load_config_or_reset(&path);
+        assert_eq!(
+            loaded.config.trigger_key,
+            HotkeyConfig::default().trigger_key
+        );
+        assert!(loaded.notice.unwrap().contains("corrupt-"));
+        assert!(!loaded.unreadable);
+        assert!(!path.exists());
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].starts_with("hotkey_config.json.corrupt-"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn non_utf8_config_is_backed_up_like_a_parse_error() {
+        let dir = std::env::temp_dir().join(format!("jx3-hotkey-non-utf8-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+
+        fs::write(&path, b"{\"triggerKey\": \"\xff\xfe\"}").unwrap();
+        let loaded = load_config_or_reset(&path);
+        assert_eq!(
+            loaded.config.trigger_key,
+            HotkeyConfig::default().trigger_key
+        );
+        assert!(loaded.notice.unwrap().contains("corrupt-"));
+        assert!(!loaded.unreadable);
+        assert!(!path.exists());
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].starts_with("hotkey_config.json.corrupt-"));
+        assert_eq!(
+            fs::read(dir.join(&backups[0])).unwrap(),
+            b"{\"triggerKey\": \"\xff\xfe\"}"
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn trigger_key_must_not_match_a_control_hotkey() {
+        let mut config = config_with_interval(1000);
+        config.trigger_key = "F11".into();
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("开始热键"), "{err}");
+
+        config.trigger_key = "f12".into();
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("结束热键"), "{err}");
+
+        config.trigger_key = "F10".into();
+        assert!(validate_config(&config).is_ok());
+
+        // 鼠标点击不发送触发按键，不存在冲突
+        config.trigger_key = String::new();
+        config.trigger_action = TriggerAction::MouseClick {
+            button: MouseButton::Left,
+        };
+        config.start_hotkey = "F11".into();
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn emergency_hotkey_is_required_and_distinct() {
+        let mut config = config_with_interval(1000);
+        assert!(validate_config(&config).is_ok());
+
+        config.emergency_stop_hotkey = " ".into();
+        assert!(validate_config(&config).is_err());
+        config.emergency_stop_hotkey = "F12".into();
+        assert!(validate_config(&config).is_err());
+
+        config.emergency_stop_hotkey = "Ctrl+Q".into();
+        config.trigger_key = "Q".into();
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn emergency_hotkey_defaults_for_old_configs() {
+        let config: HotkeyConfig = serde_json::from_str(
+            r#"{"triggerKey":"A","intervalMs":1000,"startHotkey":"F11","stopHotkey":"F12"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.emergency_stop_hotkey, DEFAULT_EMERGENCY_STOP_HOTKEY);
+    }
+
+    #[test]
+    fn restrict_to_process_is_a_bare_name_on_windows_only() {
+        assert!(validate_restrict_to_process(None).is_ok());
+        assert!(validate_restrict_to_process(Some("  ")).is_err());
+        assert!(validate_restrict_to_process(Some(r"C:\Game\JX3ClientX64.exe")).is_err());
+        assert_eq!(
+            validate_restrict_to_process(Some("JX3ClientX64.exe")).is_ok(),
+            cfg!(target_os = "windows")
+        );
+    }
+
+    #[test]
+    fn stop_on_focus_loss_needs_a_process_on_windows_only() {
+        let mut config = HotkeyConfig::default();
+        assert!(validate_stop_on_focus_loss(&config).is_ok());
+        config.stop_on_focus_loss = true;
+        assert!(validate_stop_on_focus_loss(&config).is_err());
+        config.restrict_to_process = Some("JX3ClientX64.exe".into());
+        assert_eq!(
+            validate_stop_on_focus_loss(&config).is_ok(),
+            cfg!(target_os = "windows")
+        );
+    }
+
+    #[test]
+    fn hold_modifier_is_a_modifier_in_global_mode() {
+        let stop = [(
+            "结束热键",
+            "Ctrl+Shift+F10",
+            keymap::parse_shortcut("Ctrl+Shift+F10").unwrap(),
+        )];
+        let mut config = HotkeyConfig::default();
+        assert!(validate_hold_modifier(&config, &stop).is_ok());
+        config.hold_modifier = Some("Shift".into());
+        assert!(validate_hold_modifier(&config, &stop).is_ok());
+        config.hold_modifier = Some("A".into());
+        assert!(validate_hold_modifier(&config, &stop).is_err());
+        config.hold_modifier = Some("Ctrl+Shift".into());
+        assert!(validate_hold_modifier(&config, &stop).is_err());
+        // Win 按住时每次点按都是系统快捷键（Win+D、Win+L…）
+        for label in ["Win", "LWin", "RWin", "Windows"] {
+            config.hold_modifier = Some(label.into());
+            assert!(validate_hold_modifier(&config, &stop).is_err(), "{label}");
+        }
+        config.hold_modifier = Some("Ctrl".into());
+        config.key_mode = KeyMode::Window;
+        assert!(validate_hold_modifier(&config, &stop).is_err());
+    }
+
+    #[test]
+    fn held_modifier_must_not_shadow_stop_hotkeys() {
+        let config = HotkeyConfig {
+            hold_modifier: Some("Shift".into()),
+            ..HotkeyConfig::default()
+        };
+        let hotkey =
+            |label: &'static str| ("结束热键", label, keymap::parse_shortcut(label).unwrap());
+        // 按住 Shift 时按 F10 到达系统的是 Shift+F10
+        let err = validate_hold_modifier(&config, &[hotkey("F10")]).unwrap_err();
+        assert!(err.to_string().contains("Shift+F10"));
+        assert!(validate_hold_modifier(&config, &[hotkey("Ctrl+F10")]).is_err());
+        assert!(validate_hold_modifier(&config, &[hotkey("Shift+F10")]).is_ok());
+
+        // 默认紧急停止 Ctrl+Alt+Shift+Esc 本就含 Shift，整份配置只需改结束热键
+        let mut config = config_with_interval(1000);
+        config.trigger_key = "1".into();
+        config.hold_modifier = Some("Shift".into());
+        assert!(validate_config(&config).is_err());
+        config.stop_hotkey = "Shift+F12".into();
+        assert!(validate_config(&config).is_ok());
+        config.hold_modifier = Some("Alt".into());
+        assert!(validate_config(&config).is_err());
+        config.stop_hotkey = "Alt+F12".into();
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn device_filter_is_a_keyboard_index_on_windows_only() {
+        assert!(validate_device_filter(None).is_ok());
+        assert!(validate_device_filter(Some(-1)).is_err());
+        assert!(validate_device_filter(Some(10)).is_err());
+        for index in [0, HotkeyConfig::MAX_DEVICE_INDEX] {
+            assert_eq!(
+                validate_device_filter(Some(index)).is_ok(),
+                cfg!(target_os = "windows")
+            );
+        }
+    }
+
+    #[test]
+    fn key_overrides_are_validated_as_a_whole() {
+        let overrides = parse_key_overrides(
+            r#"{
+                "F13": { "scancode": 100, "vk": 124 },
+                "Ins2": { "scancode": 82, "extended": true }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(overrides["F13"].vk, 124);
+        assert!(overrides["Ins2"].extended);
+
+        assert!(parse_key_overrides(r#"{ "F13": { "scancode": 0 } }"#).is_err());
+        assert!(parse_key_overrides(r#"{ "F13": { "scancode": 200 } }"#).is_err());
+        assert!(parse_key_overrides(r#"{ "F13": { "scancode": 100, "vk": 300 } }"#).is_err());
+        assert!(parse_key_overrides(r#"{ "Ctrl+A": { "scancode": 30 } }"#).is_err());
+        assert!(parse_key_overrides(r#"{ "F13": { "scan": 100 } }"#).is_err());
+
+        let missing = std::env::temp_dir().join("jx3-no-such-overrides.json");
+        assert!(load_key_overrides(&missing).unwrap().is_empty());
+    }
+
+    #[test]
+    fn burst_timings_are_validated() {
+        let mut config = config_with_interval(1000);
+        config.burst = Some(BurstConfig {
+            count: 3,
+            burst_gap_ms: 50,
+            pause_ms: 2000,
+        });
+        assert!(validate_config(&config).is_ok());
+        assert_eq!(config.shortest_period_ms(), 50);
+
+        let valid = config.burst;
+        config.burst = valid.map(|b| BurstConfig { count: 0, ..b });
+        assert!(validate_config(&config).is_err());
+        config.burst = valid.map(|b| BurstConfig {
+            burst_gap_ms: HotkeyConfig::MIN_INTERVAL_MS - 1,
+            ..b
+        });
+        assert!(validate_config(&config).is_err());
+        config.burst = valid.map(|b| BurstConfig {
+            pause_ms: HotkeyConfig::MIN_INTERVAL_MS - 1,
+            ..b
+        });
+        assert!(validate_config(&config).is_err());
+
+        // 按住时长要短于连发间隔，而不只是 interval_ms
+        config.burst = valid;
+        config.hold_ms = 60;
+        assert!(validate_config(&config).is_err());
+        // 每轮只按一次时只有停顿这一个周期
+        config.burst = valid.map(|b| BurstConfig { count: 1, ..b });
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn skip_chance_must_be_a_probability() {
+        let mut config = config_with_interval(1000);
+        for chance in [0.0, 0.25, 1.0] {
+            config.skip_chance = chance;
+            assert!(validate_config(&config).is_ok());
+        }
+        for chance in [-0.1, 1.5, f32::NAN] {
+            config.skip_chance = chance;
+            assert!(validate_config(&config).is_err());
+        }
+    }
+
+    #[test]
+    fn reserved_trigger_keys_are_rejected() {
+        let mut config = config_with_interval(1000);
+        config.trigger_key = "Win".into();
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("系统保留按键"), "{err}");
+        config.trigger_key = "SC:0xE05F".into();
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn schema_uses_wire_field_names() {
+        let schema: serde_json::Value = serde_json::from_str(&config_schema()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for field in ["triggerKey", "intervalMs", "keyMode", "burst", "skipChance"] {
+            assert!(properties.contains_key(field), "缺少 {field}");
+        }
+        assert!(!properties.contains_key("trigger_key"));
+
+        // 没有 serde 默认值的字段才是必填
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        required.sort_unstable();
+        assert_eq!(
+            required,
+            ["intervalMs", "startHotkey", "stopHotkey", "triggerKey"]
+        );
+
+        // 默认配置必须能通过自己的 schema 描述的字段集
+        let defaults = serde_json::to_value(HotkeyConfig::default()).unwrap();
+        for key in defaults.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "schema 缺少 {key}");
+        }
+    }
+
+    #[test]
+    fn scancode_literal_triggers_are_global_only() {
+        let mut config = HotkeyConfig {
+            trigger_key: "SC:0x64".into(),
+            ..HotkeyConfig::default()
+        };
+        // macOS 按 VK 换算 CGKeyCode，vk 为 0 的字面量在那里也不可用
+        assert_eq!(
+            validate_trigger_action(&config).is_ok(),
+            !cfg!(target_os = "macos")
+        );
+        config.key_mode = KeyMode::Window;
+        assert!(validate_trigger_action(&config).is_err());
+    }
+
+    #[test]
+    fn mac_triggers_need_a_mac_keycode() {
+        let check = |label: &str| ensure_mac_keycode(label, keymap::resolve_key(label).unwrap());
+        assert!(check("F5").is_ok());
+        assert!(check("Num8").is_ok());
+        assert!(check("SC:0x64").is_err());
+        assert!(check("ScrollLock").is_err());
+        assert!(check("F20").is_ok());
+        assert!(check("F21").is_err());
+        assert!(check("F24").is_err());
+    }
+
+    #[test]
+    fn interval_bounds_are_inclusive() {
+        assert!(validate_config(&config_with_interval(HotkeyConfig::MIN_INTERVAL_MS)).is_ok());
+        assert!(validate_config(&config_with_interval(HotkeyConfig::MAX_INTERVAL_MS)).is_ok());
+    }
+
+    #[test]
+    fn max_duration_is_optional_but_has_a_floor() {
+        let mut config = config_with_interval(1000);
+        assert!(validate_config(&config).is_ok());
+        config.max_duration_secs = Some(HotkeyConfig::MIN_DURATION_SECS);
+        assert!(validate_config(&config).is_ok());
+        config.max_duration_secs = Some(1);
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn start_delay_is_capped() {
+        let mut config = config_with_interval(1000);
+        assert_eq!(config.start_delay_ms, 0);
+        config.start_delay_ms = HotkeyConfig::MAX_START_DELAY_MS;
+        assert!(validate_config(&config).is_ok());
+        config.start_delay_ms = HotkeyConfig::MAX_START_DELAY_MS + 1;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn hold_must_be_shorter_than_interval() {
+        let mut config = config_with_interval(1000);
+        config.hold_ms = 999;
+        assert!(validate_config(&config).is_ok());
+        config.hold_ms = 1000;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn press_gap_has_a_floor_and_stays_below_interval() {
+        let mut config = config_with_interval(100);
+        config.press_gap_ms = HotkeyConfig::MIN_PRESS_GAP_MS;
+        assert!(validate_config(&config).is_ok());
+        config.press_gap_ms = 0;
+        assert!(validate_config(&config).is_err());
+        config.press_gap_ms = 100;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn mouse_click_ignores_trigger_key_but_rejects_modifiers() {
+        let mut config = HotkeyConfig {
+            trigger_key: String::new(),
+            trigger_action: TriggerAction::MouseClick {
+                button: MouseButton::Left,
+            },
+            ..config_with_interval(1000)
+        };
+        assert!(validate_config(&config).is_ok());
+        config.trigger_key = "Ctrl".into();
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn interval_outside_bounds_is_rejected() {
+        assert!(validate_config(&config_with_interval(HotkeyConfig::MIN_INTERVAL_MS - 1)).is_err());
+        assert!(validate_config(&config_with_interval(HotkeyConfig::MAX_INTERVAL_MS + 1)).is_err());
+        assert!(validate_config(&config_with_interval(600_000)).is_err());
     }
-    Ok(())
 }
@@ -3,9 +3,20 @@
 //! Single source of truth mapping UI key labels (what the frontend records,
 //! e.g. "F5", "A", ";", "Up", "Ctrl+Alt+X") to:
 //! - Windows scancode + virtual-key code for key simulation (`resolve_key`)
+//! - macOS CGKeyCode for key simulation (`mac_keycode`, derived from the VK)
 //! - global-shortcut strings for hotkey registration (`label_to_shortcut`)
+//!
+//! Two escape hatches for keys the built-in table doesn't cover: a literal
+//! `SC:0x3B` label, and a user override table (`key_overrides.json`, loaded by
+//! the service at start-up) consulted before the built-in table. Both only
+//! affect simulation; hotkey registration still goes through the plugin's
+//! own key names.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
 
-use tauri_plugin_global_shortcut::Shortcut;
+use serde::Serialize;
+use tauri_plugin_global_shortcut::{Modifiers, Shortcut};
 
 use crate::error::{AppError, AppResult};
 
@@ -21,6 +32,79 @@ pub struct KeyDef {
     pub extended: bool,
 }
 
+impl KeyDef {
+    /// lParam for WM_KEYDOWN/WM_KEYUP: repeat count 1, scancode in bits 16-23,
+    /// extended flag in bit 24; releases also set bits 30/31 (previous state /
+    /// transition). Without bit 24 the game reads Right as numpad 6.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn message_lparam(&self, release: bool) -> u32 {
+        let mut lparam = 1 | (u32::from(self.scancode & 0xFF) << 16);
+        if self.extended {
+            lparam |= 1 << 24;
+        }
+        if release {
+            lparam |= 0xC000_0000;
+        }
+        lparam
+    }
+}
+
+/// User overrides keyed by uppercase label, merged over the built-in table
+static KEY_OVERRIDES: RwLock<BTreeMap<String, KeyDef>> = RwLock::new(BTreeMap::new());
+
+/// Highest set-1 make code; 0x80+ are break codes
+const MAX_SCANCODE: u16 = 0x7F;
+
+/// Replace the user override table (labels are matched case-insensitively)
+pub fn set_key_overrides(overrides: BTreeMap<String, KeyDef>) {
+    let overrides = overrides
+        .into_iter()
+        .map(|(label, def)| (label.trim().to_uppercase(), def))
+        .collect();
+    match KEY_OVERRIDES.write() {
+        Ok(mut guard) => *guard = overrides,
+        Err(poisoned) => *poisoned.into_inner() = overrides,
+    }
+}
+
+fn key_override(upper: &str) -> Option<KeyDef> {
+    KEY_OVERRIDES.read().ok()?.get(upper).copied()
+}
+
+/// Set-1 make codes only (0x01-0x7F)
+pub fn validate_scancode(scancode: u16) -> AppResult<()> {
+    if scancode == 0 || scancode > MAX_SCANCODE {
+        return Err(AppError::Hotkey(format!(
+            "扫描码超出范围: 0x{scancode:X}（应为 0x01-0x7F）"
+        )));
+    }
+    Ok(())
+}
+
+/// `SC:0x3B` / `SC:59`; an `0xE0` prefix (`SC:0xE048`) marks an extended key.
+/// The VK is unknown (0), so these keys only work where the scancode itself
+/// is sent (global mode). None when `upper` isn't an `SC:` label.
+fn parse_scancode_label(upper: &str) -> Option<AppResult<KeyDef>> {
+    let literal = upper.strip_prefix("SC:")?.trim();
+    let value = match literal.strip_prefix("0X") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => literal.parse::<u16>(),
+    };
+    let Ok(value) = value else {
+        return Some(Err(AppError::Hotkey(format!("扫描码格式无效: {literal}"))));
+    };
+    let (scancode, extended) = if value & 0xFF00 == 0xE000 {
+        (value & 0xFF, true)
+    } else {
+        (value, false)
+    };
+    Some(validate_scancode(scancode).map(|()| KeyDef {
+        scancode,
+        vk: 0,
+        extended,
+    }))
+}
+
 const fn key(scancode: u16, vk: u16) -> KeyDef {
     KeyDef { scancode, vk, extended: false }
 }
@@ -38,14 +122,18 @@ pub fn resolve_key(label: &str) -> AppResult<KeyDef> {
 
     let upper = trimmed.to_uppercase();
 
+    if let Some(def) = key_override(&upper) {
+        return Ok(def);
+    }
+    if let Some(def) = parse_scancode_label(&upper) {
+        return def;
+    }
+
     // Letters A-Z / digits 0-9
     if upper.len() == 1 {
         let ch = upper.chars().next().unwrap_or_default();
-        if ch.is_ascii_uppercase() {
-            return Ok(key(letter_scancode(ch), ch as u16));
-        }
-        if ch.is_ascii_digit() {
-            return Ok(key(digit_scancode(ch), ch as u16));
+        if let Some(scancode) = letter_scancode(ch).or_else(|| digit_scancode(ch)) {
+            return Ok(key(scancode, ch as u16));
         }
     }
 
@@ -63,13 +151,28 @@ pub fn resolve_key(label: &str) -> AppResult<KeyDef> {
         "F10" => key(0x44, 0x79),
         "F11" => key(0x57, 0x7A),
         "F12" => key(0x58, 0x7B),
+        // F13–F24 不带 E0 前缀，F24 的扫描码不连续
+        "F13" => key(0x64, 0x7C),
+        "F14" => key(0x65, 0x7D),
+        "F15" => key(0x66, 0x7E),
+        "F16" => key(0x67, 0x7F),
+        "F17" => key(0x68, 0x80),
+        "F18" => key(0x69, 0x81),
+        "F19" => key(0x6A, 0x82),
+        "F20" => key(0x6B, 0x83),
+        "F21" => key(0x6C, 0x84),
+        "F22" => key(0x6D, 0x85),
+        "F23" => key(0x6E, 0x86),
+        "F24" => key(0x76, 0x87),
         "BACKSPACE" => key(0x0E, 0x08),
         "TAB" => key(0x0F, 0x09),
         "ENTER" | "RETURN" => key(0x1C, 0x0D),
         "CTRL" | "CONTROL" | "LCTRL" => key(0x1D, 0x11),
+        "RCTRL" => ext_key(0x1D, 0xA3),
         "SHIFT" | "LSHIFT" => key(0x2A, 0x10),
         "RSHIFT" => key(0x36, 0xA1),
         "ALT" | "LALT" => key(0x38, 0x12),
+        "RALT" => ext_key(0x38, 0xA5),
         "SPACE" => key(0x39, 0x20),
         "CAPSLOCK" | "CAPS" => key(0x3A, 0x14),
         "NUMLOCK" => key(0x45, 0x90),
@@ -84,6 +187,13 @@ pub fn resolve_key(label: &str) -> AppResult<KeyDef> {
         "PAGEDOWN" => ext_key(0x51, 0x22),
         "INSERT" => ext_key(0x52, 0x2D),
         "DELETE" | "DEL" => ext_key(0x53, 0x2E),
+        // 系统键：能解析以便给出明确提示，但不能作为触发按键（见 RESERVED_KEYS）
+        "WIN" | "LWIN" | "WINDOWS" | "META" | "SUPER" => ext_key(0x5B, 0x5B),
+        "RWIN" => ext_key(0x5C, 0x5C),
+        "APPS" | "MENU" | "CONTEXTMENU" => ext_key(0x5D, 0x5D),
+        "POWER" => ext_key(0x5E, 0),
+        "SLEEP" => ext_key(0x5F, 0x5F),
+        "WAKE" => ext_key(0x63, 0),
         // Numpad
         "NUM0" | "NUMPAD0" => key(0x52, 0x60),
         "NUM1" | "NUMPAD1" => key(0x4F, 0x61),
@@ -100,6 +210,7 @@ pub fn resolve_key(label: &str) -> AppResult<KeyDef> {
         "NUMADD" | "NUMPLUS" => key(0x4E, 0x6B),
         "NUMDOT" | "NUMDECIMAL" => key(0x53, 0x6E),
         "NUMDIV" | "NUMSLASH" | "NUMDIVIDE" => ext_key(0x35, 0x6F),
+        "NUMENTER" | "NUMPADENTER" => ext_key(0x1C, 0x0D),
         // OEM keys
         ";" | "SEMICOLON" | "OEM1" => key(0x27, 0xBA),
         "=" | "EQUALS" | "OEMPLUS" => key(0x0D, 0xBB),
@@ -118,6 +229,224 @@ pub fn resolve_key(label: &str) -> AppResult<KeyDef> {
     Ok(def)
 }
 
+/// A key the OS acts on before any game sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReservedKey {
+    pub name: &'static str,
+    pub scancode: u16,
+    pub extended: bool,
+}
+
+/// Keys that can't be a trigger key: Windows handles them itself (Start menu,
+/// context menu, power/sleep/wake), so repeating one steals focus from the
+/// game or suspends the PC instead of reaching the target. Matched by
+/// scancode + extended flag, so `SC:` labels and `key_overrides.json` entries
+/// for the same physical key are rejected too.
+pub const RESERVED_KEYS: &[ReservedKey] = &[
+    ReservedKey {
+        name: "左 Win 键",
+        scancode: 0x5B,
+        extended: true,
+    },
+    ReservedKey {
+        name: "右 Win 键",
+        scancode: 0x5C,
+        extended: true,
+    },
+    ReservedKey {
+        name: "菜单键",
+        scancode: 0x5D,
+        extended: true,
+    },
+    ReservedKey {
+        name: "电源键",
+        scancode: 0x5E,
+        extended: true,
+    },
+    ReservedKey {
+        name: "睡眠键",
+        scancode: 0x5F,
+        extended: true,
+    },
+    ReservedKey {
+        name: "唤醒键",
+        scancode: 0x63,
+        extended: true,
+    },
+];
+
+/// The `RESERVED_KEYS` entry `key` is, if any
+pub fn reserved_key(key: KeyDef) -> Option<&'static ReservedKey> {
+    RESERVED_KEYS
+        .iter()
+        .find(|reserved| reserved.scancode == key.scancode && reserved.extended == key.extended)
+}
+
+/// Reject `key` (resolved from `label`) as a trigger if it is system-reserved
+pub fn ensure_not_reserved(label: &str, key: KeyDef) -> AppResult<()> {
+    match reserved_key(key) {
+        Some(reserved) => Err(AppError::Hotkey(format!(
+            "{} 是系统保留按键（{}），不能作为触发按键",
+            label.trim(),
+            reserved.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Resolve `label` for use as the trigger key: `resolve_key`, minus the
+/// system-reserved keys
+pub fn resolve_trigger_key(label: &str) -> AppResult<KeyDef> {
+    let key = resolve_key(label)?;
+    ensure_not_reserved(label, key)?;
+    Ok(key)
+}
+
+/// A section of the trigger-key picker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyGroup {
+    /// letters / digits / function / numpad / special / custom
+    pub id: String,
+    pub name: String,
+    /// Canonical labels, each accepted by `resolve_key`
+    pub keys: Vec<String>,
+    /// 选择器里显示在分组名旁的提示
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Canonical label of each `resolve_key` arm outside letters and digits
+/// (one spelling per key, matching what the frontend recorder produces).
+/// The test below keeps this in sync with the match.
+const FUNCTION_KEYS: &[&str] = &[
+    "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "F13", "F14", "F15",
+    "F16", "F17", "F18", "F19", "F20", "F21", "F22", "F23", "F24",
+];
+/// macOS has key codes up to F20 only (see `mac_keycode`)
+const MAC_FUNCTION_KEYS: usize = 20;
+/// F13 及以上多数键盘没有实体键，只能由游戏键盘的宏键或改键软件发出
+const FUNCTION_KEYS_NOTE: &str = "F13 及以上需键盘宏键或改键软件支持";
+const NUMPAD_KEYS: &[&str] = &[
+    "Num0", "Num1", "Num2", "Num3", "Num4", "Num5", "Num6", "Num7", "Num8", "Num9", "NumMul",
+    "NumSub", "NumAdd", "NumDot", "NumDiv", "NumEnter",
+];
+const SPECIAL_KEYS: &[&str] = &[
+    "Esc",
+    "Tab",
+    "CapsLock",
+    "Space",
+    "Enter",
+    "Backspace",
+    "Insert",
+    "Delete",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "NumLock",
+    "ScrollLock",
+    "Ctrl",
+    "RCtrl",
+    "Shift",
+    "RShift",
+    "Alt",
+    "RAlt",
+    ";",
+    "=",
+    ",",
+    "-",
+    ".",
+    "/",
+    "`",
+    "[",
+    "\\",
+    "]",
+    "'",
+];
+
+/// Every label the trigger key accepts, grouped for the picker. User
+/// overrides come last as their own group when any are loaded.
+pub fn supported_key_groups() -> Vec<KeyGroup> {
+    let group = |id: &str, name: &str, keys: Vec<String>| KeyGroup {
+        id: id.into(),
+        name: name.into(),
+        keys,
+        note: None,
+    };
+    let owned = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect();
+
+    let mut groups = vec![
+        group("letters", "字母", ('A'..='Z').map(String::from).collect()),
+        group("digits", "数字", ('0'..='9').map(String::from).collect()),
+        KeyGroup {
+            note: Some(FUNCTION_KEYS_NOTE.into()),
+            ..group("function", "功能键", owned(function_keys(MACOS)))
+        },
+        group("numpad", "小键盘", owned(NUMPAD_KEYS)),
+        group("special", "其他", owned(SPECIAL_KEYS)),
+    ];
+    let custom: Vec<String> = KEY_OVERRIDES
+        .read()
+        .map(|overrides| overrides.keys().cloned().collect())
+        .unwrap_or_default();
+    if !custom.is_empty() {
+        groups.push(group("custom", "自定义", custom));
+    }
+    groups
+}
+
+const MACOS: bool = cfg!(target_os = "macos");
+
+/// Function keys the picker offers: F1–F24, but only F1–F20 on macOS, which
+/// couldn't simulate F21–F24 anyway
+fn function_keys(macos: bool) -> &'static [&'static str] {
+    if macos {
+        &FUNCTION_KEYS[..MAC_FUNCTION_KEYS]
+    } else {
+        FUNCTION_KEYS
+    }
+}
+
+/// Whether `label` is a bare modifier key (Ctrl/Shift/Alt/Win)
+pub fn is_modifier_label(label: &str) -> bool {
+    matches!(
+        label.trim().to_uppercase().as_str(),
+        "CTRL"
+            | "CONTROL"
+            | "LCTRL"
+            | "RCTRL"
+            | "SHIFT"
+            | "LSHIFT"
+            | "RSHIFT"
+            | "ALT"
+            | "LALT"
+            | "RALT"
+            | "OPTION"
+            | "WIN"
+            | "WINDOWS"
+            | "META"
+            | "CMD"
+            | "COMMAND"
+            | "SUPER"
+    )
+}
+
+/// The shortcut modifier flag a bare modifier label stands for (either side)
+pub fn modifier_flag(label: &str) -> Option<Modifiers> {
+    match label.trim().to_uppercase().as_str() {
+        "CTRL" | "CONTROL" | "LCTRL" | "RCTRL" => Some(Modifiers::CONTROL),
+        "SHIFT" | "LSHIFT" | "RSHIFT" => Some(Modifiers::SHIFT),
+        "ALT" | "LALT" | "RALT" | "OPTION" => Some(Modifiers::ALT),
+        "WIN" | "WINDOWS" | "META" | "CMD" | "COMMAND" | "SUPER" => Some(Modifiers::SUPER),
+        _ => None,
+    }
+}
+
 /// Convert a hotkey label (single key or "Ctrl+Alt+X" combo) to a
 /// global-shortcut string. Key token validity is checked when the caller
 /// parses the result into a `Shortcut`.
@@ -159,51 +488,173 @@ pub fn parse_shortcut(label: &str) -> AppResult<Shortcut> {
         .map_err(|e| AppError::Hotkey(format!("无效的热键 {}: {e}", label.trim())))
 }
 
-fn letter_scancode(ch: char) -> u16 {
+/// macOS virtual keycode (CGKeyCode, `kVK_*` in HIToolbox/Events.h) for a
+/// resolved key, keyed by its Windows VK. None for keys with no Mac
+/// equivalent (e.g. ScrollLock).
+#[cfg_attr(not(any(target_os = "macos", test)), allow(dead_code))]
+pub fn mac_keycode(key: KeyDef) -> Option<u16> {
+    // 小键盘回车与主回车同 VK，只能靠扩展标志区分
+    if key.vk == 0x0D && key.extended {
+        return Some(0x4C); // kVK_ANSI_KeypadEnter
+    }
+    let code = match key.vk {
+        0x41 => 0x00, // A
+        0x42 => 0x0B, // B
+        0x43 => 0x08, // C
+        0x44 => 0x02, // D
+        0x45 => 0x0E, // E
+        0x46 => 0x03, // F
+        0x47 => 0x05, // G
+        0x48 => 0x04, // H
+        0x49 => 0x22, // I
+        0x4A => 0x26, // J
+        0x4B => 0x28, // K
+        0x4C => 0x25, // L
+        0x4D => 0x2E, // M
+        0x4E => 0x2D, // N
+        0x4F => 0x1F, // O
+        0x50 => 0x23, // P
+        0x51 => 0x0C, // Q
+        0x52 => 0x0F, // R
+        0x53 => 0x01, // S
+        0x54 => 0x11, // T
+        0x55 => 0x20, // U
+        0x56 => 0x09, // V
+        0x57 => 0x0D, // W
+        0x58 => 0x07, // X
+        0x59 => 0x10, // Y
+        0x5A => 0x06, // Z
+        0x30 => 0x1D, // 0
+        0x31 => 0x12, // 1
+        0x32 => 0x13, // 2
+        0x33 => 0x14, // 3
+        0x34 => 0x15, // 4
+        0x35 => 0x17, // 5
+        0x36 => 0x16, // 6
+        0x37 => 0x1A, // 7
+        0x38 => 0x1C, // 8
+        0x39 => 0x19, // 9
+        0x1B => 0x35, // Esc
+        0x70 => 0x7A, // F1
+        0x71 => 0x78, // F2
+        0x72 => 0x63, // F3
+        0x73 => 0x76, // F4
+        0x74 => 0x60, // F5
+        0x75 => 0x61, // F6
+        0x76 => 0x62, // F7
+        0x77 => 0x64, // F8
+        0x78 => 0x65, // F9
+        0x79 => 0x6D, // F10
+        0x7A => 0x67, // F11
+        0x7B => 0x6F, // F12
+        0x7C => 0x69, // F13
+        0x7D => 0x6B, // F14
+        0x7E => 0x71, // F15
+        0x7F => 0x6A, // F16
+        0x80 => 0x40, // F17
+        0x81 => 0x4F, // F18
+        0x82 => 0x50, // F19
+        0x83 => 0x5A, // F20
+        0x08 => 0x33, // Backspace (kVK_Delete)
+        0x09 => 0x30, // Tab
+        0x0D => 0x24, // Enter
+        0x11 => 0x3B, // Ctrl
+        0x10 => 0x38, // Shift
+        0xA1 => 0x3C, // RShift
+        0xA3 => 0x3E, // RCtrl
+        0xA5 => 0x3D, // RAlt (Right Option)
+        0x12 => 0x3A, // Alt (Option)
+        0x20 => 0x31, // Space
+        0x14 => 0x39, // CapsLock
+        0x90 => 0x47, // NumLock (kVK_ANSI_KeypadClear)
+        0x24 => 0x73, // Home
+        0x26 => 0x7E, // Up
+        0x21 => 0x74, // PageUp
+        0x25 => 0x7B, // Left
+        0x27 => 0x7C, // Right
+        0x23 => 0x77, // End
+        0x28 => 0x7D, // Down
+        0x22 => 0x79, // PageDown
+        0x2D => 0x72, // Insert (kVK_Help)
+        0x2E => 0x75, // Delete (kVK_ForwardDelete)
+        0x60 => 0x52, // Num0
+        0x61 => 0x53, // Num1
+        0x62 => 0x54, // Num2
+        0x63 => 0x55, // Num3
+        0x64 => 0x56, // Num4
+        0x65 => 0x57, // Num5
+        0x66 => 0x58, // Num6
+        0x67 => 0x59, // Num7
+        0x68 => 0x5B, // Num8
+        0x69 => 0x5C, // Num9
+        0x6A => 0x43, // NumMultiply
+        0x6D => 0x4E, // NumMinus
+        0x6B => 0x45, // NumPlus
+        0x6E => 0x41, // NumDecimal
+        0x6F => 0x4B, // NumDivide
+        0xBA => 0x29, // ;
+        0xBB => 0x18, // =
+        0xBC => 0x2B, // ,
+        0xBD => 0x1B, // -
+        0xBE => 0x2F, // .
+        0xBF => 0x2C, // /
+        0xC0 => 0x32, // `
+        0xDB => 0x21, // [
+        0xDC => 0x2A, // \
+        0xDD => 0x1E, // ]
+        0xDE => 0x27, // '
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Set-1 scancode of an uppercase ASCII letter
+fn letter_scancode(ch: char) -> Option<u16> {
     match ch {
-        'A' => 0x1E,
-        'B' => 0x30,
-        'C' => 0x2E,
-        'D' => 0x20,
-        'E' => 0x12,
-        'F' => 0x21,
-        'G' => 0x22,
-        'H' => 0x23,
-        'I' => 0x17,
-        'J' => 0x24,
-        'K' => 0x25,
-        'L' => 0x26,
-        'M' => 0x32,
-        'N' => 0x31,
-        'O' => 0x18,
-        'P' => 0x19,
-        'Q' => 0x10,
-        'R' => 0x13,
-        'S' => 0x1F,
-        'T' => 0x14,
-        'U' => 0x16,
-        'V' => 0x2F,
-        'W' => 0x11,
-        'X' => 0x2D,
-        'Y' => 0x15,
-        'Z' => 0x2C,
-        _ => 0,
+        'A' => Some(0x1E),
+        'B' => Some(0x30),
+        'C' => Some(0x2E),
+        'D' => Some(0x20),
+        'E' => Some(0x12),
+        'F' => Some(0x21),
+        'G' => Some(0x22),
+        'H' => Some(0x23),
+        'I' => Some(0x17),
+        'J' => Some(0x24),
+        'K' => Some(0x25),
+        'L' => Some(0x26),
+        'M' => Some(0x32),
+        'N' => Some(0x31),
+        'O' => Some(0x18),
+        'P' => Some(0x19),
+        'Q' => Some(0x10),
+        'R' => Some(0x13),
+        'S' => Some(0x1F),
+        'T' => Some(0x14),
+        'U' => Some(0x16),
+        'V' => Some(0x2F),
+        'W' => Some(0x11),
+        'X' => Some(0x2D),
+        'Y' => Some(0x15),
+        'Z' => Some(0x2C),
+        _ => None,
     }
 }
 
-fn digit_scancode(ch: char) -> u16 {
+/// Set-1 scancode of an ASCII digit
+fn digit_scancode(ch: char) -> Option<u16> {
     match ch {
-        '0' => 0x0B,
-        '1' => 0x02,
-        '2' => 0x03,
-        '3' => 0x04,
-        '4' => 0x05,
-        '5' => 0x06,
-        '6' => 0x07,
-        '7' => 0x08,
-        '8' => 0x09,
-        '9' => 0x0A,
-        _ => 0,
+        '0' => Some(0x0B),
+        '1' => Some(0x02),
+        '2' => Some(0x03),
+        '3' => Some(0x04),
+        '4' => Some(0x05),
+        '5' => Some(0x06),
+        '6' => Some(0x07),
+        '7' => Some(0x08),
+        '8' => Some(0x09),
+        '9' => Some(0x0A),
+        _ => None,
     }
 }
 
@@ -217,6 +668,24 @@ mod tests {
         assert_eq!(resolve_key("5").unwrap(), key(0x06, 0x35));
         assert_eq!(resolve_key("F5").unwrap(), key(0x3F, 0x74));
         assert_eq!(resolve_key(" f11 ").unwrap(), key(0x57, 0x7A));
+        assert_eq!(resolve_key("F13").unwrap(), key(0x64, 0x7C));
+        assert_eq!(resolve_key("f23").unwrap(), key(0x6E, 0x86));
+        assert_eq!(resolve_key("F24").unwrap(), key(0x76, 0x87));
+    }
+
+    #[test]
+    fn letter_and_digit_helpers_have_no_zero_sentinel() {
+        for ch in ('A'..='Z').chain('0'..='9') {
+            let scancode = letter_scancode(ch).or_else(|| digit_scancode(ch));
+            assert!(
+                scancode.is_some_and(|sc| validate_scancode(sc).is_ok()),
+                "{ch}"
+            );
+        }
+        assert_eq!(letter_scancode('1'), None);
+        assert_eq!(digit_scancode('A'), None);
+        assert_eq!(letter_scancode('a'), None);
+        assert!(validate_scancode(0).is_err());
     }
 
     #[test]
@@ -228,16 +697,125 @@ mod tests {
         assert!(!resolve_key("Num8").unwrap().extended);
     }
 
+    #[test]
+    fn arrows_and_numpad_share_scancodes_but_not_the_extended_flag() {
+        for (arrow, numpad) in [
+            ("Up", "Num8"),
+            ("Left", "Num4"),
+            ("Right", "Num6"),
+            ("Down", "Num2"),
+        ] {
+            let arrow = resolve_key(arrow).unwrap();
+            let numpad = resolve_key(numpad).unwrap();
+            assert_eq!(arrow.scancode, numpad.scancode);
+            assert!(arrow.extended && !numpad.extended);
+            assert_ne!(arrow.vk, numpad.vk);
+        }
+        let (enter, num_enter) = (
+            resolve_key("Enter").unwrap(),
+            resolve_key("NumEnter").unwrap(),
+        );
+        assert_eq!(enter.scancode, num_enter.scancode);
+        assert!(num_enter.extended && !enter.extended);
+        assert!(resolve_key("RCtrl").unwrap().extended);
+        assert!(!resolve_key("Ctrl").unwrap().extended);
+    }
+
+    #[test]
+    fn message_lparam_carries_scancode_and_extended_bit() {
+        let right = resolve_key("Right").unwrap();
+        assert_eq!(right.message_lparam(false), 0x014D_0001);
+        assert_eq!(right.message_lparam(true), 0xC14D_0001);
+        let num6 = resolve_key("Num6").unwrap();
+        assert_eq!(num6.message_lparam(false), 0x004D_0001);
+    }
+
     #[test]
     fn resolve_rejects_unknown_and_empty_labels() {
         assert!(resolve_key("").is_err());
-        assert!(resolve_key("F13").is_err());
+        assert!(resolve_key("F25").is_err());
         assert!(resolve_key("Ctrl+A").is_err());
     }
 
+    #[test]
+    fn scancode_literals_parse_hex_decimal_and_extended() {
+        assert_eq!(
+            resolve_key("SC:0x3B").unwrap(),
+            KeyDef {
+                scancode: 0x3B,
+                vk: 0,
+                extended: false
+            }
+        );
+        assert_eq!(resolve_key("sc:59").unwrap().scancode, 0x3B);
+        let up = resolve_key("SC:0xE048").unwrap();
+        assert_eq!((up.scancode, up.extended), (0x48, true));
+        assert!(resolve_key("SC:0x00").is_err());
+        assert!(resolve_key("SC:0x80").is_err());
+        assert!(resolve_key("SC:0x1FF").is_err());
+        assert!(resolve_key("SC:zz").is_err());
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_the_builtin_table() {
+        // 标签只在本测试使用，避免与并行测试互相影响
+        assert!(resolve_key("TestOverrideKey").is_err());
+        let mut overrides = BTreeMap::new();
+        overrides.insert(" testOverrideKey ".to_string(), ext_key(0x64, 0x7C));
+        set_key_overrides(overrides);
+        assert_eq!(resolve_key("TESTOVERRIDEKEY").unwrap(), ext_key(0x64, 0x7C));
+        set_key_overrides(BTreeMap::new());
+        assert!(resolve_key("TestOverrideKey").is_err());
+    }
+
+    #[test]
+    fn reserved_keys_are_rejected_as_triggers_however_spelled() {
+        assert!(resolve_trigger_key("F5").is_ok());
+        assert!(resolve_trigger_key("Delete").is_ok());
+        for label in ["Win", "lwin", "RWin", "Apps", "Sleep", "Power", "SC:0xE05B"] {
+            assert!(resolve_key(label).is_ok(), "{label} 应能解析");
+            assert!(resolve_trigger_key(label).is_err(), "{label} 应被拒绝");
+        }
+        // 非扩展的 0x5B 不是 Win 键
+        assert!(resolve_trigger_key("SC:0x5B").is_ok());
+        for reserved in RESERVED_KEYS {
+            assert_eq!(
+                reserved_key(KeyDef {
+                    scancode: reserved.scancode,
+                    vk: 0,
+                    extended: reserved.extended,
+                }),
+                Some(reserved)
+            );
+        }
+    }
+
+    #[test]
+    fn supported_keys_all_resolve_and_are_unique() {
+        let groups = supported_key_groups();
+        let ids: Vec<&str> = groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(
+            &ids[..5],
+            ["letters", "digits", "function", "numpad", "special"]
+        );
+
+        let mut seen: Vec<KeyDef> = Vec::new();
+        for key in groups.iter().flat_map(|g| &g.keys) {
+            let def = resolve_key(key).unwrap_or_else(|e| panic!("{key}: {e}"));
+            assert!(!seen.contains(&def), "{key} 与其他条目解析为同一个按键");
+            seen.push(def);
+        }
+        // 每个标签都解析为不同的按键（没有别名重复列出）
+        assert_eq!(
+            seen.len(),
+            36 + function_keys(MACOS).len() + NUMPAD_KEYS.len() + SPECIAL_KEYS.len()
+        );
+    }
+
     #[test]
     fn shortcut_passes_single_keys_through() {
         assert_eq!(label_to_shortcut("F11").unwrap(), "F11");
+        assert_eq!(label_to_shortcut("F24").unwrap(), "F24");
         assert_eq!(label_to_shortcut("a").unwrap(), "a");
         assert_eq!(label_to_shortcut(";").unwrap(), ";");
     }
@@ -259,6 +837,7 @@ mod tests {
     #[test]
     fn parse_shortcut_accepts_frontend_labels() {
         assert!(parse_shortcut("F11").is_ok());
+        assert!(parse_shortcut("F24").is_ok());
         assert!(parse_shortcut("Ctrl+Alt+F5").is_ok());
         assert!(parse_shortcut("Win+P").is_ok());
         assert!(parse_shortcut("Up").is_ok());
@@ -278,4 +857,36 @@ mod tests {
             parse_shortcut("ctrl+a").unwrap()
         );
     }
+
+    #[test]
+    fn modifier_labels_are_detected() {
+        assert!(is_modifier_label("ctrl"));
+        assert!(is_modifier_label(" Shift "));
+        assert!(!is_modifier_label("A"));
+        assert!(!is_modifier_label("Ctrl+A"));
+        assert!(!is_modifier_label(""));
+        assert_eq!(modifier_flag(" rshift "), Some(Modifiers::SHIFT));
+        assert_eq!(modifier_flag("Win"), Some(Modifiers::SUPER));
+        assert_eq!(modifier_flag("A"), None);
+    }
+
+    #[test]
+    fn mac_keycodes_cover_common_keys() {
+        let mac = |label: &str| mac_keycode(resolve_key(label).unwrap());
+        assert_eq!(mac("A"), Some(0x00));
+        assert_eq!(mac("5"), Some(0x17));
+        assert_eq!(mac("F5"), Some(0x60));
+        assert_eq!(mac("Up"), Some(0x7E));
+        assert_eq!(mac("Num8"), Some(0x5B));
+        assert_eq!(mac(";"), Some(0x29));
+        assert_eq!(mac("ScrollLock"), None);
+        assert_eq!(mac("F13"), Some(0x69));
+        assert_eq!(mac("F20"), Some(0x5A));
+        assert_eq!(mac("F21"), None);
+        // macOS 的功能键分组只列出有键码的 F1–F20
+        let mac_keys = function_keys(true);
+        assert_eq!(mac_keys.last(), Some(&"F20"));
+        assert!(mac_keys.iter().all(|key| mac(key).is_some()));
+        assert_eq!(function_keys(false).last(), Some(&"F24"));
+    }
 }
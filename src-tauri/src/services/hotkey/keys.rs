@@ -9,13 +9,15 @@
 //! driver.rs），dll 在这种部署下必然失败。注入协议非常简单（对
 //! `\\.\interception0N` 设备发 IOCTL_WRITE + KEYBOARD_INPUT_DATA，已从官方
 //! 库源码确认），这里直接实现，顺带去掉了 DLL 分发与 /DELAYLOAD 防崩 hack。
+//!
+//! 鼠标点击是例外：只装了键盘过滤器，没有鼠标设备可注入，只能走 SendInput。
+//! 这类输入可能被 TP 过滤，游戏内点击更推荐窗口模式。按键也可按配置
+//! （`SendBackend`）改走 SendInput，用于与其他键盘驱动冲突的机器。
 
 #![cfg(target_os = "windows")]
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use windows::core::PCWSTR;
 use windows::Win32::Foundation::{CloseHandle, GENERIC_READ, HANDLE};
@@ -23,8 +25,15 @@ use windows::Win32::Storage::FileSystem::{
     CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_NONE, OPEN_EXISTING,
 };
 use windows::Win32::System::IO::DeviceIoControl;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+    KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, MOUSEEVENTF_LEFTDOWN,
+    MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_RIGHTDOWN,
+    MOUSEEVENTF_RIGHTUP, MOUSEINPUT, MOUSE_EVENT_FLAGS,
+};
 
-use super::keymap::KeyDef;
+use super::keymap::{self, KeyDef};
+use super::types::{hold_for, InterceptionDevice, MouseButton, SendBackend};
 use crate::error::{AppError, AppResult};
 
 /// 按键驱动状态
@@ -39,6 +48,8 @@ const KEYBOARD_DEVICE_COUNT: usize = 10;
 
 /// IOCTL_WRITE = CTL_CODE(FILE_DEVICE_UNKNOWN, 0x820, METHOD_BUFFERED, FILE_ANY_ACCESS)
 const IOCTL_WRITE: u32 = 0x0022_2080;
+/// IOCTL_GET_HARDWARE_ID = CTL_CODE(FILE_DEVICE_UNKNOWN, 0x880, METHOD_BUFFERED, FILE_ANY_ACCESS)
+const IOCTL_GET_HARDWARE_ID: u32 = 0x0022_2200;
 
 /// 驱动侧的 KEYBOARD_INPUT_DATA（12 字节，布局见 wdm.h / interception 库源码）
 #[repr(C)]
@@ -58,6 +69,7 @@ const KEY_E0: u16 = 0x02;
 /// 已打开的键盘设备句柄。HANDLE 是裸指针不自动 Send/Sync，但内核对象句柄
 /// 本身可跨线程使用，DeviceIoControl 也是线程安全的内核调用。
 struct Device {
+    index: usize,
     handle: HANDLE,
 }
 
@@ -76,6 +88,12 @@ impl Drop for Device {
 static SENDER: Mutex<Option<Sender>> = Mutex::new(None);
 /// 标记是否已完成首次探测
 static PROBED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+/// 只向该序号的设备注入；-1 = 全部设备。由 `set_device_filter` 在启动/测试前设置
+static DEVICE_FILTER: AtomicI32 = AtomicI32::new(-1);
+/// 串行化一次完整的 按下 + 按住 + 释放。运行循环与测试按键等多个入口可能同时发送，
+/// 不加锁时两段序列会交错（A 按下、B 按下、A 释放…），表现为按键卡住。
+/// 只包住单次按键，不包住循环间隔；与 SENDER 分开，长按期间驱动状态查询不受影响
+static SEND_LOCK: Mutex<()> = Mutex::new(());
 
 struct Sender {
     devices: Vec<Device>,
@@ -97,7 +115,7 @@ fn open_keyboard_device(index: usize) -> Result<Device, windows::core::Error> {
             None,
         )
     }?;
-    Ok(Device { handle })
+    Ok(Device { index, handle })
 }
 
 fn init_sender() -> Option<Sender> {
@@ -128,7 +146,18 @@ fn init_sender() -> Option<Sender> {
         }
         return None;
     }
-    log::info!("Interception 键盘设备已打开（{} 个）", devices.len());
+    // 槽位与物理键盘的对应因机器而异：记下哪些槽位挂着真实键盘，便于排查
+    // "按键不生效"（注入默认写全部设备，不依赖这一结果）
+    let keyboards: Vec<usize> = devices
+        .iter()
+        .filter(|device| hardware_id(device).is_some())
+        .map(|device| device.index)
+        .collect();
+    log::info!(
+        "Interception 键盘设备已打开（{} 个），挂有键盘的槽位: {:?}",
+        devices.len(),
+        keyboards
+    );
     Some(Sender { devices })
 }
 
@@ -149,6 +178,14 @@ where
     f(guard.as_ref())
 }
 
+/// 独占发送通道直到返回的 guard 释放。锁里没有数据，持锁线程 panic 后照常可用
+fn lock_send() -> MutexGuard<'static, ()> {
+    match SEND_LOCK.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
 /// 强制重新探测 interception 设备（安装/卸载驱动后调用）
 pub fn reprobe() {
     PROBED.store(false, Ordering::Release);
@@ -157,6 +194,51 @@ pub fn reprobe() {
     PROBED.store(true, Ordering::Release);
 }
 
+/// 限定注入的键盘设备序号（0–9）；None 恢复为注入全部设备
+pub fn set_device_filter(index: Option<i32>) {
+    DEVICE_FILTER.store(index.unwrap_or(-1), Ordering::Relaxed);
+}
+
+/// 已打开的键盘设备及其挂接的硬件 ID（空槽位没有硬件 ID），供用户挑选注入设备
+pub fn list_devices() -> Vec<InterceptionDevice> {
+    with_sender(|s| {
+        s.map(|sender| {
+            sender
+                .devices
+                .iter()
+                .map(|device| InterceptionDevice {
+                    index: device.index as i32,
+                    hardware_id: hardware_id(device),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+    })
+}
+
+/// 设备槽位上真实键盘的硬件 ID（REG_MULTI_SZ，取第一项）；槽位为空时驱动不返回数据
+fn hardware_id(device: &Device) -> Option<String> {
+    let mut buffer = [0u16; 500];
+    let mut returned: u32 = 0;
+    unsafe {
+        DeviceIoControl(
+            device.handle,
+            IOCTL_GET_HARDWARE_ID,
+            None,
+            0,
+            Some(buffer.as_mut_ptr().cast()),
+            std::mem::size_of_val(&buffer) as u32,
+            Some(&mut returned),
+            None,
+        )
+    }
+    .ok()?;
+    let units = (returned as usize / 2).min(buffer.len());
+    let first = buffer[..units].split(|&c| c == 0).next()?;
+    let id = String::from_utf16_lossy(first);
+    (!id.is_empty()).then_some(id)
+}
+
 /// 查询按键驱动是否就绪（键盘设备可打开 = 内核驱动已加载）
 pub fn driver_status() -> DriverStatus {
     with_sender(|s| {
@@ -188,39 +270,48 @@ impl Sender {
         ok.is_ok() && written == size
     }
 
-    /// 向指定设备发送一次按下+释放，返回 keydown 是否注入成功
-    fn try_send(&self, device: &Device, scancode: u16, extended: bool) -> bool {
-        let base = if extended { KEY_E0 } else { 0 };
-        let stroke = |flags: u16| KeyboardInputData {
+    /// 本次注入的目标设备：设置了 `DEVICE_FILTER` 时只有该序号的设备
+    fn targets(&self) -> impl Iterator<Item = &Device> {
+        let filter = DEVICE_FILTER.load(Ordering::Relaxed);
+        self.devices
+            .iter()
+            .filter(move |device| filter < 0 || device.index as i32 == filter)
+    }
+
+    /// 向目标设备写入 `key` 的一次按下（`release` 为 false）或释放，返回成功的设备数
+    fn write_key(&self, key: KeyDef, release: bool) -> usize {
+        let mut flags = if key.extended { KEY_E0 } else { 0 };
+        if release {
+            flags |= KEY_BREAK;
+        }
+        let stroke = KeyboardInputData {
             unit_id: 0,
-            make_code: scancode,
+            make_code: key.scancode,
             flags,
             reserved: 0,
             extra_information: 0,
         };
-
-        if !self.write_strokes(device, &[stroke(base)]) {
-            return false;
-        }
-        thread::sleep(Duration::from_millis(10));
-        self.write_strokes(device, &[stroke(base | KEY_BREAK)]);
-        true
+        self.targets()
+            .filter(|device| self.write_strokes(device, &[stroke]))
+            .count()
     }
 
-    fn send_key(&self, key: KeyDef) -> AppResult<()> {
-        // 注入到所有已打开的键盘设备：真实键盘所在的槽位必定收到，空槽位无害。
+    fn key_down(&self, key: KeyDef) -> AppResult<()> {
+        // 默认注入到所有已打开的键盘设备：真实键盘所在的槽位必定收到，空槽位无害。
         // 不再"写成功第一个就停"——部分设备会接受写入却不产生真实输入，停在那种
-        // 设备上会表现为"已启动却无效果"。
-        let mut success = 0usize;
-        for device in &self.devices {
-            if self.try_send(device, key.scancode, key.extended) {
-                success += 1;
-            }
+        // 设备上会表现为"已启动却无效果"。接了多把键盘时可用 DEVICE_FILTER 限定一把
+        let targets = self.targets().count();
+        if targets == 0 {
+            return Err(AppError::Hotkey(format!(
+                "未找到 Interception 键盘设备 {}，请重新选择注入设备",
+                DEVICE_FILTER.load(Ordering::Relaxed)
+            )));
         }
+        let success = self.write_key(key, false);
         log::debug!(
             "注入 scancode={:#06x} 到 {} 个设备，成功 {}",
             key.scancode,
-            self.devices.len(),
+            targets,
             success
         );
         if success > 0 {
@@ -233,22 +324,117 @@ impl Sender {
     }
 }
 
-/// 模拟按键点击（按下 + 释放），经 Interception 内核注入
-pub fn simulate_key_press(key: KeyDef) -> AppResult<()> {
+/// 模拟按键点击（按下 + 按住 `hold_ms` + 释放），按 `backend` 经 Interception
+/// 内核注入或 SendInput 发出。整个序列持有 SEND_LOCK，与其他入口的发送不交错；
+/// 按住期间不持有 SENDER 锁，长按不会卡住驱动状态查询。
+/// 扫描码为 0 等无效值直接报错，不会静默发出一个不存在的键
+pub fn simulate_key_press(
+    key: KeyDef,
+    backend: SendBackend,
+    hold_ms: u64,
+    stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    keymap::validate_scancode(key.scancode)?;
+    let _send = lock_send();
+    if backend.resolve(driver_status() == DriverStatus::Ready) == Some(SendBackend::SendInput) {
+        send_key_input(key, false)?;
+        hold_for(hold_ms, stop_flag);
+        return send_key_input(key, true);
+    }
+    with_sender(|s| match s {
+        Some(sender) => sender.key_down(key),
+        None => Err(AppError::driver_missing(
+            "按键驱动未就绪，请先在按键页面安装驱动",
+        )),
+    })?;
+    hold_for(hold_ms, stop_flag);
+    // 被停止打断也要释放，避免按键卡在按下状态
+    with_sender(|s| {
+        if let Some(sender) = s {
+            sender.write_key(key, true);
+        }
+    });
+    Ok(())
+}
+
+/// 只按下（`down`）或只释放 `key`，用于整个运行期间按住的修饰键。
+/// 与 `simulate_key_press` 走同一后端、同一把 SEND_LOCK，但只持锁发这一个事件
+pub fn set_key_held(key: KeyDef, backend: SendBackend, down: bool) -> AppResult<()> {
+    keymap::validate_scancode(key.scancode)?;
+    let _send = lock_send();
+    if backend.resolve(driver_status() == DriverStatus::Ready) == Some(SendBackend::SendInput) {
+        return send_key_input(key, !down);
+    }
     with_sender(|s| match s {
-        Some(sender) => sender.send_key(key),
-        None => Err(AppError::Hotkey(
-            "按键驱动未就绪，请先在按键页面安装驱动".into(),
+        Some(sender) if down => sender.key_down(key),
+        Some(sender) => {
+            sender.write_key(key, true);
+            Ok(())
+        }
+        None => Err(AppError::driver_missing(
+            "按键驱动未就绪，请先在按键页面安装驱动",
         )),
     })
 }
 
-/// Sleep with interrupt capability
-pub fn sleep_with_interrupt(flag: &Arc<AtomicBool>, total_ms: u64) {
-    let mut remaining = if total_ms == 0 { 1 } else { total_ms };
-    while remaining > 0 && !flag.load(Ordering::SeqCst) {
-        let step = remaining.min(50);
-        thread::sleep(Duration::from_millis(step));
-        remaining = remaining.saturating_sub(step);
+/// 经 SendInput 发出 `key` 的一次按下或释放（按扫描码，与 Interception 注入的键一致）
+fn send_key_input(key: KeyDef, release: bool) -> AppResult<()> {
+    let mut flags: KEYBD_EVENT_FLAGS = KEYEVENTF_SCANCODE;
+    if key.extended {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    if release {
+        flags |= KEYEVENTF_KEYUP;
+    }
+    let input = INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wScan: key.scancode,
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
+    };
+    let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+    if sent == 1 {
+        Ok(())
+    } else {
+        Err(AppError::Hotkey("SendInput 发送按键失败".into()))
+    }
+}
+
+/// 模拟鼠标点击（按下 + 释放），在当前光标位置经 SendInput 发出
+pub fn simulate_mouse_click(
+    button: MouseButton,
+    hold_ms: u64,
+    stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    let (down, up) = match button {
+        MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+        MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+        MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+    };
+    let _send = lock_send();
+    send_mouse_input(down)?;
+    hold_for(hold_ms, stop_flag);
+    send_mouse_input(up)
+}
+
+fn send_mouse_input(flags: MOUSE_EVENT_FLAGS) -> AppResult<()> {
+    let input = INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
+    };
+    let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+    if sent == 1 {
+        Ok(())
+    } else {
+        Err(AppError::Hotkey("SendInput 发送鼠标点击失败".into()))
     }
 }
@@ -0,0 +1,93 @@
+//! 按键模拟模块（macOS）- 经 CGEvent 向 HID 事件流投递按键
+//!
+//! 仅用于在 macOS 开发机上跑通全局模式；窗口模式仍只支持 Windows。
+//! 投递合成事件需要在「系统设置 → 隐私与安全性 → 辅助功能」中授权本程序，
+//! 未授权时 CGEventPost 不会报错，只是事件被系统静默丢弃。
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use core_graphics::event::{CGEvent, CGEventTapLocation, CGEventType, CGMouseButton};
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+use super::keymap::{self, KeyDef};
+use super::types::{hold_for, MouseButton, SendBackend};
+use crate::error::{AppError, AppResult};
+
+/// Press `key`, hold it for `hold_ms` and release. `_backend` only applies
+/// on Windows; macOS always posts CGEvents
+pub fn simulate_key_press(
+    key: KeyDef,
+    _backend: SendBackend,
+    hold_ms: u64,
+    stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    let keycode = keymap::mac_keycode(key)
+        .ok_or_else(|| AppError::Hotkey("该按键在 macOS 上没有对应键码".into()))?;
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| AppError::Hotkey("创建 CGEventSource 失败".into()))?;
+
+    for key_down in [true, false] {
+        let event = CGEvent::new_keyboard_event(source.clone(), keycode, key_down)
+            .map_err(|_| AppError::Hotkey("创建按键事件失败".into()))?;
+        event.post(CGEventTapLocation::HID);
+        if key_down {
+            hold_for(hold_ms, stop_flag);
+        }
+    }
+    Ok(())
+}
+
+/// Only press (`down`) or only release `key`, for a modifier held across a
+/// whole run
+pub fn set_key_held(key: KeyDef, _backend: SendBackend, down: bool) -> AppResult<()> {
+    let keycode = keymap::mac_keycode(key)
+        .ok_or_else(|| AppError::Hotkey("该按键在 macOS 上没有对应键码".into()))?;
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| AppError::Hotkey("创建 CGEventSource 失败".into()))?;
+    CGEvent::new_keyboard_event(source, keycode, down)
+        .map_err(|_| AppError::Hotkey("创建按键事件失败".into()))?
+        .post(CGEventTapLocation::HID);
+    Ok(())
+}
+
+/// Click `button` once at the current cursor position, holding it `hold_ms`
+pub fn simulate_mouse_click(
+    button: MouseButton,
+    hold_ms: u64,
+    stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    let (down, up, cg_button) = match button {
+        MouseButton::Left => (
+            CGEventType::LeftMouseDown,
+            CGEventType::LeftMouseUp,
+            CGMouseButton::Left,
+        ),
+        MouseButton::Right => (
+            CGEventType::RightMouseDown,
+            CGEventType::RightMouseUp,
+            CGMouseButton::Right,
+        ),
+        MouseButton::Middle => (
+            CGEventType::OtherMouseDown,
+            CGEventType::OtherMouseUp,
+            CGMouseButton::Center,
+        ),
+    };
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| AppError::Hotkey("创建 CGEventSource 失败".into()))?;
+    // 空事件的 location 即当前光标位置
+    let location = CGEvent::new(source.clone())
+        .map_err(|_| AppError::Hotkey("读取光标位置失败".into()))?
+        .location();
+
+    for (event_type, pressed) in [(down, true), (up, false)] {
+        let event = CGEvent::new_mouse_event(source.clone(), event_type, location, cg_button)
+            .map_err(|_| AppError::Hotkey("创建鼠标事件失败".into()))?;
+        event.post(CGEventTapLocation::HID);
+        if pressed {
+            hold_for(hold_ms, stop_flag);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,520 @@
+//! 按键宏：录制一段真实按键及其时间间隔，作为触发动作循环回放。
+//!
+//! 录制用 Windows 低级键盘钩子（`WH_KEYBOARD_LL`），只记按下沿——按住产生
+//! 的自动重复、其他程序注入的按键、本程序窗口在前台时的按键与系统保留按键
+//! 都忽略——每条记录距录制开始的毫秒数。宏名称在开始录制时给出，停止时不
+//! 再需要在本程序里打字。宏存于配置目录 `macros/<名称>.json`。回放时每个按键沿用配置的按住时长，按键
+//! 之间按录制时的间隔等待；整段宏算一次触发，之后照常等待触发周期。
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::keymap::{self, KeyDef};
+use crate::error::{AppError, AppResult};
+use crate::fs_util::{remove_if_exists, write_atomic};
+
+/// 宏目录名（位于配置目录下）
+pub const MACROS_DIR: &str = "macros";
+
+/// 宏名称长度上限（字符）
+const MAX_NAME_LEN: usize = 64;
+
+/// 单个宏最多记录的按键数；忘了停止录制时不至于无限增长
+pub const MAX_EVENTS: usize = 1000;
+
+/// 录制到的一次按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroEvent {
+    /// set-1 扫描码（0x01-0x7F）
+    pub scancode: u16,
+    #[serde(default)]
+    pub extended: bool,
+    /// 虚拟键码，窗口模式 PostMessage 用
+    pub vk: u16,
+    /// 距录制开始的毫秒数
+    pub at_ms: u64,
+}
+
+impl MacroEvent {
+    pub fn key(&self) -> KeyDef {
+        KeyDef {
+            scancode: self.scancode,
+            vk: self.vk,
+            extended: self.extended,
+        }
+    }
+}
+
+/// 一段录制好的按键序列（按 `at_ms` 递增）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Macro {
+    pub events: Vec<MacroEvent>,
+}
+
+impl Macro {
+    /// 每个按键及其前的等待（毫秒），第一个按键不等待
+    pub fn steps(&self) -> Vec<(KeyDef, u64)> {
+        let mut last = self.events.first().map_or(0, |event| event.at_ms);
+        self.events
+            .iter()
+            .map(|event| {
+                let delay = event.at_ms.saturating_sub(last);
+                last = event.at_ms;
+                (event.key(), delay)
+            })
+            .collect()
+    }
+
+    /// 第一个到最后一个按键的时长
+    pub fn duration_ms(&self) -> u64 {
+        match (self.events.first(), self.events.last()) {
+            (Some(first), Some(last)) => last.at_ms.saturating_sub(first.at_ms),
+            _ => 0,
+        }
+    }
+
+    fn validate(&self) -> AppResult<()> {
+        if self.events.is_empty() {
+            return Err(AppError::Hotkey("宏里没有任何按键".into()));
+        }
+        if self.events.len() > MAX_EVENTS {
+            return Err(AppError::Hotkey(format!(
+                "宏按键过多: {}（最多 {MAX_EVENTS}）",
+                self.events.len()
+            )));
+        }
+        for event in &self.events {
+            keymap::validate_scancode(event.scancode)?;
+            if let Some(reserved) = keymap::reserved_key(event.key()) {
+                return Err(AppError::Hotkey(format!(
+                    "宏里含有系统保留按键（{}），不能回放",
+                    reserved.name
+                )));
+            }
+        }
+        if self
+            .events
+            .windows(2)
+            .any(|pair| pair[1].at_ms < pair[0].at_ms)
+        {
+            return Err(AppError::Hotkey("宏按键时间必须递增".into()));
+        }
+        Ok(())
+    }
+}
+
+/// 已保存的宏（用于前端列表）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroInfo {
+    pub name: String,
+    pub event_count: usize,
+    pub duration_ms: u64,
+}
+
+impl MacroInfo {
+    fn new(name: &str, recorded: &Macro) -> Self {
+        Self {
+            name: name.to_string(),
+            event_count: recorded.events.len(),
+            duration_ms: recorded.duration_ms(),
+        }
+    }
+}
+
+/// Macro names double as file names: non-empty, no path separators or
+/// characters Windows rejects in file names
+pub fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LEN
+        && !name.starts_with('.')
+        && !name.chars().any(|c| {
+            c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+        });
+    if !valid {
+        return Err(AppError::validation("name", format!("宏名称无效: {name}")));
+    }
+    Ok(name.to_string())
+}
+
+pub fn macros_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join(MACROS_DIR)
+}
+
+fn macro_path(config_dir: &Path, name: &str) -> AppResult<PathBuf> {
+    let name = validate_name(name)?;
+    Ok(macros_dir(config_dir).join(format!("{name}.json")))
+}
+
+pub fn load_macro(config_dir: &Path, name: &str) -> AppResult<Macro> {
+    let path = macro_path(config_dir, name)?;
+    if !path.is_file() {
+        return Err(AppError::Hotkey(format!("宏不存在: {}", name.trim())));
+    }
+    let recorded: Macro = serde_json::from_slice(&fs::read(&path)?)
+        .map_err(|e| AppError::Config(format!("宏 {} 解析失败: {e}", name.trim())))?;
+    recorded.validate()?;
+    Ok(recorded)
+}
+
+pub fn save_macro(config_dir: &Path, name: &str, recorded: &Macro) -> AppResult<MacroInfo> {
+    recorded.validate()?;
+    let path = macro_path(config_dir, name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&path, &serde_json::to_vec_pretty(recorded)?)?;
+    Ok(MacroInfo::new(&validate_name(name)?, recorded))
+}
+
+/// Delete a saved macro; returns whether it existed
+pub fn delete_macro(config_dir: &Path, name: &str) -> AppResult<bool> {
+    Ok(remove_if_exists(&macro_path(config_dir, name)?)?)
+}
+
+/// 全部已保存的宏，按名称排序；解析失败的文件跳过
+pub fn list_macros(config_dir: &Path) -> AppResult<Vec<MacroInfo>> {
+    let dir = macros_dir(config_dir);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut macros = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        match load_macro(config_dir, name) {
+            Ok(recorded) => macros.push(MacroInfo::new(name, &recorded)),
+            Err(err) => log::warn!("跳过无效的宏文件 {}: {err}", path.display()),
+        }
+    }
+    macros.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(macros)
+}
+
+/// 进行中的录制：只在按键由抬起变为按下时记一条
+#[derive(Debug)]
+struct Recording {
+    /// 开始录制时给出的宏名称（已校验）
+    name: String,
+    started: Instant,
+    events: Vec<MacroEvent>,
+    held: HashSet<(u16, bool)>,
+}
+
+impl Recording {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            started: Instant::now(),
+            events: Vec::new(),
+            held: HashSet::new(),
+        }
+    }
+
+    fn key(&mut self, scancode: u16, extended: bool, vk: u16, down: bool, at_ms: u64) {
+        // 超出 set-1 范围的扫描码（部分多媒体键）无法回放，系统保留按键不该回放
+        let key = KeyDef {
+            scancode,
+            vk,
+            extended,
+        };
+        if keymap::validate_scancode(scancode).is_err() || keymap::reserved_key(key).is_some() {
+            return;
+        }
+        if !down {
+            self.held.remove(&(scancode, extended));
+            return;
+        }
+        // 按住时的自动重复不算新按键
+        if !self.held.insert((scancode, extended)) || self.events.len() >= MAX_EVENTS {
+            return;
+        }
+        self.events.push(MacroEvent {
+            scancode,
+            extended,
+            vk,
+            at_ms,
+        });
+    }
+}
+
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// 钩子回调：把一次按键交给进行中的录制
+fn record_key(scancode: u16, extended: bool, vk: u16, down: bool) {
+    if let Ok(mut guard) = RECORDING.lock() {
+        if let Some(recording) = guard.as_mut() {
+            let at_ms = recording.started.elapsed().as_millis() as u64;
+            recording.key(scancode, extended, vk, down, at_ms);
+        }
+    }
+}
+
+pub fn is_recording() -> bool {
+    RECORDING.lock().is_ok_and(|guard| guard.is_some())
+}
+
+/// Start recording keypresses system-wide into the macro `name`
+#[cfg(target_os = "windows")]
+pub fn start_recording(name: &str) -> AppResult<()> {
+    let name = validate_name(name)?;
+    {
+        let mut guard = RECORDING
+            .lock()
+            .map_err(|e| AppError::Hotkey(format!("宏录制状态锁定失败: {e}")))?;
+        if guard.is_some() {
+            return Err(AppError::Hotkey("已经在录制宏".into()));
+        }
+        *guard = Some(Recording::new(name));
+    }
+    if let Err(err) = windows_impl::install_hook() {
+        if let Ok(mut guard) = RECORDING.lock() {
+            *guard = None;
+        }
+        return Err(err);
+    }
+    log::info!("开始录制宏");
+    Ok(())
+}
+
+/// Stop recording and return the macro name with what was captured
+#[cfg(target_os = "windows")]
+pub fn stop_recording() -> AppResult<(String, Macro)> {
+    windows_impl::remove_hook();
+    let recording = RECORDING
+        .lock()
+        .map_err(|e| AppError::Hotkey(format!("宏录制状态锁定失败: {e}")))?
+        .take()
+        .ok_or_else(|| AppError::Hotkey("没有进行中的宏录制".into()))?;
+    log::info!("停止录制宏，共 {} 个按键", recording.events.len());
+    Ok((
+        recording.name,
+        Macro {
+            events: recording.events,
+        },
+    ))
+}
+
+/// 宏录制依赖 Windows 低级键盘钩子
+#[cfg(not(target_os = "windows"))]
+pub fn start_recording(_name: &str) -> AppResult<()> {
+    Err(AppError::platform_not_supported("宏录制"))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn stop_recording() -> AppResult<(String, Macro)> {
+    Err(AppError::platform_not_supported("宏录制"))
+}
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use std::sync::{mpsc, Mutex};
+    use std::thread::{self, JoinHandle};
+
+    use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::Threading::{GetCurrentProcessId, GetCurrentThreadId};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetForegroundWindow, GetMessageW, GetWindowThreadProcessId,
+        PostThreadMessageW, SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION, KBDLLHOOKSTRUCT,
+        LLKHF_EXTENDED, LLKHF_INJECTED, MSG, WH_KEYBOARD_LL, WM_KEYDOWN, WM_QUIT, WM_SYSKEYDOWN,
+    };
+
+    use crate::error::{AppError, AppResult};
+
+    /// 钩子线程：线程 id（用于投递 WM_QUIT）与句柄
+    static HOOK_THREAD: Mutex<Option<(u32, JoinHandle<()>)>> = Mutex::new(None);
+
+    unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 {
+            let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+            let down = matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+            // 注入的按键（包括回放中的 SendInput 点击）不是用户按的；本程序窗口在
+            // 前台时按下的键是在操作本程序（抬起照常交给录制，免得按住状态残留）
+            if !info.flags.contains(LLKHF_INJECTED) && !(down && own_window_focused()) {
+                super::record_key(
+                    info.scanCode as u16,
+                    info.flags.contains(LLKHF_EXTENDED),
+                    info.vkCode as u16,
+                    down,
+                );
+            }
+        }
+        CallNextHookEx(None, code, wparam, lparam)
+    }
+
+    /// Whether the foreground window belongs to this process
+    fn own_window_focused() -> bool {
+        let mut pid = 0u32;
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return false;
+            }
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            pid == GetCurrentProcessId()
+        }
+    }
+
+    /// 在专用线程上安装低级键盘钩子；钩子回调靠该线程的消息循环驱动
+    pub fn install_hook() -> AppResult<()> {
+        let mut guard = HOOK_THREAD
+            .lock()
+            .map_err(|e| AppError::Hotkey(format!("宏录制状态锁定失败: {e}")))?;
+        if guard.is_some() {
+            return Ok(());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || unsafe {
+            let hook = match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), None, 0) {
+                Ok(hook) => hook,
+                Err(e) => {
+                    let _ = tx.send(Err(AppError::Hotkey(format!("安装键盘钩子失败: {e}"))));
+                    return;
+                }
+            };
+            let _ = tx.send(Ok(GetCurrentThreadId()));
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {}
+            let _ = UnhookWindowsHookEx(hook);
+        });
+
+        let thread_id = rx
+            .recv()
+            .map_err(|_| AppError::Hotkey("键盘钩子线程意外退出".into()))??;
+        *guard = Some((thread_id, handle));
+        Ok(())
+    }
+
+    /// 结束消息循环并卸载钩子
+    pub fn remove_hook() {
+        let Some((thread_id, handle)) = HOOK_THREAD.lock().ok().and_then(|mut guard| guard.take())
+        else {
+            return;
+        };
+        unsafe {
+            let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+        }
+        let _ = handle.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(scancode: u16, at_ms: u64) -> MacroEvent {
+        MacroEvent {
+            scancode,
+            extended: false,
+            vk: 0x41,
+            at_ms,
+        }
+    }
+
+    fn temp_config_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jx3-macros-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn recording_keeps_key_down_edges_only() {
+        let mut recording = Recording::new("test".into());
+        recording.key(0x1E, false, 0x41, true, 100);
+        // 按住时的自动重复
+        recording.key(0x1E, false, 0x41, true, 130);
+        recording.key(0x1E, false, 0x41, false, 180);
+        recording.key(0x1E, false, 0x41, true, 400);
+        // 超出 set-1 的扫描码
+        recording.key(0x0121, false, 0xAD, true, 450);
+        let at: Vec<u64> = recording.events.iter().map(|e| e.at_ms).collect();
+        assert_eq!(at, [100, 400]);
+    }
+
+    #[test]
+    fn reserved_keys_are_neither_recorded_nor_saved() {
+        let mut recording = Recording::new("test".into());
+        // 左 Win 键、菜单键
+        recording.key(0x5B, true, 0x5B, true, 100);
+        recording.key(0x5D, true, 0x5D, true, 200);
+        recording.key(0x1E, false, 0x41, true, 300);
+        let at: Vec<u64> = recording.events.iter().map(|e| e.at_ms).collect();
+        assert_eq!(at, [300]);
+
+        let mut win = event(0x5B, 100);
+        win.extended = true;
+        let recorded = Macro {
+            events: vec![event(0x1E, 0), win],
+        };
+        assert!(recorded.validate().is_err());
+    }
+
+    #[test]
+    fn steps_carry_inter_event_delays() {
+        let recorded = Macro {
+            events: vec![event(0x02, 250), event(0x03, 400), event(0x04, 1000)],
+        };
+        let delays: Vec<u64> = recorded.steps().iter().map(|(_, delay)| *delay).collect();
+        assert_eq!(delays, [0, 150, 600]);
+        assert_eq!(recorded.duration_ms(), 750);
+        assert_eq!(recorded.steps()[1].0.scancode, 0x03);
+    }
+
+    #[test]
+    fn names_must_be_safe_file_names() {
+        assert_eq!(validate_name(" 起手 ").unwrap(), "起手");
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../evil").is_err());
+        assert!(validate_name("a:b").is_err());
+        assert!(validate_name(".hidden").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn save_list_delete_roundtrip() {
+        let dir = temp_config_dir("roundtrip");
+        assert!(list_macros(&dir).unwrap().is_empty());
+
+        let recorded = Macro {
+            events: vec![event(0x02, 0), event(0x03, 120)],
+        };
+        assert!(save_macro(&dir, "empty", &Macro::default()).is_err());
+        let info = save_macro(&dir, " 连招 ", &recorded).unwrap();
+        assert_eq!(info.name, "连招");
+        assert_eq!(load_macro(&dir, "连招").unwrap(), recorded);
+
+        fs::write(macros_dir(&dir).join("broken.json"), b"{").unwrap();
+        let listed = list_macros(&dir).unwrap();
+        assert_eq!(
+            listed,
+            [MacroInfo {
+                name: "连招".into(),
+                event_count: 2,
+                duration_ms: 120
+            }]
+        );
+
+        assert!(delete_macro(&dir, "连招").unwrap());
+        assert!(!delete_macro(&dir, "连招").unwrap());
+        assert!(load_macro(&dir, "连招").is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -4,43 +4,75 @@
 //! (RegisterHotKey under the hood) — no kernel driver required for listening.
 //! The runner thread presses the trigger key via Interception kernel injection
 //! (global mode, keyboard-only driver, see keys.rs/driver.rs) or PostMessage
-//! (window mode).
+//! (window mode). On macOS global mode posts CGEvents instead
+//! (keys_macos.rs); window mode stays Windows-only.
 
+mod cleanup;
 mod config;
 pub mod driver;
 pub mod keymap;
 #[cfg(target_os = "windows")]
 pub(crate) mod keys;
+#[cfg(target_os = "macos")]
+#[path = "keys_macos.rs"]
+pub(crate) mod keys;
+mod macros;
+pub mod process;
+mod session_log;
 mod types;
-#[cfg(target_os = "windows")]
 pub mod window;
 
-pub use config::CONFIG_FILE_NAME;
-pub use types::{HotkeyConfig, HotkeyStatus};
+pub use cleanup::CleanupReport;
+pub use config::{config_schema, CONFIG_FILE_NAME};
+pub use macros::MacroInfo;
+pub use session_log::SessionLogInfo;
+pub use types::{
+    set_stop_poll_ms, BurstConfig, HotkeyConfig, HotkeyStatus, InterceptionDevice, ListenerHealth,
+    ListenerStats, MouseButton, RunnerTransition, SavedConfigInfo, TriggerAction,
+    DEFAULT_STOP_POLL_MS, STOP_POLL_RANGE_MS,
+};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use tauri::{AppHandle, Emitter};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 
 use crate::error::{AppError, AppResult};
-use config::{ensure_app_config_dir, load_config, save_config, validate_config};
+use crate::fs_util::remove_if_exists;
+use config::{
+    ensure_app_config_dir, load_config_or_reset, load_key_overrides, save_config, validate_config,
+    KEY_OVERRIDES_FILE_NAME,
+};
 use keymap::parse_shortcut;
-use types::HotkeyInner;
+use types::{
+    sleep_with_interrupt, Debounce, HotkeyInner, ListenerCounters, RunStopped, SendBackend,
+    ShortcutKind, HOTKEY_DEBOUNCE,
+};
 
-#[cfg(target_os = "windows")]
-use std::sync::atomic::{AtomicBool, Ordering};
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "macos"))]
 use config::validate_runtime_config;
-#[cfg(target_os = "windows")]
-use keys::{simulate_key_press, sleep_with_interrupt};
-#[cfg(target_os = "windows")]
-use types::Runner;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use keys::{set_key_held, simulate_key_press, simulate_mouse_click};
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use session_log::SessionLog;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use types::{
+    FocusLoss, Heartbeat, RunHeartbeat, RunStarted, Runner, FOCUS_LOSS_GRACE, HEARTBEAT_INTERVAL,
+};
 
 /// Event name for hotkey status updates
 pub const HOTKEY_STATUS_EVENT: &str = "hotkey://status";
+/// Emitted once per run on the start edge (payload: `RunStarted`); the status
+/// event stays the source of truth for state, these are for one-shot reactions
+pub const HOTKEY_STARTED_EVENT: &str = "hotkey://started";
+/// Emitted once per run on the stop edge (payload: `RunStopped`)
+pub const HOTKEY_STOPPED_EVENT: &str = "hotkey://stopped";
+/// Emitted about once a second while a run is active (payload: `RunHeartbeat`)
+pub const HOTKEY_HEARTBEAT_EVENT: &str = "hotkey://heartbeat";
 
 /// Service for managing hotkey automation
 pub struct HotkeyService {
@@ -48,6 +80,13 @@ pub struct HotkeyService {
     inner: Mutex<HotkeyInner>,
     /// Shortcuts currently registered with the global-shortcut plugin
     registered_shortcuts: Mutex<Vec<tauri_plugin_global_shortcut::Shortcut>>,
+    /// Presses seen by the shortcut callbacks since the last registration
+    listener_counters: ListenerCounters,
+    /// false while capture is paused (`set_listener_enabled`); not persisted
+    listener_enabled: AtomicBool,
+    /// true when hotkey_config.json existed but couldn't be read at start-up:
+    /// saves are refused so the defaults never overwrite it
+    config_unreadable: AtomicBool,
 }
 
 impl HotkeyService {
@@ -59,12 +98,35 @@ impl HotkeyService {
             config_path,
             inner: Mutex::new(HotkeyInner::default()),
             registered_shortcuts: Mutex::new(Vec::new()),
+            listener_counters: ListenerCounters::default(),
+            listener_enabled: AtomicBool::new(true),
+            config_unreadable: AtomicBool::new(false),
         })
     }
 
     /// Initialize the service with saved config
     pub fn initialize(self: &Arc<Self>, app: &AppHandle) -> AppResult<()> {
-        let config = load_config(&self.config_path).unwrap_or_default();
+        let loaded = load_config_or_reset(&self.config_path);
+        let (config, mut reset_notice) = (loaded.config, loaded.notice);
+        self.config_unreadable
+            .store(loaded.unreadable, Ordering::SeqCst);
+        let overrides_path = self.config_path.with_file_name(KEY_OVERRIDES_FILE_NAME);
+        match load_key_overrides(&overrides_path) {
+            Ok(overrides) => {
+                if !overrides.is_empty() {
+                    log::info!("已加载 {} 个按键覆盖", overrides.len());
+                }
+                keymap::set_key_overrides(overrides);
+            }
+            Err(err) => {
+                log::warn!("按键覆盖表无效，已忽略: {err}");
+                // 与配置重置提示一样并入 last_error，让用户知道覆盖没有生效
+                reset_notice = Some(match reset_notice {
+                    Some(notice) => format!("{notice}；{err}"),
+                    None => err.to_string(),
+                });
+            }
+        }
         {
             let mut guard = self
                 .inner
@@ -74,40 +136,62 @@ impl HotkeyService {
             guard.status.last_error = None;
         }
 
-        match self.register_listener(app) {
-            Ok(()) => self.update_status(app, |status| {
-                status.registered = true;
-                status.last_error = None;
-            }),
-            Err(err) => {
-                log::warn!("注册热键失败: {err}");
-                self.update_status(app, |status| {
-                    status.registered = false;
-                    status.last_error = Some(err.to_string());
+        let result = self.register_listener(app);
+        if let Err(err) = &result {
+            log::warn!("注册热键失败: {err}");
+        }
+        self.update_status(app, |status| {
+            apply_registration_result(status, &result);
+            // 配置被重置需要让用户看到，与注册结果并列显示
+            if let Some(notice) = reset_notice.take() {
+                status.last_error = Some(match status.last_error.take() {
+                    Some(err) => format!("{notice}；{err}"),
+                    None => notice,
                 });
             }
-        }
+        });
+
+        Ok(())
+    }
 
+    /// Unregister every shortcut this service registered
+    fn unregister_shortcuts(&self, app: &AppHandle) -> AppResult<()> {
+        let mut guard = self
+            .registered_shortcuts
+            .lock()
+            .map_err(|e| AppError::Hotkey(format!("热键注册表锁定失败: {e}")))?;
+        for shortcut in guard.drain(..) {
+            if let Err(err) = app.global_shortcut().unregister(shortcut) {
+                log::warn!("注销旧热键失败: {err}");
+            }
+        }
         Ok(())
     }
 
+    /// Tear down before the process exits: stop the runner (joining it lets
+    /// a held key be released), discard an unfinished macro recording (its
+    /// keyboard hook) and unregister the global shortcuts
+    pub fn shutdown(self: &Arc<Self>, app: &AppHandle) {
+        self.stop_runner(app);
+        if macros::is_recording() {
+            if let Err(err) = macros::stop_recording() {
+                log::warn!("退出时停止宏录制失败: {err}");
+            }
+        }
+        if let Err(err) = self.unregister_shortcuts(app) {
+            log::warn!("退出时注销热键失败: {err}");
+        }
+        log::info!("热键服务已关闭");
+    }
+
     /// Register start/stop hotkeys with the global-shortcut plugin,
     /// replacing any previously registered ones
     fn register_listener(self: &Arc<Self>, app: &AppHandle) -> AppResult<()> {
         let config = self.get_config();
 
         // 注销旧热键
-        {
-            let mut guard = self
-                .registered_shortcuts
-                .lock()
-                .map_err(|e| AppError::Hotkey(format!("热键注册表锁定失败: {e}")))?;
-            for shortcut in guard.drain(..) {
-                if let Err(err) = app.global_shortcut().unregister(shortcut) {
-                    log::warn!("注销旧热键失败: {err}");
-                }
-            }
-        }
+        self.unregister_shortcuts(app)?;
+        self.listener_counters.reset();
 
         // 跳过空热键
         if config.start_hotkey.trim().is_empty() || config.stop_hotkey.trim().is_empty() {
@@ -117,11 +201,20 @@ impl HotkeyService {
         let start = parse_shortcut(&config.start_hotkey)?;
         let stop = parse_shortcut(&config.stop_hotkey)?;
 
-        // 事件回调跑在主线程，任务启停派发到新线程，避免阻塞事件循环
+        // 事件回调跑在主线程，任务启停派发到新线程，避免阻塞事件循环。
+        // 连按/按住时防抖，避免一串线程争抢 start_runner。
+        // Windows 上热键经 RegisterHotKey 分发，系统只吞掉触发的那次按下，松开照常
+        // 送达前台程序（修饰键做热键也不会卡键），因此这里无需也无法转发按键松开
         let service = Arc::clone(self);
+        let debounce = Debounce::default();
         app.global_shortcut()
             .on_shortcut(start, move |app, _shortcut, event| {
-                if event.state() != ShortcutState::Pressed {
+                if event.state() != ShortcutState::Pressed || !service.capturing() {
+                    return;
+                }
+                let fired = debounce.should_fire(Instant::now(), HOTKEY_DEBOUNCE);
+                service.listener_counters.record(ShortcutKind::Start, fired);
+                if !fired {
                     return;
                 }
                 let service = Arc::clone(&service);
@@ -138,10 +231,16 @@ impl HotkeyService {
             .map_err(|e| AppError::Hotkey(format!("注册开始热键失败: {e}")))?;
 
         let service = Arc::clone(self);
+        let debounce = Debounce::default();
         if let Err(e) = app
             .global_shortcut()
             .on_shortcut(stop, move |app, _shortcut, event| {
-                if event.state() != ShortcutState::Pressed {
+                if event.state() != ShortcutState::Pressed || !service.capturing() {
+                    return;
+                }
+                let fired = debounce.should_fire(Instant::now(), HOTKEY_DEBOUNCE);
+                service.listener_counters.record(ShortcutKind::Stop, fired);
+                if !fired {
                     return;
                 }
                 let service = Arc::clone(&service);
@@ -154,6 +253,31 @@ impl HotkeyService {
             return Err(AppError::Hotkey(format!("注册结束热键失败: {e}")));
         }
 
+        // 紧急停止不防抖、不派发线程、不等待任务退出：只置停止标志，
+        // 保证 stop_runner 的 join 或任务本身卡住时仍然有效。
+        // 它独立注册：失败（如组合键被别的程序占用）只记警告，开始/结束热键照常可用
+        let service = Arc::clone(self);
+        let emergency = parse_shortcut(&config.emergency_stop_hotkey).and_then(|emergency| {
+            app.global_shortcut()
+                .on_shortcut(emergency, move |_app, _, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        service
+                            .listener_counters
+                            .record(ShortcutKind::Emergency, true);
+                        service.emergency_stop();
+                    }
+                })
+                .map(|()| emergency)
+                .map_err(|e| AppError::Hotkey(format!("注册紧急停止热键失败: {e}")))
+        });
+        let emergency = match emergency {
+            Ok(emergency) => Some(emergency),
+            Err(err) => {
+                log::warn!("{err}，开始/结束热键仍然有效");
+                None
+            }
+        };
+
         {
             let mut guard = self
                 .registered_shortcuts
@@ -161,17 +285,174 @@ impl HotkeyService {
                 .map_err(|e| AppError::Hotkey(format!("热键注册表锁定失败: {e}")))?;
             guard.push(start);
             guard.push(stop);
+            guard.extend(emergency);
         }
 
         log::info!(
-            "全局热键已注册: 开始={}, 停止={}",
+            "全局热键已注册: 开始={}, 停止={}, 紧急停止={}",
             config.start_hotkey,
-            config.stop_hotkey
+            config.stop_hotkey,
+            if emergency.is_some() {
+                config.emergency_stop_hotkey.as_str()
+            } else {
+                "（未注册）"
+            }
         );
 
+        // 暂停捕获期间重新注册（保存配置、掉线恢复）：开始/结束键注册后立即让出
+        if !self.capturing() {
+            self.release_run_shortcuts(app, &[start, stop])?;
+        }
+
         Ok(())
     }
 
+    /// Whether start/stop presses are acted on (capture not paused)
+    fn capturing(&self) -> bool {
+        self.listener_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Pause or resume hotkey capture without touching the saved config.
+    /// RegisterHotKey swallows the registered keys, so pausing hands the
+    /// start/stop keys back to the OS (they type normally, e.g. in chat) and
+    /// ignores their presses; the emergency stop stays registered. Resuming
+    /// registers them again from the current config. A running task keeps
+    /// running either way
+    pub fn set_listener_enabled(self: &Arc<Self>, app: &AppHandle, enabled: bool) -> AppResult<()> {
+        if self.listener_enabled.swap(enabled, Ordering::SeqCst) == enabled {
+            self.emit_status(app);
+            return Ok(());
+        }
+
+        let result = if enabled {
+            self.register_listener(app)
+        } else {
+            let config = self.get_config();
+            let run_keys: Vec<_> = [&config.start_hotkey, &config.stop_hotkey]
+                .into_iter()
+                .filter_map(|hotkey| parse_shortcut(hotkey).ok())
+                .collect();
+            self.release_run_shortcuts(app, &run_keys)
+        };
+        match &result {
+            Ok(()) => log::info!("热键捕获已{}", if enabled { "恢复" } else { "暂停" }),
+            Err(err) => log::warn!("切换热键捕获失败: {err}"),
+        }
+        if enabled {
+            self.update_status(app, |status| apply_registration_result(status, &result));
+        } else {
+            self.emit_status(app);
+        }
+        result
+    }
+
+    /// Unregister `run_keys` (the start/stop shortcuts) and drop them from the
+    /// registered list, leaving the emergency stop in place
+    fn release_run_shortcuts(
+        &self,
+        app: &AppHandle,
+        run_keys: &[tauri_plugin_global_shortcut::Shortcut],
+    ) -> AppResult<()> {
+        let mut guard = self
+            .registered_shortcuts
+            .lock()
+            .map_err(|e| AppError::Hotkey(format!("热键注册表锁定失败: {e}")))?;
+        guard.retain(|shortcut| {
+            if !run_keys.contains(shortcut) {
+                return true;
+            }
+            if let Err(err) = app.global_shortcut().unregister(*shortcut) {
+                log::warn!("让出热键失败: {err}");
+            }
+            false
+        });
+        Ok(())
+    }
+
+    /// Re-register the current start/stop hotkeys without touching the saved
+    /// config, for recovering after the OS or another app dropped them
+    pub fn reregister(self: &Arc<Self>, app: &AppHandle) -> AppResult<()> {
+        let result = self.register_listener(app);
+        if let Err(err) = &result {
+            log::warn!("重新注册热键失败: {err}");
+        }
+        self.update_status(app, |status| apply_registration_result(status, &result));
+        result
+    }
+
+    /// Directory holding hotkey_config.json (and the sessions/ logs)
+    fn config_dir(&self) -> &Path {
+        self.config_path.parent().unwrap_or_else(|| Path::new("."))
+    }
+
+    /// Directory of per-run session logs
+    pub fn session_log_dir(&self) -> PathBuf {
+        session_log::sessions_dir(self.config_dir())
+    }
+
+    /// List session logs, newest first
+    pub fn list_session_logs(&self) -> AppResult<Vec<SessionLogInfo>> {
+        session_log::list_logs(self.config_dir())
+    }
+
+    /// Summarize every session log into a CSV at `path`; returns the number
+    /// of sessions written
+    pub fn export_session_stats(&self, path: &Path) -> AppResult<usize> {
+        session_log::export_stats(self.config_dir(), path)
+    }
+
+    /// Delete old session logs, keyboard `.bak-*` backups under
+    /// `keyboard_base` and corrupt-config backups (see `cleanup::cleanup`).
+    /// Refused while running: the active session log is still open
+    pub fn cleanup_app_data(
+        &self,
+        keyboard_base: Option<&Path>,
+        keep_sessions: usize,
+        keep_backups: usize,
+        max_age_days: u64,
+    ) -> AppResult<CleanupReport> {
+        if self.snapshot_status().running {
+            return Err(AppError::Hotkey("自动按键运行中，请先停止再清理".into()));
+        }
+        cleanup::cleanup(
+            self.config_dir(),
+            keyboard_base,
+            keep_sessions,
+            keep_backups,
+            max_age_days,
+        )
+    }
+
+    /// Saved keypress macros, sorted by name
+    pub fn list_macros(&self) -> AppResult<Vec<MacroInfo>> {
+        macros::list_macros(self.config_dir())
+    }
+
+    /// Delete a saved macro; returns whether it existed
+    pub fn delete_macro(&self, name: &str) -> AppResult<bool> {
+        macros::delete_macro(self.config_dir(), name)
+    }
+
+    /// Start recording a macro to be saved as `name`. The name is taken up
+    /// front so nothing has to be typed into the app while the hook records.
+    /// Refused while the runner is active: keys it injects through the
+    /// driver look like real presses to the hook.
+    pub fn start_macro_record(&self, name: &str) -> AppResult<()> {
+        if self.snapshot_status().running {
+            return Err(AppError::Hotkey("自动按键运行中，请先停止再录制宏".into()));
+        }
+        macros::start_recording(name)
+    }
+
+    /// Stop recording and save the captured keys under the name given at start
+    pub fn stop_macro_record(&self) -> AppResult<MacroInfo> {
+        let (name, recorded) = macros::stop_recording()?;
+        if recorded.events.is_empty() {
+            return Err(AppError::Hotkey("没有录到任何按键，宏未保存".into()));
+        }
+        macros::save_macro(self.config_dir(), &name, &recorded)
+    }
+
     /// Get the current config
     pub fn get_config(&self) -> HotkeyConfig {
         match self.inner.lock() {
@@ -188,6 +469,29 @@ impl HotkeyService {
         self.snapshot_status()
     }
 
+    /// Check that the registered hotkeys are still held by the global-shortcut
+    /// plugin. Listening has no thread of its own, so this is the liveness probe
+    pub fn listener_health(&self, app: &AppHandle) -> ListenerHealth {
+        let registered = self.snapshot_status().registered;
+        let alive: Vec<bool> = match self.registered_shortcuts.lock() {
+            Ok(guard) => guard
+                .iter()
+                .map(|shortcut| app.global_shortcut().is_registered(*shortcut))
+                .collect(),
+            Err(err) => {
+                log::warn!("热键注册表锁定失败: {err}");
+                vec![false]
+            }
+        };
+        health_from(registered, &alive)
+    }
+
+    /// Shortcut press counts since the last (re)registration, for telling
+    /// dropped presses apart from a slow runner
+    pub fn listener_stats(&self) -> ListenerStats {
+        self.listener_counters.snapshot()
+    }
+
     /// Clone the stored status and fill the live driver fields
     fn snapshot_status(&self) -> HotkeyStatus {
         let mut status = match self.inner.lock() {
@@ -198,6 +502,7 @@ impl HotkeyService {
             }
         };
         fill_driver_status(&mut status);
+        status.listener_enabled = self.capturing();
         status
     }
 
@@ -206,7 +511,8 @@ impl HotkeyService {
         self: &Arc<Self>,
         app: &AppHandle,
         config: HotkeyConfig,
-    ) -> AppResult<HotkeyConfig> {
+    ) -> AppResult<SavedConfigInfo> {
+        self.ensure_config_writable()?;
         validate_config(&config)?;
 
         // Stop any running task first
@@ -236,55 +542,160 @@ impl HotkeyService {
 
         save_config(&self.config_path, &config)?;
 
-        match self.register_listener(app) {
-            Ok(()) => self.update_status(app, |status| {
-                status.registered = true;
-                status.last_error = None;
-            }),
-            Err(err) => {
-                self.update_status(app, |status| {
-                    status.registered = false;
-                    status.last_error = Some(err.to_string());
-                });
-                return Err(err);
+        // 配置已落盘：即使注册失败/平台不支持，用户的编辑也不会丢
+        let result = self.register_listener(app);
+        self.update_status(app, |status| apply_registration_result(status, &result));
+        result?;
+
+        Ok(saved_config_info(config))
+    }
+
+    /// Patch only the start/stop hotkeys (None = unchanged) on the held
+    /// config, then persist and re-register. The patch is applied and saved
+    /// under the config lock, so a stale frontend copy can't clobber other
+    /// fields. A running task keeps running: only the listener changes
+    pub fn update_hotkeys(
+        self: &Arc<Self>,
+        app: &AppHandle,
+        start: Option<String>,
+        stop: Option<String>,
+    ) -> AppResult<SavedConfigInfo> {
+        self.ensure_config_writable()?;
+        let config = {
+            let mut guard = self
+                .inner
+                .lock()
+                .map_err(|e| AppError::Hotkey(format!("热键状态锁定失败: {e}")))?;
+            let mut config = guard.config.clone();
+            if let Some(start) = start {
+                config.start_hotkey = start;
             }
+            if let Some(stop) = stop {
+                config.stop_hotkey = stop;
+            }
+            validate_config(&config)?;
+            save_config(&self.config_path, &config)?;
+            guard.config = config.clone();
+            guard.status.last_error = None;
+            config
+        };
+
+        let result = self.register_listener(app);
+        self.update_status(app, |status| apply_registration_result(status, &result));
+        result?;
+
+        log::info!(
+            "热键已更新: 开始={}, 停止={}",
+            config.start_hotkey,
+            config.stop_hotkey
+        );
+        Ok(saved_config_info(config))
+    }
+
+    /// Refuse to save over a config file that couldn't be read at start-up
+    fn ensure_config_writable(&self) -> AppResult<()> {
+        if self.config_unreadable.load(Ordering::SeqCst) {
+            return Err(AppError::Config(format!(
+                "热键配置文件读取失败，为避免覆盖原文件已拒绝保存；请检查 {} 的权限或占用后重启工具",
+                self.config_path.display()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Delete hotkey_config.json and fall back to the default config: stops
+    /// any running task, re-registers the default hotkeys and emits the fresh
+    /// status. Returns whether a config file was actually removed
+    pub fn reset_config(self: &Arc<Self>, app: &AppHandle) -> AppResult<bool> {
+        self.stop_runner(app);
+        let removed = remove_if_exists(&self.config_path)?;
+        // 文件已删除，之后的保存不会再覆盖读不了的原配置
+        self.config_unreadable.store(false, Ordering::SeqCst);
+        {
+            let mut guard = self
+                .inner
+                .lock()
+                .map_err(|e| AppError::Hotkey(format!("热键状态锁定失败: {e}")))?;
+            guard.config = HotkeyConfig::default();
+            guard.status.last_error = None;
         }
 
-        Ok(config)
+        // 注册失败只体现在状态里：配置已经重置成功
+        let result = self.register_listener(app);
+        if let Err(err) = &result {
+            log::warn!("重置后注册热键失败: {err}");
+        }
+        self.update_status(app, |status| apply_registration_result(status, &result));
+        Ok(removed)
     }
 
-    /// Stop the running automation task
-    pub fn stop_runner(self: &Arc<Self>, app: &AppHandle) {
+    /// Stop the running automation task. Returns `NotRunning` when there was
+    /// no task to stop
+    pub fn stop_runner(self: &Arc<Self>, app: &AppHandle) -> RunnerTransition {
         let runner = {
             let mut guard = match self.inner.lock() {
                 Ok(lock) => lock,
                 Err(err) => {
                     log::error!("停止热键任务时加锁失败: {}", err);
-                    return;
+                    return RunnerTransition::NotRunning;
                 }
             };
             guard.runner.take()
         };
 
+        let had_runner = runner.is_some();
         if let Some(mut runner) = runner {
             runner.request_stop();
             runner.join();
         }
 
-        if let Ok(mut guard) = self.inner.lock() {
-            guard.status.running = false;
-        }
+        // 正常情况下 runner 线程退出时 finish_running 已经发过 stopped，
+        // 这里只兜底 join 超时的情况
+        let was_running = match self.inner.lock() {
+            Ok(mut guard) => std::mem::replace(&mut guard.status.running, false),
+            Err(_) => false,
+        };
         self.emit_status(app);
+        if was_running {
+            self.emit_stopped(app, None);
+        }
+        if had_runner || was_running {
+            RunnerTransition::Stopped
+        } else {
+            RunnerTransition::NotRunning
+        }
     }
 
-    /// Start the automation runner
-    #[cfg(target_os = "windows")]
-    pub fn start_runner(self: &Arc<Self>, app: &AppHandle) -> AppResult<()> {
+    /// Raise the running task's stop flag without taking or joining it. The
+    /// runner exits at its next check and clears itself via `finish_running`
+    pub fn emergency_stop(&self) {
+        let guard = match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(runner) = guard.runner.as_ref() {
+            runner.request_stop();
+            log::warn!("紧急停止热键触发，已通知热键任务停止");
+        }
+    }
+
+    /// Start the automation runner. Returns `AlreadyRunning` without touching
+    /// the running task when one is active
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    pub fn start_runner(self: &Arc<Self>, app: &AppHandle) -> AppResult<RunnerTransition> {
         // 驱动未就绪直接拒绝：否则会空转一个无法注入按键的 runner
-        if keys::driver_status() != keys::DriverStatus::Ready {
-            return Err(AppError::Hotkey(
-                "按键驱动未就绪，请先在按键页面安装驱动并重启电脑".into(),
-            ));
+        // 鼠标点击走 SendInput，不依赖驱动
+        // 强制 Interception（默认）时才拒绝；Auto/SendInput 可不经驱动发送
+        #[cfg(target_os = "windows")]
+        {
+            let config = self.get_config();
+            if config.trigger_action.sends_keys()
+                && config.send_backend.resolve(driver_ready()).is_none()
+            {
+                return Err(AppError::driver_missing(
+                    "按键驱动未就绪，请先在按键页面安装驱动并重启电脑",
+                ));
+            }
         }
 
         // First, stop any existing runner to prevent multiple runners
@@ -296,7 +707,10 @@ impl HotkeyService {
 
             // Already running, skip
             if guard.status.running && guard.runner.is_some() {
-                return Ok(());
+                return Ok(RunnerTransition::AlreadyRunning);
+            }
+            if macros::is_recording() {
+                return Err(AppError::Hotkey("正在录制宏，请先停止录制".into()));
             }
 
             // Take any existing runner for cleanup
@@ -309,57 +723,80 @@ impl HotkeyService {
             runner.join();
         }
 
-        let (config, trigger_key) = {
+        let (mut config, stroke) = {
+            let guard = self
+                .inner
+                .lock()
+                .map_err(|e| AppError::Hotkey(format!("热键状态锁定失败: {e}")))?;
+            validate_runtime_config(&guard.config)?;
+            let stroke = resolve_stroke(&guard.config, self.config_dir())?;
+            (guard.config.clone(), stroke)
+        };
+
+        // 窗口模式额外验证：失效的目标窗口跳过并提示，全部失效且允许回退时改用全局模式。
+        // 在标记运行之前做，失败时状态保持未运行
+        let (target_hwnds, start_notice) = if config.key_mode == types::KeyMode::Window {
+            let found = valid_target_hwnds(&config);
+            resolve_targets(&mut config, found)?
+        } else {
+            (Vec::new(), None)
+        };
+
+        {
             let mut guard = self
                 .inner
                 .lock()
                 .map_err(|e| AppError::Hotkey(format!("热键状态锁定失败: {e}")))?;
 
             // Double-check after re-acquiring lock
-            if guard.status.running && guard.runner.is_some() {
-                return Ok(());
+            if guard.status.running {
+                return Ok(RunnerTransition::AlreadyRunning);
             }
-
-            validate_runtime_config(&guard.config)?;
-            let key = keymap::resolve_key(&guard.config.trigger_key)?;
             guard.status.running = true;
             guard.status.last_error = None;
-            (guard.config.clone(), key)
-        };
+        }
 
-        // 窗口模式额外验证
-        let (key_mode, target_hwnd) = {
-            let mode = config.key_mode.clone();
-            let hwnd = if mode == types::KeyMode::Window {
-                match &config.target_window {
-                    Some(tw) => {
-                        if !window::is_window_valid(tw.hwnd) {
-                            return Err(AppError::Hotkey("目标窗口已关闭，请重新选择".into()));
-                        }
-                        Some(tw.hwnd)
-                    }
-                    None => return Err(AppError::Hotkey("窗口模式需要选择目标窗口".into())),
+        #[cfg(target_os = "windows")]
+        keys::set_device_filter(config.device_filter);
+
+        let backend = backend_name(&config);
+        let session_log = if config.session_logging {
+            match SessionLog::create(self.config_dir(), config.key_mode.clone(), backend) {
+                Ok(log) => Some(log),
+                Err(err) => {
+                    log::warn!("创建会话日志失败，本次不记录: {err}");
+                    None
                 }
-            } else {
-                None
-            };
-            (mode, hwnd)
+            }
+        } else {
+            None
         };
 
+        let started = RunStarted {
+            trigger_key: config.trigger_key.clone(),
+            trigger_action: config.trigger_action.clone(),
+            key_mode: config.key_mode.clone(),
+        };
         let stop_flag = Arc::new(AtomicBool::new(false));
         let stop_clone = Arc::clone(&stop_flag);
         let service = Arc::clone(self);
         let app_handle = app.clone();
 
         let handle = thread::spawn(move || {
-            run_key_loop(
+            let on_heartbeat = |beat: RunHeartbeat| {
+                if let Err(err) = app_handle.emit(HOTKEY_HEARTBEAT_EVENT, beat) {
+                    log::warn!("广播热键任务心跳失败: {}", err);
+                }
+            };
+            let reason = run_key_loop(
                 &stop_clone,
-                trigger_key,
-                config.interval_ms,
-                key_mode,
-                target_hwnd,
+                &config,
+                stroke,
+                target_hwnds,
+                session_log,
+                &on_heartbeat,
             );
-            service.finish_running(&app_handle);
+            service.finish_running(&app_handle, reason);
         });
 
         let mut guard = self
@@ -367,25 +804,118 @@ impl HotkeyService {
             .lock()
             .map_err(|e| AppError::Hotkey(format!("热键状态锁定失败: {e}")))?;
         guard.runner = Some(Runner::new(stop_flag, handle));
+        guard.status.backend = Some(backend.to_string());
+        if start_notice.is_some() {
+            guard.status.last_error = start_notice;
+        }
         drop(guard);
         self.emit_status(app);
-        Ok(())
+        if let Err(err) = app.emit(HOTKEY_STARTED_EVENT, started) {
+            log::warn!("广播热键任务开始失败: {}", err);
+        }
+        Ok(RunnerTransition::Started)
+    }
+
+    /// Start the automation runner (platforms without key simulation)
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    pub fn start_runner(self: &Arc<Self>, _app: &AppHandle) -> AppResult<RunnerTransition> {
+        Err(AppError::Hotkey(
+            "按键模拟仅支持 Windows 和 macOS 平台".into(),
+        ))
+    }
+
+    /// Send the configured trigger key exactly once (to every target window in
+    /// window mode), for verifying delivery before a long run. Does not touch
+    /// `status.running` or spawn the runner.
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    pub fn test_trigger_key(&self) -> AppResult<()> {
+        let config = self.get_config();
+        let stroke = resolve_stroke(&config, self.config_dir())?;
+        #[cfg(target_os = "windows")]
+        keys::set_device_filter(config.device_filter);
+        match config.key_mode {
+            types::KeyMode::Global => send_global_stroke(
+                &stroke,
+                config.send_backend,
+                config.effective_hold_ms(),
+                None,
+            ),
+            types::KeyMode::Window => {
+                for hwnd in valid_target_hwnds(&config)? {
+                    send_window_stroke(hwnd, &stroke, config.effective_hold_ms(), None)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Send the configured trigger key once (platforms without key simulation)
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    pub fn test_trigger_key(&self) -> AppResult<()> {
+        Err(AppError::platform_not_supported("按键模拟"))
+    }
+
+    /// Post `key` to `hwnd` exactly once, for checking that the game accepts
+    /// window-mode (PostMessage) input. A closed window is reported as a
+    /// validation error, a failed post as a hotkey error.
+    #[cfg(target_os = "windows")]
+    pub fn test_window_send(&self, hwnd: u64, key: &str) -> AppResult<()> {
+        let key = window_test_key(key)?;
+        if !window::is_window_valid(hwnd) {
+            return Err(AppError::validation(
+                "hwnd",
+                "目标窗口无效或已关闭，请重新选择",
+            ));
+        }
+        let hold_ms = self.get_config().press_gap_ms;
+        window::send_key_to_window(hwnd, key, hold_ms, None)
     }
 
-    /// Start the automation runner (non-Windows)
+    /// Window-mode test send (Windows only)
     #[cfg(not(target_os = "windows"))]
-    pub fn start_runner(self: &Arc<Self>, _app: &AppHandle) -> AppResult<()> {
-        Err(AppError::Hotkey("按键模拟仅支持 Windows 平台".into()))
+    pub fn test_window_send(&self, _hwnd: u64, _key: &str) -> AppResult<()> {
+        Err(AppError::platform_not_supported("窗口模式"))
     }
 
-    /// Mark runner as finished
+    /// Interception keyboard devices that opened, for picking `device_filter`
     #[cfg(target_os = "windows")]
-    fn finish_running(&self, app: &AppHandle) {
+    pub fn list_interception_devices(&self) -> AppResult<Vec<InterceptionDevice>> {
+        if keys::driver_status() != keys::DriverStatus::Ready {
+            return Err(AppError::driver_missing(
+                "按键驱动未就绪，请先在按键页面安装驱动",
+            ));
+        }
+        Ok(keys::list_devices())
+    }
+
+    /// Interception keyboard devices (Windows only)
+    #[cfg(not(target_os = "windows"))]
+    pub fn list_interception_devices(&self) -> AppResult<Vec<InterceptionDevice>> {
+        Err(AppError::platform_not_supported("Interception 驱动"))
+    }
+
+    /// Mark runner as finished; `reason` (e.g. auto-stop) is surfaced as last_error
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    fn finish_running(&self, app: &AppHandle, reason: Option<String>) {
+        let mut was_running = false;
         if let Ok(mut guard) = self.inner.lock() {
-            guard.status.running = false;
+            was_running = std::mem::replace(&mut guard.status.running, false);
             guard.runner = None;
+            if reason.is_some() {
+                guard.status.last_error = reason.clone();
+            }
         }
         self.emit_status(app);
+        if was_running {
+            self.emit_stopped(app, reason);
+        }
+    }
+
+    /// Emit the stop edge; callers only do so when they flipped `running` off
+    fn emit_stopped(&self, app: &AppHandle, reason: Option<String>) {
+        if let Err(err) = app.emit(HOTKEY_STOPPED_EVENT, RunStopped { reason }) {
+            log::warn!("广播热键任务停止失败: {}", err);
+        }
     }
 
     /// Emit current status to frontend
@@ -408,10 +938,295 @@ impl HotkeyService {
     }
 }
 
+/// 把热键注册结果写入状态。非 Windows 上热键能注册，但按键模拟不可用，
+/// 因此始终带上说明，避免"保存成功"让用户误以为可以运行
+fn apply_registration_result(status: &mut HotkeyStatus, result: &AppResult<()>) {
+    status.registered = result.is_ok();
+    status.last_error = match result {
+        Ok(()) => platform_notice(),
+        Err(err) => Some(err.to_string()),
+    };
+}
+
+/// 解析保存后的配置，回报各按键的扫描码与实际发送方式
+fn saved_config_info(config: HotkeyConfig) -> SavedConfigInfo {
+    let trigger_scancode = match config.trigger_action {
+        TriggerAction::Key => keymap::resolve_key(&config.trigger_key)
+            .ok()
+            .map(|key| key.scancode),
+        TriggerAction::MouseClick { .. } | TriggerAction::Macro { .. } => None,
+    };
+    SavedConfigInfo {
+        trigger_scancode,
+        start_scancode: hotkey_scancode(&config.start_hotkey),
+        stop_scancode: hotkey_scancode(&config.stop_hotkey),
+        backend: backend_name(&config).to_string(),
+        config,
+    }
+}
+
+/// 热键标签主键（"Ctrl+F1" 取 "F1"）的扫描码
+fn hotkey_scancode(label: &str) -> Option<u16> {
+    let main_key = label.rsplit('+').next()?;
+    keymap::resolve_key(main_key).ok().map(|key| key.scancode)
+}
+
+/// 当前平台下按该配置发送时使用的方式
+fn backend_name(config: &HotkeyConfig) -> &'static str {
+    let is_key = config.trigger_action.sends_keys();
+    if cfg!(target_os = "windows") {
+        match (&config.key_mode, is_key) {
+            (types::KeyMode::Window, _) => "PostMessage",
+            // 强制 Interception 而驱动未就绪时照报 Interception，启动时会报错
+            (types::KeyMode::Global, true) => config
+                .send_backend
+                .resolve(driver_ready())
+                .unwrap_or(SendBackend::Interception)
+                .name(),
+            (types::KeyMode::Global, false) => "SendInput",
+        }
+    } else if cfg!(target_os = "macos") && config.key_mode == types::KeyMode::Global {
+        "CGEvent"
+    } else {
+        "Unsupported"
+    }
+}
+
+/// 未注册时一律视为不存活；已注册则要求每个热键仍被插件持有
+fn health_from(registered: bool, shortcuts_alive: &[bool]) -> ListenerHealth {
+    ListenerHealth {
+        registered,
+        shortcuts_alive: registered && shortcuts_alive.iter().all(|alive| *alive),
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn platform_notice() -> Option<String> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_notice() -> Option<String> {
+    Some("按键模拟仅支持 Windows 和 macOS 平台，当前只能编辑配置".into())
+}
+
+/// 窗口模式的目标窗口句柄（至少选择一个）。保存的句柄失效（客户端重启过）时，
+/// 只有类名与进程名都相同的窗口恰好一个才改用它；多开时有多个则报错，不猜是哪个。
+/// 找不到的跳过并记日志，一个都找不到才报错
+#[cfg(target_os = "windows")]
+fn valid_target_hwnds(config: &HotkeyConfig) -> AppResult<Vec<u64>> {
+    if config.target_windows.is_empty() {
+        return Err(AppError::Hotkey("窗口模式需要选择目标窗口".into()));
+    }
+    let mut hwnds = Vec::new();
+    for target in &config.target_windows {
+        match locate_target_window(target)? {
+            // 多开时类名相同，重新定位可能落到已在列表里的窗口
+            Some(hwnd) if !hwnds.contains(&hwnd) => hwnds.push(hwnd),
+            _ => log::warn!("目标窗口 {} 已关闭，跳过", target.title),
+        }
+    }
+    if hwnds.is_empty() {
+        return Err(AppError::Hotkey("目标窗口已关闭，请重新选择".into()));
+    }
+    Ok(hwnds)
+}
+
+#[cfg(target_os = "windows")]
+fn locate_target_window(target: &types::TargetWindow) -> AppResult<Option<u64>> {
+    if window::is_window_valid(target.hwnd) {
+        return Ok(Some(target.hwnd));
+    }
+    let Some(found) = window::find_unique_window(&target.class_name, &target.process_name)? else {
+        return Ok(None);
+    };
+    log::info!(
+        "目标窗口句柄已失效，按类名 {} 重新定位到 {}（0x{:X}）",
+        target.class_name,
+        found.display_name,
+        found.hwnd
+    );
+    Ok(Some(found.hwnd))
+}
+
+/// 窗口模式测试发送的按键：与触发按键同样解析，且必须有虚拟键码
+/// （PostMessage 的 wParam）
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn window_test_key(label: &str) -> AppResult<keymap::KeyDef> {
+    let key = keymap::resolve_trigger_key(label)?;
+    if key.vk == 0 {
+        return Err(AppError::validation(
+            "key",
+            format!("按键 {} 没有虚拟键码，无法发送到窗口", label.trim()),
+        ));
+    }
+    Ok(key)
+}
+
+/// 目标窗口查找失败（`err`）时：开启了 `fallback_to_global` 则把本次运行的
+/// 配置切到全局模式并返回给 last_error 的提示，否则原样返回错误
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn fall_back_to_global(config: &mut HotkeyConfig, err: AppError) -> AppResult<String> {
+    if !config.fallback_to_global {
+        return Err(err);
+    }
+    log::warn!("{err}，本次改用全局模式运行");
+    config.key_mode = types::KeyMode::Global;
+    Ok(format!("{err}，本次已改用全局模式运行"))
+}
+
+/// 窗口模式本次运行的目标：`found` 为 `valid_target_hwnds` 的结果。部分窗口
+/// 失效时跳过并给出提示；全部失效时按 `fall_back_to_global` 回退或返回错误
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn resolve_targets(
+    config: &mut HotkeyConfig,
+    found: AppResult<Vec<u64>>,
+) -> AppResult<(Vec<u64>, Option<String>)> {
+    match found {
+        Ok(hwnds) => {
+            let skipped = config.target_windows.len().saturating_sub(hwnds.len());
+            let notice = (skipped > 0).then(|| format!("{skipped} 个目标窗口已关闭，本次跳过"));
+            Ok((hwnds, notice))
+        }
+        Err(err) => Ok((Vec::new(), Some(fall_back_to_global(config, err)?))),
+    }
+}
+
+/// 窗口模式仅支持 Windows
+#[cfg(target_os = "macos")]
+fn valid_target_hwnds(_config: &HotkeyConfig) -> AppResult<Vec<u64>> {
+    Err(AppError::platform_not_supported("窗口模式"))
+}
+
+/// 一次触发要发出的输入：按键、鼠标点击，或一段宏（按键及其前的等待）
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+#[derive(Debug, Clone)]
+enum Stroke {
+    Key(keymap::KeyDef),
+    Click(MouseButton),
+    Macro(Vec<(keymap::KeyDef, u64)>),
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl Stroke {
+    /// 会话日志里记录的扫描码（鼠标点击与宏没有）
+    fn scancode(&self) -> Option<u16> {
+        match self {
+            Stroke::Key(key) => Some(key.scancode),
+            Stroke::Click(_) | Stroke::Macro(_) => None,
+        }
+    }
+
+    /// Every key this stroke sends has a valid scancode (never 0)
+    fn validate(&self) -> AppResult<()> {
+        match self {
+            Stroke::Key(key) => keymap::validate_scancode(key.scancode),
+            Stroke::Click(_) => Ok(()),
+            Stroke::Macro(steps) => steps
+                .iter()
+                .try_for_each(|(key, _)| keymap::validate_scancode(key.scancode)),
+        }
+    }
+}
+
+/// Resolve the configured trigger into what the runner sends, refusing to
+/// start with a key that would be sent as scancode 0
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn resolve_stroke(config: &HotkeyConfig, config_dir: &Path) -> AppResult<Stroke> {
+    let stroke = match &config.trigger_action {
+        TriggerAction::Key => keymap::resolve_trigger_key(&config.trigger_key).map(Stroke::Key),
+        TriggerAction::MouseClick { button } => Ok(Stroke::Click(*button)),
+        TriggerAction::Macro { name } => {
+            macros::load_macro(config_dir, name).map(|recorded| Stroke::Macro(recorded.steps()))
+        }
+    }?;
+    stroke.validate()?;
+    Ok(stroke)
+}
+
+/// 依次发送宏里的按键，每个按键前先等待录制时的间隔；收到停止即中断
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn replay_macro(
+    steps: &[(keymap::KeyDef, u64)],
+    stop_flag: Option<&Arc<AtomicBool>>,
+    mut send: impl FnMut(keymap::KeyDef) -> AppResult<()>,
+) -> AppResult<()> {
+    for &(key, delay_ms) in steps {
+        if delay_ms > 0 {
+            match stop_flag {
+                Some(flag) => sleep_with_interrupt(flag, delay_ms),
+                None => thread::sleep(Duration::from_millis(delay_ms)),
+            }
+        }
+        if stop_flag.is_some_and(|flag| flag.load(Ordering::SeqCst)) {
+            break;
+        }
+        send(key)?;
+    }
+    Ok(())
+}
+
+/// 全局模式：按键按 `backend` 经 Interception 内核注入或 SendInput 发出
+/// （macOS 为 CGEvent），鼠标点击走
+/// 用户态注入（见 `keys::simulate_mouse_click`）
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn send_global_stroke(
+    stroke: &Stroke,
+    backend: SendBackend,
+    hold_ms: u64,
+    stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    match stroke {
+        Stroke::Key(key) => simulate_key_press(*key, backend, hold_ms, stop_flag),
+        Stroke::Click(button) => simulate_mouse_click(*button, hold_ms, stop_flag),
+        Stroke::Macro(steps) => replay_macro(steps, stop_flag, |key| {
+            simulate_key_press(key, backend, hold_ms, stop_flag)
+        }),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn send_window_stroke(
+    hwnd: u64,
+    stroke: &Stroke,
+    hold_ms: u64,
+    stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    match stroke {
+        Stroke::Key(key) => window::send_key_to_window(hwnd, *key, hold_ms, stop_flag),
+        Stroke::Click(button) => window::send_click_to_window(hwnd, *button, hold_ms, stop_flag),
+        Stroke::Macro(steps) => replay_macro(steps, stop_flag, |key| {
+            window::send_key_to_window(hwnd, key, hold_ms, stop_flag)
+        }),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn send_window_stroke(
+    _hwnd: u64,
+    _stroke: &Stroke,
+    _hold_ms: u64,
+    _stop_flag: Option<&Arc<AtomicBool>>,
+) -> AppResult<()> {
+    Err(AppError::platform_not_supported("窗口模式"))
+}
+
+/// 按键驱动（Interception）是否就绪
+#[cfg(target_os = "windows")]
+fn driver_ready() -> bool {
+    keys::driver_status() == keys::DriverStatus::Ready
+}
+
+/// 按键驱动是否就绪（非 Windows 恒为否）
+#[cfg(not(target_os = "windows"))]
+fn driver_ready() -> bool {
+    false
+}
+
 /// 动态填充驱动相关状态字段（不持久化）
 #[cfg(target_os = "windows")]
 fn fill_driver_status(status: &mut HotkeyStatus) {
-    status.driver_ready = keys::driver_status() == keys::DriverStatus::Ready;
+    status.driver_ready = driver_ready();
     status.driver_state = if status.driver_ready {
         driver::DriverState::Ready
     } else {
@@ -428,42 +1243,571 @@ fn fill_driver_status(status: &mut HotkeyStatus) {
     status.mouse_filter_present = false;
 }
 
-/// Run the key sending loop
-#[cfg(target_os = "windows")]
+/// Run the key sending loop. Returns why it stopped on its own (max duration
+/// elapsed, target window gone, game out of focus, send failure) for
+/// `last_error`; None when it was stopped through the stop flag.
+/// `on_heartbeat` is called about once a second, from the waits between
+/// sends, for as long as the loop is alive.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
 fn run_key_loop(
     stop_flag: &Arc<AtomicBool>,
-    trigger_key: keymap::KeyDef,
-    interval_ms: u64,
-    key_mode: types::KeyMode,
-    target_hwnd: Option<u64>,
-) {
-    match key_mode {
+    config: &HotkeyConfig,
+    stroke: Stroke,
+    mut target_hwnds: Vec<u64>,
+    mut session_log: Option<SessionLog>,
+    on_heartbeat: &dyn Fn(RunHeartbeat),
+) -> Option<String> {
+    let heartbeat = Heartbeat::new(Instant::now());
+    // 预热：给用户切回游戏的时间；期间可被结束热键打断。
+    // 放在截止时间计算之前，最长运行时长从第一次发送算起
+    if config.start_delay_ms > 0 {
+        sleep_with_heartbeat(stop_flag, config.start_delay_ms, &heartbeat, on_heartbeat);
+        if stop_flag.load(Ordering::SeqCst) {
+            return None;
+        }
+    }
+
+    let mut cadence = Cadence::new(config);
+    let hold_ms = config.effective_hold_ms();
+    let deadline = config
+        .max_duration_secs
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+    let mut record = |result: &AppResult<()>| {
+        if result.is_ok() {
+            heartbeat.record_sent();
+        }
+        if let Some(log) = session_log.as_mut() {
+            log.record(stroke.scancode(), result.is_ok());
+        }
+    };
+    // 休眠到本轮周期结束（扣掉按住与发送耗时，实际周期即 period_ms），
+    // 但不睡过截止时间；返回是否已到截止时间
+    let pause = |stop_flag: &Arc<AtomicBool>, started: Instant, period_ms: u64| -> bool {
+        let rest = period_ms.saturating_sub(started.elapsed().as_millis() as u64);
+        match deadline {
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                let rest = rest.min(left.as_millis() as u64);
+                sleep_with_heartbeat(stop_flag, rest, &heartbeat, on_heartbeat);
+                Instant::now() >= deadline
+            }
+            None => {
+                sleep_with_heartbeat(stop_flag, rest, &heartbeat, on_heartbeat);
+                false
+            }
+        }
+    };
+
+    // 失焦自动停止：游戏离开前台期间不发送，超过宽限期仍未切回则停下
+    let focus_processes = config.focus_processes();
+    let mut focus_loss = FocusLoss::default();
+
+    match config.key_mode {
         types::KeyMode::Global => {
-            // 全局模式：Interception 内核注入
+            // 限定进程（或失焦自动停止）时每轮检查前台窗口：切到别的程序期间
+            // 照常计时但不发送，避免模拟按键打进聊天软件等其他窗口
+            let check_foreground =
+                config.restrict_to_process.is_some() || config.stop_on_focus_loss;
+            let mut in_foreground = true;
+            let mut failures = FailureStreak::default();
+            // 常按修饰键：guard 在本分支任何返回（停止、超时、失焦、连续发送失败）时释放
+            let press = |label: &str| HeldModifier::press(label, config.send_backend);
+            let mut held = match config.hold_modifier.as_deref().map(press).transpose() {
+                Ok(held) => held,
+                Err(err) => {
+                    log::error!("按下常按修饰键失败: {}", err);
+                    return Some(format!("按下常按修饰键失败，任务已停止: {err}"));
+                }
+            };
             while !stop_flag.load(Ordering::SeqCst) {
-                if let Err(err) = simulate_key_press(trigger_key) {
-                    log::error!("热键触发失败: {}", err);
+                let started = Instant::now();
+                let allowed = !check_foreground || game_in_foreground(&focus_processes);
+                if allowed != in_foreground {
+                    in_foreground = allowed;
+                    if allowed {
+                        log::info!("{} 回到前台，恢复发送", focus_processes.join("/"));
+                    } else {
+                        log::info!("前台不是 {}，暂停发送", focus_processes.join("/"));
+                    }
+                    // 切出期间松开修饰键，免得它带进别的程序
+                    if let Some(held) = held.as_mut() {
+                        if let Err(err) = held.set(allowed) {
+                            log::warn!("切换常按修饰键状态失败: {}", err);
+                        }
+                    }
+                }
+                if config.stop_on_focus_loss && focus_loss.record(allowed, started) {
+                    return focus_lost(stop_flag);
+                }
+                // 按概率漏掉这一次（照常等待本轮周期），模拟手动操作
+                if allowed && !skip_press(config.skip_chance) {
+                    let result =
+                        send_global_stroke(&stroke, config.send_backend, hold_ms, Some(stop_flag));
+                    record(&result);
+                    // 驱动中途消失时每次都会失败：连续失败到阈值就停下并报错，
+                    // 而不是一直显示"运行中"却什么也没发出去
+                    let gave_up = failures.record(result.is_ok());
+                    if let Err(err) = result {
+                        log::error!("热键触发失败: {}", err);
+                        if gave_up && !stop_flag.load(Ordering::SeqCst) {
+                            return Some(format!(
+                                "按键连续 {MAX_CONSECUTIVE_SEND_FAILURES} 次发送失败，任务已停止: {err}"
+                            ));
+                        }
+                    }
+                }
+                if pause(stop_flag, started, cadence.next_period_ms()) {
+                    log::info!("达到最大运行时长，自动停止");
+                    return timed_out(stop_flag);
                 }
-                sleep_with_interrupt(stop_flag, interval_ms);
             }
         }
         types::KeyMode::Window => {
-            // 窗口模式：PostMessage 发送虚拟键码
-            let hwnd = match target_hwnd {
-                Some(h) => h,
-                None => {
-                    log::error!("窗口模式未指定目标窗口");
-                    return;
-                }
-            };
+            // 窗口模式：PostMessage 发送虚拟键码/鼠标消息，每次触发依次发给每个目标窗口
+            if target_hwnds.is_empty() {
+                log::error!("窗口模式未指定目标窗口");
+                return Some("窗口模式未指定目标窗口".into());
+            }
 
+            let mut focus_ok = true;
             while !stop_flag.load(Ordering::SeqCst) {
-                if let Err(err) = window::send_key_to_window(hwnd, trigger_key.vk) {
-                    log::error!("发送窗口按键失败: {}", err);
-                    break;
+                let started = Instant::now();
+                // 每轮先剔除已关闭的窗口（多开时关掉其中一个不影响其余），
+                // 全部关闭后立即停下而不是等 PostMessage 失败
+                target_hwnds.retain(|&hwnd| {
+                    let valid = window::is_window_valid(hwnd);
+                    if !valid {
+                        log::warn!("目标窗口 0x{:X} 已关闭，不再向其发送", hwnd);
+                    }
+                    valid
+                });
+                if target_hwnds.is_empty() {
+                    log::warn!("目标窗口已全部关闭，停止发送");
+                    return Some(WINDOW_CLOSED.into());
+                }
+                // 先于"发送前置前"检查：用户切走后不再把游戏抢回前台
+                let focused = !config.stop_on_focus_loss || game_in_foreground(&focus_processes);
+                if config.stop_on_focus_loss && focus_loss.record(focused, started) {
+                    return focus_lost(stop_flag);
+                }
+                // 按概率漏掉这一次（照常等待本轮周期），模拟手动操作
+                if focused && !skip_press(config.skip_chance) {
+                    for &hwnd in &target_hwnds {
+                        if stop_flag.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        if config.focus_before_send {
+                            match window::focus_window(hwnd) {
+                                Ok(()) => focus_ok = true,
+                                Err(err) => {
+                                    // 只在刚开始失败时记一次，避免每个间隔刷一行
+                                    if focus_ok {
+                                        log::warn!("切换目标窗口到前台失败，继续发送: {}", err);
+                                    }
+                                    focus_ok = false;
+                                }
+                            }
+                        }
+                        let result = send_window_stroke(hwnd, &stroke, hold_ms, Some(stop_flag));
+                        record(&result);
+                        if let Err(err) = result {
+                            log::error!("发送窗口按键失败: {}", err);
+                            if stop_flag.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            // 发送途中关闭的窗口留给下一轮剔除
+                            if window::is_window_valid(hwnd) {
+                                return Some(format!("发送窗口按键失败，任务已停止: {err}"));
+                            }
+                        }
+                    }
+                }
+                if pause(stop_flag, started, cadence.next_period_ms()) {
+                    log::info!("达到最大运行时长，自动停止");
+                    return timed_out(stop_flag);
                 }
-                sleep_with_interrupt(stop_flag, interval_ms);
             }
         }
     }
+    None
+}
+
+/// `sleep_with_interrupt` in HEARTBEAT_INTERVAL slices, emitting a heartbeat
+/// once one is due, so long waits (start delay, long intervals) still beat
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn sleep_with_heartbeat(
+    stop_flag: &Arc<AtomicBool>,
+    total_ms: u64,
+    heartbeat: &Heartbeat,
+    on_heartbeat: &dyn Fn(RunHeartbeat),
+) {
+    let slice_ms = HEARTBEAT_INTERVAL.as_millis() as u64;
+    let mut remaining = total_ms;
+    loop {
+        let step = remaining.min(slice_ms);
+        sleep_with_interrupt(stop_flag, step);
+        remaining -= step;
+        if let Some(beat) = heartbeat.poll(Instant::now()) {
+            on_heartbeat(beat);
+        }
+        if remaining == 0 || stop_flag.load(Ordering::SeqCst) {
+            break;
+        }
+    }
+}
+
+/// Period before the next send: `interval_ms` every time, or in burst mode
+/// `burst_gap_ms` between the presses of a burst and `pause_ms` after its last
+#[derive(Debug)]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+struct Cadence {
+    interval_ms: u64,
+    burst: Option<BurstConfig>,
+    /// 本轮连发已按的次数
+    pressed: u32,
+}
+
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+impl Cadence {
+    fn new(config: &HotkeyConfig) -> Self {
+        Self {
+            interval_ms: config.interval_ms,
+            burst: config.burst,
+            pressed: 0,
+        }
+    }
+
+    /// Advance past one send and return the period that follows it
+    fn next_period_ms(&mut self) -> u64 {
+        let Some(burst) = self.burst else {
+            return self.interval_ms;
+        };
+        self.pressed += 1;
+        if self.pressed >= burst.count {
+            self.pressed = 0;
+            burst.pause_ms
+        } else {
+            burst.burst_gap_ms
+        }
+    }
+}
+
+/// Whether a press rolled with `roll` (uniform over `u32`) is skipped at
+/// probability `chance`: 0.0 never skips, 1.0 always does
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn skip_roll(chance: f32, roll: u32) -> bool {
+    (roll as f64) < f64::from(chance) * (u32::MAX as f64 + 1.0)
+}
+
+/// Roll the dice for one scheduled press; never skips when the OS RNG fails
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn skip_press(chance: f32) -> bool {
+    if chance <= 0.0 {
+        return false;
+    }
+    let mut bytes = [0u8; 4];
+    match getrandom::fill(&mut bytes) {
+        Ok(()) => skip_roll(chance, u32::from_ne_bytes(bytes)),
+        Err(err) => {
+            log::warn!("生成随机数失败，不跳过本次触发: {err}");
+            false
+        }
+    }
+}
+
+/// Consecutive global-mode send failures tolerated before the run auto-stops
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+const MAX_CONSECUTIVE_SEND_FAILURES: u32 = 5;
+
+/// Consecutive send failures; any success resets the streak
+#[derive(Debug, Default)]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+struct FailureStreak(u32);
+
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+impl FailureStreak {
+    /// Record one send; true once `MAX_CONSECUTIVE_SEND_FAILURES` failed in a row
+    fn record(&mut self, ok: bool) -> bool {
+        if ok {
+            self.0 = 0;
+        } else {
+            self.0 += 1;
+        }
+        self.0 >= MAX_CONSECUTIVE_SEND_FAILURES
+    }
+}
+
+/// `hold_modifier` kept down across a global-mode run. Released on drop, so
+/// every way out of the loop, a send error or a panic included, lets go of it
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+struct HeldModifier {
+    key: keymap::KeyDef,
+    backend: SendBackend,
+    down: bool,
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl HeldModifier {
+    /// Resolve `label` and press it
+    fn press(label: &str, backend: SendBackend) -> AppResult<Self> {
+        let mut held = Self {
+            key: keymap::resolve_key(label)?,
+            backend,
+            down: false,
+        };
+        held.set(true)?;
+        log::info!("已按住修饰键 {}", label.trim());
+        Ok(held)
+    }
+
+    /// Press or release the modifier; a no-op when already in that state
+    fn set(&mut self, down: bool) -> AppResult<()> {
+        if self.down != down {
+            set_key_held(self.key, self.backend, down)?;
+            self.down = down;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl Drop for HeldModifier {
+    fn drop(&mut self) {
+        if let Err(err) = self.set(false) {
+            log::error!("释放常按修饰键失败: {}", err);
+        }
+    }
+}
+
+/// Stop reason shown when the target window disappears mid-run
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+const WINDOW_CLOSED: &str = "目标窗口已关闭，任务已停止";
+
+/// Whether the foreground window belongs to one of `processes`
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn game_in_foreground(processes: &[&str]) -> bool {
+    window::foreground_process_name().is_some_and(|name| {
+        processes
+            .iter()
+            .any(|process| window::process_name_matches(&name, process))
+    })
+}
+
+/// Stop reason once the game stayed out of focus past the grace period,
+/// unless the user stopped first
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn focus_lost(stop_flag: &Arc<AtomicBool>) -> Option<String> {
+    log::info!("游戏离开前台超过宽限期，自动停止");
+    (!stop_flag.load(Ordering::SeqCst)).then(|| {
+        format!(
+            "游戏窗口离开前台超过 {} 秒，已自动停止",
+            FOCUS_LOSS_GRACE.as_secs()
+        )
+    })
+}
+
+/// Stop reason once the max duration elapsed, unless the user stopped first
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn timed_out(stop_flag: &Arc<AtomicBool>) -> Option<String> {
+    (!stop_flag.load(Ordering::SeqCst)).then(|| "达到最大运行时长，已自动停止".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration_failure_is_reported() {
+        let mut status = HotkeyStatus::default();
+        apply_registration_result(
+            &mut status,
+            &Err(AppError::Hotkey("注册开始热键失败".into())),
+        );
+        assert!(!status.registered);
+        assert_eq!(
+            status.last_error.as_deref(),
+            Some("热键错误: 注册开始热键失败")
+        );
+    }
+
+    #[test]
+    fn saved_info_reports_resolved_scancodes() {
+        let info = saved_config_info(HotkeyConfig {
+            trigger_key: "F".into(),
+            start_hotkey: "Ctrl+F1".into(),
+            stop_hotkey: "F12".into(),
+            ..HotkeyConfig::default()
+        });
+        assert_eq!(info.trigger_scancode, Some(0x21));
+        assert_eq!(info.start_scancode, Some(0x3B));
+        assert_eq!(info.stop_scancode, Some(0x58));
+
+        let click = saved_config_info(HotkeyConfig {
+            trigger_key: "F".into(),
+            trigger_action: TriggerAction::MouseClick {
+                button: MouseButton::Left,
+            },
+            ..HotkeyConfig::default()
+        });
+        assert_eq!(click.trigger_scancode, None);
+    }
+
+    #[test]
+    fn cadence_alternates_burst_gaps_and_pause() {
+        let mut config = HotkeyConfig {
+            interval_ms: 500,
+            ..HotkeyConfig::default()
+        };
+        let mut even = Cadence::new(&config);
+        assert_eq!([even.next_period_ms(), even.next_period_ms()], [500, 500]);
+
+        config.burst = Some(BurstConfig {
+            count: 3,
+            burst_gap_ms: 40,
+            pause_ms: 1500,
+        });
+        let mut burst = Cadence::new(&config);
+        let periods: Vec<u64> = (0..6).map(|_| burst.next_period_ms()).collect();
+        assert_eq!(periods, [40, 40, 1500, 40, 40, 1500]);
+    }
+
+    #[test]
+    fn skip_roll_honours_probability_bounds() {
+        assert!(!skip_roll(0.0, 0));
+        assert!(skip_roll(1.0, u32::MAX));
+        assert!(skip_roll(0.5, u32::MAX / 2 - 1));
+        assert!(!skip_roll(0.5, u32::MAX / 2 + 1));
+        assert!(!skip_press(0.0));
+        assert!(skip_press(1.0));
+    }
+
+    #[test]
+    fn macro_replay_sends_in_order_and_stops_early() {
+        let key = |scancode| keymap::KeyDef {
+            scancode,
+            vk: 0,
+            extended: false,
+        };
+        let steps = [(key(0x02), 0), (key(0x03), 5), (key(0x04), 5)];
+        let mut sent = Vec::new();
+        replay_macro(&steps, None, |key| {
+            sent.push(key.scancode);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(sent, [0x02, 0x03, 0x04]);
+
+        // 停止标志在第一个按键后置位：剩下的不再发送
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut sent = Vec::new();
+        replay_macro(&steps, Some(&stop), |key| {
+            sent.push(key.scancode);
+            stop.store(true, Ordering::SeqCst);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(sent, [0x02]);
+
+        assert!(replay_macro(&steps, None, |_| Err(AppError::Hotkey("发送失败".into()))).is_err());
+    }
+
+    #[test]
+    fn window_test_key_needs_a_virtual_key() {
+        assert_eq!(window_test_key("F1").unwrap().vk, 0x70);
+        assert!(window_test_key(" ").is_err());
+        assert!(window_test_key("SC:0x1E").is_err());
+        assert!(window_test_key("LWIN").is_err());
+    }
+
+    #[test]
+    fn missing_window_falls_back_to_global_only_when_enabled() {
+        let gone = || AppError::Hotkey("目标窗口已关闭，请重新选择".into());
+        let mut config = HotkeyConfig {
+            key_mode: types::KeyMode::Window,
+            ..HotkeyConfig::default()
+        };
+        assert!(fall_back_to_global(&mut config, gone()).is_err());
+        assert_eq!(config.key_mode, types::KeyMode::Window);
+
+        config.fallback_to_global = true;
+        let notice = fall_back_to_global(&mut config, gone()).unwrap();
+        assert!(notice.contains("目标窗口已关闭"));
+        assert!(notice.contains("全局模式"));
+        assert_eq!(config.key_mode, types::KeyMode::Global);
+    }
+
+    #[test]
+    fn all_windows_gone_without_fallback_fails_before_starting() {
+        let gone = || Err(AppError::Hotkey("目标窗口已关闭，请重新选择".into()));
+        let target = |hwnd| types::TargetWindow {
+            hwnd,
+            ..Default::default()
+        };
+        let mut config = HotkeyConfig {
+            key_mode: types::KeyMode::Window,
+            target_windows: vec![target(1), target(2)],
+            ..HotkeyConfig::default()
+        };
+        assert!(resolve_targets(&mut config, gone()).is_err());
+        assert_eq!(config.key_mode, types::KeyMode::Window);
+
+        let (hwnds, notice) = resolve_targets(&mut config, Ok(vec![2])).unwrap();
+        assert_eq!(hwnds, [2]);
+        assert!(notice.unwrap().contains("1 个目标窗口"));
+        let (_, notice) = resolve_targets(&mut config, Ok(vec![1, 2])).unwrap();
+        assert!(notice.is_none());
+
+        config.fallback_to_global = true;
+        let (hwnds, notice) = resolve_targets(&mut config, gone()).unwrap();
+        assert!(hwnds.is_empty());
+        assert!(notice.unwrap().contains("全局模式"));
+        assert_eq!(config.key_mode, types::KeyMode::Global);
+    }
+
+    #[test]
+    fn failure_streak_trips_after_consecutive_failures_only() {
+        let mut streak = FailureStreak::default();
+        for _ in 1..MAX_CONSECUTIVE_SEND_FAILURES {
+            assert!(!streak.record(false));
+        }
+        assert!(!streak.record(true), "一次成功即清零");
+        for _ in 1..MAX_CONSECUTIVE_SEND_FAILURES {
+            assert!(!streak.record(false));
+        }
+        assert!(streak.record(false));
+    }
+
+    #[test]
+    fn health_requires_every_shortcut_alive() {
+        assert!(health_from(true, &[true, true]).shortcuts_alive);
+        assert!(!health_from(true, &[true, false]).shortcuts_alive);
+        assert_eq!(
+            health_from(false, &[true, true]),
+            ListenerHealth {
+                registered: false,
+                shortcuts_alive: false,
+            }
+        );
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    #[test]
+    fn registration_success_clears_error_on_supported_platforms() {
+        let mut status = HotkeyStatus {
+            last_error: Some("旧错误".into()),
+            ..HotkeyStatus::default()
+        };
+        apply_registration_result(&mut status, &Ok(()));
+        assert!(status.registered);
+        assert!(status.last_error.is_none());
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[test]
+    fn registration_success_still_flags_unsupported_platform() {
+        let mut status = HotkeyStatus::default();
+        apply_registration_result(&mut status, &Ok(()));
+        assert!(status.registered);
+        assert!(status.last_error.unwrap().contains("仅支持 Windows"));
+    }
 }
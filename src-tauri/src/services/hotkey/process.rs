@@ -0,0 +1,196 @@
+//! Running-process enumeration (Toolhelp snapshot), used to find game clients
+//! by executable name. More reliable than window titles when several clients
+//! run at once: every instance is its own pid even when the titles match.
+
+use serde::Serialize;
+
+use super::window::process_name_matches;
+use crate::error::{AppError, AppResult};
+
+/// 一个正在运行的进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// 可执行文件名，如 `JX3ClientX64.exe`
+    pub name: String,
+    /// 完整路径；权限不足（如游戏以管理员身份运行）时为空
+    pub path: String,
+}
+
+/// Trimmed, non-empty executable names to look for
+fn normalize_names(names: &[String]) -> AppResult<Vec<&str>> {
+    let names: Vec<&str> = names
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if names.is_empty() {
+        return Err(AppError::validation("names", "至少需要一个进程名"));
+    }
+    if let Some(name) = names.iter().find(|name| name.contains(['/', '\\'])) {
+        return Err(AppError::validation(
+            "names",
+            format!("只填进程名，不要带路径: {name}"),
+        ));
+    }
+    Ok(names)
+}
+
+/// Whether executable `exe` is one of `names` (case-insensitive, `.exe` optional)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_wanted(exe: &str, names: &[&str]) -> bool {
+    names.iter().any(|name| process_name_matches(exe, name))
+}
+
+/// Sort by name then pid
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn sort_processes(mut processes: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+    processes.sort_by_cached_key(|p| (p.name.to_lowercase(), p.pid));
+    processes
+}
+
+/// Running processes whose executable is one of `names`
+pub fn list_game_processes(names: &[String]) -> AppResult<Vec<ProcessInfo>> {
+    let names = normalize_names(names)?;
+    #[cfg(target_os = "windows")]
+    {
+        let matched = windows_impl::snapshot(|exe| is_wanted(exe, &names))?;
+        Ok(sort_processes(matched))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = names;
+        Err(AppError::platform_not_supported("进程枚举"))
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use windows_impl::image_path;
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    use super::ProcessInfo;
+    use crate::error::{AppError, AppResult};
+
+    /// 以 NUL 结尾的 UTF-16 缓冲转字符串
+    fn from_wide_nul(buf: &[u16]) -> String {
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        OsString::from_wide(&buf[..len])
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// 进程可执行文件的完整路径；进程已退出或无权访问时为 None
+    pub(crate) fn image_path(pid: u32) -> Option<String> {
+        if pid == 0 {
+            return None;
+        }
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut buf: Vec<u16> = vec![0; 260];
+            let mut size = buf.len() as u32;
+            let result = QueryFullProcessImageNameW(
+                handle,
+                PROCESS_NAME_WIN32,
+                windows::core::PWSTR(buf.as_mut_ptr()),
+                &mut size,
+            )
+            .ok()
+            .map(|()| {
+                // 确保 size 不超过 buf 长度
+                let len = (size as usize).min(buf.len());
+                OsString::from_wide(&buf[..len])
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            // 关闭进程句柄，避免资源泄漏
+            let _ = CloseHandle(handle);
+            result
+        }
+    }
+
+    /// 当前进程中可执行文件名满足 `keep` 的那些（Toolhelp 快照）；
+    /// 先按名字过滤，只为留下的进程打开句柄取路径
+    pub fn snapshot(keep: impl Fn(&str) -> bool) -> AppResult<Vec<ProcessInfo>> {
+        let mut processes = Vec::new();
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)
+                .map_err(|e| AppError::Hotkey(format!("枚举进程失败: {e}")))?;
+            let mut entry = PROCESSENTRY32W {
+                dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+                ..Default::default()
+            };
+            let mut next = Process32FirstW(snapshot, &mut entry);
+            while next.is_ok() {
+                let name = from_wide_nul(&entry.szExeFile);
+                if keep(&name) {
+                    let pid = entry.th32ProcessID;
+                    processes.push(ProcessInfo {
+                        pid,
+                        name,
+                        path: image_path(pid).unwrap_or_default(),
+                    });
+                }
+                next = Process32NextW(snapshot, &mut entry);
+            }
+            let _ = CloseHandle(snapshot);
+        }
+        Ok(processes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.into(),
+            path: format!(r"C:\JX3\bin\{name}"),
+        }
+    }
+
+    #[test]
+    fn names_are_trimmed_and_must_be_bare() {
+        let names = vec![" JX3ClientX64.exe ".to_string(), String::new()];
+        assert_eq!(normalize_names(&names).unwrap(), ["JX3ClientX64.exe"]);
+        assert!(normalize_names(&[]).is_err());
+        assert!(normalize_names(&[" ".into()]).is_err());
+        assert!(normalize_names(&[r"C:\JX3\JX3.exe".into()]).is_err());
+    }
+
+    #[test]
+    fn filter_keeps_named_processes_sorted() {
+        let processes = vec![
+            process(300, "JX3ClientX64.exe"),
+            process(4, "System"),
+            process(120, "jx3clientx64.exe"),
+            process(88, "JX3.exe"),
+            process(90, "JX3Launcher.exe"),
+        ];
+        let names = ["JX3ClientX64", "jx3.exe"];
+        let kept = processes
+            .into_iter()
+            .filter(|p| is_wanted(&p.name, &names))
+            .collect();
+        let matched = sort_processes(kept);
+        let pids: Vec<u32> = matched.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, [88, 120, 300]);
+    }
+}
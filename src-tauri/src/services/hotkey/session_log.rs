@@ -0,0 +1,406 @@
+//! 自动按键会话日志：`session_logging` 开启时，每次启动 runner 在配置目录
+//! `sessions/<毫秒时间戳>.jsonl` 新建一个文件，每次发送追加一行
+//! `{"tMs":…,"scancode":…,"mode":"global","backend":"Interception","ok":true}`。
+//! 缓冲写入、攒够 `FLUSH_EVERY` 行才落盘，短间隔下也不拖慢发送。
+//!
+//! `export_stats` 把全部会话汇总成 CSV（每个会话一行），用于分析循环覆盖率。
+
+#![cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::types::KeyMode;
+use crate::error::{AppError, AppResult};
+use crate::fs_util::write_atomic;
+
+/// 会话日志目录名（位于配置目录下）
+pub const SESSIONS_DIR: &str = "sessions";
+
+/// 每写这么多行 flush 一次；runner 结束时 drop 也会 flush
+const FLUSH_EVERY: u32 = 32;
+
+/// 会话日志文件（用于前端列表）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogInfo {
+    pub file_name: String,
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionEntry<'a> {
+    /// 距会话开始的毫秒数
+    t_ms: u64,
+    /// 鼠标点击时为 None
+    scancode: Option<u16>,
+    mode: &'a KeyMode,
+    /// 实际发送方式（Interception / PostMessage / SendInput / CGEvent）
+    backend: &'static str,
+    ok: bool,
+}
+
+/// 读回的一行日志；只取统计需要的字段
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionRecord {
+    t_ms: u64,
+    mode: KeyMode,
+    /// 旧版日志没有该字段
+    #[serde(default)]
+    backend: Option<String>,
+    ok: bool,
+}
+
+/// 单次 runner 会话的日志写入器
+pub struct SessionLog {
+    writer: BufWriter<File>,
+    started: Instant,
+    mode: KeyMode,
+    backend: &'static str,
+    pending: u32,
+}
+
+impl SessionLog {
+    /// 在 `config_dir/sessions/` 下新建本次会话的日志文件
+    pub fn create(config_dir: &Path, mode: KeyMode, backend: &'static str) -> AppResult<Self> {
+        let dir = sessions_dir(config_dir);
+        fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(format!("{}.jsonl", now_millis())))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            started: Instant::now(),
+            mode,
+            backend,
+            pending: 0,
+        })
+    }
+
+    /// 记录一次发送；写入失败只打日志，不影响按键循环
+    pub fn record(&mut self, scancode: Option<u16>, ok: bool) {
+        let entry = SessionEntry {
+            t_ms: self.started.elapsed().as_millis() as u64,
+            scancode,
+            mode: &self.mode,
+            backend: self.backend,
+            ok,
+        };
+        let written = serde_json::to_writer(&mut self.writer, &entry)
+            .map_err(io::Error::from)
+            .and_then(|_| self.writer.write_all(b"\n"));
+        if let Err(err) = written {
+            log::warn!("写入会话日志失败: {err}");
+            return;
+        }
+
+        self.pending += 1;
+        if self.pending >= FLUSH_EVERY {
+            self.pending = 0;
+            if let Err(err) = self.writer.flush() {
+                log::warn!("刷新会话日志失败: {err}");
+            }
+        }
+    }
+}
+
+pub fn sessions_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join(SESSIONS_DIR)
+}
+
+/// 列出全部会话日志，最新在前（文件名即开始时间戳）
+pub fn list_logs(config_dir: &Path) -> AppResult<Vec<SessionLogInfo>> {
+    let dir = sessions_dir(config_dir);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut logs = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+            continue;
+        }
+        logs.push(SessionLogInfo {
+            file_name: entry.file_name().to_string_lossy().into_owned(),
+            path: path.to_string_lossy().into_owned(),
+            bytes: entry.metadata()?.len(),
+        });
+    }
+    logs.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(logs)
+}
+
+/// 一个会话的汇总（CSV 的一行）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionStats {
+    /// 会话开始时间（Unix 毫秒，取自文件名）
+    pub started_ms: u64,
+    /// 最后一次发送距开始的毫秒数
+    pub duration_ms: u64,
+    /// 成功发送次数
+    pub presses: u64,
+    pub failures: u64,
+    pub backend: String,
+}
+
+impl SessionStats {
+    /// 每分钟成功发送次数；时长为 0（只发了一次或没发）时为 0
+    pub fn apm(&self) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        self.presses as f64 * 60_000.0 / self.duration_ms as f64
+    }
+}
+
+/// 汇总一个会话文件；损坏的行（如写到一半时进程退出）跳过
+fn session_stats(started_ms: u64, content: &str) -> SessionStats {
+    let mut stats = SessionStats {
+        started_ms,
+        duration_ms: 0,
+        presses: 0,
+        failures: 0,
+        backend: String::new(),
+    };
+    for record in content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<SessionRecord>(line).ok())
+    {
+        stats.duration_ms = stats.duration_ms.max(record.t_ms);
+        if record.ok {
+            stats.presses += 1;
+        } else {
+            stats.failures += 1;
+        }
+        if stats.backend.is_empty() {
+            // 旧版日志只记了模式
+            stats.backend = record.backend.unwrap_or_else(|| match record.mode {
+                KeyMode::Global => "global".into(),
+                KeyMode::Window => "window".into(),
+            });
+        }
+    }
+    stats
+}
+
+/// 全部会话的汇总，按开始时间从早到晚
+pub fn collect_stats(config_dir: &Path) -> AppResult<Vec<SessionStats>> {
+    let mut stats = Vec::new();
+    for log in list_logs(config_dir)? {
+        let Some(started_ms) = log
+            .file_name
+            .strip_suffix(".jsonl")
+            .and_then(|stem| stem.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        stats.push(session_stats(started_ms, &fs::read_to_string(&log.path)?));
+    }
+    stats.sort_by_key(|s| s.started_ms);
+    Ok(stats)
+}
+
+/// CSV 表头；没有任何会话时也会写出
+const CSV_HEADER: &str = "started_at,duration_secs,presses,failures,apm,backend";
+
+fn stats_csv(stats: &[SessionStats]) -> String {
+    let mut csv = format!("{CSV_HEADER}\r\n");
+    for s in stats {
+        let _ = write!(
+            csv,
+            "{},{:.1},{},{},{:.1},{}\r\n",
+            format_utc(s.started_ms),
+            s.duration_ms as f64 / 1000.0,
+            s.presses,
+            s.failures,
+            s.apm(),
+            s.backend
+        );
+    }
+    csv
+}
+
+/// Write the per-session summary to `path` as CSV (atomically); returns the
+/// number of sessions written
+pub fn export_stats(config_dir: &Path, path: &Path) -> AppResult<usize> {
+    if path.as_os_str().is_empty() {
+        return Err(AppError::validation("path", "导出路径不能为空"));
+    }
+    if path
+        .parent()
+        .is_some_and(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+    {
+        return Err(AppError::validation("path", "导出目录不存在"));
+    }
+    let stats = collect_stats(config_dir)?;
+    write_atomic(path, stats_csv(&stats).as_bytes())?;
+    Ok(stats.len())
+}
+
+/// Unix 毫秒 → `2026-01-31T08:05:09Z`（UTC，公历换算见 Howard Hinnant 的
+/// civil_from_days）
+fn format_utc(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jx3-sessionlog-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn records_one_json_line_per_send() {
+        let dir = temp_config_dir("record");
+        {
+            let mut log = SessionLog::create(&dir, KeyMode::Window, "PostMessage").unwrap();
+            log.record(Some(0x1E), true);
+            log.record(None, false);
+        }
+
+        let logs = list_logs(&dir).unwrap();
+        assert_eq!(logs.len(), 1);
+        let content = fs::read_to_string(&logs[0].path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["scancode"], 0x1E);
+        assert_eq!(lines[0]["mode"], "window");
+        assert_eq!(lines[0]["backend"], "PostMessage");
+        assert_eq!(lines[0]["ok"], true);
+        assert!(lines[1]["scancode"].is_null());
+        assert!(lines[1]["tMs"].is_u64());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn list_skips_other_files_and_sorts_newest_first() {
+        let dir = temp_config_dir("list");
+        let sessions = sessions_dir(&dir);
+        fs::create_dir_all(&sessions).unwrap();
+        fs::write(sessions.join("1000.jsonl"), "").unwrap();
+        fs::write(sessions.join("2000.jsonl"), "").unwrap();
+        fs::write(sessions.join("notes.txt"), "").unwrap();
+
+        let names: Vec<String> = list_logs(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|log| log.file_name)
+            .collect();
+        assert_eq!(names, vec!["2000.jsonl", "1000.jsonl"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stats_count_presses_and_skip_torn_lines() {
+        let content = concat!(
+            r#"{"tMs":0,"scancode":30,"mode":"global","backend":"Interception","ok":true}"#,
+            "\n",
+            r#"{"tMs":500,"scancode":30,"mode":"global","backend":"Interception","ok":false}"#,
+            "\n",
+            r#"{"tMs":30000,"scancode":30,"mode":"global","backend":"Interception","ok":true}"#,
+            "\n",
+            r#"{"tMs":30500,"scanco"#,
+        );
+        let stats = session_stats(1_700_000_000_000, content);
+        assert_eq!(stats.duration_ms, 30_000);
+        assert_eq!(stats.presses, 2);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.backend, "Interception");
+        assert!((stats.apm() - 4.0).abs() < 1e-9);
+
+        // 旧版日志没有 backend，退回模式名
+        let legacy = session_stats(0, r#"{"tMs":0,"scancode":null,"mode":"window","ok":true}"#);
+        assert_eq!(legacy.backend, "window");
+        assert_eq!(legacy.apm(), 0.0);
+    }
+
+    #[test]
+    fn utc_timestamps_are_iso_8601() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400_000), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_700_000_000_999), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn export_writes_header_even_without_sessions() {
+        let dir = temp_config_dir("export");
+        fs::create_dir_all(&dir).unwrap();
+        let csv_path = dir.join("stats.csv");
+
+        assert_eq!(export_stats(&dir, &csv_path).unwrap(), 0);
+        assert_eq!(
+            fs::read_to_string(&csv_path).unwrap(),
+            format!("{CSV_HEADER}\r\n")
+        );
+
+        let sessions = sessions_dir(&dir);
+        fs::create_dir_all(&sessions).unwrap();
+        fs::write(
+            sessions.join("2000.jsonl"),
+            r#"{"tMs":60000,"scancode":30,"mode":"window","backend":"PostMessage","ok":true}"#,
+        )
+        .unwrap();
+        fs::write(
+            sessions.join("1000.jsonl"),
+            r#"{"tMs":0,"scancode":30,"mode":"global","backend":"Interception","ok":true}"#,
+        )
+        .unwrap();
+        assert_eq!(export_stats(&dir, &csv_path).unwrap(), 2);
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows,
+            [
+                CSV_HEADER,
+                "1970-01-01T00:00:01Z,0.0,1,0,0.0,Interception",
+                "1970-01-01T00:00:02Z,60.0,1,0,1.0,PostMessage",
+            ]
+        );
+
+        assert!(export_stats(&dir, &dir.join("missing/stats.csv")).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,14 +1,16 @@
+use std::cell::Cell;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
 };
 use std::thread;
 use std::time::{Duration, Instant};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// 按键发送模式
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum KeyMode {
     #[default]
@@ -16,8 +18,71 @@ pub enum KeyMode {
     Window, // 窗口模式，使用 Windows API 发送到指定窗口
 }
 
+/// 全局模式下按键的注入方式（仅 Windows；macOS 恒为 CGEvent）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SendBackend {
+    /// 驱动就绪时用 Interception，否则退回 SendInput
+    Auto,
+    /// 只用 Interception，驱动未就绪时报错（默认）
+    #[default]
+    Interception,
+    /// 只用 SendInput（与其他驱动冲突时使用；可能被 TP 过滤）
+    SendInput,
+}
+
+impl SendBackend {
+    /// 实际使用的注入方式；强制 Interception 而驱动未就绪时为 None
+    pub fn resolve(self, driver_ready: bool) -> Option<SendBackend> {
+        match self {
+            SendBackend::SendInput => Some(SendBackend::SendInput),
+            _ if driver_ready => Some(SendBackend::Interception),
+            SendBackend::Auto => Some(SendBackend::SendInput),
+            SendBackend::Interception => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SendBackend::Auto => "Auto",
+            SendBackend::Interception => "Interception",
+            SendBackend::SendInput => "SendInput",
+        }
+    }
+}
+
+/// 每次触发执行的动作
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum TriggerAction {
+    /// 按下触发按键（`trigger_key`）
+    #[default]
+    Key,
+    /// 鼠标点击（忽略 `trigger_key`）
+    MouseClick { button: MouseButton },
+    /// 回放已录制的按键宏（`macros/<name>.json`，忽略 `trigger_key`）
+    Macro { name: String },
+}
+
+impl TriggerAction {
+    /// 是否发送键盘按键（全局模式下需要按键驱动）
+    pub fn sends_keys(&self) -> bool {
+        !matches!(self, TriggerAction::MouseClick { .. })
+    }
+}
+
+/// 鼠标按键
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Right,
+    Middle,
+}
+
 /// 目标窗口信息
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TargetWindow {
     pub hwnd: u64,           // 窗口句柄
@@ -26,29 +91,196 @@ pub struct TargetWindow {
     pub process_name: String, // 进程名
 }
 
+/// 连发节奏：连按 `count` 次（每次相隔 `burst_gap_ms`），再停 `pause_ms`，循环
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BurstConfig {
+    pub count: u32,
+    pub burst_gap_ms: u64,
+    pub pause_ms: u64,
+}
+
 /// Configuration for hotkey automation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct HotkeyConfig {
     pub trigger_key: String,
+    #[serde(default)]
+    pub trigger_action: TriggerAction,
     pub interval_ms: u64,
     pub start_hotkey: String,
     pub stop_hotkey: String,
+    /// 紧急停止热键：直接置停止标志、不等待任务退出，任务卡住时也能打断
+    #[serde(default = "default_emergency_stop_hotkey")]
+    pub emergency_stop_hotkey: String,
     #[serde(default)]
     pub key_mode: KeyMode,
+    /// 窗口模式的目标窗口，每次触发依次发给每一个（多开）。旧配置的单个
+    /// `targetWindow` 读成最多一个元素的列表
+    #[serde(
+        default,
+        alias = "targetWindow",
+        deserialize_with = "one_or_many_windows"
+    )]
+    pub target_windows: Vec<TargetWindow>,
+    /// 最长运行时长（秒），到点自动停止；None = 不限制
+    #[serde(default)]
+    pub max_duration_secs: Option<u64>,
+    /// 每次触发按住的时长（毫秒），用于蓄力技能；0 = 普通点按
+    #[serde(default)]
+    pub hold_ms: u64,
+    /// 普通点按时按下与释放之间的间隔（毫秒）；计入触发周期，不额外累加
+    #[serde(default = "default_press_gap_ms")]
+    pub press_gap_ms: u64,
+    /// 是否把每次发送写入会话日志（sessions/*.jsonl），默认关闭
+    #[serde(default)]
+    pub session_logging: bool,
+    /// 窗口模式下每次发送前把目标窗口切到前台（部分客户端会忽略后台窗口的消息）
+    #[serde(default)]
+    pub focus_before_send: bool,
+    /// 窗口模式下启动时目标窗口已失效：开启则本次改用全局模式运行并在状态里
+    /// 提示，保存的配置仍是窗口模式；关闭（默认）则直接报错
+    #[serde(default)]
+    pub fallback_to_global: bool,
+    /// 全局模式下仅当前台窗口属于该进程（如 `JX3ClientX64.exe`，不区分大小写，
+    /// .exe 可省略）时才发送，切到别的程序时暂停；None = 不限制（仅 Windows）
+    #[serde(default)]
+    pub restrict_to_process: Option<String>,
+    /// 游戏（`restrict_to_process`，未设置时为目标窗口所属进程）离开前台期间
+    /// 暂停发送，超过 `FOCUS_LOSS_GRACE` 仍未切回则自动停止。每轮触发检查一次，
+    /// 间隔较长时实际宽限至少一个周期。默认关闭（仅 Windows）
     #[serde(default)]
-    pub target_window: Option<TargetWindow>,
+    pub stop_on_focus_loss: bool,
+    /// 全局模式下整个运行期间一直按住的修饰键（如 `Shift`）：开始发送前按下一次，
+    /// 停止、超时或发送失败退出时释放，其间照常点按触发键。与按键组合不同，
+    /// 修饰键不随每次点按抬起。仅 Ctrl/Shift/Alt（Win 为系统保留）；结束与紧急停止
+    /// 热键必须包含它，否则按住期间无法触发。None = 不按住
+    #[serde(default)]
+    pub hold_modifier: Option<String>,
+    /// 按下开始热键后、第一次发送前的等待（毫秒），留出切回游戏窗口的时间；
+    /// 期间按结束热键可直接取消
+    #[serde(default)]
+    pub start_delay_ms: u64,
+    /// 连发模式；设置后忽略 `interval_ms`，None = 按 `interval_ms` 均匀触发
+    #[serde(default)]
+    pub burst: Option<BurstConfig>,
+    /// 每次触发被跳过的概率（0.0–1.0），模拟手动操作偶尔漏按；跳过时照常
+    /// 等待本轮周期，默认 0 = 从不跳过
+    #[serde(default)]
+    pub skip_chance: f32,
+    /// 全局模式只向该序号（0–9）的 Interception 键盘设备注入，接了多把键盘时
+    /// 避免按键落到别的键盘上；None = 注入全部设备（仅 Windows）
+    #[serde(default)]
+    pub device_filter: Option<i32>,
+    /// 全局模式下按键的注入方式；默认只用 Interception
+    #[serde(default)]
+    pub send_backend: SendBackend,
+}
+
+impl HotkeyConfig {
+    /// 触发间隔下限（毫秒）
+    pub const MIN_INTERVAL_MS: u64 = 20;
+    /// 触发间隔上限（毫秒）；更大的值多半是手误，看起来像程序卡死
+    pub const MAX_INTERVAL_MS: u64 = 60_000;
+    /// 最长运行时长的下限（秒）
+    pub const MIN_DURATION_SECS: u64 = 5;
+    /// 点按间隔下限（毫秒）
+    pub const MIN_PRESS_GAP_MS: u64 = 1;
+    /// 开始延迟上限（毫秒）
+    pub const MAX_START_DELAY_MS: u64 = 10_000;
+    /// 注入设备序号上限（interception00 ~ 09 是键盘设备）
+    pub const MAX_DEVICE_INDEX: i32 = 9;
+
+    /// 每次触发实际按住的时长：设置了 `hold_ms` 用它，否则是点按间隔
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    pub fn effective_hold_ms(&self) -> u64 {
+        if self.hold_ms == 0 {
+            self.press_gap_ms
+        } else {
+            self.hold_ms
+        }
+    }
+
+    /// Processes that count as "the game" for `stop_on_focus_loss`:
+    /// `restrict_to_process` when set, otherwise the target windows' processes
+    pub fn focus_processes(&self) -> Vec<&str> {
+        if let Some(process) = self.restrict_to_process.as_deref() {
+            return vec![process];
+        }
+        let mut processes: Vec<&str> = Vec::new();
+        for window in &self.target_windows {
+            let name = window.process_name.as_str();
+            if !name.trim().is_empty() && !processes.contains(&name) {
+                processes.push(name);
+            }
+        }
+        processes
+    }
+
+    /// 相邻两次发送之间最短的周期：连发模式取连发间隔与停顿中较小者
+    /// （每轮只按一次时没有连发间隔），否则就是 `interval_ms`。
+    /// 按住时长与点按间隔都必须小于它
+    pub fn shortest_period_ms(&self) -> u64 {
+        match self.burst {
+            Some(burst) if burst.count > 1 => burst.burst_gap_ms.min(burst.pause_ms),
+            Some(burst) => burst.pause_ms,
+            None => self.interval_ms,
+        }
+    }
+}
+
+fn default_press_gap_ms() -> u64 {
+    TAP_HOLD_MS
+}
+
+/// `targetWindows` 数组，或旧版的单个 `targetWindow`（可为 null）
+fn one_or_many_windows<'de, D>(deserializer: D) -> Result<Vec<TargetWindow>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        Many(Vec<TargetWindow>),
+        One(TargetWindow),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::Many(windows)) => windows,
+        Some(OneOrMany::One(window)) => vec![window],
+        None => Vec::new(),
+    })
+}
+
+fn default_emergency_stop_hotkey() -> String {
+    DEFAULT_EMERGENCY_STOP_HOTKEY.to_string()
 }
 
 impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
             trigger_key: String::new(),
+            trigger_action: TriggerAction::default(),
             interval_ms: 1000,
             start_hotkey: "F11".to_string(),
             stop_hotkey: "F12".to_string(),
+            emergency_stop_hotkey: default_emergency_stop_hotkey(),
             key_mode: KeyMode::default(),
-            target_window: None,
+            target_windows: Vec::new(),
+            max_duration_secs: None,
+            hold_ms: 0,
+            press_gap_ms: TAP_HOLD_MS,
+            session_logging: false,
+            focus_before_send: false,
+            fallback_to_global: false,
+            restrict_to_process: None,
+            stop_on_focus_loss: false,
+            hold_modifier: None,
+            start_delay_ms: 0,
+            burst: None,
+            skip_chance: 0.0,
+            device_filter: None,
+            send_backend: SendBackend::default(),
         }
     }
 }
@@ -66,6 +298,102 @@ pub struct HotkeyStatus {
     pub driver_state: super::driver::DriverState,
     /// 是否残留 interception 鼠标过滤器（旧版安装包遗留，需引导用户清理）
     pub mouse_filter_present: bool,
+    /// 最近一次启动实际使用的发送方式（同 `SavedConfigInfo.backend`）；未启动过为 None
+    pub backend: Option<String>,
+    /// 热键捕获是否开启；暂停时开始/结束键照常输入，动态查询填充
+    pub listener_enabled: bool,
+}
+
+/// Result of saving a config: how the labels were interpreted, so the UI can
+/// show e.g. "F → 0x21 via Interception" before the first run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedConfigInfo {
+    pub config: HotkeyConfig,
+    /// 触发按键的扫描码；鼠标点击动作为 None
+    pub trigger_scancode: Option<u16>,
+    /// 开始/结束热键主键（去掉修饰键）的扫描码；不在模拟键表内时为 None
+    pub start_scancode: Option<u16>,
+    pub stop_scancode: Option<u16>,
+    /// 实际发送方式：Interception / SendInput / PostMessage / CGEvent / Unsupported
+    pub backend: String,
+}
+
+/// Payload of `hotkey://started`, emitted once when a run begins
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub struct RunStarted {
+    pub trigger_key: String,
+    pub trigger_action: TriggerAction,
+    pub key_mode: KeyMode,
+}
+
+/// Payload of `hotkey://heartbeat`, emitted about once a second while a run
+/// is active. Heartbeats stopping while `running` is still true means the
+/// runner is wedged
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunHeartbeat {
+    /// 自任务开始（含预热）经过的毫秒数
+    pub elapsed_ms: u64,
+    /// 已成功发送的次数（窗口模式每个窗口各计一次）
+    pub sent_count: u64,
+}
+
+/// Payload of `hotkey://stopped`, emitted once when a run ends
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RunStopped {
+    /// 自动停止/出错的原因；用户手动停止时为 None
+    pub reason: Option<String>,
+}
+
+/// Health of the start/stop hotkey registration
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenerHealth {
+    /// 上次注册是否成功（同 `HotkeyStatus.registered`）
+    pub registered: bool,
+    /// global-shortcut 插件当前是否仍持有全部已注册热键
+    pub shortcuts_alive: bool,
+}
+
+/// Press counts for the registered shortcuts since the last (re)registration.
+/// The OS only delivers the registered combos, so nothing else passes through
+/// the listener; `debounced` is what it dropped
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenerStats {
+    /// 收到的热键按下总数（含被防抖丢弃的）
+    pub received: u64,
+    /// 防抖窗口内被丢弃的按下
+    pub debounced: u64,
+    pub start_hits: u64,
+    pub stop_hits: u64,
+    pub emergency_hits: u64,
+}
+
+/// Outcome of a start/stop request, so callers can tell a transition from a no-op
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RunnerTransition {
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    Started,
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    AlreadyRunning,
+    Stopped,
+    NotRunning,
+}
+
+/// One Interception keyboard device (`\\.\interception0N`) that could be opened
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InterceptionDevice {
+    /// 设备序号 0–9，即 `device_filter` 的取值
+    pub index: i32,
+    /// 该槽位上真实键盘的硬件 ID；None = 空槽位
+    pub hardware_id: Option<String>,
 }
 
 /// Internal state of the hotkey service
@@ -87,7 +415,7 @@ impl Default for HotkeyInner {
 }
 
 /// Thread runner for key automation
-#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
 #[derive(Debug)]
 pub struct Runner {
     stop_flag: Arc<AtomicBool>,
@@ -97,7 +425,7 @@ pub struct Runner {
 /// Maximum time to wait for thread to join (in milliseconds)
 const THREAD_JOIN_TIMEOUT_MS: u64 = 500;
 
-#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
 impl Runner {
     pub fn new(stop_flag: Arc<AtomicBool>, handle: thread::JoinHandle<()>) -> Self {
         Self {
@@ -144,3 +472,371 @@ impl Drop for Runner {
         self.join();
     }
 }
+
+/// 停止检查间隔的默认值（毫秒），见 `set_stop_poll_ms`
+pub const DEFAULT_STOP_POLL_MS: u64 = 50;
+/// 停止检查间隔的允许范围（毫秒）
+pub const STOP_POLL_RANGE_MS: std::ops::RangeInclusive<u64> = 10..=1000;
+
+static STOP_POLL_MS: AtomicU64 = AtomicU64::new(DEFAULT_STOP_POLL_MS);
+
+/// How often interruptible sleeps check the stop flag, i.e. the worst-case
+/// delay before a stop request is noticed. Clamped to `STOP_POLL_RANGE_MS`
+pub fn set_stop_poll_ms(ms: u64) {
+    let ms = ms.clamp(*STOP_POLL_RANGE_MS.start(), *STOP_POLL_RANGE_MS.end());
+    STOP_POLL_MS.store(ms, Ordering::Relaxed);
+}
+
+/// Sleep with interrupt capability
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub fn sleep_with_interrupt(flag: &Arc<AtomicBool>, total_ms: u64) {
+    let poll_ms = STOP_POLL_MS.load(Ordering::Relaxed);
+    let mut remaining = if total_ms == 0 { 1 } else { total_ms };
+    while remaining > 0 && !flag.load(Ordering::SeqCst) {
+        let step = remaining.min(poll_ms);
+        thread::sleep(Duration::from_millis(step));
+        remaining = remaining.saturating_sub(step);
+    }
+}
+
+/// `press_gap_ms` 的默认值（毫秒）
+pub const TAP_HOLD_MS: u64 = 10;
+
+/// 默认紧急停止热键：四键组合，游戏内几乎不会误触
+pub const DEFAULT_EMERGENCY_STOP_HOTKEY: &str = "Ctrl+Alt+Shift+Esc";
+
+/// Wait `hold_ms` between press and release (see `HotkeyConfig::effective_hold_ms`).
+/// With a stop flag the wait is cut short on stop; the caller must still send
+/// the release
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub fn hold_for(hold_ms: u64, stop_flag: Option<&Arc<AtomicBool>>) {
+    let ms = hold_ms.max(1);
+    match stop_flag {
+        Some(flag) => sleep_with_interrupt(flag, ms),
+        None => thread::sleep(Duration::from_millis(ms)),
+    }
+}
+
+/// 开始/结束热键的防抖窗口：按住或连按时同一热键在此时间内只处理一次
+pub const HOTKEY_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// 单个热键的防抖状态（每个热键回调各持有一份）
+#[derive(Debug, Default)]
+pub struct Debounce {
+    last: Mutex<Option<Instant>>,
+}
+
+impl Debounce {
+    /// 距上次放行超过 `window` 才放行，并记录本次时间
+    pub fn should_fire(&self, now: Instant, window: Duration) -> bool {
+        let mut last = match self.last.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if matches!(*last, Some(prev) if now.saturating_duration_since(prev) < window) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+}
+
+/// `stop_on_focus_loss`：游戏离开前台超过这么久才自动停止，短暂切出（看一眼
+/// 聊天、弹窗抢焦点）只会暂停
+pub const FOCUS_LOSS_GRACE: Duration = Duration::from_secs(5);
+
+/// How long the game has been out of the foreground, for `stop_on_focus_loss`
+#[derive(Debug, Default)]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub struct FocusLoss {
+    since: Option<Instant>,
+}
+
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+impl FocusLoss {
+    /// Record one foreground check at `now`; true once the game has been out
+    /// of focus for longer than FOCUS_LOSS_GRACE
+    pub fn record(&mut self, focused: bool, now: Instant) -> bool {
+        if focused {
+            self.since = None;
+            return false;
+        }
+        let since = *self.since.get_or_insert(now);
+        now.saturating_duration_since(since) > FOCUS_LOSS_GRACE
+    }
+}
+
+/// 两次心跳之间的最短间隔
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Heartbeat state of one run; rate-limits `RunHeartbeat`s to
+/// HEARTBEAT_INTERVAL. Cells so the send path and the sleeps can share it
+#[derive(Debug)]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub struct Heartbeat {
+    started: Instant,
+    last: Cell<Instant>,
+    sent: Cell<u64>,
+}
+
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+impl Heartbeat {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            last: Cell::new(now),
+            sent: Cell::new(0),
+        }
+    }
+
+    /// Count one successful send
+    pub fn record_sent(&self) {
+        self.sent.set(self.sent.get() + 1);
+    }
+
+    /// The heartbeat due at `now`, if HEARTBEAT_INTERVAL has passed since the last one
+    pub fn poll(&self, now: Instant) -> Option<RunHeartbeat> {
+        if now.saturating_duration_since(self.last.get()) < HEARTBEAT_INTERVAL {
+            return None;
+        }
+        self.last.set(now);
+        Some(RunHeartbeat {
+            elapsed_ms: now.saturating_duration_since(self.started).as_millis() as u64,
+            sent_count: self.sent.get(),
+        })
+    }
+}
+
+/// Which registered shortcut a press belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutKind {
+    Start,
+    Stop,
+    Emergency,
+}
+
+/// Live counters behind `ListenerStats`, bumped from the shortcut callbacks
+#[derive(Debug, Default)]
+pub struct ListenerCounters {
+    received: AtomicU64,
+    debounced: AtomicU64,
+    start_hits: AtomicU64,
+    stop_hits: AtomicU64,
+    emergency_hits: AtomicU64,
+}
+
+impl ListenerCounters {
+    /// Count one press; `fired` = it got past the debounce and was acted on
+    pub fn record(&self, kind: ShortcutKind, fired: bool) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        let counter = match (fired, kind) {
+            (false, _) => &self.debounced,
+            (true, ShortcutKind::Start) => &self.start_hits,
+            (true, ShortcutKind::Stop) => &self.stop_hits,
+            (true, ShortcutKind::Emergency) => &self.emergency_hits,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        for counter in [
+            &self.received,
+            &self.debounced,
+            &self.start_hits,
+            &self.stop_hits,
+            &self.emergency_hits,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> ListenerStats {
+        ListenerStats {
+            received: self.received.load(Ordering::Relaxed),
+            debounced: self.debounced.load(Ordering::Relaxed),
+            start_hits: self.start_hits.load(Ordering::Relaxed),
+            stop_hits: self.stop_hits.load(Ordering::Relaxed),
+            emergency_hits: self.emergency_hits.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_action_defaults_to_key_for_old_configs() {
+        let json = r#"{"triggerKey":"A","intervalMs":1000,"startHotkey":"F11","stopHotkey":"F12"}"#;
+        let config: HotkeyConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.trigger_action, TriggerAction::Key);
+
+        let click = TriggerAction::MouseClick {
+            button: MouseButton::Right,
+        };
+        assert_eq!(
+            serde_json::to_string(&click).unwrap(),
+            r#"{"type":"mouseClick","button":"right"}"#
+        );
+        let replay: TriggerAction =
+            serde_json::from_str(r#"{"type":"macro","name":"连招"}"#).unwrap();
+        assert!(replay.sends_keys());
+        assert!(!click.sends_keys());
+    }
+
+    #[test]
+    fn single_target_window_loads_as_a_list() {
+        let base = r#""triggerKey":"A","intervalMs":1000,"startHotkey":"F11","stopHotkey":"F12""#;
+        let window = r#"{"hwnd":1,"title":"剑网3","className":"KGWin32App","processName":"JX3ClientX64.exe"}"#;
+        let load = |extra: &str| -> HotkeyConfig {
+            serde_json::from_str(&format!("{{{base}{extra}}}")).unwrap()
+        };
+
+        assert!(load("").target_windows.is_empty());
+        assert!(load(r#","targetWindow":null"#).target_windows.is_empty());
+        let old = load(&format!(r#","targetWindow":{window}"#));
+        assert_eq!(old.target_windows.len(), 1);
+        assert_eq!(old.target_windows[0].class_name, "KGWin32App");
+        let many = load(&format!(r#","targetWindows":[{window},{window}]"#));
+        assert_eq!(many.target_windows.len(), 2);
+
+        let saved = serde_json::to_value(&many).unwrap();
+        assert!(saved.get("targetWindow").is_none());
+        assert_eq!(saved["targetWindows"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn send_backend_resolves_against_driver_state() {
+        assert_eq!(
+            SendBackend::Auto.resolve(true),
+            Some(SendBackend::Interception)
+        );
+        assert_eq!(
+            SendBackend::Auto.resolve(false),
+            Some(SendBackend::SendInput)
+        );
+        assert_eq!(
+            SendBackend::Interception.resolve(true),
+            Some(SendBackend::Interception)
+        );
+        assert_eq!(SendBackend::Interception.resolve(false), None);
+        assert_eq!(
+            SendBackend::SendInput.resolve(true),
+            Some(SendBackend::SendInput)
+        );
+        // 旧配置没有该字段：保持只用 Interception
+        let config: HotkeyConfig = serde_json::from_str(
+            r#"{"triggerKey":"A","intervalMs":1000,"startHotkey":"F11","stopHotkey":"F12"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.send_backend, SendBackend::Interception);
+    }
+
+    #[test]
+    fn runner_transition_matches_frontend_names() {
+        assert_eq!(
+            serde_json::to_string(&RunnerTransition::AlreadyRunning).unwrap(),
+            r#""alreadyRunning""#
+        );
+        assert_eq!(
+            serde_json::to_string(&RunnerTransition::NotRunning).unwrap(),
+            r#""notRunning""#
+        );
+    }
+
+    #[test]
+    fn focus_loss_stops_only_after_the_grace_period() {
+        let t0 = Instant::now();
+        let mut loss = FocusLoss::default();
+        assert!(!loss.record(true, t0));
+        assert!(!loss.record(false, t0 + Duration::from_secs(1)));
+        assert!(!loss.record(false, t0 + Duration::from_secs(6)));
+        // 切回前台后重新计时
+        assert!(!loss.record(true, t0 + Duration::from_secs(6)));
+        assert!(!loss.record(false, t0 + Duration::from_secs(7)));
+        assert!(loss.record(false, t0 + Duration::from_millis(12_001)));
+    }
+
+    #[test]
+    fn focus_processes_prefer_restrict_then_target_windows() {
+        let window = |process: &str| TargetWindow {
+            hwnd: 1,
+            title: String::new(),
+            class_name: String::new(),
+            process_name: process.into(),
+        };
+        let mut config = HotkeyConfig {
+            target_windows: vec![
+                window("JX3ClientX64.exe"),
+                window("JX3ClientX64.exe"),
+                window(""),
+                window("JX3Client.exe"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.focus_processes(),
+            ["JX3ClientX64.exe", "JX3Client.exe"]
+        );
+        config.restrict_to_process = Some("Game.exe".into());
+        assert_eq!(config.focus_processes(), ["Game.exe"]);
+        assert!(HotkeyConfig::default().focus_processes().is_empty());
+    }
+
+    #[test]
+    fn heartbeat_fires_at_most_once_per_interval() {
+        let t0 = Instant::now();
+        let heartbeat = Heartbeat::new(t0);
+        assert_eq!(heartbeat.poll(t0 + Duration::from_millis(999)), None);
+        heartbeat.record_sent();
+        heartbeat.record_sent();
+        assert_eq!(
+            heartbeat.poll(t0 + Duration::from_millis(1000)),
+            Some(RunHeartbeat {
+                elapsed_ms: 1000,
+                sent_count: 2
+            })
+        );
+        // 间隔从上一次心跳算起
+        assert_eq!(heartbeat.poll(t0 + Duration::from_millis(1500)), None);
+        heartbeat.record_sent();
+        assert_eq!(
+            heartbeat.poll(t0 + Duration::from_millis(2600)),
+            Some(RunHeartbeat {
+                elapsed_ms: 2600,
+                sent_count: 3
+            })
+        );
+    }
+
+    #[test]
+    fn debounce_ignores_repeats_within_window() {
+        let debounce = Debounce::default();
+        let t0 = Instant::now();
+        assert!(debounce.should_fire(t0, HOTKEY_DEBOUNCE));
+        assert!(!debounce.should_fire(t0 + Duration::from_millis(50), HOTKEY_DEBOUNCE));
+        assert!(!debounce.should_fire(t0 + Duration::from_millis(199), HOTKEY_DEBOUNCE));
+        assert!(debounce.should_fire(t0 + Duration::from_millis(200), HOTKEY_DEBOUNCE));
+    }
+
+    #[test]
+    fn listener_counters_split_hits_from_debounced() {
+        let counters = ListenerCounters::default();
+        counters.record(ShortcutKind::Start, true);
+        counters.record(ShortcutKind::Start, false);
+        counters.record(ShortcutKind::Stop, true);
+        counters.record(ShortcutKind::Emergency, true);
+        assert_eq!(
+            counters.snapshot(),
+            ListenerStats {
+                received: 4,
+                debounced: 1,
+                start_hits: 1,
+                stop_hits: 1,
+                emergency_hits: 1,
+            }
+        );
+        counters.reset();
+        assert_eq!(counters.snapshot(), ListenerStats::default());
+    }
+}
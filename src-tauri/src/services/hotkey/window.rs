@@ -1,6 +1,8 @@
 //! Windows-specific window enumeration and key sending
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
 
 /// 窗口信息（用于前端显示）
 #[derive(Debug, Clone, Serialize)]
@@ -10,57 +12,233 @@ pub struct WindowInfo {
     pub title: String,
     pub class_name: String,
     pub process_name: String,
+    /// 所属进程 ID（多开同一客户端时用来区分）
+    pub pid: u32,
     pub display_name: String,
 }
 
+/// 窗口列表过滤条件（只给 keyword 时与原先的子串过滤行为一致）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowFilter {
+    /// 不区分大小写的子串，匹配标题 / 进程名 / 类名
+    pub keyword: Option<String>,
+    /// keyword 只匹配进程名，避免命中标题里偶然出现的字样
+    pub process_only: bool,
+    /// 跳过不可见窗口（默认开启）
+    pub visible_only: bool,
+    /// 对标题做不区分大小写的正则匹配；与 keyword 同时给出时需同时满足
+    pub regex: Option<String>,
+}
+
+impl Default for WindowFilter {
+    fn default() -> Self {
+        Self {
+            keyword: None,
+            process_only: false,
+            visible_only: true,
+            regex: None,
+        }
+    }
+}
+
+/// 编译后的过滤条件（正则只编译一次）
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) struct WindowMatcher {
+    keyword: Option<String>,
+    process_only: bool,
+    regex: Option<regex::Regex>,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl WindowFilter {
+    pub(crate) fn matcher(&self) -> AppResult<WindowMatcher> {
+        let keyword = self
+            .keyword
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(str::to_lowercase);
+        let regex = match self.regex.as_deref().map(str::trim) {
+            Some(pattern) if !pattern.is_empty() => Some(
+                regex::RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .size_limit(1 << 20)
+                    .build()
+                    .map_err(|e| AppError::validation("regex", format!("正则表达式无效: {e}")))?,
+            ),
+            _ => None,
+        };
+        Ok(WindowMatcher {
+            keyword,
+            process_only: self.process_only,
+            regex,
+        })
+    }
+}
+
+/// Whether `foreground` (an image name like `JX3ClientX64.exe`) is the
+/// process `wanted`; case-insensitive, `.exe` optional on either side
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos")),
+    allow(dead_code)
+)]
+pub(crate) fn process_name_matches(foreground: &str, wanted: &str) -> bool {
+    fn normalize(name: &str) -> String {
+        let name = name.trim().to_lowercase();
+        match name.strip_suffix(".exe") {
+            Some(stem) => stem.to_string(),
+            None => name,
+        }
+    }
+    let wanted = normalize(wanted);
+    !wanted.is_empty() && normalize(foreground) == wanted
+}
+
+/// First window of class `class_name` (case-insensitive, as Win32 compares
+/// class names); with `process_name`, only that process's windows count
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn first_with_class<'a>(
+    windows: &'a [WindowInfo],
+    class_name: &str,
+    process_name: Option<&str>,
+) -> Option<&'a WindowInfo> {
+    let class_name = class_name.trim();
+    let process_name = process_name.filter(|name| !name.trim().is_empty());
+    windows.iter().find(|w| {
+        w.class_name.eq_ignore_ascii_case(class_name)
+            && process_name.map_or(true, |wanted| process_name_matches(&w.process_name, wanted))
+    })
+}
+
+/// First visible window of class `class_name`, optionally limited to
+/// `process_name`. The JX3 client keeps its class while the title follows the
+/// character name, so the class is the stable key for finding it again
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn find_window_by_class(
+    class_name: &str,
+    process_name: Option<&str>,
+) -> AppResult<Option<WindowInfo>> {
+    if class_name.trim().is_empty() {
+        return Err(AppError::validation("class_name", "窗口类名不能为空"));
+    }
+    let windows = enumerate_windows(&WindowFilter::default())?;
+    Ok(first_with_class(&windows, class_name, process_name).cloned())
+}
+
+/// The only window of class `class_name` in process `process_name`. Several
+/// matches (multiboxed clients share both) are an error rather than a guess
+/// at which one was meant; a blank process name matches nothing
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn unique_with_class<'a>(
+    windows: &'a [WindowInfo],
+    class_name: &str,
+    process_name: &str,
+) -> AppResult<Option<&'a WindowInfo>> {
+    let class_name = class_name.trim();
+    let mut matches = windows.iter().filter(|w| {
+        w.class_name.eq_ignore_ascii_case(class_name)
+            && process_name_matches(&w.process_name, process_name)
+    });
+    let found = matches.next();
+    let others = matches.count();
+    if others > 0 {
+        return Err(AppError::Hotkey(format!(
+            "有 {} 个 {} 窗口（类名 {class_name}），无法确定原来的目标窗口，请重新选择",
+            others + 1,
+            process_name.trim()
+        )));
+    }
+    Ok(found)
+}
+
+/// Re-find a target window whose handle went stale (client restarted): the
+/// one visible window with its class and process, see `unique_with_class`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn find_unique_window(class_name: &str, process_name: &str) -> AppResult<Option<WindowInfo>> {
+    if class_name.trim().is_empty() {
+        return Ok(None);
+    }
+    let windows = enumerate_windows(&WindowFilter::default())?;
+    Ok(unique_with_class(&windows, class_name, process_name)?.cloned())
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl WindowMatcher {
+    pub(crate) fn matches(&self, window: &WindowInfo) -> bool {
+        if let Some(keyword) = &self.keyword {
+            let hit = if self.process_only {
+                window.process_name.to_lowercase().contains(keyword)
+            } else {
+                window.title.to_lowercase().contains(keyword)
+                    || window.process_name.to_lowercase().contains(keyword)
+                    || window.class_name.to_lowercase().contains(keyword)
+            };
+            if !hit {
+                return false;
+            }
+        }
+        self.regex
+            .as_ref()
+            .map_or(true, |regex| regex.is_match(&window.title))
+    }
+}
+
 #[cfg(target_os = "windows")]
 mod windows_impl {
     use std::ffi::OsString;
     use std::os::windows::ffi::OsStringExt;
 
     use windows::core::BOOL;
-    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
-    use windows::Win32::Foundation::CloseHandle;
-    use windows::Win32::System::Threading::{
-        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
-        PROCESS_QUERY_LIMITED_INFORMATION,
-    };
+    use windows::Win32::Foundation::{HWND, LPARAM, RECT, WPARAM};
     use windows::Win32::UI::WindowsAndMessaging::{
-        EnumWindows, GetClassNameW, GetWindowTextLengthW, GetWindowTextW,
-        GetWindowThreadProcessId, IsWindow, IsWindowVisible, PostMessageW, WM_KEYDOWN, WM_KEYUP,
+        EnumWindows, GetClassNameW, GetClientRect, GetForegroundWindow, GetWindowTextLengthW,
+        GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow, IsWindowVisible,
+        PostMessageW, SetForegroundWindow, ShowWindow, SW_RESTORE, WM_KEYDOWN, WM_KEYUP,
+        WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_RBUTTONDOWN, WM_RBUTTONUP,
     };
 
-    use super::WindowInfo;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use super::super::keymap::KeyDef;
+    use super::super::process::image_path;
+    use super::super::types::hold_for;
+    use super::{WindowFilter, WindowInfo};
     use crate::error::{AppError, AppResult};
+    use crate::services::hotkey::MouseButton;
+
+    /// 枚举回调的上下文
+    struct EnumContext {
+        visible_only: bool,
+        windows: Vec<WindowInfo>,
+    }
 
-    /// 枚举所有可见窗口
-    pub fn enumerate_windows(filter: Option<&str>) -> AppResult<Vec<WindowInfo>> {
-        let mut windows: Vec<WindowInfo> = Vec::new();
+    /// 枚举有标题的顶层窗口并按 `filter` 过滤
+    pub fn enumerate_windows(filter: &WindowFilter) -> AppResult<Vec<WindowInfo>> {
+        // 先编译正则，表达式无效时不必枚举
+        let matcher = filter.matcher()?;
+        let mut context = EnumContext {
+            visible_only: filter.visible_only,
+            windows: Vec::new(),
+        };
 
         unsafe {
-            EnumWindows(Some(enum_window_callback), LPARAM(&mut windows as *mut _ as isize))
+            EnumWindows(Some(enum_window_callback), LPARAM(&mut context as *mut _ as isize))
                 .map_err(|e| AppError::Hotkey(format!("枚举窗口失败: {e}")))?;
         }
 
-        // 应用过滤器
-        if let Some(keyword) = filter {
-            if !keyword.is_empty() {
-                let keyword_lower = keyword.to_lowercase();
-                windows.retain(|w| {
-                    w.title.to_lowercase().contains(&keyword_lower)
-                        || w.process_name.to_lowercase().contains(&keyword_lower)
-                        || w.class_name.to_lowercase().contains(&keyword_lower)
-                });
-            }
-        }
-
-        Ok(windows)
+        let mut windows = context.windows;
+        windows.retain(|w| matcher.matches(w));
+        Ok(sort_and_dedup(windows))
     }
 
     /// 窗口枚举回调
     unsafe extern "system" fn enum_window_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let context = &mut *(lparam.0 as *mut EnumContext);
+
         // 跳过不可见窗口
-        if !IsWindowVisible(hwnd).as_bool() {
+        if context.visible_only && !IsWindowVisible(hwnd).as_bool() {
             return BOOL(1); // 继续枚举
         }
 
@@ -91,7 +269,9 @@ mod windows_impl {
             .to_string();
 
         // 获取进程名
-        let process_name = get_process_name(hwnd).unwrap_or_default();
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        let process_name = get_process_name(pid).unwrap_or_default();
 
         // 构建显示名称
         let display_name = if process_name.is_empty() {
@@ -100,12 +280,12 @@ mod windows_impl {
             format!("[{}] {}", process_name, title)
         };
 
-        let windows = &mut *(lparam.0 as *mut Vec<WindowInfo>);
-        windows.push(WindowInfo {
+        context.windows.push(WindowInfo {
             hwnd: hwnd.0 as usize as u64,
             title,
             class_name,
             process_name,
+            pid,
             display_name,
         });
 
@@ -113,35 +293,23 @@ mod windows_impl {
     }
 
     /// 获取窗口所属进程名
-    unsafe fn get_process_name(hwnd: HWND) -> Option<String> {
-        let mut pid: u32 = 0;
-        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    fn get_process_name(pid: u32) -> Option<String> {
+        let path = image_path(pid)?;
+        // 提取文件名
+        path.rsplit('\\').next().map(|s| s.to_string())
+    }
 
-        if pid == 0 {
-            return None;
+    /// 前台窗口所属进程名；拿不到（锁屏、UAC 安全桌面等）时为 None
+    pub fn foreground_process_name() -> Option<String> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+            let mut pid: u32 = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            get_process_name(pid)
         }
-
-        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
-
-        let mut buf: Vec<u16> = vec![0; 260];
-        let mut size = buf.len() as u32;
-
-        let result = if QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, windows::core::PWSTR(buf.as_mut_ptr()), &mut size).is_ok() {
-            // 确保 size 不超过 buf 长度
-            let actual_size = (size as usize).min(buf.len());
-            let path = OsString::from_wide(&buf[..actual_size])
-                .to_string_lossy()
-                .to_string();
-            // 提取文件名
-            path.rsplit('\\').next().map(|s| s.to_string())
-        } else {
-            None
-        };
-
-        // 关闭进程句柄，避免资源泄漏
-        let _ = CloseHandle(handle);
-
-        result
     }
 
     /// 将 u64 转换为 HWND
@@ -154,8 +322,34 @@ mod windows_impl {
         unsafe { IsWindow(Some(u64_to_hwnd(hwnd))).as_bool() }
     }
 
-    /// 向指定窗口发送按键
-    pub fn send_key_to_window(hwnd: u64, virtual_key: u16) -> AppResult<()> {
+    /// 把目标窗口切到前台（最小化时先还原），已在前台则什么也不做。
+    /// 其他程序持有前台锁时 SetForegroundWindow 会失败，由调用方决定是否继续
+    pub fn focus_window(hwnd: u64) -> AppResult<()> {
+        let hwnd = u64_to_hwnd(hwnd);
+        unsafe {
+            if GetForegroundWindow() == hwnd {
+                return Ok(());
+            }
+            if IsIconic(hwnd).as_bool() {
+                let _ = ShowWindow(hwnd, SW_RESTORE);
+            }
+            if !SetForegroundWindow(hwnd).as_bool() {
+                return Err(AppError::Hotkey(
+                    "无法将目标窗口切到前台（前台可能被其他程序锁定）".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 向指定窗口发送按键，按下后按住 `hold_ms` 再释放。
+    /// lParam 带扫描码与扩展键标志，方向键不会被当成小键盘
+    pub fn send_key_to_window(
+        hwnd: u64,
+        key: KeyDef,
+        hold_ms: u64,
+        stop_flag: Option<&Arc<AtomicBool>>,
+    ) -> AppResult<()> {
         let hwnd = u64_to_hwnd(hwnd);
 
         unsafe {
@@ -164,24 +358,81 @@ mod windows_impl {
             }
 
             // 发送 WM_KEYDOWN
-            PostMessageW(Some(hwnd), WM_KEYDOWN, WPARAM(virtual_key as usize), LPARAM(0))
-                .map_err(|e| AppError::Hotkey(format!("发送 WM_KEYDOWN 失败: {e}")))?;
+            PostMessageW(
+                Some(hwnd),
+                WM_KEYDOWN,
+                WPARAM(key.vk as usize),
+                LPARAM(key.message_lparam(false) as isize),
+            )
+            .map_err(|e| AppError::Hotkey(format!("发送 WM_KEYDOWN 失败: {e}")))?;
 
-            // 短暂延迟
-            std::thread::sleep(std::time::Duration::from_millis(10));
+            hold_for(hold_ms, stop_flag);
 
-            // 发送 WM_KEYUP (设置 bit 31 和 bit 30 表示 key release)
+            // 发送 WM_KEYUP (lParam 置 bit 31 和 bit 30 表示 key release)
             PostMessageW(
                 Some(hwnd),
                 WM_KEYUP,
-                WPARAM(virtual_key as usize),
-                LPARAM(0xC0000001u32 as isize),
+                WPARAM(key.vk as usize),
+                LPARAM(key.message_lparam(true) as isize),
             )
             .map_err(|e| AppError::Hotkey(format!("发送 WM_KEYUP 失败: {e}")))?;
         }
 
         Ok(())
     }
+
+    /// 向指定窗口客户区中心发送一次鼠标点击，按住 `hold_ms` 后释放
+    pub fn send_click_to_window(
+        hwnd: u64,
+        button: MouseButton,
+        hold_ms: u64,
+        stop_flag: Option<&Arc<AtomicBool>>,
+    ) -> AppResult<()> {
+        let hwnd = u64_to_hwnd(hwnd);
+        // wParam 为按下时的 MK_* 按键状态位（MK_LBUTTON/MK_RBUTTON/MK_MBUTTON）
+        let (down, up, mk) = match button {
+            MouseButton::Left => (WM_LBUTTONDOWN, WM_LBUTTONUP, 0x0001),
+            MouseButton::Right => (WM_RBUTTONDOWN, WM_RBUTTONUP, 0x0002),
+            MouseButton::Middle => (WM_MBUTTONDOWN, WM_MBUTTONUP, 0x0010),
+        };
+
+        unsafe {
+            if !IsWindow(Some(hwnd)).as_bool() {
+                return Err(AppError::Hotkey("目标窗口已关闭".into()));
+            }
+
+            let mut rect = RECT::default();
+            GetClientRect(hwnd, &mut rect)
+                .map_err(|e| AppError::Hotkey(format!("获取窗口客户区失败: {e}")))?;
+            // lParam 低 16 位 x、高 16 位 y（客户区坐标）
+            let x = ((rect.right - rect.left) / 2) as isize & 0xFFFF;
+            let y = ((rect.bottom - rect.top) / 2) as isize & 0xFFFF;
+            let pos = LPARAM((y << 16) | x);
+
+            PostMessageW(Some(hwnd), down, WPARAM(mk), pos)
+                .map_err(|e| AppError::Hotkey(format!("发送鼠标按下消息失败: {e}")))?;
+
+            hold_for(hold_ms, stop_flag);
+
+            PostMessageW(Some(hwnd), up, WPARAM(0), pos)
+                .map_err(|e| AppError::Hotkey(format!("发送鼠标释放消息失败: {e}")))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 按进程名、标题排序（不区分大小写），并合并同一进程下标题相同的窗口——
+/// 同一客户端常有多个同名顶层窗口，选哪个都一样，只保留 Z 序最靠前的那个
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn sort_and_dedup(windows: Vec<WindowInfo>) -> Vec<WindowInfo> {
+    let mut seen = std::collections::HashSet::new();
+    let mut windows: Vec<WindowInfo> = windows
+        .into_iter()
+        .filter(|w| seen.insert((w.pid, w.title.clone())))
+        .collect();
+    windows.sort_by_cached_key(|w| (w.process_name.to_lowercase(), w.title.to_lowercase()));
+    windows
 }
 
 #[cfg(target_os = "windows")]
@@ -189,17 +440,176 @@ pub use windows_impl::*;
 
 // 非 Windows 平台的占位实现
 #[cfg(not(target_os = "windows"))]
-pub fn enumerate_windows(_filter: Option<&str>) -> AppResult<Vec<WindowInfo>> {
+#[allow(dead_code)]
+pub fn enumerate_windows(_filter: &WindowFilter) -> AppResult<Vec<WindowInfo>> {
     Err(AppError::Hotkey("窗口模式仅支持 Windows".into()))
 }
 
 #[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
 pub fn is_window_valid(_hwnd: u64) -> bool {
     false
 }
 
 #[cfg(not(target_os = "windows"))]
 #[allow(dead_code)]
-pub fn send_key_to_window(_hwnd: u64, _virtual_key: u16) -> AppResult<()> {
+pub fn foreground_process_name() -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn focus_window(_hwnd: u64) -> AppResult<()> {
     Err(AppError::Hotkey("窗口模式仅支持 Windows".into()))
 }
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn send_key_to_window(
+    _hwnd: u64,
+    _key: super::keymap::KeyDef,
+    _hold_ms: u64,
+    _stop_flag: Option<&std::sync::Arc<std::sync::atomic::AtomicBool>>,
+) -> AppResult<()> {
+    Err(AppError::Hotkey("窗口模式仅支持 Windows".into()))
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn send_click_to_window(
+    _hwnd: u64,
+    _button: super::MouseButton,
+    _hold_ms: u64,
+    _stop_flag: Option<&std::sync::Arc<std::sync::atomic::AtomicBool>>,
+) -> AppResult<()> {
+    Err(AppError::Hotkey("窗口模式仅支持 Windows".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(title: &str, process_name: &str) -> WindowInfo {
+        WindowInfo {
+            hwnd: 1,
+            title: title.into(),
+            class_name: "KGWin32App".into(),
+            process_name: process_name.into(),
+            pid: 100,
+            display_name: format!("[{process_name}] {title}"),
+        }
+    }
+
+    fn filter(keyword: Option<&str>, process_only: bool, regex: Option<&str>) -> WindowFilter {
+        WindowFilter {
+            keyword: keyword.map(Into::into),
+            process_only,
+            regex: regex.map(Into::into),
+            ..WindowFilter::default()
+        }
+    }
+
+    #[test]
+    fn class_lookup_is_case_insensitive_and_can_require_process() {
+        let mut other = window("记事本", "notepad.exe");
+        other.class_name = "Notepad".into();
+        let mut second = window("剑网3 - 小号", "JX3ClientX64.exe");
+        second.hwnd = 2;
+        let windows = [other, window("剑网3", "JX3ClientX64.exe"), second];
+
+        let hit = first_with_class(&windows, " kgwin32app ", None).unwrap();
+        assert_eq!(hit.hwnd, 1);
+        assert_eq!(hit.title, "剑网3");
+        assert!(first_with_class(&windows, "KGWin32App", Some("JX3ClientX64")).is_some());
+        assert!(first_with_class(&windows, "KGWin32App", Some("")).is_some());
+        assert!(first_with_class(&windows, "KGWin32App", Some("notepad.exe")).is_none());
+        assert!(first_with_class(&windows, "UnrealWindow", None).is_none());
+    }
+
+    #[test]
+    fn stale_targets_are_only_refound_when_unambiguous() {
+        let mut other = window("记事本", "notepad.exe");
+        other.class_name = "Notepad".into();
+        let windows = [other.clone(), window("剑网3", "JX3ClientX64.exe")];
+        let hit = unique_with_class(&windows, "kgwin32app", "JX3ClientX64").unwrap();
+        assert_eq!(hit.map(|w| w.hwnd), Some(1));
+        assert!(unique_with_class(&windows, "KGWin32App", "")
+            .unwrap()
+            .is_none());
+        assert!(unique_with_class(&windows, "KGWin32App", "notepad.exe")
+            .unwrap()
+            .is_none());
+
+        let mut second = window("剑网3 - 小号", "JX3ClientX64.exe");
+        second.hwnd = 2;
+        let multibox = [other, window("剑网3", "JX3ClientX64.exe"), second];
+        assert!(unique_with_class(&multibox, "KGWin32App", "JX3ClientX64.exe").is_err());
+    }
+
+    #[test]
+    fn process_names_match_case_insensitively_with_optional_exe() {
+        assert!(process_name_matches("JX3ClientX64.exe", "jx3clientx64.exe"));
+        assert!(process_name_matches("JX3ClientX64.exe", "JX3ClientX64"));
+        assert!(process_name_matches("JX3ClientX64", " jx3clientx64.EXE "));
+        assert!(!process_name_matches("JX3ClientX64.exe", "JX3Client"));
+        assert!(!process_name_matches("notepad.exe", "JX3ClientX64.exe"));
+        assert!(!process_name_matches("", ""));
+    }
+
+    #[test]
+    fn keyword_matches_title_process_or_class_by_default() {
+        let matcher = filter(Some("JX3"), false, None).matcher().unwrap();
+        assert!(matcher.matches(&window("剑网3", "JX3ClientX64.exe")));
+        assert!(matcher.matches(&window("JX3 攻略 - Chrome", "chrome.exe")));
+        assert!(filter(Some("kgwin32"), false, None)
+            .matcher()
+            .unwrap()
+            .matches(&window("剑网3", "JX3ClientX64.exe")));
+    }
+
+    #[test]
+    fn process_only_ignores_incidental_title_text() {
+        let matcher = filter(Some("jx3"), true, None).matcher().unwrap();
+        assert!(matcher.matches(&window("剑网3", "JX3ClientX64.exe")));
+        assert!(!matcher.matches(&window("JX3 攻略 - Chrome", "chrome.exe")));
+    }
+
+    #[test]
+    fn regex_applies_to_title_and_combines_with_keyword() {
+        let matcher = filter(Some("jx3client"), false, Some(r"^剑网3\s*\d*$"))
+            .matcher()
+            .unwrap();
+        assert!(matcher.matches(&window("剑网3 2", "JX3ClientX64.exe")));
+        assert!(!matcher.matches(&window("剑网3 - 登录器", "JX3ClientX64.exe")));
+        assert!(filter(None, false, Some("(")).matcher().is_err());
+    }
+
+    #[test]
+    fn empty_filter_matches_everything_and_defaults_to_visible_only() {
+        let default = WindowFilter::default();
+        assert!(default.visible_only);
+        let matcher = default.matcher().unwrap();
+        assert!(matcher.matches(&window("任意", "any.exe")));
+        let blank = filter(Some("  "), true, Some(""));
+        assert!(blank.matcher().unwrap().matches(&window("任意", "any.exe")));
+    }
+
+    #[test]
+    fn results_are_sorted_and_same_process_titles_collapsed() {
+        let entry = |hwnd: u64, title: &str, process_name: &str, pid: u32| WindowInfo {
+            hwnd,
+            pid,
+            ..window(title, process_name)
+        };
+        let windows = sort_and_dedup(vec![
+            entry(1, "剑网3", "JX3ClientX64.exe", 200),
+            entry(2, "剑网3", "JX3ClientX64.exe", 200),
+            entry(3, "剑网3", "JX3ClientX64.exe", 300),
+            entry(4, "Notes", "Notepad.exe", 400),
+            entry(5, "about:blank", "chrome.exe", 500),
+        ]);
+        let hwnds: Vec<u64> = windows.iter().map(|w| w.hwnd).collect();
+        // 同 pid 同标题只留先枚举到的；多开的另一个 pid 保留
+        assert_eq!(hwnds, vec![5, 1, 3, 4]);
+    }
+}
@@ -1,16 +1,26 @@
+use std::cmp::Reverse;
 use std::collections::hash_map::DefaultHasher;
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{self, File};
 use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
 
 use crate::error::{AppError, AppResult};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct FileEntry {
     id: u64,
+    /// 显示用名称；非 UTF-8 名称按 lossy 转换，此时 lossy_name 为 true。
+    /// 复制按 id 匹配真实路径，不依赖此名称
     name: String,
+    lossy_name: bool,
     is_dir: bool,
     selected: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -21,25 +31,750 @@ pub struct FileEntry {
 pub struct CopyParams {
     pub source_path: String,
     pub target_path: String,
+    /// 复制完成、替换目标之前逐文件比对源与副本（流式哈希），不一致则中止
+    #[serde(default)]
+    pub verify: bool,
+    #[serde(default)]
+    pub mode: CopyMode,
 }
 
+/// 键位复制方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CopyMode {
+    /// 目标整体换成源的内容（默认）
+    #[default]
+    Replace,
+    /// 源文件叠加到目标现有内容上：同名文件覆盖，目标独有的文件保留
+    Merge,
+}
+
+/// `copy_source_to_target` 的结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyOutcome {
+    /// 实际执行的复制方式
+    pub mode: CopyMode,
+    /// 旧目标的备份路径（两种方式都会备份）；目标原本不存在时为 None
+    pub backup_path: Option<String>,
+    /// 实际从源复制过去的内容（合并时不含目标原有文件）；files 为 0 说明源是空的
+    pub copied: DirStats,
+}
+
+/// 目录树读取选项；缺省即当前客户端布局（角色在第 4 层，跳过 userpreferences）。
+/// 以 . 开头的隐藏目录（含复制用的临时/备份目录）始终跳过，不受 skip_names 影响。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ListOptions {
+    /// 角色目录所在深度（从 1 开始），该层为可选中的叶子
+    pub max_depth: usize,
+    /// 按名称跳过的目录
+    pub skip_names: Vec<String>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: ROLE_DEPTH,
+            skip_names: vec!["userpreferences".into()],
+        }
+    }
+}
+
+impl ListOptions {
+    /// 隐藏目录（含复制用的临时/备份目录）与 skip_names 中的目录不列出
+    fn skips(&self, dir_name: &str) -> bool {
+        dir_name.starts_with('.') || self.skip_names.iter().any(|name| name == dir_name)
+    }
+}
+
+/// 角色层目录（`list_roles` 把目录树拍平后的一项）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleEntry {
+    /// 与目录树中同一节点的 `FileEntry::id` 相同
+    pub id: u64,
+    pub name: String,
+    /// 完整路径，可直接作为 `copy_role` 的源
+    pub path: String,
+    /// 相对所列根目录的路径，`/` 分隔（如 `账号/区服/服务器/角色`）
+    pub relative_path: String,
+}
+
+/// 目录树统计（不含根目录本身；符号链接与 copy_dir_all 同口径跳过，只计数）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirStats {
+    pub files: u64,
+    pub dirs: u64,
+    pub bytes: u64,
+    /// 跳过的符号链接数
+    pub skipped_symlinks: u64,
+}
+
+/// 键位复制预演结果：会复制什么、会替换掉什么，全程不改动磁盘
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyPreview {
+    pub source: DirStats,
+    pub target_exists: bool,
+    /// 目标现有内容（复制后会被整体替换）；目标不存在时为 None
+    pub target: Option<DirStats>,
+}
+
+/// 多目标复制中单个目标的结果（逐个汇报，部分失败可见）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetCopyResult {
+    pub target_path: String,
+    /// 失败原因；None 表示复制成功
+    pub error: Option<String>,
+}
+
+/// 两个目录的文件级差异（相对路径，`/` 分隔，已排序）。
+/// 同一相对路径两边都有时先比大小，大小相同再比内容哈希
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub different: Vec<String>,
+}
+
+/// 键位复制进度（每复制完一个文件推送一次；total 为预先统计的文件总数）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    pub copied: u64,
+    pub total: u64,
+    /// 相对源目录的路径
+    pub current_file: String,
+}
+
+/// 进度回调：命令层把它接成 Tauri 事件，测试用空函数或记录闭包
+pub type CopyProgressFn<'a> = dyn Fn(CopyProgress) + 'a;
+
 pub struct KeyboardService;
 
 /// 角色目录所在的树深度（userdata/<账号>/<区服>/<服务器>/<角色>）
 const ROLE_DEPTH: usize = 4;
 
+/// 键位复制前保留的旧目标备份数（每个目标目录各自计数）
+pub const KEEP_BACKUPS: usize = 5;
+
+/// 导入键位压缩包时解压总量上限，防 zip 炸弹（键位目录通常只有几 MB）
+pub const MAX_IMPORT_BYTES: u64 = 256 * 1024 * 1024;
+
 impl KeyboardService {
     /// List directory contents recursively for keyboard config selection
-    pub fn list_directory_contents(path: &str) -> AppResult<Vec<FileEntry>> {
+    pub fn list_directory_contents(path: &str, options: &ListOptions) -> AppResult<Vec<FileEntry>> {
         let path = Path::new(path);
         if !path.is_dir() {
             return Err(AppError::Keyboard("提供的路径不是一个目录".into()));
         }
-        Self::read_directory(path, 1)
+        if options.max_depth == 0 {
+            return Err(AppError::validation("max_depth", "目录深度至少为 1"));
+        }
+        Self::read_directory(path, 1, options)
+    }
+
+    /// The role-level directories of the tree (depth `max_depth`) as a flat
+    /// list with full paths, sorted by relative path
+    pub fn list_roles(path: &str, options: &ListOptions) -> AppResult<Vec<RoleEntry>> {
+        let root = Path::new(path);
+        if !root.is_dir() {
+            return Err(AppError::Keyboard("提供的路径不是一个目录".into()));
+        }
+        if options.max_depth == 0 {
+            return Err(AppError::validation("max_depth", "目录深度至少为 1"));
+        }
+        let mut roles = vec![];
+        Self::collect_roles(root, root, 1, options, &mut roles)?;
+        roles.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        Ok(roles)
+    }
+
+    fn collect_roles(
+        root: &Path,
+        dir: &Path,
+        depth: usize,
+        options: &ListOptions,
+        out: &mut Vec<RoleEntry>,
+    ) -> AppResult<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if options.skips(&name) {
+                continue;
+            }
+            let path = entry.path();
+            if depth < options.max_depth {
+                Self::collect_roles(root, &path, depth + 1, options, out)?;
+                continue;
+            }
+            let relative_path = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.push(RoleEntry {
+                id: Self::generate_id(&name, &path),
+                name,
+                path: path.to_string_lossy().into_owned(),
+                relative_path,
+            });
+        }
+        Ok(())
+    }
+
+    /// Dry run of `copy_source_to_target`: same validation, read-only walk
+    pub fn preview_copy(params: &CopyParams) -> AppResult<CopyPreview> {
+        let (source, target) = Self::validate_copy_params(params)?;
+
+        let mut source_stats = DirStats::default();
+        Self::walk_dir(&source, None, &mut source_stats, &mut |_| {})?;
+
+        let target_exists = target.exists();
+        let target_stats = if target.is_dir() {
+            let mut stats = DirStats::default();
+            Self::walk_dir(&target, None, &mut stats, &mut |_| {})?;
+            Some(stats)
+        } else {
+            None
+        };
+
+        Ok(CopyPreview {
+            source: source_stats,
+            target_exists,
+            target: target_stats,
+        })
+    }
+
+    /// File-level diff of two directories, read-only. Uses the same walk as
+    /// copy/preview, so symlinks are skipped the same way.
+    pub fn diff_dirs(a: &str, b: &str) -> AppResult<DirDiff> {
+        if a.contains("..") || b.contains("..") {
+            return Err(AppError::Keyboard("路径不能包含 '..'".into()));
+        }
+        let a = Self::canonicalize_path(a)?;
+        let b = Self::canonicalize_path(b)?;
+        let files_a = Self::relative_files(&a)?;
+        let mut files_b = Self::relative_files(&b)?;
+
+        let mut diff = DirDiff::default();
+        for (relative, path_a) in files_a {
+            match files_b.remove(&relative) {
+                None => diff.only_in_a.push(relative),
+                Some(path_b) => {
+                    if !Self::same_content(&path_a, &path_b)? {
+                        diff.different.push(relative);
+                    }
+                }
+            }
+        }
+        diff.only_in_b = files_b.into_keys().collect();
+        Ok(diff)
+    }
+
+    /// Compare every source file with its copy under `copy` (size, then
+    /// streamed hash). Directories and skipped symlinks are not checked.
+    fn verify_copy(source: &Path, copy: &Path) -> AppResult<()> {
+        for (relative, file) in Self::relative_files(source)? {
+            let copied = copy.join(&relative);
+            if !copied.is_file() || !Self::same_content(&file, &copied)? {
+                return Err(AppError::Keyboard(format!(
+                    "副本与源文件不一致: {relative}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every file under `root`, keyed by its `/`-separated relative path
+    fn relative_files(root: &Path) -> AppResult<BTreeMap<String, PathBuf>> {
+        let mut files = BTreeMap::new();
+        Self::walk_dir(root, None, &mut DirStats::default(), &mut |file| {
+            let relative = file
+                .strip_prefix(root)
+                .unwrap_or(file)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(relative, file.to_path_buf());
+        })?;
+        Ok(files)
+    }
+
+    /// 大小不同直接判为不同，避免读取内容
+    fn same_content(a: &Path, b: &Path) -> AppResult<bool> {
+        if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+            return Ok(false);
+        }
+        Ok(Self::content_hash(a)? == Self::content_hash(b)?)
+    }
+
+    fn content_hash(path: &Path) -> AppResult<u64> {
+        let mut file = File::open(path)?;
+        let mut hasher = DefaultHasher::new();
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.write(&buf[..n]);
+        }
+        Ok(hasher.finish())
+    }
+
+    /// Copy keyboard config from source to target directory, reporting
+    /// per-file progress (files are pre-counted so `total` is known up front).
+    /// `params.mode` picks between replacing the target and merging into it
+    pub fn copy_source_to_target(
+        params: &CopyParams,
+        progress: &CopyProgressFn,
+    ) -> AppResult<CopyOutcome> {
+        let (source, target) = Self::validate_copy_params(params)?;
+
+        let mut total = DirStats::default();
+        Self::walk_dir(&source, None, &mut total, &mut |_| {})?;
+
+        let mut copied = 0;
+        let mut stats = DirStats::default();
+        let backup = Self::swap_replace(
+            &source,
+            &target,
+            params.mode,
+            true,
+            params.verify,
+            &mut stats,
+            &mut |file| {
+                copied += 1;
+                progress(CopyProgress {
+                    copied,
+                    total: total.files,
+                    current_file: file
+                        .strip_prefix(&source)
+                        .unwrap_or(file)
+                        .to_string_lossy()
+                        .to_string(),
+                });
+            },
+        )?;
+
+        log::info!(
+            "键位复制完成（{:?}）: {} -> {}（备份: {:?}，{:?}）",
+            params.mode,
+            source.display(),
+            target.display(),
+            backup,
+            stats
+        );
+
+        Ok(CopyOutcome {
+            mode: params.mode,
+            backup_path: backup.map(|path| path.to_string_lossy().into_owned()),
+            copied: stats,
+        })
+    }
+
+    /// Copy a single role (`<账号>/<区服>/<服务器>/<角色>`, e.g. from
+    /// `list_roles`) to the same nested location under `target_base` (another
+    /// userdata dir), with the backup, verification and progress of
+    /// `copy_source_to_target`. Returns the target role path
+    pub fn copy_role(
+        source_role_path: &str,
+        target_base: &str,
+        verify: bool,
+        progress: &CopyProgressFn,
+    ) -> AppResult<String> {
+        if !Path::new(target_base).is_dir() {
+            return Err(AppError::Keyboard(format!("目标目录不存在: {target_base}")));
+        }
+        let relative = role_relative_path(Path::new(source_role_path))?;
+        let params = CopyParams {
+            source_path: source_role_path.to_string(),
+            target_path: Path::new(target_base)
+                .join(relative)
+                .to_string_lossy()
+                .into_owned(),
+            verify,
+            mode: CopyMode::Replace,
+        };
+        Self::copy_source_to_target(&params, progress)?;
+        Ok(params.target_path)
+    }
+
+    /// Copy one source into several targets (e.g. two game installs), each with
+    /// the same validation and backup+swap as `copy_source_to_target`.
+    /// With `stop_on_error` the first failure ends the run and the remaining
+    /// targets are left out of the result; otherwise every target is attempted.
+    pub fn copy_source_to_targets(
+        source_path: &str,
+        target_paths: &[String],
+        stop_on_error: bool,
+        verify: bool,
+        progress: &CopyProgressFn,
+    ) -> AppResult<Vec<TargetCopyResult>> {
+        if target_paths.is_empty() {
+            return Err(AppError::validation(
+                "target_paths",
+                "请至少选择一个目标目录",
+            ));
+        }
+
+        let mut results = Vec::with_capacity(target_paths.len());
+        for target_path in target_paths {
+            let params = CopyParams {
+                source_path: source_path.to_string(),
+                target_path: target_path.clone(),
+                verify,
+                mode: CopyMode::Replace,
+            };
+            let error = Self::copy_source_to_target(&params, progress)
+                .err()
+                .map(|e| e.to_string());
+            if let Some(error) = &error {
+                log::warn!("键位复制到 {} 失败: {}", target_path, error);
+            }
+            let failed = error.is_some();
+            results.push(TargetCopyResult {
+                target_path: target_path.clone(),
+                error,
+            });
+            if failed && stop_on_error {
+                break;
+            }
+        }
+        Ok(results)
     }
 
-    /// Copy keyboard config from source to target directory
-    pub fn copy_source_to_target(params: &CopyParams) -> AppResult<bool> {
+    /// Copy only the selected subtrees (matched by `FileEntry::id` from
+    /// `list_directory_contents` on the same source root, with the same
+    /// `options`) into the same relative locations under target. Each subtree
+    /// is swap-replaced on its own, so unselected content in the target is
+    /// left untouched.
+    /// Returns the copied relative paths.
+    pub fn copy_selected_entries(
+        params: &CopyParams,
+        selected_ids: &[u64],
+        options: &ListOptions,
+    ) -> AppResult<Vec<String>> {
+        if selected_ids.is_empty() {
+            return Err(AppError::validation("selected_ids", "请至少选择一个条目"));
+        }
+        let (_, target) = Self::validate_copy_params(params)?;
+        // id 由前端拿到的原始路径生成，匹配时必须用同一个根（不能用规范化后的路径）
+        let source = PathBuf::from(&params.source_path);
+
+        let selected: HashSet<u64> = selected_ids.iter().copied().collect();
+        let mut matched = vec![];
+        Self::collect_selected(&source, 1, options, &selected, &mut matched)?;
+        if matched.is_empty() {
+            return Err(AppError::Keyboard(
+                "未找到所选条目，目录可能已变化，请刷新后重试".into(),
+            ));
+        }
+
+        let mut copied = vec![];
+        for dir in matched {
+            let relative = dir
+                .strip_prefix(&source)
+                .map_err(|_| AppError::Keyboard(format!("条目不在源目录内: {}", dir.display())))?;
+            Self::swap_replace(
+                &dir,
+                &target.join(relative),
+                CopyMode::Replace,
+                false,
+                params.verify,
+                &mut DirStats::default(),
+                &mut |_| {},
+            )?;
+            copied.push(relative.to_string_lossy().to_string());
+        }
+
+        log::info!(
+            "键位部分复制完成: {} -> {}，共 {} 项",
+            source.display(),
+            target.display(),
+            copied.len()
+        );
+        Ok(copied)
+    }
+
+    /// 深度优先找出 id 命中的目录；命中即整棵子树，不再向下。与 read_directory
+    /// 同样按 `options` 跳过目录、最深到 `max_depth`，树里没有的条目不会命中
+    fn collect_selected(
+        dir: &Path,
+        depth: usize,
+        options: &ListOptions,
+        selected: &HashSet<u64>,
+        out: &mut Vec<PathBuf>,
+    ) -> AppResult<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            // 符号链接与 copy_dir_all 同口径跳过
+            if !file_type.is_dir() || file_type.is_symlink() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if options.skips(&name) {
+                continue;
+            }
+            let path = entry.path();
+            if selected.contains(&Self::generate_id(&name, &path)) {
+                out.push(path);
+            } else if depth < options.max_depth {
+                Self::collect_selected(&path, depth + 1, options, selected, out)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// List backups left by `copy_source_to_target` for `target_path`, newest first
+    pub fn list_backups(target_path: &str) -> AppResult<Vec<String>> {
+        let target = PathBuf::from(target_path);
+        let (parent, target_name) = Self::split_target(&target)?;
+        Ok(Self::backups_of(parent, &target_name)?
+            .into_iter()
+            .map(|(_, path)| path.to_string_lossy().to_string())
+            .collect())
+    }
+
+    /// Roll a target back to one of its backups. The current content is itself
+    /// backed up first, so a restore can be undone the same way.
+    pub fn restore_backup(backup_path: &str) -> AppResult<()> {
+        if backup_path.contains("..") {
+            return Err(AppError::Keyboard("路径不能包含 '..'".into()));
+        }
+        let backup = PathBuf::from(backup_path);
+        let target = backup
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .and_then(|name| {
+                let (target_name, _) = parse_backup_name(&name)?;
+                Some(backup.with_file_name(target_name))
+            })
+            .ok_or_else(|| {
+                AppError::Keyboard(format!("不是有效的键位备份: {}", backup.display()))
+            })?;
+        if !backup.is_dir() {
+            return Err(AppError::Keyboard(format!(
+                "备份不存在: {}",
+                backup.display()
+            )));
+        }
+
+        Self::swap_replace(
+            &backup,
+            &target,
+            CopyMode::Replace,
+            true,
+            false,
+            &mut DirStats::default(),
+            &mut |_| {},
+        )?;
+        log::info!(
+            "键位已从备份恢复: {} -> {}",
+            backup.display(),
+            target.display()
+        );
+        Ok(())
+    }
+
+    /// Zip a keyboard config dir into `out` for sharing. Symlinks, hidden dirs
+    /// and the default skip_names (`userpreferences`) are left out, as in the
+    /// directory tree. Returns the number of files written.
+    pub fn export_zip(source: &str, out: &str) -> AppResult<usize> {
+        if source.contains("..") || out.contains("..") {
+            return Err(AppError::Keyboard("路径不能包含 '..'".into()));
+        }
+        let source = Self::canonicalize_path(source)?;
+        if !source.is_dir() {
+            return Err(AppError::Keyboard(format!(
+                "源路径不是目录: {}",
+                source.display()
+            )));
+        }
+        let out = PathBuf::from(out);
+        // 压缩包写进源目录会把写了一半的自己也打进去
+        if let Some(out_dir) = out.parent().and_then(|dir| dir.canonicalize().ok()) {
+            if out_dir.starts_with(&source) {
+                return Err(AppError::Keyboard("压缩包不能保存在源目录内".into()));
+            }
+        }
+
+        let file = File::create(&out)
+            .map_err(|e| AppError::Keyboard(format!("无法创建压缩包 {}: {e}", out.display())))?;
+        let mut zip = ZipWriter::new(file);
+        let mut count = 0;
+        let result = Self::zip_dir(&mut zip, &source, "", &ListOptions::default(), &mut count)
+            .and_then(|()| zip.finish().map(|_| ()).map_err(zip_err));
+        if let Err(e) = result {
+            let _ = fs::remove_file(&out);
+            return Err(e);
+        }
+
+        log::info!(
+            "键位已导出为压缩包: {} -> {}，共 {} 个文件",
+            source.display(),
+            out.display(),
+            count
+        );
+        Ok(count)
+    }
+
+    /// Add `dir` to the archive under `prefix` (entries are always `/`-separated)
+    fn zip_dir(
+        zip: &mut ZipWriter<File>,
+        dir: &Path,
+        prefix: &str,
+        options: &ListOptions,
+        count: &mut usize,
+    ) -> AppResult<()> {
+        let file_options = SimpleFileOptions::default();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            // 与 copy_dir_all 同口径：跳过符号链接
+            if file_type.is_symlink() {
+                log::warn!("导出时跳过符号链接: {}", entry.path().display());
+                continue;
+            }
+            let name = entry.file_name().into_string().map_err(|name| {
+                AppError::Keyboard(format!(
+                    "文件名不是有效 UTF-8，无法导出: {}",
+                    name.to_string_lossy()
+                ))
+            })?;
+            let rel = format!("{prefix}{name}");
+            if file_type.is_dir() {
+                if options.skips(&name) {
+                    continue;
+                }
+                zip.add_directory(format!("{rel}/"), file_options)
+                    .map_err(zip_err)?;
+                Self::zip_dir(zip, &entry.path(), &format!("{rel}/"), options, count)?;
+            } else {
+                zip.start_file(rel, file_options).map_err(zip_err)?;
+                io::copy(&mut File::open(entry.path())?, zip)?;
+                *count += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Extract a keyboard zip into `target`, replacing it the way a copy does
+    /// (the old content is kept as a backup). Entries with `..` or absolute
+    /// paths reject the whole archive, as does more than MAX_IMPORT_BYTES of
+    /// content. Returns the number of files extracted.
+    pub fn import_zip(archive: &str, target: &str) -> AppResult<usize> {
+        if archive.contains("..") || target.contains("..") {
+            return Err(AppError::Keyboard("路径不能包含 '..'".into()));
+        }
+        let target = PathBuf::from(target);
+        if target.is_file() {
+            return Err(AppError::Keyboard(format!(
+                "目标路径不是目录: {}",
+                target.display()
+            )));
+        }
+        let (parent, target_name) = Self::split_target(&target)?;
+        let file = File::open(archive)
+            .map_err(|e| AppError::Keyboard(format!("无法打开压缩包 {archive}: {e}")))?;
+        let mut zip = ZipArchive::new(file)
+            .map_err(|e| AppError::Keyboard(format!("压缩包无法解析（文件可能损坏）: {e}")))?;
+
+        // 先解到同级隐藏目录，再走与复制相同的交换替换：坏包不会伤到目标
+        fs::create_dir_all(parent)?;
+        let staging = parent.join(format!(".{target_name}.tmp-import"));
+        let _ = fs::remove_dir_all(&staging);
+        let result = Self::extract_zip(&mut zip, &staging, MAX_IMPORT_BYTES).and_then(|count| {
+            Self::swap_replace(
+                &staging,
+                &target,
+                CopyMode::Replace,
+                true,
+                false,
+                &mut DirStats::default(),
+                &mut |_| {},
+            )?;
+            Ok(count)
+        });
+        let _ = fs::remove_dir_all(&staging);
+        let count = result?;
+
+        log::info!(
+            "键位压缩包已导入: {} -> {}，共 {} 个文件",
+            archive,
+            target.display(),
+            count
+        );
+        Ok(count)
+    }
+
+    /// Extract every entry into `dst`, refusing unsafe names up front and
+    /// stopping once more than `limit` bytes have been written
+    fn extract_zip(zip: &mut ZipArchive<File>, dst: &Path, limit: u64) -> AppResult<usize> {
+        let too_large = || {
+            AppError::Keyboard(format!(
+                "压缩包解压后超过 {} MB，已拒绝导入",
+                limit / 1024 / 1024
+            ))
+        };
+
+        // 先整体检查一遍条目，坏包在落盘前就拒绝
+        let mut declared = 0u64;
+        for i in 0..zip.len() {
+            let entry = zip.by_index(i).map_err(zip_err)?;
+            archive_entry_path(entry.name())?;
+            declared = declared.saturating_add(entry.size());
+        }
+        if declared > limit {
+            return Err(too_large());
+        }
+
+        fs::create_dir_all(dst)?;
+        let mut written = 0u64;
+        let mut count = 0;
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).map_err(zip_err)?;
+            let rel = archive_entry_path(entry.name())?;
+            if entry.is_symlink() {
+                log::warn!("导入时跳过符号链接: {}", entry.name());
+                continue;
+            }
+            if rel.as_os_str().is_empty() {
+                continue;
+            }
+            let path = dst.join(&rel);
+            if entry.is_dir() {
+                fs::create_dir_all(&path)?;
+                continue;
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            // 条目头里的大小可以造假，按实际解出的字节数再卡一次
+            let mut reader = (&mut entry).take(limit - written + 1);
+            written += io::copy(&mut reader, &mut File::create(&path)?)?;
+            if written > limit {
+                return Err(too_large());
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Shared copy/preview validation; returns (canonical source, target)
+    fn validate_copy_params(params: &CopyParams) -> AppResult<(PathBuf, PathBuf)> {
         let source = Self::canonicalize_path(&params.source_path)?;
         let target = PathBuf::from(&params.target_path);
 
@@ -62,44 +797,82 @@ impl KeyboardService {
             )));
         }
 
-        Self::swap_replace_dir(&source, &target)?;
-
-        log::info!(
-            "键位复制完成: {} -> {}",
-            source.display(),
-            target.display()
-        );
-
-        Ok(true)
+        Ok((source, target))
     }
 
     /// 安全交换式复制：先把源完整复制到同级临时目录，成功后再与旧目标交换。
     /// 任何一步失败，目标原有内容都保持完好（不先删后拷）。
     /// 键位复制与插件配置同步共用此语义。
     pub(crate) fn swap_replace_dir(source: &Path, target: &Path) -> AppResult<()> {
-        let parent = target
-            .parent()
-            .filter(|p| !p.as_os_str().is_empty())
-            .ok_or_else(|| AppError::Keyboard(format!("目标路径无效: {}", target.display())))?;
-        let target_name = target
-            .file_name()
-            .ok_or_else(|| AppError::Keyboard(format!("目标路径无效: {}", target.display())))?
-            .to_string_lossy()
-            .to_string();
+        Self::swap_replace(
+            source,
+            target,
+            CopyMode::Replace,
+            false,
+            false,
+            &mut DirStats::default(),
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+
+    /// `swap_replace_dir` 的实现。`keep_backup` 时旧目标不删除，而是保留为
+    /// `.<target>.bak-<毫秒时间戳>`（只保留最近 KEEP_BACKUPS 份），返回备份路径。
+    /// 移开旧目标失败即中止，绝不在没有备份的情况下替换。
+    /// `verify` 时在交换前逐文件校验临时副本，不一致则丢弃副本、目标不动。
+    /// `stats` 累计从源复制的内容，`on_file` 在每个源文件复制完成后调用。
+    /// `CopyMode::Merge` 时临时目录先放入旧目标的一份副本，再叠加源文件，
+    /// 交换方式不变，所以合并同样失败不伤目标、也留备份。
+    fn swap_replace(
+        source: &Path,
+        target: &Path,
+        mode: CopyMode,
+        keep_backup: bool,
+        verify: bool,
+        stats: &mut DirStats,
+        on_file: &mut dyn FnMut(&Path),
+    ) -> AppResult<Option<PathBuf>> {
+        let (parent, target_name) = Self::split_target(target)?;
         fs::create_dir_all(parent).map_err(|e| {
             AppError::Keyboard(format!("无法创建目标目录 {}: {}", parent.display(), e))
         })?;
 
         // 以 . 开头：read_directory 会忽略隐藏目录，残留也不会污染树
         let tmp = parent.join(format!(".{target_name}.tmp-copy"));
-        let bak = parent.join(format!(".{target_name}.bak-copy"));
         let _ = fs::remove_dir_all(&tmp);
-        let _ = fs::remove_dir_all(&bak);
+        let bak = if keep_backup {
+            // 同一毫秒内连续操作（如从备份恢复）时顺延，绝不覆盖已有备份
+            let mut stamp = now_millis();
+            while parent.join(backup_name(&target_name, stamp)).exists() {
+                stamp += 1;
+            }
+            parent.join(backup_name(&target_name, stamp))
+        } else {
+            let bak = parent.join(format!(".{target_name}.bak-copy"));
+            let _ = fs::remove_dir_all(&bak);
+            bak
+        };
 
-        if let Err(e) = Self::copy_dir_all(source, &tmp) {
+        if mode == CopyMode::Merge && target.is_dir() {
+            if let Err(e) = Self::copy_dir_all(target, &tmp) {
+                let _ = fs::remove_dir_all(&tmp);
+                return Err(AppError::Keyboard(format!(
+                    "读取目标现有内容失败（目标未受影响）: {e}"
+                )));
+            }
+        }
+        if let Err(e) = Self::walk_dir(source, Some(&tmp), stats, on_file) {
             let _ = fs::remove_dir_all(&tmp);
             return Err(AppError::Keyboard(format!("复制失败（目标未受影响）: {e}")));
         }
+        if verify {
+            if let Err(e) = Self::verify_copy(source, &tmp) {
+                let _ = fs::remove_dir_all(&tmp);
+                return Err(AppError::Keyboard(format!(
+                    "复制校验失败（目标未受影响）: {e}"
+                )));
+            }
+        }
 
         // 交换：旧目标先挪到备份位，再把新内容就位
         if target.exists() {
@@ -118,10 +891,109 @@ impl KeyboardService {
             let _ = fs::remove_dir_all(&tmp);
             return Err(AppError::Keyboard(format!("写入目标目录失败（已恢复原内容）: {e}")));
         }
-        let _ = fs::remove_dir_all(&bak);
+
+        if !keep_backup {
+            let _ = fs::remove_dir_all(&bak);
+            return Ok(None);
+        }
+        if !bak.exists() {
+            return Ok(None);
+        }
+        // 清理超出数量的旧备份：失败只记日志，不影响已完成的复制
+        match Self::backups_of(parent, &target_name) {
+            Ok(backups) => {
+                for (_, old) in backups.into_iter().skip(KEEP_BACKUPS) {
+                    if let Err(e) = fs::remove_dir_all(&old) {
+                        log::warn!("清理旧键位备份失败 {}: {e}", old.display());
+                    }
+                }
+            }
+            Err(e) => log::warn!("读取键位备份列表失败: {e}"),
+        }
+        Ok(Some(bak))
+    }
+
+    /// Split a target path into (non-empty parent, file name)
+    fn split_target(target: &Path) -> AppResult<(&Path, String)> {
+        let parent = target
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .ok_or_else(|| AppError::Keyboard(format!("目标路径无效: {}", target.display())))?;
+        let target_name = target
+            .file_name()
+            .ok_or_else(|| AppError::Keyboard(format!("目标路径无效: {}", target.display())))?
+            .to_string_lossy()
+            .to_string();
+        Ok((parent, target_name))
+    }
+
+    /// Backups of `target_name` under `parent`, newest first
+    fn backups_of(parent: &Path, target_name: &str) -> AppResult<Vec<(u128, PathBuf)>> {
+        if !parent.is_dir() {
+            return Ok(vec![]);
+        }
+        let mut backups = vec![];
+        for entry in fs::read_dir(parent)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some((name_of, stamp)) = parse_backup_name(&name) {
+                if name_of == target_name {
+                    backups.push((stamp, entry.path()));
+                }
+            }
+        }
+        backups.sort_by_key(|entry| Reverse(entry.0));
+        Ok(backups)
+    }
+
+    /// Every `.<target>.bak-<ms>` backup in the userdata tree at `base` (down
+    /// to the role level), one group per backed-up directory, each newest
+    /// first. Hidden directories and symlinks are not descended into.
+    pub(crate) fn backup_groups(base: &Path) -> AppResult<Vec<Vec<(u128, PathBuf)>>> {
+        let mut groups = vec![];
+        Self::collect_backup_groups(base, 0, &mut groups)?;
+        Ok(groups)
+    }
+
+    fn collect_backup_groups(
+        dir: &Path,
+        depth: usize,
+        groups: &mut Vec<Vec<(u128, PathBuf)>>,
+    ) -> AppResult<()> {
+        let mut targets = BTreeSet::new();
+        let mut children = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            // file_type 不跟随符号链接：链接既不算备份也不进入
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some((target_name, _)) = parse_backup_name(&name) {
+                targets.insert(target_name.to_string());
+            } else if !name.starts_with('.') && depth + 1 < ROLE_DEPTH {
+                children.push(entry.path());
+            }
+        }
+        for target_name in targets {
+            groups.push(Self::backups_of(dir, &target_name)?);
+        }
+        for child in children {
+            Self::collect_backup_groups(&child, depth + 1, groups)?;
+        }
         Ok(())
     }
 
+    /// Total size of the files under `dir` (symlinks skipped)
+    pub(crate) fn dir_size(dir: &Path) -> AppResult<u64> {
+        let mut stats = DirStats::default();
+        Self::walk_dir(dir, None, &mut stats, &mut |_| {})?;
+        Ok(stats.bytes)
+    }
+
     /// Canonicalize path and handle errors
     fn canonicalize_path(path: &str) -> AppResult<PathBuf> {
         Path::new(path)
@@ -129,7 +1001,11 @@ impl KeyboardService {
             .map_err(|e| AppError::Keyboard(format!("无法解析路径 {}: {}", path, e)))
     }
 
-    fn read_directory(path: &Path, depth: usize) -> AppResult<Vec<FileEntry>> {
+    fn read_directory(
+        path: &Path,
+        depth: usize,
+        options: &ListOptions,
+    ) -> AppResult<Vec<FileEntry>> {
         let mut entries = vec![];
 
         for entry in fs::read_dir(path)? {
@@ -138,38 +1014,44 @@ impl KeyboardService {
             let file_type = entry.file_type()?;
 
             if file_type.is_dir() {
-                let dir_name = entry
-                    .file_name()
-                    .into_string()
-                    .map_err(|_| AppError::Keyboard("无效的目录名称".into()))?;
+                // 单个非 UTF-8 名称不能让整棵树读取失败：lossy 转换后继续，
+                // id 与 collect_selected 同样基于 lossy 名称 + 真实路径生成
+                let raw_name = entry.file_name();
+                let lossy_name = raw_name.to_str().is_none();
+                let dir_name = raw_name.to_string_lossy().into_owned();
+                if lossy_name {
+                    log::warn!("目录名称不是有效的 UTF-8: {}", entry.path().display());
+                }
 
-                // Skip userpreferences and hidden directories (incl. our tmp/bak dirs)
-                if dir_name == "userpreferences" || dir_name.starts_with('.') {
+                // Skip configured names (userpreferences by default) and hidden
+                // directories (incl. our tmp/bak dirs)
+                if options.skips(&dir_name) {
                     continue;
                 }
 
-                // 角色层（ROLE_DEPTH）即叶子：不再向下递归。
+                // 角色层（max_depth）即叶子：不再向下递归。
                 // 否则角色目录内部的子目录会被当成 children，前端会把该角色渲染成
                 // 不可选中的"文件夹"节点，同时白白读取整棵无用子树。
-                let subdir = if depth < ROLE_DEPTH {
-                    Self::read_directory(&entry.path(), depth + 1)?
+                let subdir = if depth < options.max_depth {
+                    Self::read_directory(&entry.path(), depth + 1, options)?
                 } else {
                     Vec::new()
                 };
 
                 // 账号/区服/服务器层的空目录（下面没有任何角色）直接跳过
-                if depth < ROLE_DEPTH && subdir.is_empty() {
+                if depth < options.max_depth && subdir.is_empty() {
                     continue;
                 }
 
                 // 角色层标记为可选中的叶子（is_dir = false）
-                let is_dir = depth != ROLE_DEPTH;
+                let is_dir = depth != options.max_depth;
 
                 let children = if subdir.is_empty() { None } else { Some(subdir) };
 
                 entries.push(FileEntry {
                     id: Self::generate_id(&dir_name, &entry.path()),
                     name: dir_name,
+                    lossy_name,
                     is_dir,
                     selected: false,
                     children,
@@ -180,7 +1062,21 @@ impl KeyboardService {
         Ok(entries)
     }
 
-    pub(crate) fn copy_dir_all(src: &Path, dst: &Path) -> AppResult<()> {
+    /// Copy `src` into `dst` (symlinks skipped), returning what was copied
+    pub(crate) fn copy_dir_all(src: &Path, dst: &Path) -> AppResult<DirStats> {
+        let mut stats = DirStats::default();
+        Self::walk_dir(src, Some(dst), &mut stats, &mut |_| {})?;
+        Ok(stats)
+    }
+
+    /// 递归遍历 `src` 并累计统计；`dst` 为 Some 时同时复制过去，None 时只读（预演）。
+    /// 每处理完一个文件调用一次 `on_file`（参数为源文件路径）
+    fn walk_dir(
+        src: &Path,
+        dst: Option<&Path>,
+        stats: &mut DirStats,
+        on_file: &mut dyn FnMut(&Path),
+    ) -> AppResult<()> {
         if !src.is_dir() {
             return Err(AppError::Keyboard(format!(
                 "源路径不是目录: {}",
@@ -188,43 +1084,118 @@ impl KeyboardService {
             )));
         }
 
-        fs::create_dir_all(dst)?;
+        if let Some(dst) = dst {
+            fs::create_dir_all(dst)?;
+        }
 
         for entry in fs::read_dir(src)? {
             let entry = entry?;
             let file_type = entry.file_type()?;
-            let new_dst = dst.join(entry.file_name());
+            let new_dst = dst.map(|dst| dst.join(entry.file_name()));
 
             // Skip symlinks for security (prevent symlink-based path traversal)
             if file_type.is_symlink() {
                 log::warn!("跳过符号链接: {}", entry.path().display());
+                stats.skipped_symlinks += 1;
                 continue;
             }
 
             if file_type.is_dir() {
-                Self::copy_dir_all(&entry.path(), &new_dst)?;
+                stats.dirs += 1;
+                Self::walk_dir(&entry.path(), new_dst.as_deref(), stats, on_file)?;
             } else {
-                fs::copy(entry.path(), &new_dst)?;
+                stats.files += 1;
+                stats.bytes += match &new_dst {
+                    Some(new_dst) => fs::copy(entry.path(), new_dst)?,
+                    None => entry.metadata()?.len(),
+                };
+                on_file(&entry.path());
             }
         }
 
-        Ok(())
+        Ok(())
+    }
+
+    /// 截到 53 位：id 经 JSON 变成 JS number，超出安全整数范围会失真，
+    /// 回传给 copy_selected_entries 时就匹配不上了
+    fn generate_id(name: &str, path: &Path) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (name, path).hash(&mut hasher);
+        hasher.finish() & ((1 << 53) - 1)
+    }
+}
+
+/// 角色目录的最后 ROLE_DEPTH 级（账号/区服/服务器/角色），即它在 userdata 下的位置
+fn role_relative_path(role_path: &Path) -> AppResult<PathBuf> {
+    let parts: Vec<_> = role_path
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect();
+    if parts.len() < ROLE_DEPTH {
+        return Err(AppError::Keyboard(format!(
+            "不是角色目录（应为 账号/区服/服务器/角色）: {}",
+            role_path.display()
+        )));
+    }
+    Ok(parts[parts.len() - ROLE_DEPTH..].iter().collect())
+}
+
+/// Relative path of a zip entry. Absolute names, drive prefixes and `..`
+/// components are rejected rather than sanitized: such an archive was not
+/// exported by us and is refused as a whole.
+fn archive_entry_path(name: &str) -> AppResult<PathBuf> {
+    let parts: Vec<&str> = name.split(['/', '\\']).collect();
+    let unsafe_name = name.starts_with(['/', '\\'])
+        || parts.iter().any(|part| *part == ".." || part.contains(':'));
+    if unsafe_name {
+        return Err(AppError::Keyboard(format!(
+            "压缩包含有不安全的路径，已拒绝导入: {name}"
+        )));
     }
+    Ok(parts
+        .into_iter()
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect())
+}
 
-    fn generate_id(name: &str, path: &Path) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        (name, path).hash(&mut hasher);
-        hasher.finish()
+fn zip_err(e: ZipError) -> AppError {
+    AppError::Keyboard(format!("压缩包读写失败: {e}"))
+}
+
+/// `.<target>.bak-<millis>`：以 . 开头，read_directory 不会把备份列进树
+fn backup_name(target_name: &str, millis: u128) -> String {
+    format!(".{target_name}.bak-{millis}")
+}
+
+/// Inverse of `backup_name`: (target name, millis)
+fn parse_backup_name(name: &str) -> Option<(&str, u128)> {
+    let (target_name, stamp) = name.strip_prefix('.')?.rsplit_once(".bak-")?;
+    if target_name.is_empty() || !stamp.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
     }
+    Some((target_name, stamp.parse().ok()?))
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
     use std::sync::atomic::{AtomicU32, Ordering};
 
     static TEST_DIR_SEQ: AtomicU32 = AtomicU32::new(0);
 
+    fn noop(_p: CopyProgress) {}
+
     fn temp_dir(label: &str) -> PathBuf {
         let dir = std::env::temp_dir().join(format!(
             "jx3-kb-test-{}-{}-{}",
@@ -254,7 +1225,11 @@ mod tests {
         // 没有任何角色的空账号应被跳过
         fs::create_dir_all(root.join("acc-empty/zone/server")).unwrap();
 
-        let tree = KeyboardService::list_directory_contents(root.to_str().unwrap()).unwrap();
+        let tree = KeyboardService::list_directory_contents(
+            root.to_str().unwrap(),
+            &ListOptions::default(),
+        )
+        .unwrap();
 
         assert_eq!(tree.len(), 1, "只应保留有角色的账号");
         let acc = &tree[0];
@@ -271,6 +1246,165 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn roles_are_flattened_with_full_paths() {
+        let root = temp_dir("roles");
+        fs::create_dir_all(root.join("acc2/zone/server/roleC")).unwrap();
+        fs::create_dir_all(root.join("acc1/zone/server/roleB/inner")).unwrap();
+        fs::create_dir_all(root.join("acc1/zone/server/roleA")).unwrap();
+        fs::create_dir_all(root.join("acc1/zone/server/userpreferences")).unwrap();
+        fs::create_dir_all(root.join("acc1/zone/server/.roleA.bak-1")).unwrap();
+        write_file(&root.join("acc1/zone/server/notes.txt"), "x");
+
+        let options = ListOptions::default();
+        let roles = KeyboardService::list_roles(root.to_str().unwrap(), &options).unwrap();
+        let relative: Vec<_> = roles.iter().map(|r| r.relative_path.as_str()).collect();
+        assert_eq!(
+            relative,
+            [
+                "acc1/zone/server/roleA",
+                "acc1/zone/server/roleB",
+                "acc2/zone/server/roleC"
+            ]
+        );
+        assert_eq!(roles[0].name, "roleA");
+        assert_eq!(
+            PathBuf::from(&roles[0].path),
+            root.join("acc1/zone/server/roleA")
+        );
+
+        // id 与目录树里同一个角色节点一致
+        fn contains_id(entries: &[FileEntry], id: u64) -> bool {
+            entries
+                .iter()
+                .any(|e| e.id == id || e.children.as_deref().is_some_and(|c| contains_id(c, id)))
+        }
+        let tree =
+            KeyboardService::list_directory_contents(root.to_str().unwrap(), &options).unwrap();
+        assert!(roles.iter().all(|role| contains_id(&tree, role.id)));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_role_lands_in_the_same_nested_location() {
+        let root = temp_dir("copy-role");
+        let source = root.join("src/acc/zone/server/role");
+        write_file(&source.join("keys.ini"), "new");
+        let target_base = root.join("dst");
+        write_file(&target_base.join("acc/zone/server/role/keys.ini"), "old");
+        write_file(&target_base.join("acc/zone/server/other/keys.ini"), "other");
+
+        let copied = KeyboardService::copy_role(
+            source.to_str().unwrap(),
+            target_base.to_str().unwrap(),
+            true,
+            &noop,
+        )
+        .unwrap();
+        let target_role = target_base.join("acc/zone/server/role");
+        assert_eq!(PathBuf::from(&copied), target_role);
+        assert_eq!(
+            fs::read_to_string(target_role.join("keys.ini")).unwrap(),
+            "new"
+        );
+        // 同服务器下的其他角色不受影响，旧内容留有备份
+        assert_eq!(
+            fs::read_to_string(target_base.join("acc/zone/server/other/keys.ini")).unwrap(),
+            "other"
+        );
+        assert_eq!(
+            KeyboardService::list_backups(target_role.to_str().unwrap())
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(KeyboardService::copy_role(
+            source.to_str().unwrap(),
+            root.join("missing").to_str().unwrap(),
+            false,
+            &noop,
+        )
+        .is_err());
+        assert!(role_relative_path(Path::new("server/role")).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn tree_honors_custom_depth_and_skip_names() {
+        let root = temp_dir("options");
+        // 旧版布局：<账号>/<服务器>/<角色>
+        fs::create_dir_all(root.join("acc1/server/roleA")).unwrap();
+        fs::create_dir_all(root.join("acc1/server/backup")).unwrap();
+        fs::create_dir_all(root.join("acc1/userpreferences/x")).unwrap();
+
+        let options = ListOptions {
+            max_depth: 3,
+            skip_names: vec!["backup".into()],
+        };
+        let tree =
+            KeyboardService::list_directory_contents(root.to_str().unwrap(), &options).unwrap();
+
+        let layer2 = tree[0].children.as_ref().unwrap();
+        assert!(
+            layer2.iter().any(|e| e.name == "userpreferences"),
+            "未配置跳过时 userpreferences 照常列出"
+        );
+        let server = layer2.iter().find(|e| e.name == "server").unwrap();
+        let roles = server.children.as_ref().unwrap();
+        assert_eq!(roles.len(), 1, "backup 应被跳过");
+        assert_eq!(roles[0].name, "roleA");
+        assert!(!roles[0].is_dir, "第 3 层即角色层");
+
+        let zero = ListOptions {
+            max_depth: 0,
+            ..ListOptions::default()
+        };
+        assert!(KeyboardService::list_directory_contents(root.to_str().unwrap(), &zero).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_outcome_counts_what_was_copied() {
+        let root = temp_dir("copy-counts");
+        let source = root.join("source");
+        let target = root.join("target");
+        write_file(&source.join("keys.ini"), "12345");
+        write_file(&source.join("sub/extra.ini"), "abc");
+        write_file(&target.join("old.ini"), "old");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(source.join("keys.ini"), source.join("link.ini")).unwrap();
+
+        let params = CopyParams {
+            source_path: source.to_string_lossy().to_string(),
+            target_path: target.to_string_lossy().to_string(),
+            verify: false,
+            mode: CopyMode::Merge,
+        };
+        let outcome = KeyboardService::copy_source_to_target(&params, &noop).unwrap();
+        // 合并时目标原有的 old.ini 不计入
+        assert_eq!(outcome.copied.files, 2);
+        assert_eq!(outcome.copied.dirs, 1);
+        assert_eq!(outcome.copied.bytes, 8);
+        #[cfg(unix)]
+        assert_eq!(outcome.copied.skipped_symlinks, 1);
+
+        // 源为空：复制照常完成，files 为 0 供前端提示
+        let empty = root.join("empty");
+        fs::create_dir_all(&empty).unwrap();
+        let params = CopyParams {
+            source_path: empty.to_string_lossy().to_string(),
+            ..params
+        };
+        let outcome = KeyboardService::copy_source_to_target(&params, &noop).unwrap();
+        assert_eq!(outcome.copied, DirStats::default());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn copy_replaces_target_and_leaves_no_temp_dirs() {
         let root = temp_dir("copy");
@@ -280,13 +1414,19 @@ mod tests {
         write_file(&source.join("sub/extra.ini"), "extra");
         write_file(&target.join("old.ini"), "old-keys");
 
-        let ok = KeyboardService::copy_source_to_target(&CopyParams {
-            source_path: source.to_string_lossy().to_string(),
-            target_path: target.to_string_lossy().to_string(),
-        })
+        let outcome = KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: target.to_string_lossy().to_string(),
+                verify: false,
+                mode: CopyMode::Replace,
+            },
+            &noop,
+        )
         .unwrap();
 
-        assert!(ok);
+        assert_eq!(outcome.mode, CopyMode::Replace);
+        assert!(outcome.backup_path.is_some());
         assert_eq!(
             fs::read_to_string(target.join("keys.ini")).unwrap(),
             "new-keys"
@@ -302,27 +1442,463 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn merge_overlays_source_and_keeps_unrelated_target_files() {
+        let root = temp_dir("merge");
+        let source = root.join("source");
+        let target = root.join("target");
+        write_file(&source.join("keys.ini"), "new-keys");
+        write_file(&source.join("sub/extra.ini"), "extra");
+        write_file(&target.join("keys.ini"), "old-keys");
+        write_file(&target.join("sub/mine.ini"), "mine");
+        write_file(&target.join("old.ini"), "old");
+
+        let copied = std::cell::Cell::new(0);
+        let outcome = KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: target.to_string_lossy().to_string(),
+                verify: true,
+                mode: CopyMode::Merge,
+            },
+            &|p| copied.set(p.copied),
+        )
+        .unwrap();
+
+        assert_eq!(outcome.mode, CopyMode::Merge);
+        // 进度只统计源文件
+        assert_eq!(copied.get(), 2);
+        let read = |path: &str| fs::read_to_string(target.join(path)).unwrap();
+        assert_eq!(read("keys.ini"), "new-keys");
+        assert_eq!(read("sub/extra.ini"), "extra");
+        assert_eq!(read("sub/mine.ini"), "mine");
+        assert_eq!(read("old.ini"), "old");
+        // 合并前的目标完整留作备份
+        let backup = PathBuf::from(outcome.backup_path.unwrap());
+        assert_eq!(
+            fs::read_to_string(backup.join("keys.ini")).unwrap(),
+            "old-keys"
+        );
+        assert!(!backup.join("sub/extra.ini").exists());
+        assert!(!root.join(".target.tmp-copy").exists(), "临时目录不应残留");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_reports_progress_per_file_with_known_total() {
+        let root = temp_dir("progress");
+        let source = root.join("source");
+        write_file(&source.join("a.ini"), "a");
+        write_file(&source.join("sub/b.ini"), "b");
+        write_file(&source.join("sub/c.ini"), "c");
+
+        let events = RefCell::new(Vec::<CopyProgress>::new());
+        KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: root.join("target").to_string_lossy().to_string(),
+                verify: false,
+                mode: CopyMode::Replace,
+            },
+            &|p| events.borrow_mut().push(p),
+        )
+        .unwrap();
+
+        let events = events.into_inner();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|p| p.total == 3));
+        assert_eq!(
+            events.iter().map(|p| p.copied).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(events
+            .iter()
+            .any(|p| Path::new(&p.current_file) == Path::new("sub/b.ini")));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_selected_entries_only_touches_selected_subtrees() {
+        let root = temp_dir("selected");
+        let source = root.join("source");
+        let target = root.join("target");
+        write_file(&source.join("acc/zone/server/roleA/keys.ini"), "a-new");
+        write_file(&source.join("acc/zone/server/roleB/keys.ini"), "b-new");
+        write_file(&target.join("acc/zone/server/roleA/keys.ini"), "a-old");
+        write_file(&target.join("acc/zone/server/roleB/keys.ini"), "b-old");
+
+        let params = CopyParams {
+            source_path: source.to_string_lossy().to_string(),
+            target_path: target.to_string_lossy().to_string(),
+            verify: false,
+            mode: CopyMode::Replace,
+        };
+        let tree =
+            KeyboardService::list_directory_contents(&params.source_path, &ListOptions::default())
+                .unwrap();
+        let role_a = tree[0].children.as_ref().unwrap()[0]
+            .children
+            .as_ref()
+            .unwrap()[0]
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|e| e.name == "roleA")
+            .unwrap();
+
+        let options = ListOptions::default();
+        let copied =
+            KeyboardService::copy_selected_entries(&params, &[role_a.id], &options).unwrap();
+
+        assert_eq!(copied.len(), 1);
+        assert_eq!(
+            fs::read_to_string(target.join("acc/zone/server/roleA/keys.ini")).unwrap(),
+            "a-new"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("acc/zone/server/roleB/keys.ini")).unwrap(),
+            "b-old",
+            "未选中的角色不应被改动"
+        );
+
+        assert!(KeyboardService::copy_selected_entries(&params, &[], &options).is_err());
+        assert!(KeyboardService::copy_selected_entries(&params, &[12345], &options).is_err());
+
+        // 树里不列出的目录（skip_names、超过 max_depth）即使 id 对得上也不复制
+        let prefs = source.join("acc/userpreferences");
+        write_file(&prefs.join("ui.ini"), "prefs");
+        let prefs_id = KeyboardService::generate_id("userpreferences", &prefs);
+        assert!(KeyboardService::copy_selected_entries(&params, &[prefs_id], &options).is_err());
+        let deep = source.join("acc/zone/server/roleA/sub");
+        write_file(&deep.join("x.ini"), "deep");
+        let deep_id = KeyboardService::generate_id("sub", &deep);
+        assert!(KeyboardService::copy_selected_entries(&params, &[deep_id], &options).is_err());
+        assert!(!target.join("acc/userpreferences").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_keeps_old_target_as_backup_and_restore_rolls_back() {
+        let root = temp_dir("backup");
+        let source = root.join("source");
+        let target = root.join("target");
+        write_file(&source.join("keys.ini"), "new-keys");
+        write_file(&target.join("keys.ini"), "old-keys");
+        let params = CopyParams {
+            source_path: source.to_string_lossy().to_string(),
+            target_path: target.to_string_lossy().to_string(),
+            verify: false,
+            mode: CopyMode::Replace,
+        };
+
+        KeyboardService::copy_source_to_target(&params, &noop).unwrap();
+
+        let backups = KeyboardService::list_backups(&params.target_path).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            fs::read_to_string(Path::new(&backups[0]).join("keys.ini")).unwrap(),
+            "old-keys"
+        );
+
+        KeyboardService::restore_backup(&backups[0]).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join("keys.ini")).unwrap(),
+            "old-keys"
+        );
+        // 恢复前的内容也留了备份，可以再撤回
+        assert_eq!(
+            KeyboardService::list_backups(&params.target_path)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn backups_are_pruned_to_keep_limit() {
+        let root = temp_dir("prune");
+        let target = root.join("target");
+        for stamp in 0..(KEEP_BACKUPS as u128 + 3) {
+            fs::create_dir_all(root.join(backup_name("target", stamp))).unwrap();
+        }
+        write_file(&root.join("source/keys.ini"), "x");
+        write_file(&target.join("keys.ini"), "old");
+
+        KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: root.join("source").to_string_lossy().to_string(),
+                target_path: target.to_string_lossy().to_string(),
+                verify: false,
+                mode: CopyMode::Replace,
+            },
+            &noop,
+        )
+        .unwrap();
+
+        let backups = KeyboardService::list_backups(&target.to_string_lossy()).unwrap();
+        assert_eq!(backups.len(), KEEP_BACKUPS);
+        assert!(
+            !root.join(backup_name("target", 0)).exists(),
+            "最旧的备份应被清理"
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn backup_names_roundtrip_and_reject_foreign_dirs() {
+        assert_eq!(
+            parse_backup_name(&backup_name("角色A", 42)),
+            Some(("角色A", 42))
+        );
+        assert_eq!(parse_backup_name(".target.bak-copy"), None);
+        assert_eq!(parse_backup_name("target.bak-1"), None);
+        assert!(KeyboardService::restore_backup("/tmp/not-a-backup").is_err());
+    }
+
     #[test]
     fn copy_rejects_same_path_and_traversal() {
         let root = temp_dir("guard");
         let source = root.join("source");
         fs::create_dir_all(&source).unwrap();
 
-        let same = KeyboardService::copy_source_to_target(&CopyParams {
-            source_path: source.to_string_lossy().to_string(),
-            target_path: source.to_string_lossy().to_string(),
-        });
+        let same = KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: source.to_string_lossy().to_string(),
+                verify: false,
+                mode: CopyMode::Replace,
+            },
+            &noop,
+        );
         assert!(same.is_err(), "源 == 目标应被拒绝");
 
-        let traversal = KeyboardService::copy_source_to_target(&CopyParams {
-            source_path: source.to_string_lossy().to_string(),
-            target_path: root.join("a/../b").to_string_lossy().to_string(),
-        });
+        let traversal = KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: root.join("a/../b").to_string_lossy().to_string(),
+                verify: false,
+                mode: CopyMode::Replace,
+            },
+            &noop,
+        );
         assert!(traversal.is_err(), "包含 .. 的路径应被拒绝");
 
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn preview_counts_source_and_target_without_touching_disk() {
+        let root = temp_dir("preview");
+        let source = root.join("source");
+        let target = root.join("target");
+        write_file(&source.join("keys.ini"), "12345");
+        write_file(&source.join("sub/extra.ini"), "abc");
+        write_file(&target.join("old.ini"), "old");
+
+        let params = CopyParams {
+            source_path: source.to_string_lossy().to_string(),
+            target_path: target.to_string_lossy().to_string(),
+            verify: false,
+            mode: CopyMode::Replace,
+        };
+        let preview = KeyboardService::preview_copy(&params).unwrap();
+
+        assert_eq!(
+            preview.source,
+            DirStats {
+                files: 2,
+                dirs: 1,
+                bytes: 8,
+                skipped_symlinks: 0
+            }
+        );
+        assert!(preview.target_exists);
+        assert_eq!(preview.target.unwrap().files, 1);
+        assert_eq!(
+            fs::read_to_string(target.join("old.ini")).unwrap(),
+            "old",
+            "预演不得改动目标"
+        );
+        assert!(!target.join("keys.ini").exists());
+
+        let fresh = KeyboardService::preview_copy(&CopyParams {
+            source_path: params.source_path.clone(),
+            target_path: root.join("new-target").to_string_lossy().to_string(),
+            verify: false,
+            mode: CopyMode::Replace,
+        })
+        .unwrap();
+        assert!(!fresh.target_exists);
+        assert!(fresh.target.is_none());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn diff_lists_one_sided_and_changed_files() {
+        let root = temp_dir("diff");
+        let a = root.join("a");
+        let b = root.join("b");
+        write_file(&a.join("same.ini"), "keys");
+        write_file(&b.join("same.ini"), "keys");
+        write_file(&a.join("sub/resized.ini"), "short");
+        write_file(&b.join("sub/resized.ini"), "longer value");
+        write_file(&a.join("edited.ini"), "abc");
+        write_file(&b.join("edited.ini"), "abd");
+        write_file(&a.join("sub/only-a.ini"), "x");
+        write_file(&b.join("only-b.ini"), "y");
+
+        let diff = KeyboardService::diff_dirs(a.to_str().unwrap(), b.to_str().unwrap()).unwrap();
+        assert_eq!(diff.only_in_a, vec!["sub/only-a.ini"]);
+        assert_eq!(diff.only_in_b, vec!["only-b.ini"]);
+        assert_eq!(diff.different, vec!["edited.ini", "sub/resized.ini"]);
+
+        let same = KeyboardService::diff_dirs(a.to_str().unwrap(), a.to_str().unwrap()).unwrap();
+        assert_eq!(same, DirDiff::default());
+        assert!(KeyboardService::diff_dirs(
+            a.to_str().unwrap(),
+            root.join("missing").to_str().unwrap()
+        )
+        .is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn multi_target_copy_reports_each_target() {
+        let root = temp_dir("multi");
+        let source = root.join("source");
+        write_file(&source.join("keys.ini"), "new-keys");
+        let first = root.join("install1/role");
+        let second = root.join("install2/role");
+        write_file(&second.join("keys.ini"), "old-keys");
+        let targets = vec![
+            first.to_string_lossy().to_string(),
+            root.join("install2/../escape")
+                .to_string_lossy()
+                .to_string(),
+            second.to_string_lossy().to_string(),
+        ];
+        let source_path = source.to_string_lossy().to_string();
+
+        let results =
+            KeyboardService::copy_source_to_targets(&source_path, &targets, false, false, &noop)
+                .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].error.is_none());
+        assert!(results[1].error.is_some(), "'..' 目标必须逐个拦截");
+        assert!(results[2].error.is_none());
+        assert_eq!(
+            fs::read_to_string(second.join("keys.ini")).unwrap(),
+            "new-keys"
+        );
+        assert_eq!(KeyboardService::list_backups(&targets[2]).unwrap().len(), 1);
+
+        let stopped =
+            KeyboardService::copy_source_to_targets(&source_path, &targets, true, false, &noop)
+                .unwrap();
+        assert_eq!(stopped.len(), 2, "stop_on_error 时失败后的目标不再尝试");
+        assert!(stopped[1].error.is_some());
+
+        assert!(
+            KeyboardService::copy_source_to_targets(&source_path, &[], false, false, &noop)
+                .is_err()
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn verified_copy_succeeds_and_mismatch_is_detected() {
+        let root = temp_dir("verify");
+        let source = root.join("source");
+        let target = root.join("target");
+        write_file(&source.join("keys.ini"), "new-keys");
+        write_file(&source.join("sub/extra.ini"), "extra");
+        write_file(&target.join("old.ini"), "old-keys");
+
+        KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: target.to_string_lossy().to_string(),
+                verify: true,
+                mode: CopyMode::Replace,
+            },
+            &noop,
+        )
+        .unwrap();
+        assert!(KeyboardService::verify_copy(&source, &target).is_ok());
+
+        // 模拟磁盘写满导致的截断副本与缺失文件
+        fs::write(target.join("sub/extra.ini"), "ext").unwrap();
+        assert!(KeyboardService::verify_copy(&source, &target).is_err());
+        fs::write(target.join("sub/extra.ini"), "extra").unwrap();
+        fs::remove_file(target.join("keys.ini")).unwrap();
+        assert!(KeyboardService::verify_copy(&source, &target).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names_are_listed_lossily_and_copied_by_real_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = temp_dir("non-utf8");
+        let source = root.join("source");
+        let odd = OsStr::from_bytes(b"role-\xff");
+        let odd_role = source.join(odd);
+        // 部分文件系统（如 APFS）不允许非 UTF-8 名称，此时无从测试
+        if fs::create_dir_all(&odd_role).is_err() {
+            let _ = fs::remove_dir_all(&root);
+            return;
+        }
+        write_file(&odd_role.join("keys.ini"), "odd");
+        write_file(&source.join("normal/keys.ini"), "normal");
+
+        let roles = KeyboardService::list_directory_contents(
+            source.to_str().unwrap(),
+            &ListOptions {
+                max_depth: 1,
+                skip_names: vec![],
+            },
+        )
+        .unwrap();
+        assert_eq!(roles.len(), 2, "非 UTF-8 名称不应中断整棵树的读取");
+        let odd_entry = roles.iter().find(|r| r.lossy_name).unwrap();
+        assert_eq!(odd_entry.name, "role-\u{FFFD}");
+        assert!(roles.iter().any(|r| r.name == "normal" && !r.lossy_name));
+
+        let target = root.join("target");
+        let copied = KeyboardService::copy_selected_entries(
+            &CopyParams {
+                source_path: source.to_string_lossy().to_string(),
+                target_path: target.to_string_lossy().to_string(),
+                verify: true,
+                mode: CopyMode::Replace,
+            },
+            &[odd_entry.id],
+            &ListOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(copied.len(), 1);
+        assert_eq!(
+            fs::read_to_string(target.join(odd).join("keys.ini")).unwrap(),
+            "odd",
+            "复制必须使用原始名称而不是 lossy 名称"
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn copy_failure_preserves_existing_target() {
         let root = temp_dir("preserve");
@@ -330,10 +1906,15 @@ mod tests {
         write_file(&target.join("old.ini"), "old-keys");
 
         let missing = root.join("missing-source");
-        let result = KeyboardService::copy_source_to_target(&CopyParams {
-            source_path: missing.to_string_lossy().to_string(),
-            target_path: target.to_string_lossy().to_string(),
-        });
+        let result = KeyboardService::copy_source_to_target(
+            &CopyParams {
+                source_path: missing.to_string_lossy().to_string(),
+                target_path: target.to_string_lossy().to_string(),
+                verify: false,
+                mode: CopyMode::Replace,
+            },
+            &noop,
+        );
 
         assert!(result.is_err());
         assert_eq!(
@@ -344,4 +1925,102 @@ mod tests {
 
         let _ = fs::remove_dir_all(&root);
     }
+
+    /// 手工构造压缩包：模拟不是本程序导出的包
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            io::Write::write_all(&mut zip, content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn zip_export_import_roundtrip_skips_filtered_entries() {
+        let root = temp_dir("zip-roundtrip");
+        let src = root.join("src");
+        write_file(&src.join("keybind.ini"), "keys");
+        write_file(&src.join("子目录/设置.dat"), "nested");
+        write_file(&src.join("userpreferences/pref.ini"), "pref");
+        write_file(&src.join(".src.bak-1/old.ini"), "backup");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(src.join("keybind.ini"), src.join("link.ini")).unwrap();
+
+        let archive = root.join("share.zip");
+        let archive_str = archive.to_string_lossy().to_string();
+        let exported = KeyboardService::export_zip(&src.to_string_lossy(), &archive_str).unwrap();
+        assert_eq!(exported, 2);
+
+        let target = root.join("target");
+        write_file(&target.join("old.ini"), "old-keys");
+        let imported =
+            KeyboardService::import_zip(&archive_str, &target.to_string_lossy()).unwrap();
+        assert_eq!(imported, 2);
+        assert_eq!(
+            fs::read_to_string(target.join("keybind.ini")).unwrap(),
+            "keys"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("子目录/设置.dat")).unwrap(),
+            "nested"
+        );
+        assert!(!target.join("userpreferences").exists());
+        assert!(!target.join(".src.bak-1").exists());
+        assert!(!target.join("link.ini").exists());
+        assert!(!target.join("old.ini").exists(), "导入应整体替换目标");
+        let backups = KeyboardService::list_backups(&target.to_string_lossy()).unwrap();
+        assert_eq!(backups.len(), 1, "旧目标应保留为备份");
+        assert!(!root.join(".target.tmp-import").exists());
+
+        // 压缩包不能写进源目录
+        let inside = src.join("self.zip").to_string_lossy().to_string();
+        assert!(KeyboardService::export_zip(&src.to_string_lossy(), &inside).is_err());
+        assert!(!src.join("self.zip").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn zip_import_rejects_unsafe_paths_and_oversized_content() {
+        assert_eq!(
+            archive_entry_path("a/./b\\c.ini").unwrap(),
+            ["a", "b", "c.ini"].iter().collect::<PathBuf>()
+        );
+        for name in [
+            "../evil.ini",
+            "a/../../evil.ini",
+            "/etc/evil",
+            "\\evil",
+            "C:/evil",
+            "a\\..\\b",
+        ] {
+            assert!(archive_entry_path(name).is_err(), "{name} 应被拒绝");
+        }
+
+        let root = temp_dir("zip-unsafe");
+        let target = root.join("role");
+        write_file(&target.join("keep.ini"), "keep");
+
+        let evil = root.join("evil.zip");
+        write_zip(&evil, &[("ok.ini", b"ok"), ("../escaped.ini", b"x")]);
+        let result =
+            KeyboardService::import_zip(&evil.to_string_lossy(), &target.to_string_lossy());
+        assert!(result.is_err());
+        assert!(!root.join("escaped.ini").exists());
+        assert_eq!(fs::read_to_string(target.join("keep.ini")).unwrap(), "keep");
+
+        // 解压总量超过上限即拒绝，目标不动
+        let big = root.join("big.zip");
+        write_zip(&big, &[("a.bin", &[0u8; 600]), ("b.bin", &[0u8; 600])]);
+        let mut zip = ZipArchive::new(File::open(&big).unwrap()).unwrap();
+        let dst = root.join("out");
+        assert!(KeyboardService::extract_zip(&mut zip, &dst, 1000).is_err());
+        assert_eq!(
+            KeyboardService::extract_zip(&mut zip, &dst, 2000).unwrap(),
+            2
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,246 @@
+//! Native adapter enumeration (read-only) via `GetAdaptersAddresses`.
+//!
+//! Replaces `list_adapters.ps1` for the adapter picker: no PowerShell start-up
+//! cost, no dependency on the execution policy, and no locale-dependent text
+//! to parse. `Get-NetAdapter -Physical` has no direct Win32 equivalent, so the
+//! filter here is "Ethernet/Wi-Fi interface with a 6-byte hardware address
+//! whose description doesn't name a known virtual driver". The permanent MAC
+//! isn't exposed by IP Helper; it equals the current MAC unless the adapter
+//! has a `NetworkAddress` override, in which case it is reported as unknown
+//! and the override itself is returned as the spoofed MAC.
+//!
+//! Build with the `ps-adapter-query` feature to fall back to the script.
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+/// `IF_TYPE_ETHERNET_CSMACD`
+const IF_TYPE_ETHERNET: u32 = 6;
+/// `IF_TYPE_IEEE80211`
+const IF_TYPE_WIFI: u32 = 71;
+
+/// Description fragments of virtual/tunnel adapters that report an Ethernet
+/// `IfType` (matched case-insensitively)
+const VIRTUAL_MARKERS: &[&str] = &[
+    "virtual",
+    "hyper-v",
+    "vmware",
+    "virtualbox",
+    "tap-windows",
+    "wireguard",
+    "wintun",
+    "npcap",
+    "loopback",
+    "wi-fi direct",
+    "bluetooth",
+];
+
+/// One adapter as read from IP Helper, before selection flags are applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeAdapter {
+    /// `{XXXXXXXX-...}` uppercase
+    pub guid: String,
+    pub name: String,
+    pub description: String,
+    pub status: String,
+    /// `XX:XX:XX:XX:XX:XX`
+    pub current_mac: String,
+    /// Empty when an override is active (the burned-in address is unknown)
+    pub permanent_mac: String,
+    /// Raw `NetworkAddress` override value, when one is set
+    pub spoofed_mac: Option<String>,
+}
+
+/// Whether an interface looks like a physical NIC (see module docs)
+fn is_physical(if_type: u32, mac_len: u32, description: &str) -> bool {
+    if !matches!(if_type, IF_TYPE_ETHERNET | IF_TYPE_WIFI) || mac_len != 6 {
+        return false;
+    }
+    let description = description.to_ascii_lowercase();
+    !VIRTUAL_MARKERS
+        .iter()
+        .any(|marker| description.contains(marker))
+}
+
+/// `IF_OPER_STATUS` → the status names `Get-NetAdapter` reports
+fn status_name(oper_status: i32) -> &'static str {
+    match oper_status {
+        1 => "Up",
+        2 | 7 => "Disconnected",
+        5 => "Not Present",
+        _ => "Unknown",
+    }
+}
+
+fn format_hw_address(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(target_os = "windows")]
+pub use windows_impl::list_physical_adapters;
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use std::collections::HashMap;
+
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+        GAA_FLAG_SKIP_MULTICAST, GAA_FLAG_SKIP_UNICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows::Win32::System::Registry::HKEY_LOCAL_MACHINE;
+
+    use super::{format_hw_address, is_physical, status_name, NativeAdapter};
+    use crate::error::{AppError, AppResult};
+    use crate::win_registry::RegKey;
+
+    /// Network adapter class key (same key the scripts write `NetworkAddress` to)
+    const NET_CLASS_KEY: &str =
+        r"SYSTEM\CurrentControlSet\Control\Class\{4D36E972-E325-11CE-BFC1-08002BE10318}";
+    const AF_UNSPEC: u32 = 0;
+    /// MSDN recommends starting with 15 KB to avoid a second call in most cases
+    const INITIAL_BUFFER: usize = 15 * 1024;
+
+    /// Every physical adapter, in IP Helper order
+    pub fn list_physical_adapters() -> AppResult<Vec<NativeAdapter>> {
+        let overrides = network_address_overrides();
+        let flags = GAA_FLAG_SKIP_UNICAST
+            | GAA_FLAG_SKIP_ANYCAST
+            | GAA_FLAG_SKIP_MULTICAST
+            | GAA_FLAG_SKIP_DNS_SERVER;
+
+        // u64 保证缓冲按 IP_ADAPTER_ADDRESSES_LH 的要求对齐
+        let mut buf: Vec<u64> = vec![0; INITIAL_BUFFER / 8];
+        let mut size = (buf.len() * 8) as u32;
+        let mut res = ERROR_BUFFER_OVERFLOW.0;
+        for _ in 0..3 {
+            res = unsafe {
+                GetAdaptersAddresses(
+                    AF_UNSPEC,
+                    flags,
+                    None,
+                    Some(buf.as_mut_ptr().cast()),
+                    &mut size,
+                )
+            };
+            if res != ERROR_BUFFER_OVERFLOW.0 {
+                break;
+            }
+            buf = vec![0; (size as usize).div_ceil(8)];
+        }
+        if res != ERROR_SUCCESS.0 {
+            return Err(AppError::Command(format!("枚举网卡失败（错误码 {res}）")));
+        }
+
+        let mut adapters = Vec::new();
+        let mut cursor = buf.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+        while !cursor.is_null() {
+            let entry = unsafe { &*cursor };
+            cursor = entry.Next;
+
+            let description = unsafe { entry.Description.to_string() }.unwrap_or_default();
+            let mac_len = entry.PhysicalAddressLength;
+            if !is_physical(entry.IfType, mac_len, &description) {
+                continue;
+            }
+            let guid = unsafe { entry.AdapterName.to_string() }
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+            let current_mac = format_hw_address(&entry.PhysicalAddress[..mac_len as usize]);
+            let spoofed_mac = overrides.get(&guid).cloned();
+            let permanent_mac = if spoofed_mac.is_some() {
+                String::new()
+            } else {
+                current_mac.clone()
+            };
+            adapters.push(NativeAdapter {
+                name: unsafe { entry.FriendlyName.to_string() }.unwrap_or_default(),
+                status: status_name(entry.OperStatus.0).to_string(),
+                guid,
+                description,
+                current_mac,
+                permanent_mac,
+                spoofed_mac,
+            });
+        }
+        Ok(adapters)
+    }
+
+    /// `NetworkAddress` override per InterfaceGuid (uppercase). Best effort:
+    /// unreadable keys are skipped, and a failure to open the class key just
+    /// means every MAC is treated as permanent.
+    fn network_address_overrides() -> HashMap<String, String> {
+        let mut overrides = HashMap::new();
+        let key = match RegKey::open_read(HKEY_LOCAL_MACHINE, NET_CLASS_KEY) {
+            Ok(key) => key,
+            Err(res) => {
+                log::warn!("打开网卡 class 注册表键失败（错误码 {}）", res.0);
+                return overrides;
+            }
+        };
+        for subkey in key.subkeys() {
+            let Some(value) = key
+                .read_sz(&subkey, "NetworkAddress")
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+            else {
+                continue;
+            };
+            if let Some(guid) = key.read_sz(&subkey, "NetCfgInstanceId") {
+                overrides.insert(guid.trim().to_ascii_uppercase(), value);
+            }
+        }
+        overrides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_filter_keeps_real_nics_only() {
+        assert!(is_physical(
+            IF_TYPE_ETHERNET,
+            6,
+            "Intel(R) Ethernet Connection I219-V"
+        ));
+        assert!(is_physical(
+            IF_TYPE_WIFI,
+            6,
+            "Intel(R) Wi-Fi 6 AX201 160MHz"
+        ));
+        assert!(!is_physical(
+            IF_TYPE_ETHERNET,
+            6,
+            "Hyper-V Virtual Ethernet Adapter"
+        ));
+        assert!(!is_physical(
+            IF_TYPE_WIFI,
+            6,
+            "Microsoft Wi-Fi Direct Virtual Adapter #2"
+        ));
+        assert!(!is_physical(IF_TYPE_ETHERNET, 6, "TAP-Windows Adapter V9"));
+        assert!(!is_physical(24, 0, "Software Loopback Interface 1"));
+        assert!(!is_physical(IF_TYPE_ETHERNET, 8, "Intel(R) Ethernet"));
+    }
+
+    #[test]
+    fn status_names_match_get_netadapter() {
+        assert_eq!(status_name(1), "Up");
+        assert_eq!(status_name(2), "Disconnected");
+        assert_eq!(status_name(5), "Not Present");
+        assert_eq!(status_name(42), "Unknown");
+    }
+
+    #[test]
+    fn hw_address_is_colon_separated_uppercase() {
+        assert_eq!(
+            format_hw_address(&[0x02, 0xab, 0x00, 0x1f, 0xc0, 0xff]),
+            "02:AB:00:1F:C0:FF"
+        );
+    }
+}
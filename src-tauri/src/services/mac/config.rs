@@ -0,0 +1,139 @@
+//! MAC 页用户偏好持久化：所选网卡 GUID 与自动还原任务的触发时机，存
+//! `app_local_dir()/mac_config.json`。
+//! 这里只存用户的选择，不存 MAC 状态——实际是否已修改仍以注册表为准。
+//!
+//! 网卡 GUID 只在本机有意义，所以与 Linux 的 mac_state.json 一样放在本机
+//! 数据目录，而不是可能随漫游配置同步到别的电脑的 `config_dir`；旧版本
+//! 留在 `config_dir` 的文件在第一次读写时迁移过来。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::fs_util::{app_config_dir, app_local_dir, migrate_file, remove_if_exists, write_atomic};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacConfig {
+    /// 用户选定的网卡 InterfaceGuid（`{XXXXXXXX-...}` 大写）；None = 自动选择
+    #[serde(default)]
+    pub adapter_guid: Option<String>,
+    /// 开机自动还原任务的触发时机；`verify_auto_restore_task` 据此检查现有任务
+    #[serde(default)]
+    pub restore_trigger: RestoreTrigger,
+}
+
+/// When the auto-restore scheduled task runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestoreTrigger {
+    /// At user logon, as that user with highest privileges (the original behavior)
+    #[default]
+    OnLogon,
+    /// At system startup, before anyone logs on; runs as SYSTEM
+    OnStart,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl RestoreTrigger {
+    /// schtasks `/sc` value
+    pub fn schedule(self) -> &'static str {
+        match self {
+            Self::OnLogon => "onlogon",
+            Self::OnStart => "onstart",
+        }
+    }
+
+    /// Trigger element of this schedule in the task XML (`schtasks /query /xml`)
+    pub fn xml_element(self) -> &'static str {
+        match self {
+            Self::OnLogon => "<LogonTrigger",
+            Self::OnStart => "<BootTrigger",
+        }
+    }
+}
+
+/// `app_local_dir()/<file_name>`, for machine-specific MAC files; moves a copy
+/// left in the roaming `app_config_dir` over on first use
+pub(super) fn local_state_path(file_name: &str) -> AppResult<PathBuf> {
+    let path = app_local_dir()?.join(file_name);
+    if let Ok(legacy) = app_config_dir().map(|dir| dir.join(file_name)) {
+        match migrate_file(&legacy, &path) {
+            Ok(true) => log::info!("已将 {} 迁移到 {}", legacy.display(), path.display()),
+            Ok(false) => {}
+            Err(err) => log::warn!("迁移 {} 失败: {err}", legacy.display()),
+        }
+    }
+    Ok(path)
+}
+
+fn config_path() -> AppResult<PathBuf> {
+    local_state_path("mac_config.json")
+}
+
+pub fn load_config() -> AppResult<MacConfig> {
+    load_from(&config_path()?)
+}
+
+pub fn save_config(config: &MacConfig) -> AppResult<()> {
+    save_to(config, &config_path()?)
+}
+
+/// Delete mac_config.json; returns whether it existed
+pub fn remove_config() -> AppResult<bool> {
+    Ok(remove_if_exists(&config_path()?)?)
+}
+
+fn load_from(path: &Path) -> AppResult<MacConfig> {
+    if !path.is_file() {
+        return Ok(MacConfig::default());
+    }
+    let bytes = fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|e| AppError::Config(format!("MAC 配置解析失败: {e}")))
+}
+
+fn save_to(config: &MacConfig, path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, &serde_json::to_vec_pretty(config)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_loads_default() {
+        let path = std::env::temp_dir().join(format!(
+            "jx3-maccfg-missing-{}/mac_config.json",
+            std::process::id()
+        ));
+        let config = load_from(&path).unwrap();
+        assert!(config.adapter_guid.is_none());
+        assert_eq!(config.restore_trigger, RestoreTrigger::OnLogon);
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "jx3-maccfg-test-{}/mac_config.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let config = MacConfig {
+            adapter_guid: Some("{12345678-ABCD-EF01-2345-6789ABCDEF01}".into()),
+            restore_trigger: RestoreTrigger::OnStart,
+        };
+        save_to(&config, &path).unwrap();
+        let loaded = load_from(&path).unwrap();
+        assert_eq!(loaded.adapter_guid, config.adapter_guid);
+        assert_eq!(loaded.restore_trigger, RestoreTrigger::OnStart);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,142 @@
+//! 历史 MAC 记录：每次成功修改后追加一条，存
+//! `app_config_dir()/mac_history.json`，最新在前、最多保留
+//! `HISTORY_CAP` 条。用户可以回到某个之前用过的伪装 MAC，而不只是出厂地址。
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::fs_util::{app_config_dir, write_atomic};
+
+/// 最多保留的历史条数
+pub const HISTORY_CAP: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacHistoryEntry {
+    /// `XX:XX:XX:XX:XX:XX`（驱动回读确认过的实际值）
+    pub mac: String,
+    /// Unix 秒
+    pub timestamp: u64,
+    /// 被修改网卡的 InterfaceGuid（旧记录/未知时为 None）
+    #[serde(default)]
+    pub adapter_guid: Option<String>,
+}
+
+fn history_path() -> AppResult<PathBuf> {
+    Ok(app_config_dir()?.join("mac_history.json"))
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn load_history() -> AppResult<Vec<MacHistoryEntry>> {
+    load_from(&history_path()?)
+}
+
+/// 追加一条记录并落盘
+pub fn record(entry: MacHistoryEntry) -> AppResult<()> {
+    let path = history_path()?;
+    let mut entries = load_from(&path)?;
+    push_entry(&mut entries, entry);
+    save_to(&entries, &path)
+}
+
+/// 插到最前；与最新一条同 MAC 同网卡时只刷新时间（连续去重），超出上限截断
+fn push_entry(entries: &mut Vec<MacHistoryEntry>, entry: MacHistoryEntry) {
+    if let Some(latest) = entries.first_mut() {
+        if latest.mac.eq_ignore_ascii_case(&entry.mac) && latest.adapter_guid == entry.adapter_guid
+        {
+            latest.timestamp = entry.timestamp;
+            return;
+        }
+    }
+    entries.insert(0, entry);
+    entries.truncate(HISTORY_CAP);
+}
+
+fn load_from(path: &Path) -> AppResult<Vec<MacHistoryEntry>> {
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    let bytes = fs::read(path)?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| AppError::Config(format!("MAC 历史记录解析失败: {e}")))
+}
+
+fn save_to(entries: &[MacHistoryEntry], path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, &serde_json::to_vec_pretty(entries)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mac: &str, timestamp: u64) -> MacHistoryEntry {
+        MacHistoryEntry {
+            mac: mac.into(),
+            timestamp,
+            adapter_guid: None,
+        }
+    }
+
+    #[test]
+    fn push_inserts_newest_first_and_caps_length() {
+        let mut entries = vec![];
+        for i in 0..(HISTORY_CAP as u64 + 5) {
+            push_entry(&mut entries, entry(&format!("02:00:00:00:00:{i:02X}"), i));
+        }
+        assert_eq!(entries.len(), HISTORY_CAP);
+        assert_eq!(
+            entries[0].timestamp,
+            HISTORY_CAP as u64 + 4,
+            "最新一条应在最前"
+        );
+    }
+
+    #[test]
+    fn push_collapses_consecutive_duplicates() {
+        let mut entries = vec![];
+        push_entry(&mut entries, entry("02:11:22:33:44:55", 1));
+        push_entry(&mut entries, entry("02:11:22:33:44:55", 2));
+        assert_eq!(entries, vec![entry("02:11:22:33:44:55", 2)]);
+
+        // 不连续的重复保留：用户可能在两个 MAC 间来回切换
+        push_entry(&mut entries, entry("02:AA:BB:CC:DD:EE", 3));
+        push_entry(&mut entries, entry("02:11:22:33:44:55", 4));
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "jx3-machistory-test-{}/mac_history.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        assert!(load_from(&path).unwrap().is_empty());
+        let entries = vec![MacHistoryEntry {
+            mac: "02:11:22:33:44:55".into(),
+            timestamp: 42,
+            adapter_guid: Some("{12345678-ABCD-EF01-2345-6789ABCDEF01}".into()),
+        }];
+        save_to(&entries, &path).unwrap();
+        assert_eq!(load_from(&path).unwrap(), entries);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,319 @@
+//! Linux MAC spoofing via `ip link`.
+//!
+//! Interfaces are enumerated from `/sys/class/net` (physical = has a `device`
+//! link) and identified by name, which takes the place of the Windows
+//! InterfaceGuid in `adapter_guid` parameters. Linux keeps no record of the
+//! pre-spoof address once `ip link set address` runs, so the original MAC of
+//! each interface is saved to `app_local_dir()/mac_state.json` before the
+//! first change and removed again on restore. The state also keeps
+//! each interface's sysfs device path, so a restore still finds the NIC after
+//! it was renamed (udev rules, a USB adapter re-plugged under a new name).
+
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::fs_util::{remove_if_exists, write_atomic};
+
+const SYS_CLASS_NET: &str = "/sys/class/net";
+
+/// One network interface as seen in sysfs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub name: String,
+    /// `XX:XX:XX:XX:XX:XX` uppercase
+    pub current_mac: String,
+    /// sysfs `operstate` (`up`, `down`, `dormant`, ...)
+    pub status: String,
+    /// Canonical target of the `device` link, e.g.
+    /// `/sys/devices/pci0000:00/0000:00:1f.6`; stable across renames
+    pub device: String,
+}
+
+/// Original MAC per interface name, saved before the first spoof
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacState {
+    #[serde(default)]
+    pub originals: BTreeMap<String, String>,
+    /// sysfs device path per interface name, recorded with the original MAC
+    #[serde(default)]
+    pub devices: BTreeMap<String, String>,
+}
+
+/// Validate an interface name before it reaches a path or an `ip` argument
+/// (kernel limit: 15 bytes, no `/` or whitespace)
+pub fn validate_interface_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.len() <= 15
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'))
+        && name != "."
+        && name != "..";
+    if !valid {
+        return Err(AppError::validation(
+            "adapter_guid",
+            format!("网卡名称无效: {name}"),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn read_sys(iface: &str, attr: &str) -> AppResult<String> {
+    let path = Path::new(SYS_CLASS_NET).join(iface).join(attr);
+    Ok(fs::read_to_string(path)?.trim().to_string())
+}
+
+/// Current MAC of `iface` (`XX:XX:XX:XX:XX:XX`)
+pub fn read_mac(iface: &str) -> AppResult<String> {
+    Ok(read_sys(iface, "address")?.to_ascii_uppercase())
+}
+
+/// Canonical sysfs device path of `iface`; None for virtual interfaces
+pub fn device_path(iface: &str) -> Option<String> {
+    fs::canonicalize(Path::new(SYS_CLASS_NET).join(iface).join("device"))
+        .ok()
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Every physical interface, sorted by name
+pub fn list_physical_interfaces() -> AppResult<Vec<Interface>> {
+    let mut interfaces = Vec::new();
+    for entry in fs::read_dir(SYS_CLASS_NET)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        // 虚拟网卡（lo、bridge、veth、tun…）没有 device 链接
+        let Some(device) = device_path(&name) else {
+            continue;
+        };
+        let Ok(current_mac) = read_mac(&name) else {
+            continue;
+        };
+        interfaces.push(Interface {
+            status: read_sys(&name, "operstate").unwrap_or_default(),
+            name,
+            current_mac,
+            device,
+        });
+    }
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(interfaces)
+}
+
+/// Current name of the interface saved as `stored`: the same name when it
+/// still exists, else the interface now sitting on the recorded `device`
+/// (it was renamed). None when neither is present.
+pub fn resolve_interface(
+    stored: &str,
+    device: Option<&str>,
+    interfaces: &[Interface],
+) -> Option<String> {
+    if interfaces.iter().any(|iface| iface.name == stored) {
+        return Some(stored.to_string());
+    }
+    let device = device?;
+    interfaces
+        .iter()
+        .find(|iface| iface.device == device)
+        .map(|iface| iface.name.clone())
+}
+
+/// Interface carrying the default IPv4 route, from `/proc/net/route` contents
+fn parse_default_route(route_table: &str) -> Option<String> {
+    route_table.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let iface = fields.next()?;
+        let destination = fields.next()?;
+        (destination == "00000000").then(|| iface.to_string())
+    })
+}
+
+/// Interface to operate on when none was specified: the default-route one,
+/// else the first physical interface that is up, else the first one
+pub fn default_interface() -> AppResult<String> {
+    if let Some(iface) = fs::read_to_string("/proc/net/route")
+        .ok()
+        .as_deref()
+        .and_then(parse_default_route)
+    {
+        return Ok(iface);
+    }
+    let interfaces = list_physical_interfaces()?;
+    interfaces
+        .iter()
+        .find(|iface| iface.status == "up")
+        .or_else(|| interfaces.first())
+        .map(|iface| iface.name.clone())
+        .ok_or_else(|| AppError::Message("未找到物理网卡".into()))
+}
+
+/// Map `ip` stderr to an error, treating EPERM as a missing root/CAP_NET_ADMIN
+fn ip_error(stderr: &str) -> AppError {
+    if stderr.contains("Operation not permitted") || stderr.contains("Permission denied") {
+        return AppError::permission_denied("修改网络设置（需要 root 权限）");
+    }
+    let line = stderr
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("未知错误");
+    AppError::Command(format!("ip link 执行失败: {line}"))
+}
+
+fn ip_link(args: &[&str]) -> AppResult<()> {
+    let output = std::process::Command::new("ip")
+        .arg("link")
+        .args(args)
+        .output()
+        .map_err(|e| AppError::Command(format!("执行 ip 失败: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(ip_error(&String::from_utf8_lossy(&output.stderr)))
+}
+
+/// Set `iface` to `mac` (`XX:XX:XX:XX:XX:XX`), bringing the link down for the
+/// change. The link is brought back up even when the change fails.
+pub fn set_mac(iface: &str, mac: &str) -> AppResult<()> {
+    ip_link(&["set", "dev", iface, "down"])?;
+    let result = ip_link(&["set", "dev", iface, "address", mac]);
+    let up = ip_link(&["set", "dev", iface, "up"]);
+    result.and(up)
+}
+
+/// Machine-local: the saved originals belong to this PC's NICs
+fn state_path() -> AppResult<PathBuf> {
+    super::config::local_state_path("mac_state.json")
+}
+
+pub fn load_state() -> AppResult<MacState> {
+    load_state_from(&state_path()?)
+}
+
+pub fn save_state(state: &MacState) -> AppResult<()> {
+    save_state_to(state, &state_path()?)
+}
+
+/// Delete mac_state.json; returns whether it existed
+pub fn remove_state() -> AppResult<bool> {
+    Ok(remove_if_exists(&state_path()?)?)
+}
+
+fn load_state_from(path: &Path) -> AppResult<MacState> {
+    if !path.is_file() {
+        return Ok(MacState::default());
+    }
+    let bytes = fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|e| AppError::Config(format!("MAC 状态解析失败: {e}")))
+}
+
+fn save_state_to(state: &MacState, path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, &serde_json::to_vec_pretty(state)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interface_names_are_validated() {
+        assert_eq!(validate_interface_name(" eth0 ").unwrap(), "eth0");
+        assert!(validate_interface_name("wlp0s20f3").is_ok());
+        assert!(validate_interface_name("").is_err());
+        assert!(validate_interface_name("..").is_err());
+        assert!(validate_interface_name("eth0/../../etc").is_err());
+        assert!(validate_interface_name("a-very-long-interface").is_err());
+        assert!(validate_interface_name("{12345678-ABCD-EF01-2345-6789ABCDEF01}").is_err());
+    }
+
+    #[test]
+    fn renamed_interfaces_resolve_by_device() {
+        let iface = |name: &str, device: &str| Interface {
+            name: name.into(),
+            current_mac: "02:00:00:00:00:01".into(),
+            status: "up".into(),
+            device: device.into(),
+        };
+        let interfaces = [
+            iface("enp0s31f6", "/sys/devices/pci0000:00/0000:00:1f.6"),
+            iface("wlan1", "/sys/devices/pci0000:00/0000:00:14.3"),
+        ];
+        assert_eq!(
+            resolve_interface("enp0s31f6", None, &interfaces).as_deref(),
+            Some("enp0s31f6")
+        );
+        // wlan0 改名为 wlan1：按设备路径找回
+        assert_eq!(
+            resolve_interface(
+                "wlan0",
+                Some("/sys/devices/pci0000:00/0000:00:14.3"),
+                &interfaces
+            )
+            .as_deref(),
+            Some("wlan1")
+        );
+        assert_eq!(resolve_interface("wlan0", None, &interfaces), None);
+        assert_eq!(
+            resolve_interface("usb0", Some("/sys/devices/usb1/1-2"), &interfaces),
+            None
+        );
+    }
+
+    #[test]
+    fn default_route_is_read_from_proc_table() {
+        let table = "Iface\tDestination\tGateway\tFlags\n\
+                     docker0\t000011AC\t00000000\t0001\n\
+                     wlan0\t00000000\t0101A8C0\t0003\n";
+        assert_eq!(parse_default_route(table).as_deref(), Some("wlan0"));
+        assert_eq!(parse_default_route("Iface\tDestination\n"), None);
+    }
+
+    #[test]
+    fn eperm_maps_to_permission_denied() {
+        assert!(matches!(
+            ip_error("RTNETLINK answers: Operation not permitted\n"),
+            AppError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            ip_error("Cannot find device \"eth9\"\n"),
+            AppError::Command(_)
+        ));
+    }
+
+    #[test]
+    fn state_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "jx3-macstate-test-{}/mac_state.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(load_state_from(&path).unwrap(), MacState::default());
+        let mut state = MacState::default();
+        state
+            .originals
+            .insert("eth0".into(), "AA:BB:CC:DD:EE:FF".into());
+        state
+            .devices
+            .insert("eth0".into(), "/sys/devices/pci0000:00/0000:00:1f.6".into());
+        save_state_to(&state, &path).unwrap();
+        assert_eq!(load_state_from(&path).unwrap(), state);
+
+        // 旧版状态文件没有 devices 字段
+        let legacy: MacState =
+            serde_json::from_str(r#"{"originals":{"eth0":"AA:BB:CC:DD:EE:FF"}}"#).unwrap();
+        assert!(legacy.devices.is_empty());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
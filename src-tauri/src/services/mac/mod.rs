@@ -3,67 +3,409 @@
 //! Windows strategy: write the `NetworkAddress` override on the adapter's
 //! class registry key, restart the adapter, then read the MAC back to verify
 //! the driver actually accepted it (many drivers, especially wireless ones,
-//! silently ignore the override). Restore removes the override from every
+//! silently ignore the override; a soft apply skips the restart and reports
+//! the new MAC as pending instead). Restore removes the override from every
 //! physical adapter so they fall back to the permanent (burned-in) address.
 //! The registry and Task Scheduler are the single source of truth — no local
-//! state files, so the state survives app restarts and stays accurate.
+//! state files, so the state survives app restarts and stays accurate. The
+//! only things persisted locally are the user's adapter choice (`config.rs`;
+//! without one the scripts auto-pick the adapter carrying the default route)
+//! and a history of applied MACs (`history.rs`) — neither is used to infer state.
 
+mod adapters;
+mod config;
+mod history;
+#[cfg(any(target_os = "linux", test))]
+mod linux;
 mod scripts;
 
+pub use config::RestoreTrigger;
+pub use history::MacHistoryEntry;
+
+use serde::{Deserialize, Serialize};
+#[cfg(any(target_os = "windows", test))]
+use std::process::{Command, Output};
+use std::sync::Mutex;
 #[cfg(any(target_os = "windows", test))]
-use serde::Deserialize;
-use serde::Serialize;
+use std::time::Duration;
+use std::time::Instant;
 
 use crate::error::{AppError, AppResult};
 
-#[cfg(target_os = "windows")]
+/// Auto-restore task name, suffixed with the user name (see `task_name`). The
+/// bare name is what older versions created for everyone; it is still
+/// recognized and migrated to the per-user name on verify, but only by the
+/// user it runs as
+#[cfg(any(target_os = "windows", test))]
 const TASK_NAME: &str = "JX3ToolsMacRestore";
 
+/// The at-startup task runs as SYSTEM before anyone logs on, so there is one
+/// per machine rather than one per user
+#[cfg(target_os = "windows")]
+const STARTUP_TASK_NAME: &str = "JX3ToolsMacRestoreOnStart";
+
+/// Read-only scripts (adapter list / MAC info) should return quickly; a hang
+/// here usually means a profile or policy prompt is waiting for input
+#[cfg(target_os = "windows")]
+const QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Change/restore scripts restart adapters and poll up to 10 s per adapter
+/// for the driver to come back, so they get a longer budget
+#[cfg(target_os = "windows")]
+const APPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// End of the error `output_with_timeout` returns when it kills a child
+#[cfg(any(target_os = "windows", test))]
+const TIMEOUT_SUFFIX: &str = "执行超时";
+
+/// Pause between disabling and re-enabling in `restart_adapter` (same as
+/// `Restart-TargetAdapter` in the scripts)
+#[cfg(target_os = "windows")]
+const RESTART_SETTLE: Duration = Duration::from_millis(500);
+
+/// How long a restarted adapter gets to report `Up` again before the restart
+/// counts as failed (DHCP and Wi-Fi association can take several seconds)
+#[cfg(target_os = "windows")]
+const RESTART_UP_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[cfg(target_os = "windows")]
+const RESTART_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a queried `MacInfo` is reused: long enough to cover back-to-back
+/// UI calls (info + status on page load), short enough not to hide changes
+/// made outside the app
+#[cfg(any(target_os = "windows", test))]
+const INFO_CACHE_TTL: Duration = Duration::from_secs(3);
+
 /// Adapter MAC info reported to the frontend
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MacInfo {
     pub adapter_name: String,
+    /// InterfaceGuid of the reported adapter (empty when unknown)
+    pub adapter_guid: String,
     pub current_mac: String,
     pub permanent_mac: String,
     pub is_modified: bool,
 }
 
+/// Result of a MAC change. A soft apply writes the override without restarting
+/// the adapter: `info` is then the unchanged current state and the new MAC only
+/// takes effect after the adapter reconnects or the system reboots
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacChangeResult {
+    #[serde(flatten)]
+    pub info: MacInfo,
+    /// Whether the adapter was restarted and the new MAC verified just now
+    pub applied_now: bool,
+    /// MAC waiting for a reconnect/reboot (`XX:XX:XX:XX:XX:XX`); None once applied
+    pub pending_mac: Option<String>,
+}
+
+impl MacChangeResult {
+    #[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+    fn applied(info: MacInfo) -> Self {
+        Self {
+            info,
+            applied_now: true,
+            pending_mac: None,
+        }
+    }
+}
+
+/// Stage of `restart_adapter`, in order; ends with `Up` or `Failed`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdapterRestartStage {
+    Disabling,
+    /// Adapter disabled, settling before it is enabled again
+    Waiting,
+    /// Enabling and waiting for the adapter to report `Up`
+    Enabling,
+    Up,
+    Failed,
+}
+
+/// Payload of the `mac://adapter-restart` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterRestartProgress {
+    pub stage: AdapterRestartStage,
+    /// Why the restart failed; only with `Failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AdapterRestartProgress {
+    fn stage(stage: AdapterRestartStage) -> Self {
+        Self { stage, error: None }
+    }
+}
+
+/// Restart progress callback: the command layer turns it into Tauri events
+pub type AdapterRestartProgressFn<'a> = dyn Fn(AdapterRestartProgress) + 'a;
+
+/// Separator style of MACs returned by `get_mac_info` / `get_mac_status`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MacFormat {
+    /// `XX:XX:XX:XX:XX:XX`
+    #[default]
+    Colon,
+    /// `XX-XX-XX-XX-XX-XX`
+    Dash,
+    /// `XXXXXXXXXXXX`
+    Bare,
+}
+
+/// Hex digit case of MACs returned by `get_mac_info` / `get_mac_status`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MacCase {
+    #[default]
+    Upper,
+    Lower,
+}
+
+impl MacInfo {
+    /// Render both MACs in `format`/`case` (internally always colon/upper)
+    pub fn rendered(mut self, format: MacFormat, case: MacCase) -> Self {
+        self.current_mac = render_mac(&self.current_mac, format, case);
+        self.permanent_mac = render_mac(&self.permanent_mac, format, case);
+        self
+    }
+}
+
+impl MacStatus {
+    /// Render both MACs in `format`/`case` (internally always colon/upper)
+    pub fn rendered(mut self, format: MacFormat, case: MacCase) -> Self {
+        self.current = render_mac(&self.current, format, case);
+        self.original = self.original.map(|mac| render_mac(&mac, format, case));
+        self
+    }
+}
+
+/// Spoof status summary: what the adapter reports now vs. its burned-in address
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacStatus {
+    pub current: String,
+    /// Registry override present, or the driver reports a non-permanent MAC
+    pub is_spoofed: bool,
+    /// Permanent (hardware) address; None when the driver doesn't report one
+    pub original: Option<String>,
+}
+
+/// Physical network adapter reported to the frontend (adapter picker)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterInfo {
+    pub guid: String,
+    pub name: String,
+    pub description: String,
+    pub status: String,
+    pub current_mac: String,
+    pub permanent_mac: String,
+    /// Whether this adapter is the persisted selection
+    pub selected: bool,
+    /// Whether a spoofed MAC is in effect (`NetworkAddress` override on
+    /// Windows, a saved original on Linux)
+    pub is_spoofed: bool,
+    /// The spoofed MAC (`XX:XX:XX:XX:XX:XX`) when `is_spoofed`
+    pub spoofed_mac: Option<String>,
+}
+
 /// JSON payload emitted by the PowerShell scripts
 #[cfg(any(target_os = "windows", test))]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PsAdapterInfo {
     name: String,
+    #[serde(default)]
+    guid: String,
     current_mac: String,
     permanent_mac: String,
     has_override: bool,
 }
 
+/// One entry of the adapter list emitted by `list_adapters.ps1`
+#[cfg(any(target_os = "windows", test))]
+#[cfg_attr(not(feature = "ps-adapter-query"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PsAdapterEntry {
+    guid: String,
+    name: String,
+    description: String,
+    status: String,
+    current_mac: String,
+    permanent_mac: String,
+    /// `NetworkAddress` override value; absent/null when not spoofed
+    #[serde(default)]
+    spoofed_mac: Option<String>,
+}
+
 /// Service for MAC address management
-pub struct MacService;
+pub struct MacService {
+    /// Last adapter query, reused within `INFO_CACHE_TTL` (see `cached_info`)
+    info_cache: Mutex<Option<CachedInfo>>,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+struct CachedInfo {
+    /// Adapter the query targeted (None = auto-pick)
+    target: Option<String>,
+    info: MacInfo,
+    queried_at: Instant,
+}
 
 impl MacService {
     /// Create a new MacService
     pub fn new() -> AppResult<Self> {
-        Ok(Self)
+        Ok(Self {
+            info_cache: Mutex::new(None),
+        })
+    }
+
+    /// Drop the cached adapter query, e.g. after a NIC was restarted or
+    /// reconfigured outside the app
+    pub fn refresh_adapters(&self) {
+        if let Ok(mut cache) = self.info_cache.lock() {
+            *cache = None;
+        }
+    }
+
+    /// Cached `MacInfo` for `target` if it is still fresh
+    #[cfg(any(target_os = "windows", test))]
+    fn cached_info(&self, target: Option<&str>) -> Option<MacInfo> {
+        let cache = self.info_cache.lock().ok()?;
+        cache
+            .as_ref()
+            .filter(|c| c.target.as_deref() == target && c.queried_at.elapsed() < INFO_CACHE_TTL)
+            .map(|c| c.info.clone())
+    }
+
+    /// Remember `info` as the latest state of `target`
+    #[cfg(any(target_os = "windows", test))]
+    fn store_info(&self, target: Option<&str>, info: &MacInfo) {
+        if let Ok(mut cache) = self.info_cache.lock() {
+            *cache = Some(CachedInfo {
+                target: target.map(str::to_string),
+                info: info.clone(),
+                queried_at: Instant::now(),
+            });
+        }
+    }
+
+    /// List every physical adapter, flagging the persisted selection
+    pub fn list_adapters(&self) -> AppResult<Vec<AdapterInfo>> {
+        #[cfg(target_os = "windows")]
+        {
+            let selected = self.selected_adapter();
+
+            #[cfg(not(feature = "ps-adapter-query"))]
+            {
+                Ok(adapters::list_physical_adapters()?
+                    .into_iter()
+                    .map(|entry| adapter_info_from_native(entry, selected.as_deref()))
+                    .collect())
+            }
+
+            #[cfg(feature = "ps-adapter-query")]
+            {
+                let stdout = run_powershell(&scripts::list_adapters_script(), QUERY_TIMEOUT)?;
+                Ok(parse_adapter_list(&stdout)?
+                    .into_iter()
+                    .map(|entry| adapter_info_from_ps(entry, selected.as_deref()))
+                    .collect())
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let state = linux::load_state().unwrap_or_default();
+            Ok(linux::list_physical_interfaces()?
+                .into_iter()
+                .map(|iface| {
+                    let original = state.originals.get(&iface.name).cloned();
+                    let is_spoofed = original
+                        .as_ref()
+                        .is_some_and(|mac| !mac.eq_ignore_ascii_case(&iface.current_mac));
+                    AdapterInfo {
+                        permanent_mac: original.unwrap_or_else(|| iface.current_mac.clone()),
+                        guid: iface.name.clone(),
+                        description: String::new(),
+                        status: iface.status,
+                        spoofed_mac: is_spoofed.then(|| iface.current_mac.clone()),
+                        is_spoofed,
+                        current_mac: iface.current_mac,
+                        name: iface.name,
+                        selected: false,
+                    }
+                })
+                .collect())
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Ok(vec![])
+        }
     }
 
-    /// Get the primary adapter's MAC info
+    /// The persisted adapter selection (None = auto-pick)
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn selected_adapter(&self) -> Option<String> {
+        match config::load_config() {
+            Ok(config) => config.adapter_guid,
+            Err(err) => {
+                log::warn!("读取 MAC 配置失败，改为自动选择网卡: {err}");
+                None
+            }
+        }
+    }
+
+    /// Persist the adapter selection (None = back to auto-pick);
+    /// returns the normalized GUID that was stored
+    pub fn select_adapter(&self, guid: Option<&str>) -> AppResult<Option<String>> {
+        let adapter_guid = guid.map(normalize_adapter_guid).transpose()?;
+        let mut config = config::load_config().unwrap_or_default();
+        config.adapter_guid = adapter_guid.clone();
+        config::save_config(&config)?;
+        log::info!("网卡选择已更新: {:?}", adapter_guid);
+        Ok(adapter_guid)
+    }
+
+    /// Get the selected (or auto-picked) adapter's MAC info
     pub fn get_mac_info(&self) -> AppResult<MacInfo> {
         #[cfg(target_os = "windows")]
         {
-            let stdout = run_powershell(&scripts::get_mac_info_script())?;
-            Ok(mac_info_from_ps(parse_adapter_info(&stdout)?))
+            let target = self.selected_adapter();
+            if let Some(info) = self.cached_info(target.as_deref()) {
+                return Ok(info);
+            }
+            let stdout = run_powershell(
+                &scripts::get_mac_info_script(target.as_deref()),
+                QUERY_TIMEOUT,
+            )?;
+            let info = mac_info_from_ps(parse_adapter_info(&stdout)?);
+            self.store_info(target.as_deref(), &info);
+            Ok(info)
         }
 
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(target_os = "linux")]
+        {
+            linux_mac_info(&linux::default_interface()?)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
         {
             match mac_address::get_mac_address() {
                 Ok(Some(addr)) => {
                     let mac = format_mac_address(&addr.to_string());
                     Ok(MacInfo {
                         adapter_name: "本机网卡".into(),
+                        adapter_guid: String::new(),
                         current_mac: mac.clone(),
                         permanent_mac: mac,
                         is_modified: false,
@@ -75,44 +417,493 @@ impl MacService {
         }
     }
 
-    /// Change the MAC address to a random locally-administered value (Windows only).
-    /// The change is verified by reading the MAC back; if the driver ignored it
-    /// the registry override is rolled back and an error is returned.
-    pub fn randomize_mac_address(&self) -> AppResult<MacInfo> {
-        #[cfg(not(target_os = "windows"))]
+    /// Current vs. original MAC of the selected (or auto-picked) adapter, so the
+    /// UI can confirm a spoof actually took effect after the adapter restart
+    pub fn get_mac_status(&self) -> AppResult<MacStatus> {
+        Ok(mac_status_from_info(self.get_mac_info()?))
+    }
+
+    /// Change the MAC address to a random locally-administered value (Windows
+    /// and Linux). The change is verified by reading the MAC back; if the driver
+    /// ignored it the override is rolled back and an error is returned.
+    /// On Windows an explicit `adapter_guid` is remembered for subsequent
+    /// operations and None uses the persisted selection (or auto-picks); on
+    /// Linux it is an interface name and None means the default-route interface.
+    /// `soft_apply` skips the adapter restart (Windows only; Linux always
+    /// applies immediately, it has no deferred override).
+    pub fn randomize_mac_address(
+        &self,
+        adapter_guid: Option<&str>,
+        soft_apply: bool,
+    ) -> AppResult<MacChangeResult> {
+        #[cfg(target_os = "linux")]
         {
+            let _ = soft_apply;
+            let iface = linux_target(adapter_guid)?;
+            let new_mac = generate_random_mac(None)?;
+            self.apply_mac(&new_mac, &iface)
+                .map(MacChangeResult::applied)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            let _ = (adapter_guid, soft_apply);
             return Err(AppError::platform_not_supported("MAC 地址修改"));
         }
 
         #[cfg(target_os = "windows")]
         {
-            let new_mac = generate_random_mac()?;
-            log::info!("修改 MAC 地址为 {new_mac}");
-            let stdout = run_powershell(&scripts::change_mac_script(&new_mac))?;
-            Ok(mac_info_from_ps(parse_adapter_info(&stdout)?))
+            let target = match adapter_guid {
+                Some(guid) => self.select_adapter(Some(guid))?,
+                None => self.selected_adapter(),
+            };
+            let new_mac = generate_random_mac(None)?;
+            self.apply_mac(&new_mac, target.as_deref(), soft_apply)
         }
     }
 
-    /// Restore the original MAC address by removing all overrides (Windows only).
+    /// Re-apply a MAC from the history (`index` 0 = most recent), on the adapter
+    /// it was originally applied to (falls back to the current selection).
+    /// `soft_apply` as in `randomize_mac_address`
+    pub fn apply_mac_from_history(
+        &self,
+        index: usize,
+        soft_apply: bool,
+    ) -> AppResult<MacChangeResult> {
+        #[cfg(target_os = "linux")]
+        {
+            let _ = soft_apply;
+            let entries = history::load_history()?;
+            let entry = entries
+                .get(index)
+                .ok_or_else(|| AppError::validation("index", "该历史记录不存在"))?;
+            let mac = sanitize_mac_input(&entry.mac)?;
+            // Windows 下记录的是 GUID，不是合法网卡名时退回默认网卡
+            let iface = match entry
+                .adapter_guid
+                .as_deref()
+                .and_then(|name| linux::validate_interface_name(name).ok())
+            {
+                Some(name) => name,
+                None => linux::default_interface()?,
+            };
+            self.apply_mac(&mac, &iface).map(MacChangeResult::applied)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            let _ = (index, soft_apply);
+            return Err(AppError::platform_not_supported("MAC 地址修改"));
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let entries = history::load_history()?;
+            let entry = entries
+                .get(index)
+                .ok_or_else(|| AppError::validation("index", "该历史记录不存在"))?;
+            let mac = sanitize_mac_input(&entry.mac)?;
+            let target = match &entry.adapter_guid {
+                Some(guid) => Some(normalize_adapter_guid(guid)?),
+                None => self.selected_adapter(),
+            };
+            self.apply_mac(&mac, target.as_deref(), soft_apply)
+        }
+    }
+
+    /// History of applied MACs, most recent first
+    pub fn get_mac_history(&self) -> AppResult<Vec<MacHistoryEntry>> {
+        history::load_history()
+    }
+
+    /// Apply `mac` (12 uppercase hex chars) to `target`, verify it, and record it
+    /// in the history. A history write failure doesn't fail the change itself.
+    /// A soft apply only writes the override: nothing can be verified until the
+    /// adapter reconnects, so the result carries the MAC as pending.
+    #[cfg(target_os = "windows")]
+    fn apply_mac(
+        &self,
+        mac: &str,
+        target: Option<&str>,
+        soft_apply: bool,
+    ) -> AppResult<MacChangeResult> {
+        log::info!(
+            "修改 MAC 地址为 {mac}（网卡 {:?}，soft_apply={soft_apply}）",
+            target
+        );
+        self.refresh_adapters();
+        let script = scripts::change_mac_script(mac, target, soft_apply);
+        let stdout = self.run_apply_script(&script)?;
+        let info = mac_info_from_ps(parse_adapter_info(&stdout)?);
+        let new_mac = format_mac_address(mac);
+        let entry = MacHistoryEntry {
+            mac: new_mac.clone(),
+            timestamp: history::now_secs(),
+            adapter_guid: Some(info.adapter_guid.clone()).filter(|guid| !guid.is_empty()),
+        };
+        if let Err(err) = history::record(entry) {
+            log::warn!("写入 MAC 历史记录失败: {err}");
+        }
+        if soft_apply {
+            return Ok(MacChangeResult {
+                info,
+                applied_now: false,
+                pending_mac: Some(new_mac),
+            });
+        }
+        // 脚本已回读验证，直接作为最新状态，紧随其后的状态查询无需再跑一次
+        self.store_info(target, &info);
+        Ok(MacChangeResult::applied(info))
+    }
+
+    /// Run a change/restore script under `APPLY_TIMEOUT`. The scripts disable
+    /// and re-enable adapters, so killing one on timeout can leave an adapter
+    /// disabled: the adapters enabled beforehand are re-checked and the ones
+    /// gone since re-enabled, and the timeout error says how that went
+    #[cfg(target_os = "windows")]
+    fn run_apply_script(&self, script: &str) -> AppResult<String> {
+        let enabled = match self.list_adapters() {
+            Ok(adapters) => Some(enabled_adapters(&adapters)),
+            Err(err) => {
+                log::warn!("读取网卡状态失败，超时后将无法确认网卡是否被禁用: {err}");
+                None
+            }
+        };
+        match run_powershell(script, APPLY_TIMEOUT) {
+            Err(err) if is_timeout(&err) => Err(self.recover_after_timeout(err, enabled)),
+            result => result,
+        }
+    }
+
+    /// Re-enable the adapters in `enabled` that a killed apply script left
+    /// disabled; returns `err` extended with the outcome
+    #[cfg(target_os = "windows")]
+    fn recover_after_timeout(&self, err: AppError, enabled: Option<Vec<String>>) -> AppError {
+        use scripts::RestartStep;
+
+        log::warn!("MAC 脚本执行超时，重新检查网卡状态: {err}");
+        self.refresh_adapters();
+        let note = match enabled.zip(self.list_adapters().ok()) {
+            None => "无法确认网卡状态，请在系统网络设置中检查网卡是否被禁用".to_string(),
+            Some((before, now)) => {
+                let failed: Vec<String> = adapters_to_reenable(&before, &now)
+                    .into_iter()
+                    .filter(|guid| {
+                        let script =
+                            scripts::restart_adapter_script(Some(guid), RestartStep::Enable);
+                        match run_powershell(&script, QUERY_TIMEOUT) {
+                            Ok(_) => {
+                                log::info!("已重新启用网卡 {guid}");
+                                false
+                            }
+                            Err(e) => {
+                                log::error!("重新启用网卡 {guid} 失败: {e}");
+                                true
+                            }
+                        }
+                    })
+                    .collect();
+                if failed.is_empty() {
+                    "已重新检查网卡状态，被禁用的网卡已重新启用".to_string()
+                } else {
+                    format!(
+                        "已重新检查网卡状态，但网卡 {} 未能重新启用，请在系统网络设置中手动启用",
+                        failed.join("、")
+                    )
+                }
+            }
+        };
+        match err {
+            AppError::Command(msg) => AppError::Command(format!("{msg}，{note}")),
+            other => other,
+        }
+    }
+
+    /// Linux counterpart of the change script: save the original MAC on first
+    /// change, `ip link set address`, then verify by reading sysfs back. Like
+    /// the script, a change the driver didn't take is rolled back: the MAC the
+    /// interface had before is set again and an error is returned
+    #[cfg(target_os = "linux")]
+    fn apply_mac(&self, mac: &str, iface: &str) -> AppResult<MacInfo> {
+        let mac = format_mac_address(mac);
+        log::info!("修改 MAC 地址为 {mac}（网卡 {iface}）");
+        let previous = linux::read_mac(iface)?;
+        let mut state = linux::load_state()?;
+        if !state.originals.contains_key(iface) {
+            state.originals.insert(iface.to_string(), previous.clone());
+            if let Some(device) = linux::device_path(iface) {
+                state.devices.insert(iface.to_string(), device);
+            }
+            linux::save_state(&state)?;
+        }
+        linux::set_mac(iface, &mac)?;
+        let info = linux_mac_info(iface)?;
+        if info.current_mac != mac {
+            let restored = info.current_mac == previous
+                || match linux::set_mac(iface, &previous) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        log::error!("恢复网卡 {iface} 原 MAC {previous} 失败: {err}");
+                        false
+                    }
+                };
+            return Err(AppError::Command(format!(
+                "网卡驱动未接受新 MAC（当前为 {}），{}",
+                info.current_mac,
+                if restored {
+                    format!("已恢复为 {previous}")
+                } else {
+                    format!("恢复原 MAC {previous} 失败")
+                }
+            )));
+        }
+        let entry = MacHistoryEntry {
+            mac: info.current_mac.clone(),
+            timestamp: history::now_secs(),
+            adapter_guid: Some(iface.to_string()),
+        };
+        if let Err(err) = history::record(entry) {
+            log::warn!("写入 MAC 历史记录失败: {err}");
+        }
+        Ok(info)
+    }
+
+    /// Restore the original MAC address (Windows and Linux).
+    /// `adapter_guid` limits the restore to that adapter; None clears every
+    /// physical adapter (what the logon task does) and reports the selected one.
     /// No-op (and non-disruptive) when nothing is overridden.
-    pub fn restore_mac_address(&self) -> AppResult<MacInfo> {
-        #[cfg(not(target_os = "windows"))]
+    pub fn restore_mac_address(&self, adapter_guid: Option<&str>) -> AppResult<MacInfo> {
+        #[cfg(target_os = "linux")]
+        {
+            let mut state = linux::load_state()?;
+            let interfaces = linux::list_physical_interfaces()?;
+            let mut report = linux_target(adapter_guid)?;
+            let targets: Vec<String> = match adapter_guid {
+                Some(_) => vec![report.clone()],
+                None => state.originals.keys().cloned().collect(),
+            };
+            for iface in &targets {
+                let Some(original) = state.originals.get(iface).cloned() else {
+                    continue;
+                };
+                // 按保存时的名称找不到就按设备路径找改名后的网卡；都找不到时
+                // 保留记录（网卡可能只是暂时拔出），不去动别的网卡
+                let device = state.devices.get(iface).map(String::as_str);
+                let Some(current) = linux::resolve_interface(iface, device, &interfaces) else {
+                    log::warn!("网卡 {iface} 已不存在，也找不到同一硬件的新名称，跳过还原");
+                    continue;
+                };
+                if current != *iface {
+                    log::info!("网卡 {iface} 已改名为 {current}，按新名称还原");
+                    if report == *iface {
+                        report = current.clone();
+                    }
+                }
+                if linux::read_mac(&current)? != original {
+                    linux::set_mac(&current, &original)?;
+                }
+                state.originals.remove(iface);
+                state.devices.remove(iface);
+                linux::save_state(&state)?;
+            }
+            if !interfaces.iter().any(|iface| iface.name == report) {
+                log::warn!("网卡 {report} 不存在，改为报告默认网卡");
+                report = linux::default_interface()?;
+            }
+            linux_mac_info(&report)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
         {
+            let _ = adapter_guid;
             return Err(AppError::platform_not_supported("MAC 地址还原"));
         }
 
         #[cfg(target_os = "windows")]
         {
-            let stdout = run_powershell(&scripts::restore_mac_script())?;
-            Ok(mac_info_from_ps(parse_adapter_info(&stdout)?))
+            let (target, all_adapters) = match adapter_guid {
+                Some(guid) => (Some(normalize_adapter_guid(guid)?), false),
+                None => (self.selected_adapter(), true),
+            };
+            self.refresh_adapters();
+            let script = scripts::restore_mac_script(target.as_deref(), all_adapters);
+            let stdout = self.run_apply_script(&script)?;
+            let info = mac_info_from_ps(parse_adapter_info(&stdout)?);
+            self.store_info(target.as_deref(), &info);
+            Ok(info)
+        }
+    }
+
+    /// Restart the given (else selected or auto-picked) adapter in stages —
+    /// disable, settle, enable, wait until it is `Up` again — reporting each
+    /// stage through `progress`, then return its fresh MAC info. Fails, also
+    /// reported as `Failed`, when a stage errors or the adapter isn't back
+    /// `Up` within `RESTART_UP_TIMEOUT`. Windows only
+    pub fn restart_adapter(
+        &self,
+        adapter_guid: Option<&str>,
+        progress: &AdapterRestartProgressFn,
+    ) -> AppResult<MacInfo> {
+        let result = self.restart_adapter_stages(adapter_guid, progress);
+        match &result {
+            Ok(_) => progress(AdapterRestartProgress::stage(AdapterRestartStage::Up)),
+            Err(err) => {
+                log::error!("重启网卡失败: {err}");
+                progress(AdapterRestartProgress {
+                    stage: AdapterRestartStage::Failed,
+                    error: Some(err.to_string()),
+                });
+            }
         }
+        result
     }
 
-    /// Whether the auto-restore-on-logon scheduled task exists
+    #[cfg(target_os = "windows")]
+    fn restart_adapter_stages(
+        &self,
+        adapter_guid: Option<&str>,
+        progress: &AdapterRestartProgressFn,
+    ) -> AppResult<MacInfo> {
+        use scripts::RestartStep;
+
+        let target = match adapter_guid {
+            Some(guid) => Some(normalize_adapter_guid(guid)?),
+            None => self.selected_adapter(),
+        };
+        self.refresh_adapters();
+        let report = |stage| progress(AdapterRestartProgress::stage(stage));
+
+        // 禁用前先定下网卡：禁用后自动选择会换到别的网卡上，之后的步骤固定用它
+        let stdout = run_powershell(
+            &scripts::restart_adapter_script(target.as_deref(), RestartStep::Guid),
+            QUERY_TIMEOUT,
+        )?;
+        let guid = normalize_adapter_guid(&stdout)?;
+        let enable = || {
+            run_powershell(
+                &scripts::restart_adapter_script(Some(&guid), RestartStep::Enable),
+                QUERY_TIMEOUT,
+            )
+            .map_err(|e| {
+                AppError::Command(format!("重新启用网卡失败，请在系统网络设置中手动启用: {e}"))
+            })
+        };
+
+        report(AdapterRestartStage::Disabling);
+        let disabled = run_powershell(
+            &scripts::restart_adapter_script(Some(&guid), RestartStep::Disable),
+            QUERY_TIMEOUT,
+        );
+        if let Err(err) = disabled {
+            // 报错或超时时网卡可能已经被禁用了，仍要启用一次
+            log::warn!("禁用网卡 {guid} 失败，尝试重新启用: {err}");
+            report(AdapterRestartStage::Enabling);
+            return Err(match (err, enable()) {
+                (AppError::Command(msg), Ok(_)) => {
+                    AppError::Command(format!("{msg}，已重新启用网卡"))
+                }
+                (AppError::Command(msg), Err(e)) => AppError::Command(format!("{msg}；{e}")),
+                (other, _) => other,
+            });
+        }
+        log::info!("已禁用网卡 {guid}，准备重新启用");
+
+        report(AdapterRestartStage::Waiting);
+        std::thread::sleep(RESTART_SETTLE);
+
+        report(AdapterRestartStage::Enabling);
+        enable()?;
+        let status_script = scripts::restart_adapter_script(Some(&guid), RestartStep::Status);
+        wait_until_up(
+            || run_powershell(&status_script, QUERY_TIMEOUT),
+            RESTART_UP_TIMEOUT,
+            RESTART_POLL_INTERVAL,
+        )?;
+        log::info!("网卡 {guid} 已恢复连接");
+
+        let stdout = run_powershell(&scripts::get_mac_info_script(Some(&guid)), QUERY_TIMEOUT)?;
+        let info = mac_info_from_ps(parse_adapter_info(&stdout)?);
+        self.store_info(target.as_deref(), &info);
+        Ok(info)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn restart_adapter_stages(
+        &self,
+        adapter_guid: Option<&str>,
+        _progress: &AdapterRestartProgressFn,
+    ) -> AppResult<MacInfo> {
+        let _ = adapter_guid;
+        Err(AppError::platform_not_supported("重启网卡"))
+    }
+
+    /// Generate a random unicast MAC (`XX:XX:XX:XX:XX:XX`) without touching any
+    /// adapter. Locally administered by default; `oui` keeps a vendor prefix.
+    pub fn generate_random_mac(&self, oui: Option<&str>) -> AppResult<String> {
+        let prefix = oui.map(parse_oui).transpose()?;
+        Ok(format_mac_address(&generate_random_mac(prefix)?))
+    }
+
+    /// Check a user-entered MAC without touching any adapter: returns it as
+    /// `XX:XX:XX:XX:XX:XX`, or the same validation error a change would hit
+    pub fn validate_mac(&self, mac: &str) -> AppResult<String> {
+        Ok(format_mac_address(&sanitize_mac_input(mac)?))
+    }
+
+    /// Whether the auto-restore task for the persisted trigger exists. The old
+    /// shared task only counts when it runs as the current user
     pub fn get_auto_restore_setting(&self) -> AppResult<bool> {
         #[cfg(target_os = "windows")]
         {
-            auto_restore_task_exists()
+            auto_restore_task_exists(self.restore_trigger())
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Ok(false)
+        }
+    }
+
+    /// Persisted trigger of the auto-restore task (default: at logon)
+    pub fn restore_trigger(&self) -> RestoreTrigger {
+        match config::load_config() {
+            Ok(config) => config.restore_trigger,
+            Err(err) => {
+                log::warn!("读取 MAC 配置失败，自动还原按登录时触发: {err}");
+                RestoreTrigger::default()
+            }
+        }
+    }
+
+    /// Check the auto-restore task still points at this executable, uses the
+    /// persisted trigger and, at startup, restores the selected adapter. A task
+    /// left behind by a moved/updated install, with another trigger or adapter,
+    /// or under the old shared name (when it is this user's) is re-created;
+    /// returns whether the task exists afterwards
+    pub fn verify_auto_restore_task(&self) -> AppResult<bool> {
+        #[cfg(target_os = "windows")]
+        {
+            let trigger = self.restore_trigger();
+            let Some(name) = existing_task_name(trigger)? else {
+                return Ok(false);
+            };
+            let adapter = self.startup_adapter(trigger);
+            let xml = query_task_xml(&name)?;
+            let exe = std::env::current_exe()?;
+            if name != restore_task_name(trigger) {
+                log::info!("将旧版共用的自动还原任务 {name} 迁移为当前用户的任务");
+                create_auto_restore_task(trigger, adapter.as_deref())?;
+            } else if !task_runs_exe(&xml, &exe.to_string_lossy()) {
+                log::warn!("开机自动还原任务指向的程序已失效，重新创建");
+                create_auto_restore_task(trigger, adapter.as_deref())?;
+            } else if !task_has_trigger(&xml, trigger) {
+                log::warn!("开机自动还原任务的触发方式与设置不符，按 {trigger:?} 重新创建");
+                create_auto_restore_task(trigger, adapter.as_deref())?;
+            } else if !task_has_arguments(&xml, &restore_task_arguments(adapter.as_deref())) {
+                log::warn!("开机自动还原任务的网卡与当前选择不符，重新创建");
+                create_auto_restore_task(trigger, adapter.as_deref())?;
+            }
+            Ok(true)
         }
 
         #[cfg(not(target_os = "windows"))]
@@ -121,25 +912,88 @@ impl MacService {
         }
     }
 
-    /// Create or delete the auto-restore-on-logon scheduled task (Windows only)
-    pub fn set_auto_restore_setting(&self, auto_restore: bool) -> AppResult<()> {
+    /// Create or delete the auto-restore scheduled task (Windows only).
+    /// `trigger` picks logon vs. startup and is persisted once the task is
+    /// created; None keeps the persisted choice. Switching trigger removes
+    /// the task of the previous one
+    pub fn set_auto_restore_setting(
+        &self,
+        auto_restore: bool,
+        trigger: Option<RestoreTrigger>,
+    ) -> AppResult<()> {
         #[cfg(not(target_os = "windows"))]
         {
+            let _ = trigger;
             if auto_restore {
                 return Err(AppError::platform_not_supported("MAC 地址自动还原"));
             }
-            return Ok(());
+            Ok(())
         }
 
         #[cfg(target_os = "windows")]
         {
-            if auto_restore {
-                create_auto_restore_task()
-            } else {
-                delete_auto_restore_task()
+            let previous = self.restore_trigger();
+            if !auto_restore {
+                return delete_auto_restore_task(previous);
             }
+            let trigger = trigger.unwrap_or(previous);
+            let adapter = self.startup_adapter(trigger);
+            create_auto_restore_task(trigger, adapter.as_deref())?;
+            if trigger == previous {
+                return Ok(());
+            }
+            remove_task_quietly(&restore_task_name(previous));
+            let mut config = config::load_config().unwrap_or_default();
+            config.restore_trigger = trigger;
+            config::save_config(&config)
+        }
+    }
+
+    /// Adapter the at-startup task restores, passed on its command line: under
+    /// SYSTEM `app_local_dir()` is the systemprofile's, so `--restore-mac`
+    /// can't read this user's `mac_config.json`. None (logon task, or no
+    /// adapter selected) restores every physical adapter
+    #[cfg(target_os = "windows")]
+    fn startup_adapter(&self, trigger: RestoreTrigger) -> Option<String> {
+        match trigger {
+            RestoreTrigger::OnLogon => None,
+            RestoreTrigger::OnStart => self.selected_adapter(),
+        }
+    }
+
+    /// Re-launch this executable elevated (UAC `runas`) so a MAC change that
+    /// failed with `PermissionDenied` can be retried. Returns whether a new
+    /// instance was started — the caller then exits; always `false` off Windows
+    pub fn relaunch_as_admin(&self) -> AppResult<bool> {
+        #[cfg(target_os = "windows")]
+        {
+            let exe = std::env::current_exe()?;
+            shell_execute_runas(&exe)?;
+            log::info!("已以管理员身份重新启动: {}", exe.display());
+            Ok(true)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Ok(false)
         }
     }
+
+    /// Delete the MAC page's local files (adapter choice and, on Linux, the
+    /// saved original MACs); missing files are skipped. Returns the names of
+    /// the files actually removed
+    pub fn reset_config(&self) -> AppResult<Vec<String>> {
+        let mut removed = Vec::new();
+        if config::remove_config()? {
+            removed.push("mac_config.json".to_string());
+        }
+        #[cfg(target_os = "linux")]
+        if linux::remove_state()? {
+            removed.push("mac_state.json".to_string());
+        }
+        self.refresh_adapters();
+        Ok(removed)
+    }
 }
 
 // ============================================================================
@@ -148,37 +1002,172 @@ impl MacService {
 
 /// Format MAC address to standard format (XX:XX:XX:XX:XX:XX)
 fn format_mac_address(mac: &str) -> String {
+    render_mac(mac, MacFormat::Colon, MacCase::Upper)
+}
+
+/// Render `mac` from its 12 hex digits in the requested separator style and
+/// case; input without exactly 12 hex digits is returned trimmed
+fn render_mac(mac: &str, format: MacFormat, case: MacCase) -> String {
     let cleaned: String = mac
         .chars()
         .filter(|c| c.is_ascii_hexdigit())
-        .map(|c| c.to_ascii_uppercase())
+        .map(|c| match case {
+            MacCase::Upper => c.to_ascii_uppercase(),
+            MacCase::Lower => c.to_ascii_lowercase(),
+        })
         .collect();
 
     if cleaned.len() != 12 {
         return mac.trim().to_string();
     }
 
-    let mut formatted = String::with_capacity(17);
-    for i in 0..6 {
-        if i > 0 {
-            formatted.push(':');
-        }
-        formatted.push_str(&cleaned[i * 2..i * 2 + 2]);
-    }
+    let separator = match format {
+        MacFormat::Colon => ":",
+        MacFormat::Dash => "-",
+        MacFormat::Bare => "",
+    };
+    (0..6)
+        .map(|i| &cleaned[i * 2..i * 2 + 2])
+        .collect::<Vec<_>>()
+        .join(separator)
+}
 
-    formatted
+/// Canonicalize user/history MAC input to 12 uppercase hex chars (the form the
+/// change script expects), rejecting wrong length, non-hex and multicast input
+fn sanitize_mac_input(mac: &str) -> AppResult<String> {
+    let cleaned: String = mac
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if !cleaned.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::validation(
+            "mac",
+            format!("MAC 地址包含非十六进制字符: {}", mac.trim()),
+        ));
+    }
+    if cleaned.len() != 12 {
+        return Err(AppError::validation(
+            "mac",
+            format!("MAC 地址应为 12 位十六进制，实际 {} 位", cleaned.len()),
+        ));
+    }
+    let first_byte = u8::from_str_radix(&cleaned[0..2], 16)
+        .map_err(|e| AppError::validation("mac", e.to_string()))?;
+    if first_byte & 0x01 != 0 {
+        return Err(AppError::validation("mac", "组播地址不能用作网卡 MAC"));
+    }
+    Ok(cleaned)
 }
 
-/// Generate a random unicast, locally-administered MAC (12 uppercase hex chars).
-/// Drivers commonly reject spoofed MACs without the locally-administered bit.
-#[cfg(any(target_os = "windows", test))]
-fn generate_random_mac() -> AppResult<String> {
+/// Generate a random unicast MAC (12 uppercase hex chars).
+/// Without `oui` the address is locally administered — drivers commonly reject
+/// spoofed MACs without that bit; with `oui` the first three bytes are kept as given.
+fn generate_random_mac(oui: Option<[u8; 3]>) -> AppResult<String> {
     let mut bytes = [0u8; 6];
     getrandom::fill(&mut bytes).map_err(|e| AppError::Command(format!("生成随机 MAC 失败: {e}")))?;
-    bytes[0] = (bytes[0] | 0x02) & 0xFE;
+    match oui {
+        Some(prefix) => bytes[..3].copy_from_slice(&prefix),
+        None => bytes[0] = (bytes[0] | 0x02) & 0xFE,
+    }
     Ok(bytes.iter().map(|b| format!("{b:02X}")).collect())
 }
 
+/// Parse a vendor OUI prefix ("AA:BB:CC" / "AA-BB-CC" / "AABBCC").
+/// Multicast prefixes are rejected: an adapter address must be unicast.
+fn parse_oui(oui: &str) -> AppResult<[u8; 3]> {
+    let cleaned: String = oui
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | ' '))
+        .collect();
+    if cleaned.len() != 6 || !cleaned.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::validation(
+            "oui",
+            format!("厂商前缀应为 3 字节十六进制（如 00:1A:2B）: {}", oui.trim()),
+        ));
+    }
+    let mut prefix = [0u8; 3];
+    for (i, byte) in prefix.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&cleaned[i * 2..i * 2 + 2], 16)
+            .map_err(|e| AppError::validation("oui", e.to_string()))?;
+    }
+    if prefix[0] & 0x01 != 0 {
+        return Err(AppError::validation(
+            "oui",
+            "厂商前缀是组播地址，不能用作网卡 MAC",
+        ));
+    }
+    Ok(prefix)
+}
+
+/// Normalize an adapter InterfaceGuid to `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`
+/// (uppercase, braced) — the form the scripts compare against. Rejecting
+/// anything else also keeps the value safe to embed in a PowerShell literal.
+fn normalize_adapter_guid(guid: &str) -> AppResult<String> {
+    let inner = guid.trim().trim_start_matches('{').trim_end_matches('}');
+    let groups: Vec<&str> = inner.split('-').collect();
+    let valid = groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()));
+    if !valid {
+        return Err(AppError::validation(
+            "adapter_guid",
+            format!("网卡 GUID 格式无效: {}", guid.trim()),
+        ));
+    }
+    Ok(format!("{{{}}}", inner.to_ascii_uppercase()))
+}
+
+#[cfg(any(target_os = "windows", test))]
+#[cfg_attr(not(feature = "ps-adapter-query"), allow(dead_code))]
+fn parse_adapter_list(stdout: &str) -> AppResult<Vec<PsAdapterEntry>> {
+    let trimmed = stdout.trim();
+    if trimmed.is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(trimmed).map_err(|e| AppError::Command(format!("解析网卡列表失败: {e}")))
+}
+
+#[cfg(any(target_os = "windows", test))]
+#[cfg_attr(not(feature = "ps-adapter-query"), allow(dead_code))]
+fn adapter_info_from_ps(entry: PsAdapterEntry, selected: Option<&str>) -> AdapterInfo {
+    let spoofed_mac = entry
+        .spoofed_mac
+        .as_deref()
+        .map(str::trim)
+        .filter(|mac| !mac.is_empty())
+        .map(format_mac_address);
+    AdapterInfo {
+        is_spoofed: spoofed_mac.is_some(),
+        spoofed_mac,
+        selected: selected.is_some_and(|guid| guid.eq_ignore_ascii_case(&entry.guid)),
+        guid: entry.guid,
+        name: entry.name,
+        description: entry.description,
+        status: entry.status,
+        current_mac: format_mac_address(&entry.current_mac),
+        permanent_mac: format_mac_address(&entry.permanent_mac),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn adapter_info_from_native(entry: adapters::NativeAdapter, selected: Option<&str>) -> AdapterInfo {
+    AdapterInfo {
+        is_spoofed: entry.spoofed_mac.is_some(),
+        spoofed_mac: entry.spoofed_mac.as_deref().map(format_mac_address),
+        selected: selected.is_some_and(|guid| guid.eq_ignore_ascii_case(&entry.guid)),
+        guid: entry.guid,
+        name: entry.name,
+        description: entry.description,
+        status: entry.status,
+        current_mac: entry.current_mac,
+        permanent_mac: entry.permanent_mac,
+    }
+}
+
 #[cfg(any(target_os = "windows", test))]
 fn parse_adapter_info(stdout: &str) -> AppResult<PsAdapterInfo> {
     let trimmed = stdout.trim();
@@ -196,12 +1185,49 @@ fn mac_info_from_ps(info: PsAdapterInfo) -> MacInfo {
         !current_mac.is_empty() && !permanent_mac.is_empty() && current_mac != permanent_mac;
     MacInfo {
         adapter_name: info.name,
+        adapter_guid: info.guid,
         current_mac,
         permanent_mac,
         is_modified: info.has_override || differs,
     }
 }
 
+fn mac_status_from_info(info: MacInfo) -> MacStatus {
+    MacStatus {
+        current: info.current_mac,
+        is_spoofed: info.is_modified,
+        original: Some(info.permanent_mac).filter(|mac| !mac.is_empty()),
+    }
+}
+
+/// Whether `err` is `output_with_timeout` giving up on a child
+#[cfg(any(target_os = "windows", test))]
+fn is_timeout(err: &AppError) -> bool {
+    matches!(err, AppError::Command(msg) if msg.ends_with(TIMEOUT_SUFFIX))
+}
+
+/// GUIDs of the adapters that are enabled (disabled ones are reported as
+/// `Disabled`, or not at all by the native query)
+#[cfg(any(target_os = "windows", test))]
+fn enabled_adapters(adapters: &[AdapterInfo]) -> Vec<String> {
+    adapters
+        .iter()
+        .filter(|adapter| adapter.status != "Disabled")
+        .map(|adapter| adapter.guid.clone())
+        .collect()
+}
+
+/// Adapters enabled `before` that are disabled or missing `now`
+#[cfg(any(target_os = "windows", test))]
+fn adapters_to_reenable(before: &[String], now: &[AdapterInfo]) -> Vec<String> {
+    let enabled = enabled_adapters(now);
+    before
+        .iter()
+        .filter(|guid| !enabled.contains(guid))
+        .cloned()
+        .collect()
+}
+
 #[cfg(any(target_os = "windows", test))]
 fn is_permission_error(stderr: &str) -> bool {
     [
@@ -216,6 +1242,133 @@ fn is_permission_error(stderr: &str) -> bool {
     .any(|pattern| stderr.contains(pattern))
 }
 
+/// Auto-restore task name for `user`: users sharing a PC each get their own
+/// task instead of overwriting one. Characters schtasks rejects in task
+/// names become `_`; without a user name it is the bare `TASK_NAME`
+#[cfg(any(target_os = "windows", test))]
+fn task_name(user: Option<&str>) -> String {
+    let user: String = user
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if user.is_empty() {
+        TASK_NAME.to_string()
+    } else {
+        format!("{TASK_NAME}-{user}")
+    }
+}
+
+/// Whether the task definition XML fires on `trigger`
+#[cfg(any(target_os = "windows", test))]
+fn task_has_trigger(task_xml: &str, trigger: RestoreTrigger) -> bool {
+    task_xml.contains(trigger.xml_element())
+}
+
+/// `--restore-mac` arguments of the auto-restore task, with the adapter GUID
+/// when the task must not rely on the config file (see `startup_adapter`)
+#[cfg(any(target_os = "windows", test))]
+fn restore_task_arguments(adapter_guid: Option<&str>) -> String {
+    match adapter_guid {
+        Some(guid) => format!("--restore-mac {guid}"),
+        None => "--restore-mac".to_string(),
+    }
+}
+
+/// Whether the task definition XML passes exactly `arguments` to the program
+#[cfg(any(target_os = "windows", test))]
+fn task_has_arguments(task_xml: &str, arguments: &str) -> bool {
+    task_xml.contains(&format!("<Arguments>{arguments}</Arguments>"))
+}
+
+/// `<UserId>` of the task's principal — the account it runs as, given as
+/// `DOMAIN\user` or a SID. Trigger elements may carry their own `<UserId>`,
+/// so only the one inside `<Principal>` counts
+#[cfg(any(target_os = "windows", test))]
+fn task_user_id(task_xml: &str) -> Option<&str> {
+    let start = task_xml
+        .find("<Principal ")
+        .or_else(|| task_xml.find("<Principal>"))?;
+    let principal = &task_xml[start..];
+    let principal = &principal[..principal.find("</Principal>")?];
+    let id_start = principal.find("<UserId>")? + "<UserId>".len();
+    let id_len = principal[id_start..].find("</UserId>")?;
+    Some(principal[id_start..id_start + id_len].trim())
+}
+
+/// Whether the task runs as one of `user_ids` (case-insensitive)
+#[cfg(any(target_os = "windows", test))]
+fn task_owned_by(task_xml: &str, user_ids: &[String]) -> bool {
+    task_user_id(task_xml)
+        .is_some_and(|id| user_ids.iter().any(|user| user.eq_ignore_ascii_case(id)))
+}
+
+/// SID from `whoami /user /fo csv /nh` (`"domain\user","S-1-5-…"`)
+#[cfg(any(target_os = "windows", test))]
+fn parse_whoami_sid(stdout: &str) -> Option<String> {
+    let sid = stdout.trim().rsplit(',').next()?.trim().trim_matches('"');
+    sid.starts_with("S-").then(|| sid.to_string())
+}
+
+/// Undo the entity escapes of XML element text (`&amp;` last, so an escaped
+/// `&amp;lt;` stays `&lt;`)
+#[cfg(any(target_os = "windows", test))]
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Whether the task definition XML launches `exe`: the unescaped, unquoted
+/// `<Command>` compared case-insensitively, as Windows paths are
+#[cfg(any(target_os = "windows", test))]
+fn task_runs_exe(task_xml: &str, exe: &str) -> bool {
+    let Some(start) = task_xml.find("<Command>") else {
+        return false;
+    };
+    let command = &task_xml[start + "<Command>".len()..];
+    let Some(end) = command.find("</Command>") else {
+        return false;
+    };
+    let command = xml_unescape(&command[..end]);
+    command.trim().trim_matches('"').to_lowercase() == exe.to_lowercase()
+}
+
+/// Poll `status` until it reports `Up`, giving up after `timeout`. A failed
+/// poll counts as not up yet: the adapter can vanish briefly while its
+/// driver reloads. The timeout error names the last status seen
+#[cfg(any(target_os = "windows", test))]
+fn wait_until_up(
+    mut status: impl FnMut() -> AppResult<String>,
+    timeout: Duration,
+    interval: Duration,
+) -> AppResult<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let last = match status() {
+            Ok(status) if status.trim().eq_ignore_ascii_case("Up") => return Ok(()),
+            Ok(status) => status.trim().to_string(),
+            Err(err) => err.to_string(),
+        };
+        if Instant::now() + interval > deadline {
+            return Err(AppError::Command(format!(
+                "网卡在 {} 秒内未恢复连接（最后状态: {last}），请检查网线或无线连接",
+                timeout.as_secs()
+            )));
+        }
+        std::thread::sleep(interval);
+    }
+}
+
 /// First non-empty line of PowerShell stderr — the human-readable message,
 /// before the CategoryInfo/position noise
 #[cfg(any(target_os = "windows", test))]
@@ -227,20 +1380,47 @@ fn first_meaningful_line(text: &str) -> String {
         .to_string()
 }
 
+// ============================================================================
+// Linux
+// ============================================================================
+
+/// Interface named by `adapter_guid`, or the default-route one
+#[cfg(target_os = "linux")]
+fn linux_target(adapter_guid: Option<&str>) -> AppResult<String> {
+    match adapter_guid {
+        Some(name) => linux::validate_interface_name(name),
+        None => linux::default_interface(),
+    }
+}
+
+/// `MacInfo` for `iface`; the original comes from `mac_state.json`
+#[cfg(target_os = "linux")]
+fn linux_mac_info(iface: &str) -> AppResult<MacInfo> {
+    let current_mac = linux::read_mac(iface)?;
+    let original = linux::load_state()?.originals.remove(iface);
+    Ok(MacInfo {
+        adapter_name: iface.to_string(),
+        adapter_guid: iface.to_string(),
+        is_modified: original.as_ref().is_some_and(|mac| *mac != current_mac),
+        permanent_mac: original.unwrap_or_else(|| current_mac.clone()),
+        current_mac,
+    })
+}
+
 // ============================================================================
 // Windows process execution
 // ============================================================================
 
-/// Execute a PowerShell script and return its stdout
+/// Execute a PowerShell script and return its stdout, killing it after `timeout`
 #[cfg(target_os = "windows")]
-fn run_powershell(script: &str) -> AppResult<String> {
+fn run_powershell(script: &str, timeout: Duration) -> AppResult<String> {
     use std::os::windows::process::CommandExt;
-    use std::process::Command;
 
     // 隐藏控制台窗口，避免每次操作闪现 PowerShell 黑窗
     const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
-    let output = Command::new("powershell")
+    let mut command = Command::new("powershell");
+    command
         .args([
             "-NoProfile",
             "-NonInteractive",
@@ -249,9 +1429,8 @@ fn run_powershell(script: &str) -> AppResult<String> {
             "-Command",
             script,
         ])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map_err(|e| AppError::Command(format!("执行 PowerShell 失败: {e}")))?;
+        .creation_flags(CREATE_NO_WINDOW);
+    let output = output_with_timeout(&mut command, timeout, "PowerShell")?;
 
     if output.status.success() {
         return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
@@ -264,10 +1443,77 @@ fn run_powershell(script: &str) -> AppResult<String> {
     Err(AppError::Command(first_meaningful_line(&stderr)))
 }
 
+/// `Command::output` with a deadline. stdout/stderr are drained on their own
+/// threads so a chatty child can't block on a full pipe while we poll it.
+#[cfg(any(target_os = "windows", test))]
+fn output_with_timeout(command: &mut Command, timeout: Duration, name: &str) -> AppResult<Output> {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::time::Instant;
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Command(format!("执行 {name} 失败: {e}")))?;
+
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Command(format!("{name} {TIMEOUT_SUFFIX}")));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// schtasks writes in the console's OEM code page (GBK on Chinese Windows),
+/// not UTF-8: decode it so non-ASCII paths and messages survive
 #[cfg(target_os = "windows")]
-fn run_schtasks(args: &[&str]) -> AppResult<std::process::Output> {
+fn decode_oem(bytes: &[u8]) -> String {
+    use windows::Win32::Globalization::{
+        MultiByteToWideChar, CP_OEMCP, MULTI_BYTE_TO_WIDE_CHAR_FLAGS,
+    };
+
+    if bytes.is_empty() {
+        return String::new();
+    }
+    let flags = MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0);
+    let len = unsafe { MultiByteToWideChar(CP_OEMCP, flags, bytes, None) };
+    if len <= 0 {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let mut wide = vec![0u16; len as usize];
+    let written = unsafe { MultiByteToWideChar(CP_OEMCP, flags, bytes, Some(&mut wide)) };
+    String::from_utf16_lossy(&wide[..written.max(0) as usize])
+}
+
+#[cfg(target_os = "windows")]
+fn run_schtasks(args: &[&str]) -> AppResult<Output> {
     use std::os::windows::process::CommandExt;
-    use std::process::Command;
 
     const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
@@ -278,23 +1524,152 @@ fn run_schtasks(args: &[&str]) -> AppResult<std::process::Output> {
         .map_err(|e| AppError::Command(format!("执行 schtasks 失败: {e}")))
 }
 
+/// `ShellExecuteW(…, "runas", exe)`: blocks until the UAC prompt is answered
+#[cfg(target_os = "windows")]
+fn shell_execute_runas(exe: &std::path::Path) -> AppResult<()> {
+    use windows::core::{w, HSTRING, PCWSTR};
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let file = HSTRING::from(exe.as_os_str());
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("runas"),
+            &file,
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // 返回值 <= 32 表示失败（含用户在 UAC 弹窗点了"否"）
+    if result.0 as isize <= 32 {
+        return Err(AppError::Command(format!(
+            "以管理员身份启动失败（{}），可能已取消 UAC 提示",
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+/// This user's auto-restore task name
+#[cfg(target_os = "windows")]
+fn current_task_name() -> String {
+    task_name(std::env::var("USERNAME").ok().as_deref())
+}
+
+/// Auto-restore task name for `trigger`: this user's at logon, the
+/// machine-wide one at startup
+#[cfg(target_os = "windows")]
+fn restore_task_name(trigger: RestoreTrigger) -> String {
+    match trigger {
+        RestoreTrigger::OnLogon => current_task_name(),
+        RestoreTrigger::OnStart => STARTUP_TASK_NAME.to_string(),
+    }
+}
+
+/// Ways a task's `<UserId>` can name the current user: `DOMAIN\user`, the
+/// bare user name and, best effort, the SID
+#[cfg(target_os = "windows")]
+fn current_user_ids() -> Vec<String> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut ids = Vec::new();
+    if let Ok(user) = std::env::var("USERNAME") {
+        if let Ok(domain) = std::env::var("USERDOMAIN") {
+            ids.push(format!("{domain}\\{user}"));
+        }
+        ids.push(user);
+    }
+    match Command::new("whoami")
+        .args(["/user", "/fo", "csv", "/nh"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            ids.extend(parse_whoami_sid(&String::from_utf8_lossy(&output.stdout)));
+        }
+        Ok(_) => log::warn!("读取当前用户 SID 失败"),
+        Err(err) => log::warn!("读取当前用户 SID 失败: {err}"),
+    }
+    ids
+}
+
+#[cfg(target_os = "windows")]
+fn task_exists(name: &str) -> AppResult<bool> {
+    Ok(run_schtasks(&["/query", "/tn", name])?.status.success())
+}
+
 #[cfg(target_os = "windows")]
-fn auto_restore_task_exists() -> AppResult<bool> {
-    Ok(run_schtasks(&["/query", "/tn", TASK_NAME])?.status.success())
+fn query_task_xml(name: &str) -> AppResult<String> {
+    let output = run_schtasks(&["/query", "/tn", name, "/xml"])?;
+    Ok(decode_oem(&output.stdout))
 }
 
+/// Whether the old shared task exists and runs as the current user. Another
+/// user's is left alone: it is theirs to migrate or delete
 #[cfg(target_os = "windows")]
-fn create_auto_restore_task() -> AppResult<()> {
+fn owns_shared_task() -> AppResult<bool> {
+    if !task_exists(TASK_NAME)? {
+        return Ok(false);
+    }
+    let owned = task_owned_by(&query_task_xml(TASK_NAME)?, &current_user_ids());
+    if !owned {
+        log::info!("旧版共用的自动还原任务属于其他用户，保持不变");
+    }
+    Ok(owned)
+}
+
+/// The auto-restore task for `trigger` that exists, else the old shared one
+/// when it is this user's
+#[cfg(target_os = "windows")]
+fn existing_task_name(trigger: RestoreTrigger) -> AppResult<Option<String>> {
+    let name = restore_task_name(trigger);
+    if task_exists(&name)? {
+        return Ok(Some(name));
+    }
+    if name != TASK_NAME && owns_shared_task()? {
+        return Ok(Some(TASK_NAME.to_string()));
+    }
+    Ok(None)
+}
+
+#[cfg(target_os = "windows")]
+fn auto_restore_task_exists(trigger: RestoreTrigger) -> AppResult<bool> {
+    Ok(existing_task_name(trigger)?.is_some())
+}
+
+/// Create the task for `trigger`. The startup task runs as SYSTEM and gets
+/// `adapter_guid` on its command line (see `MacService::startup_adapter`)
+#[cfg(target_os = "windows")]
+fn create_auto_restore_task(trigger: RestoreTrigger, adapter_guid: Option<&str>) -> AppResult<()> {
     let app_path = std::env::current_exe()?;
-    let action = format!("\"{}\" --restore-mac", app_path.to_string_lossy());
+    let action = format!(
+        "\"{}\" {}",
+        app_path.to_string_lossy(),
+        restore_task_arguments(adapter_guid)
+    );
+    let name = restore_task_name(trigger);
+    let schedule = trigger.schedule();
     // /rl HIGHEST：还原需要管理员权限，否则任务会在登录时静默失败
-    let output = run_schtasks(&[
-        "/create", "/tn", TASK_NAME, "/tr", &action, "/sc", "onlogon", "/rl", "HIGHEST", "/f",
-    ])?;
+    let mut args = vec![
+        "/create", "/tn", &name, "/tr", &action, "/sc", schedule, "/rl", "HIGHEST",
+    ];
+    // 开机时还没有用户登录，只能以 SYSTEM 运行
+    if trigger == RestoreTrigger::OnStart {
+        args.extend(["/ru", "SYSTEM"]);
+    }
+    args.push("/f");
+    let output = run_schtasks(&args)?;
     if output.status.success() {
-        return Ok(());
+        if name != TASK_NAME && owns_shared_task().unwrap_or(false) {
+            remove_task_quietly(TASK_NAME);
+        }
+        return ensure_task_state(trigger, true);
     }
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = decode_oem(&output.stderr);
     if is_permission_error(&stderr) {
         return Err(AppError::permission_denied("创建开机自动还原任务"));
     }
@@ -304,23 +1679,65 @@ fn create_auto_restore_task() -> AppResult<()> {
     )))
 }
 
+/// Drop a task another one took over (the old shared task, or the one of the
+/// previous trigger). Best effort: a leftover only means the MAC gets
+/// restored twice
 #[cfg(target_os = "windows")]
-fn delete_auto_restore_task() -> AppResult<()> {
-    if !auto_restore_task_exists()? {
-        return Ok(());
+fn remove_task_quietly(name: &str) {
+    match run_schtasks(&["/query", "/tn", name]) {
+        Ok(output) if output.status.success() => {}
+        _ => return,
     }
-    let output = run_schtasks(&["/delete", "/tn", TASK_NAME, "/f"])?;
-    if output.status.success() {
-        return Ok(());
+    match run_schtasks(&["/delete", "/tn", name, "/f"]) {
+        Ok(output) if output.status.success() => log::info!("已删除自动还原任务 {name}"),
+        Ok(output) => log::warn!(
+            "删除自动还原任务 {name} 失败: {}",
+            first_meaningful_line(&decode_oem(&output.stderr))
+        ),
+        Err(err) => log::warn!("删除自动还原任务 {name} 失败: {err}"),
     }
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if is_permission_error(&stderr) {
-        return Err(AppError::permission_denied("删除开机自动还原任务"));
+}
+
+/// Delete the task for `trigger` and the old shared one when it is this
+/// user's, whichever exist
+#[cfg(target_os = "windows")]
+fn delete_auto_restore_task(trigger: RestoreTrigger) -> AppResult<()> {
+    let mut names = vec![restore_task_name(trigger)];
+    if names[0] != TASK_NAME && owns_shared_task()? {
+        names.push(TASK_NAME.to_string());
     }
-    Err(AppError::Command(format!(
-        "删除计划任务失败: {}",
-        first_meaningful_line(&stderr)
-    )))
+    for name in &names {
+        if !task_exists(name)? {
+            continue;
+        }
+        let output = run_schtasks(&["/delete", "/tn", name, "/f"])?;
+        if output.status.success() {
+            continue;
+        }
+        let stderr = decode_oem(&output.stderr);
+        if is_permission_error(&stderr) {
+            return Err(AppError::permission_denied("删除开机自动还原任务"));
+        }
+        return Err(AppError::Command(format!(
+            "删除计划任务失败: {}",
+            first_meaningful_line(&stderr)
+        )));
+    }
+    ensure_task_state(trigger, false)
+}
+
+/// schtasks 退出码为 0 不代表任务状态真的变了（例如被组策略拦截），
+/// 再查一次，保证前端开关与实际计划任务一致
+#[cfg(target_os = "windows")]
+fn ensure_task_state(trigger: RestoreTrigger, expected: bool) -> AppResult<()> {
+    if auto_restore_task_exists(trigger)? == expected {
+        return Ok(());
+    }
+    Err(AppError::Command(if expected {
+        "计划任务创建后未找到，请以管理员身份重试".into()
+    } else {
+        "计划任务删除后仍然存在，请以管理员身份重试".into()
+    }))
 }
 
 // ============================================================================
@@ -338,6 +1755,32 @@ mod tests {
         assert_eq!(format_mac_address("aa:bb:cc:dd:ee:ff"), "AA:BB:CC:DD:EE:FF");
     }
 
+    #[test]
+    fn render_mac_honors_format_and_case() {
+        assert_eq!(
+            render_mac("aa:bb:cc:dd:ee:ff", MacFormat::Dash, MacCase::Upper),
+            "AA-BB-CC-DD-EE-FF"
+        );
+        assert_eq!(
+            render_mac("AA-BB-CC-DD-EE-FF", MacFormat::Bare, MacCase::Lower),
+            "aabbccddeeff"
+        );
+        assert_eq!(
+            render_mac("AABBCCDDEEFF", MacFormat::Colon, MacCase::Lower),
+            "aa:bb:cc:dd:ee:ff"
+        );
+        assert_eq!(render_mac(" n/a ", MacFormat::Dash, MacCase::Lower), "n/a");
+
+        let status = MacStatus {
+            current: "02:00:00:00:00:01".into(),
+            is_spoofed: true,
+            original: Some("AA:BB:CC:DD:EE:FF".into()),
+        }
+        .rendered(MacFormat::Dash, MacCase::Lower);
+        assert_eq!(status.current, "02-00-00-00-00-01");
+        assert_eq!(status.original.as_deref(), Some("aa-bb-cc-dd-ee-ff"));
+    }
+
     #[test]
     fn format_mac_address_keeps_invalid_input_trimmed() {
         assert_eq!(format_mac_address(""), "");
@@ -348,7 +1791,7 @@ mod tests {
     #[test]
     fn generate_random_mac_is_unicast_and_locally_administered() {
         for _ in 0..50 {
-            let mac = generate_random_mac().unwrap();
+            let mac = generate_random_mac(None).unwrap();
             assert_eq!(mac.len(), 12);
             assert!(mac.chars().all(|c| c.is_ascii_hexdigit()));
             let first_byte = u8::from_str_radix(&mac[0..2], 16).unwrap();
@@ -357,6 +1800,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn generate_random_mac_keeps_oui_prefix() {
+        let prefix = parse_oui("00-1a-2B").unwrap();
+        assert_eq!(prefix, [0x00, 0x1A, 0x2B]);
+        for _ in 0..20 {
+            let mac = generate_random_mac(Some(prefix)).unwrap();
+            assert!(mac.starts_with("001A2B"));
+        }
+    }
+
+    #[test]
+    fn parse_oui_rejects_malformed_and_multicast_prefixes() {
+        assert!(parse_oui("").is_err());
+        assert!(parse_oui("00:1A").is_err());
+        assert!(parse_oui("00:1A:2G").is_err());
+        assert!(parse_oui("01:00:5E").is_err(), "组播前缀应被拒绝");
+    }
+
+    #[test]
+    fn service_generates_formatted_mac() {
+        let service = MacService::new().unwrap();
+        let mac = service.generate_random_mac(None).unwrap();
+        assert_eq!(mac.len(), 17);
+        assert_eq!(format_mac_address(&mac), mac);
+    }
+
     #[test]
     fn parse_adapter_info_reads_script_json() {
         let info = parse_adapter_info(
@@ -369,6 +1838,45 @@ mod tests {
         assert!(!info.has_override);
     }
 
+    #[test]
+    fn sanitize_mac_input_accepts_common_separators() {
+        assert_eq!(
+            sanitize_mac_input("02:11:22:33:44:55").unwrap(),
+            "021122334455"
+        );
+        assert_eq!(
+            sanitize_mac_input("02-aa-bb-cc-dd-ee").unwrap(),
+            "02AABBCCDDEE"
+        );
+        assert_eq!(
+            sanitize_mac_input(" 0211.2233.4455 ").unwrap(),
+            "021122334455"
+        );
+    }
+
+    #[test]
+    fn sanitize_mac_input_rejects_bad_length_hex_and_multicast() {
+        assert!(sanitize_mac_input("02:11:22:33:44").is_err());
+        assert!(sanitize_mac_input("02:11:22:33:44:GG").is_err());
+        assert!(sanitize_mac_input("01:00:5E:00:00:01").is_err());
+    }
+
+    #[test]
+    fn validate_mac_returns_colon_form_or_precise_error() {
+        let service = MacService::new().unwrap();
+        assert_eq!(
+            service.validate_mac("02-aa-bb-cc-dd-ee").unwrap(),
+            "02:AA:BB:CC:DD:EE"
+        );
+        let err = service.validate_mac("02:11:22").unwrap_err().to_string();
+        assert!(err.contains("12 位"), "{err}");
+        let err = service
+            .validate_mac("01:00:5E:00:00:01")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("组播"), "{err}");
+    }
+
     #[test]
     fn parse_adapter_info_rejects_empty_output() {
         assert!(parse_adapter_info("  \n").is_err());
@@ -378,6 +1886,7 @@ mod tests {
     fn mac_info_unmodified_when_current_equals_permanent() {
         let info = mac_info_from_ps(PsAdapterInfo {
             name: "以太网".into(),
+            guid: String::new(),
             current_mac: "AA-BB-CC-DD-EE-FF".into(),
             permanent_mac: "AABBCCDDEEFF".into(),
             has_override: false,
@@ -391,6 +1900,7 @@ mod tests {
     fn mac_info_modified_when_override_present_or_macs_differ() {
         let by_override = mac_info_from_ps(PsAdapterInfo {
             name: "以太网".into(),
+            guid: String::new(),
             current_mac: "AA-BB-CC-DD-EE-FF".into(),
             permanent_mac: "AABBCCDDEEFF".into(),
             has_override: true,
@@ -399,6 +1909,7 @@ mod tests {
 
         let by_difference = mac_info_from_ps(PsAdapterInfo {
             name: "以太网".into(),
+            guid: String::new(),
             current_mac: "02-11-22-33-44-55".into(),
             permanent_mac: "AABBCCDDEEFF".into(),
             has_override: false,
@@ -410,6 +1921,7 @@ mod tests {
     fn mac_info_not_modified_when_permanent_unknown_without_override() {
         let info = mac_info_from_ps(PsAdapterInfo {
             name: "以太网".into(),
+            guid: String::new(),
             current_mac: "AA-BB-CC-DD-EE-FF".into(),
             permanent_mac: "".into(),
             has_override: false,
@@ -417,6 +1929,173 @@ mod tests {
         assert!(!info.is_modified);
     }
 
+    #[test]
+    fn info_cache_is_keyed_by_target_and_can_be_refreshed() {
+        let service = MacService::new().unwrap();
+        let info = mac_info_from_ps(PsAdapterInfo {
+            name: "以太网".into(),
+            guid: "{12345678-ABCD-EF01-2345-6789ABCDEF01}".into(),
+            current_mac: "02-11-22-33-44-55".into(),
+            permanent_mac: "AA-BB-CC-DD-EE-FF".into(),
+            has_override: true,
+        });
+        assert!(service.cached_info(None).is_none());
+
+        service.store_info(None, &info);
+        assert_eq!(
+            service.cached_info(None).unwrap().current_mac,
+            info.current_mac
+        );
+        assert!(
+            service.cached_info(Some(&info.adapter_guid)).is_none(),
+            "换了网卡不应命中缓存"
+        );
+
+        service.refresh_adapters();
+        assert!(service.cached_info(None).is_none());
+    }
+
+    #[test]
+    fn wait_until_up_tolerates_failed_polls_and_times_out() {
+        let mut polls = vec![Ok("Up".to_string()), Err(AppError::Command("x".into()))];
+        polls.push(Ok("Disabled".into()));
+        let result = wait_until_up(
+            || polls.pop().unwrap(),
+            Duration::from_secs(5),
+            Duration::from_millis(1),
+        );
+        assert!(result.is_ok());
+        assert!(polls.is_empty());
+
+        let err = wait_until_up(
+            || Ok("Disconnected".into()),
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Disconnected"));
+    }
+
+    #[test]
+    fn restart_progress_carries_error_only_on_failure() {
+        assert_eq!(
+            serde_json::to_value(AdapterRestartProgress::stage(AdapterRestartStage::Waiting))
+                .unwrap(),
+            serde_json::json!({ "stage": "waiting" })
+        );
+        let failed = AdapterRestartProgress {
+            stage: AdapterRestartStage::Failed,
+            error: Some("网卡在 20 秒内未恢复连接".into()),
+        };
+        assert_eq!(serde_json::to_value(&failed).unwrap()["stage"], "failed");
+    }
+
+    #[test]
+    fn mac_status_reports_original_only_when_known() {
+        let spoofed = mac_status_from_info(mac_info_from_ps(PsAdapterInfo {
+            name: "以太网".into(),
+            guid: String::new(),
+            current_mac: "02-11-22-33-44-55".into(),
+            permanent_mac: "AA-BB-CC-DD-EE-FF".into(),
+            has_override: true,
+        }));
+        assert_eq!(
+            spoofed,
+            MacStatus {
+                current: "02:11:22:33:44:55".into(),
+                is_spoofed: true,
+                original: Some("AA:BB:CC:DD:EE:FF".into()),
+            }
+        );
+
+        let unknown = mac_status_from_info(mac_info_from_ps(PsAdapterInfo {
+            name: "以太网".into(),
+            guid: String::new(),
+            current_mac: "AA-BB-CC-DD-EE-FF".into(),
+            permanent_mac: "".into(),
+            has_override: false,
+        }));
+        assert!(!unknown.is_spoofed);
+        assert_eq!(unknown.original, None);
+    }
+
+    #[test]
+    fn normalize_adapter_guid_accepts_braced_and_bare_forms() {
+        let expected = "{12345678-ABCD-EF01-2345-6789ABCDEF01}";
+        assert_eq!(
+            normalize_adapter_guid("{12345678-abcd-ef01-2345-6789abcdef01}").unwrap(),
+            expected
+        );
+        assert_eq!(
+            normalize_adapter_guid(" 12345678-ABCD-EF01-2345-6789ABCDEF01 ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn normalize_adapter_guid_rejects_malformed_and_injected_values() {
+        assert!(normalize_adapter_guid("").is_err());
+        assert!(normalize_adapter_guid("12345678-ABCD-EF01-2345").is_err());
+        assert!(normalize_adapter_guid("1234567G-ABCD-EF01-2345-6789ABCDEF01").is_err());
+        assert!(normalize_adapter_guid("'; Remove-Item C:\\ -Recurse; '").is_err());
+    }
+
+    #[test]
+    fn parse_adapter_list_reads_array_and_flags_selection() {
+        let entries = parse_adapter_list(
+            r#"[{"guid":"{AAAAAAAA-0000-0000-0000-000000000001}","name":"以太网","description":"Intel","status":"Up","currentMac":"02-BB-CC-DD-EE-FF","permanentMac":"AA-BB-CC-DD-EE-FF","spoofedMac":"02BBCCDDEEFF"},{"guid":"{AAAAAAAA-0000-0000-0000-000000000002}","name":"WLAN","description":"Wi-Fi","status":"Disconnected","currentMac":"11-22-33-44-55-66","permanentMac":"11-22-33-44-55-66"}]"#,
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let infos: Vec<AdapterInfo> = entries
+            .into_iter()
+            .map(|e| adapter_info_from_ps(e, Some("{aaaaaaaa-0000-0000-0000-000000000002}")))
+            .collect();
+        assert!(!infos[0].selected);
+        assert!(infos[1].selected);
+        assert_eq!(infos[1].current_mac, "11:22:33:44:55:66");
+        assert!(infos[0].is_spoofed);
+        assert_eq!(infos[0].spoofed_mac.as_deref(), Some("02:BB:CC:DD:EE:FF"));
+        assert!(!infos[1].is_spoofed);
+        assert_eq!(infos[1].spoofed_mac, None);
+    }
+
+    #[test]
+    fn change_result_flattens_mac_info() {
+        let info = MacInfo {
+            adapter_name: "以太网".into(),
+            adapter_guid: String::new(),
+            current_mac: "AA:BB:CC:DD:EE:FF".into(),
+            permanent_mac: "AA:BB:CC:DD:EE:FF".into(),
+            is_modified: true,
+        };
+        let value = serde_json::to_value(MacChangeResult {
+            info,
+            applied_now: false,
+            pending_mac: Some("02:11:22:33:44:55".into()),
+        })
+        .unwrap();
+        assert_eq!(value["currentMac"], "AA:BB:CC:DD:EE:FF");
+        assert_eq!(value["appliedNow"], false);
+        assert_eq!(value["pendingMac"], "02:11:22:33:44:55");
+    }
+
+    #[test]
+    fn change_script_embeds_soft_apply_flag() {
+        let soft = scripts::change_mac_script("021122334455", None, true);
+        assert!(soft.contains("$softApply = $true"));
+        let hard = scripts::change_mac_script("021122334455", None, false);
+        assert!(hard.contains("$softApply = $false"));
+        assert!(!hard.contains("{SOFT}") && !hard.contains("{MAC}"));
+    }
+
+    #[test]
+    fn parse_adapter_list_treats_empty_output_as_no_adapters() {
+        assert!(parse_adapter_list("").unwrap().is_empty());
+        assert!(parse_adapter_list("[]").unwrap().is_empty());
+    }
+
     #[test]
     fn permission_errors_are_detected_from_stderr() {
         assert!(is_permission_error("需要管理员权限，请以管理员身份运行本程序"));
@@ -424,9 +2103,138 @@ mod tests {
         assert!(!is_permission_error("未找到物理网卡"));
     }
 
+    #[test]
+    fn adapters_left_disabled_are_found_for_reenabling() {
+        let adapter = |guid: &str, status: &str| AdapterInfo {
+            guid: guid.into(),
+            name: guid.into(),
+            description: String::new(),
+            status: status.into(),
+            current_mac: String::new(),
+            permanent_mac: String::new(),
+            selected: false,
+            is_spoofed: false,
+            spoofed_mac: None,
+        };
+        let before = enabled_adapters(&[
+            adapter("{A}", "Up"),
+            adapter("{B}", "Disconnected"),
+            adapter("{C}", "Up"),
+            adapter("{D}", "Disabled"),
+        ]);
+        assert_eq!(before, ["{A}", "{B}", "{C}"]);
+        // {B} 被禁用，{C} 从原生查询里消失（禁用的网卡不会被列出），{D} 本来就禁用
+        let now = [
+            adapter("{A}", "Up"),
+            adapter("{B}", "Disabled"),
+            adapter("{D}", "Disabled"),
+        ];
+        assert_eq!(adapters_to_reenable(&before, &now), ["{B}", "{C}"]);
+        assert!(!is_timeout(&AppError::Command("拒绝访问".into())));
+    }
+
     #[test]
     fn first_meaningful_line_skips_blank_lines() {
         assert_eq!(first_meaningful_line("\n  \n错误信息\n+ CategoryInfo"), "错误信息");
         assert_eq!(first_meaningful_line(""), "未知错误");
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn output_with_timeout_kills_slow_child() {
+        let started = std::time::Instant::now();
+        let err = output_with_timeout(
+            Command::new("sleep").arg("5"),
+            Duration::from_millis(200),
+            "PowerShell",
+        )
+        .unwrap_err();
+        assert!(is_timeout(&err));
+        assert!(matches!(err, AppError::Command(msg) if msg == "PowerShell 执行超时"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn output_with_timeout_collects_output() {
+        let output = output_with_timeout(
+            Command::new("sh").args(["-c", "echo out; echo err >&2"]),
+            Duration::from_secs(5),
+            "sh",
+        )
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn task_name_is_per_user_and_schtasks_safe() {
+        assert_eq!(task_name(Some("alice")), "JX3ToolsMacRestore-alice");
+        assert_eq!(task_name(Some(" 张三 ")), "JX3ToolsMacRestore-张三");
+        assert_eq!(task_name(Some(r"PC\bob:1")), "JX3ToolsMacRestore-PC_bob_1");
+        assert_eq!(task_name(Some("  ")), TASK_NAME);
+        assert_eq!(task_name(None), TASK_NAME);
+    }
+
+    #[test]
+    fn task_trigger_is_read_from_xml() {
+        let logon = "<Triggers><LogonTrigger><Enabled>true</Enabled></LogonTrigger></Triggers>";
+        assert!(task_has_trigger(logon, RestoreTrigger::OnLogon));
+        assert!(!task_has_trigger(logon, RestoreTrigger::OnStart));
+        let boot = "<Triggers><BootTrigger><Enabled>true</Enabled></BootTrigger></Triggers>";
+        assert!(task_has_trigger(boot, RestoreTrigger::OnStart));
+    }
+
+    #[test]
+    fn startup_task_gets_the_adapter_on_its_command_line() {
+        assert_eq!(restore_task_arguments(None), "--restore-mac");
+        let guid = "{12345678-ABCD-EF01-2345-6789ABCDEF01}";
+        let arguments = restore_task_arguments(Some(guid));
+        assert_eq!(arguments, format!("--restore-mac {guid}"));
+        let xml =
+            format!("<Exec><Command>a.exe</Command><Arguments>{arguments}</Arguments></Exec>");
+        assert!(task_has_arguments(&xml, &arguments));
+        assert!(!task_has_arguments(&xml, "--restore-mac"));
+    }
+
+    #[test]
+    fn shared_task_is_only_owned_by_the_user_it_runs_as() {
+        let xml = r#"<Triggers><LogonTrigger><UserId>PC\bob</UserId></LogonTrigger></Triggers>
+<Principals><Principal id="Author"><UserId>PC\Alice</UserId><RunLevel>HighestAvailable</RunLevel></Principal></Principals>"#;
+        assert_eq!(task_user_id(xml), Some(r"PC\Alice"));
+        let alice = vec![r"pc\alice".to_string(), "alice".to_string()];
+        let bob = vec![r"PC\bob".to_string(), "bob".to_string()];
+        assert!(task_owned_by(xml, &alice));
+        assert!(!task_owned_by(xml, &bob));
+        assert!(!task_owned_by("<Principals></Principals>", &alice));
+
+        let sid = "S-1-5-21-1004336348-1177238915-682003330-1001";
+        let by_sid = format!(r#"<Principal id="Author"><UserId>{sid}</UserId></Principal>"#);
+        let whoami = format!("\"pc\\alice\",\"{sid}\"\r\n");
+        let sids: Vec<String> = parse_whoami_sid(&whoami).into_iter().collect();
+        assert_eq!(sids, [sid]);
+        assert!(task_owned_by(&by_sid, &sids));
+        assert_eq!(parse_whoami_sid("错误: 拒绝访问"), None);
+    }
+
+    #[test]
+    fn task_runs_exe_matches_command_path_ignoring_case() {
+        let xml = r#"<Exec><Command>"C:\Tools\JX3-Tools.exe"</Command><Arguments>--restore-mac</Arguments></Exec>"#;
+        assert!(task_runs_exe(xml, r"c:\tools\jx3-tools.exe"));
+        assert!(!task_runs_exe(xml, r"D:\New\jx3-tools.exe"));
+        assert!(!task_runs_exe(xml, r"C:\Tools"));
+        assert!(!task_runs_exe("<Exec></Exec>", r"c:\tools\jx3-tools.exe"));
+    }
+
+    #[test]
+    fn task_runs_exe_unescapes_non_ascii_paths() {
+        let xml = r#"<Exec><Command>"D:\剑网3 工具\JX3 &amp; Tools.exe"</Command></Exec>"#;
+        assert!(task_runs_exe(xml, r"D:\剑网3 工具\JX3 & Tools.exe"));
+        assert!(!task_runs_exe(xml, r"D:\剑网3 工具\JX3 &amp; Tools.exe"));
+        assert_eq!(
+            xml_unescape("&lt;a&gt; &quot;b&quot; &apos;c&apos; &amp;lt;"),
+            r#"<a> "b" 'c' &lt;"#
+        );
+    }
 }
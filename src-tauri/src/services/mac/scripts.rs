@@ -8,18 +8,72 @@ const COMMON: &str = include_str!("scripts/common.ps1");
 const GET_MAC_INFO: &str = include_str!("scripts/get_mac_info.ps1");
 const CHANGE_MAC: &str = include_str!("scripts/change_mac.ps1");
 const RESTORE_MAC: &str = include_str!("scripts/restore_mac.ps1");
+const LIST_ADAPTERS: &str = include_str!("scripts/list_adapters.ps1");
+const RESTART_ADAPTER: &str = include_str!("scripts/restart_adapter.ps1");
 
-/// Script to query the primary adapter's MAC info (read-only)
-pub fn get_mac_info_script() -> String {
-    format!("{COMMON}\n{GET_MAC_INFO}")
+/// One stage of the staged adapter restart (`restart_adapter.ps1`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartStep {
+    /// Print the adapter's GUID (read-only)
+    Guid,
+    Disable,
+    Enable,
+    /// Print the adapter's `Status` (`Up`, `Disabled`, `Disconnected`…)
+    Status,
 }
 
-/// Script to apply `mac` (12 uppercase hex chars) and verify the driver accepted it
-pub fn change_mac_script(mac: &str) -> String {
-    format!("{COMMON}\n{}", CHANGE_MAC.replace("{MAC}", mac))
+/// Shared prelude: `$targetGuid` (selected adapter, empty = auto-pick) + helpers.
+/// `target` must already be normalized by `normalize_adapter_guid`, so it can
+/// be embedded in a single-quoted literal safely.
+fn prelude(target: Option<&str>) -> String {
+    format!("$targetGuid = '{}'\n{COMMON}", target.unwrap_or_default())
 }
 
-/// Script to remove all MAC overrides and fall back to permanent addresses
-pub fn restore_mac_script() -> String {
-    format!("{COMMON}\n{RESTORE_MAC}")
+/// Script to query the target adapter's MAC info (read-only)
+pub fn get_mac_info_script(target: Option<&str>) -> String {
+    format!("{}\n{GET_MAC_INFO}", prelude(target))
+}
+
+/// Script to apply `mac` (12 uppercase hex chars) and verify the driver accepted
+/// it. `soft_apply` only writes the override, without restarting the adapter
+pub fn change_mac_script(mac: &str, target: Option<&str>, soft_apply: bool) -> String {
+    let soft = if soft_apply { "$true" } else { "$false" };
+    format!(
+        "{}\n{}",
+        prelude(target),
+        CHANGE_MAC.replace("{MAC}", mac).replace("{SOFT}", soft)
+    )
+}
+
+/// Script to remove MAC overrides and fall back to permanent addresses.
+/// `all_adapters` clears every physical adapter; otherwise only `target`.
+pub fn restore_mac_script(target: Option<&str>, all_adapters: bool) -> String {
+    let scope = if all_adapters { "all" } else { "target" };
+    format!(
+        "{}\n{}",
+        prelude(target),
+        RESTORE_MAC.replace("{SCOPE}", scope)
+    )
+}
+
+/// Script to run one restart `step` on `target`. Pass the GUID printed by
+/// `Guid` to the later steps, not the auto-pick
+pub fn restart_adapter_script(target: Option<&str>, step: RestartStep) -> String {
+    let stage = match step {
+        RestartStep::Guid => "guid",
+        RestartStep::Disable => "disable",
+        RestartStep::Enable => "enable",
+        RestartStep::Status => "status",
+    };
+    format!(
+        "{}\n{}",
+        prelude(target),
+        RESTART_ADAPTER.replace("{STAGE}", stage)
+    )
+}
+
+/// Script to list every physical adapter (read-only)
+#[cfg_attr(not(feature = "ps-adapter-query"), allow(dead_code))]
+pub fn list_adapters_script() -> String {
+    format!("{}\n{LIST_ADAPTERS}", prelude(None))
 }
@@ -15,9 +15,19 @@ function Assert-Admin {
     }
 }
 
+function Format-Guid($guid) {
+    '{' + "$guid".Trim('{}').ToUpper() + '}'
+}
+
 function Get-TargetAdapter {
     $physical = @(Get-NetAdapter -Physical -ErrorAction SilentlyContinue)
     if ($physical.Count -eq 0) { throw '未找到物理网卡' }
+    # 用户选定了网卡：只认这一块，找不到就报错而不是悄悄改到别的网卡上
+    if ($targetGuid) {
+        $hit = $physical | Where-Object { (Format-Guid $_.InterfaceGuid) -eq $targetGuid } | Select-Object -First 1
+        if (-not $hit) { throw '未找到所选网卡，请重新选择网卡' }
+        return $hit
+    }
     $up = @($physical | Where-Object { $_.Status -eq 'Up' })
     $pool = if ($up.Count -gt 0) { $up } else { $physical }
     # 优先选择承载默认路由的网卡（当前实际联网的那块）
@@ -31,7 +41,7 @@ function Get-TargetAdapter {
 }
 
 function Get-AdapterRegKey($adapter) {
-    $guid = '{' + "$($adapter.InterfaceGuid)".Trim('{}').ToUpper() + '}'
+    $guid = Format-Guid $adapter.InterfaceGuid
     Get-ChildItem $classKey -ErrorAction SilentlyContinue | Where-Object {
         $props = Get-ItemProperty $_.PSPath -ErrorAction SilentlyContinue
         $props -and $props.PSObject.Properties['NetCfgInstanceId'] -and ($props.NetCfgInstanceId.ToUpper() -eq $guid)
@@ -56,6 +66,7 @@ function Emit-AdapterInfo($name) {
     $adapter = Get-NetAdapter -Name $name
     [PSCustomObject]@{
         name         = "$($adapter.Name)"
+        guid         = Format-Guid $adapter.InterfaceGuid
         currentMac   = "$($adapter.MacAddress)"
         permanentMac = "$($adapter.PermanentAddress)"
         hasOverride  = [bool](Get-MacOverride $adapter)
@@ -0,0 +1,14 @@
+# List every physical adapter (read-only, no admin required).
+# -InputObject @(...) keeps a single adapter serialized as a JSON array.
+$list = foreach ($adapter in @(Get-NetAdapter -Physical -ErrorAction SilentlyContinue)) {
+    [PSCustomObject]@{
+        guid         = Format-Guid $adapter.InterfaceGuid
+        name         = "$($adapter.Name)"
+        description  = "$($adapter.InterfaceDescription)"
+        status       = "$($adapter.Status)"
+        currentMac   = "$($adapter.MacAddress)"
+        permanentMac = "$($adapter.PermanentAddress)"
+        spoofedMac   = Get-MacOverride $adapter
+    }
+}
+ConvertTo-Json -InputObject @($list) -Compress
@@ -0,0 +1,21 @@
+# One stage of a staged adapter restart, driven from Rust so each stage can be
+# reported. {STAGE} is 'guid', 'disable', 'enable' or 'status'. 'guid' prints
+# the adapter's GUID without touching it: later stages must target that GUID,
+# since auto-pick prefers adapters that are Up and would land on another one
+# once this one is down.
+Assert-Admin
+$adapter = Get-TargetAdapter
+switch ('{STAGE}') {
+    'guid' {
+        Format-Guid $adapter.InterfaceGuid
+    }
+    'disable' {
+        Disable-NetAdapter -Name $adapter.Name -Confirm:$false
+    }
+    'enable' {
+        Enable-NetAdapter -Name $adapter.Name -Confirm:$false
+    }
+    'status' {
+        "$($adapter.Status)"
+    }
+}
@@ -1,9 +1,11 @@
-# Remove MAC overrides from every physical adapter and restart the affected
-# ones so they fall back to the permanent (burned-in) address.
+# Remove MAC overrides from every physical adapter (or only the selected one when
+# {SCOPE} is 'target') and restart the affected ones so they fall back to the
+# permanent (burned-in) address.
 # No-op when nothing is overridden, so the logon task never restarts adapters needlessly.
 Assert-Admin
+$adapters = if ('{SCOPE}' -eq 'target') { @(Get-TargetAdapter) } else { @(Get-NetAdapter -Physical -ErrorAction SilentlyContinue) }
 $restored = @()
-foreach ($adapter in @(Get-NetAdapter -Physical -ErrorAction SilentlyContinue)) {
+foreach ($adapter in $adapters) {
     $override = Get-MacOverride $adapter
     if (-not $override) { continue }
     $regKey = Get-AdapterRegKey $adapter
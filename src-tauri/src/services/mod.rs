@@ -1,5 +1,8 @@
 pub mod cloud;
+pub mod diagnostics;
+pub mod game_dirs;
 pub mod hotkey;
 pub mod keyboard;
 pub mod mac;
 pub mod plugin_data;
+pub mod settings;
@@ -344,6 +344,7 @@ fn extract_number(s: &str, key: &str) -> Option<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::keyboard::CopyMode;
     use std::sync::atomic::{AtomicU32, Ordering};
 
     static TEST_DIR_SEQ: AtomicU32 = AtomicU32::new(0);
@@ -413,6 +414,8 @@ mod tests {
         PluginDataService::sync_plugin_config(&CopyParams {
             source_path: source.to_string_lossy().to_string(),
             target_path: target.to_string_lossy().to_string(),
+            verify: false,
+            mode: CopyMode::Replace,
         })
     }
 
@@ -0,0 +1,272 @@
+//! 应用级偏好设置：语言、启动最小化、关闭到托盘、主题、日志级别、热键任务的
+//! 停止检查间隔，存 `app_config_dir()/settings.json`（与 hotkey_config.json 同目录）。
+//! 各功能自己的配置仍在各自文件里，这里只放不属于任何单一功能的偏好。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::fs_util::{app_config_dir, remove_if_exists, write_atomic};
+use crate::services::hotkey::{self, DEFAULT_STOP_POLL_MS, STOP_POLL_RANGE_MS};
+
+/// 界面主题（与前端 `ThemeMode` 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// 日志级别。tauri_plugin_log 以 Trace 构建，实际过滤靠 `log::max_level`，
+/// 因此运行时调高/调低都能立即生效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(self) -> log::LevelFilter {
+        match self {
+            Self::Error => log::LevelFilter::Error,
+            Self::Warn => log::LevelFilter::Warn,
+            Self::Info => log::LevelFilter::Info,
+            Self::Debug => log::LevelFilter::Debug,
+            Self::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" | "warning" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(AppError::validation(
+                "level",
+                format!(
+                    "未知的日志级别: {}（可选 error/warn/info/debug/trace）",
+                    s.trim()
+                ),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// BCP 47 语言标记，如 `zh-CN`
+    pub language: String,
+    pub start_minimized: bool,
+    pub close_to_tray: bool,
+    pub theme: Theme,
+    pub log_level: LogLevel,
+    /// 热键任务等待期间检查停止请求的间隔（毫秒）：越小停得越快，CPU 唤醒越频繁
+    pub stop_poll_ms: u64,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            language: "zh-CN".into(),
+            start_minimized: false,
+            close_to_tray: false,
+            theme: Theme::default(),
+            log_level: LogLevel::default(),
+            stop_poll_ms: DEFAULT_STOP_POLL_MS,
+        }
+    }
+}
+
+pub fn validate(settings: &AppSettings) -> AppResult<()> {
+    let language = settings.language.trim();
+    let valid = !language.is_empty()
+        && language.len() <= 35
+        && language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return Err(AppError::validation(
+            "language",
+            format!("语言标记无效: {language}"),
+        ));
+    }
+    if !STOP_POLL_RANGE_MS.contains(&settings.stop_poll_ms) {
+        return Err(AppError::validation(
+            "stop_poll_ms",
+            format!(
+                "停止检查间隔应在 {}-{} 毫秒之间（当前 {}）：过小会频繁唤醒线程、占用 CPU，过大则按结束热键后要等更久才停",
+                STOP_POLL_RANGE_MS.start(),
+                STOP_POLL_RANGE_MS.end(),
+                settings.stop_poll_ms
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// What `reset_all_config` actually removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetSummary {
+    /// 被删除的文件名（本来就不存在的不列出）
+    pub removed_files: Vec<String>,
+    /// 是否删除了开机自动还原计划任务
+    pub auto_restore_task_removed: bool,
+}
+
+fn settings_path() -> AppResult<PathBuf> {
+    Ok(app_config_dir()?.join("settings.json"))
+}
+
+/// Apply `level` to the global log filter (takes effect immediately)
+pub fn apply_log_level(level: LogLevel) {
+    log::set_max_level(level.filter());
+}
+
+/// Apply every setting that takes effect at runtime (log level, stop poll)
+pub fn apply_runtime(settings: &AppSettings) {
+    apply_log_level(settings.log_level);
+    hotkey::set_stop_poll_ms(settings.stop_poll_ms);
+}
+
+/// Apply `level` now and persist it so the next launch starts with it
+pub fn set_log_level(level: LogLevel) -> AppResult<()> {
+    apply_log_level(level);
+    let mut settings = load_settings()?;
+    settings.log_level = level;
+    save_settings(&settings)
+}
+
+pub fn load_settings() -> AppResult<AppSettings> {
+    load_from(&settings_path()?)
+}
+
+pub fn save_settings(settings: &AppSettings) -> AppResult<()> {
+    save_to(settings, &settings_path()?)
+}
+
+/// Delete settings.json; returns whether it existed
+pub fn remove_settings() -> AppResult<bool> {
+    Ok(remove_if_exists(&settings_path()?)?)
+}
+
+fn load_from(path: &Path) -> AppResult<AppSettings> {
+    if !path.is_file() {
+        return Ok(AppSettings::default());
+    }
+    let bytes = fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|e| AppError::Config(format!("应用设置解析失败: {e}")))
+}
+
+fn save_to(settings: &AppSettings, path: &Path) -> AppResult<()> {
+    validate(settings)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, &serde_json::to_vec_pretty(settings)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let settings: AppSettings = serde_json::from_str(r#"{"theme":"dark"}"#).unwrap();
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.language, "zh-CN");
+        assert!(!settings.close_to_tray);
+    }
+
+    #[test]
+    fn log_levels_parse_case_insensitively() {
+        assert_eq!("DEBUG".parse::<LogLevel>().unwrap(), LogLevel::Debug);
+        assert_eq!(" warn ".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+        assert_eq!(
+            "trace".parse::<LogLevel>().unwrap().filter(),
+            log::LevelFilter::Trace
+        );
+        assert!("verbose".parse::<LogLevel>().is_err());
+        assert!("".parse::<LogLevel>().is_err());
+
+        let settings: AppSettings = serde_json::from_str(r#"{"logLevel":"debug"}"#).unwrap();
+        assert_eq!(settings.log_level, LogLevel::Debug);
+        assert_eq!(AppSettings::default().log_level, LogLevel::Info);
+    }
+
+    #[test]
+    fn validate_rejects_malformed_language() {
+        assert!(validate(&AppSettings::default()).is_ok());
+        let mut settings = AppSettings {
+            language: "en-US".into(),
+            ..AppSettings::default()
+        };
+        assert!(validate(&settings).is_ok());
+        settings.language = " ".into();
+        assert!(validate(&settings).is_err());
+        settings.language = "../zh".into();
+        assert!(validate(&settings).is_err());
+    }
+
+    #[test]
+    fn stop_poll_is_range_checked() {
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.stop_poll_ms, DEFAULT_STOP_POLL_MS);
+        for ms in [10, 50, 1000] {
+            let settings = AppSettings {
+                stop_poll_ms: ms,
+                ..AppSettings::default()
+            };
+            assert!(validate(&settings).is_ok());
+        }
+        for ms in [0, 9, 1001] {
+            let settings = AppSettings {
+                stop_poll_ms: ms,
+                ..AppSettings::default()
+            };
+            let err = validate(&settings).unwrap_err().to_string();
+            assert!(err.contains("CPU"), "{err}");
+        }
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "jx3-settings-test-{}/settings.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(load_from(&path).unwrap(), AppSettings::default());
+        let settings = AppSettings {
+            language: "en-US".into(),
+            start_minimized: true,
+            close_to_tray: true,
+            theme: Theme::Light,
+            log_level: LogLevel::Debug,
+            stop_poll_ms: 20,
+        };
+        save_to(&settings, &path).unwrap();
+        assert_eq!(load_from(&path).unwrap(), settings);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
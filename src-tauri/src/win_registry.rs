@@ -0,0 +1,101 @@
+//! Read-only registry access shared by the Windows-only scans (game install
+//! locations, adapter `NetworkAddress` overrides): open a key, walk its
+//! subkeys, read REG_SZ values. The handle is closed when `RegKey` drops.
+
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::{ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, WIN32_ERROR};
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegEnumKeyExW, RegGetValueW, RegOpenKeyExW, HKEY, KEY_ENUMERATE_SUB_KEYS,
+    KEY_QUERY_VALUE, RRF_RT_REG_SZ,
+};
+
+/// NUL-terminated UTF-16 for the `W` APIs
+pub fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// An open registry key
+pub struct RegKey(HKEY);
+
+impl RegKey {
+    /// Open `hive\path` for enumerating subkeys and reading values
+    pub fn open_read(hive: HKEY, path: &str) -> Result<Self, WIN32_ERROR> {
+        let path = to_wide(path);
+        let mut hkey = HKEY::default();
+        let res = unsafe {
+            RegOpenKeyExW(
+                hive,
+                PCWSTR(path.as_ptr()),
+                Some(0),
+                KEY_ENUMERATE_SUB_KEYS | KEY_QUERY_VALUE,
+                &mut hkey,
+            )
+        };
+        if res != ERROR_SUCCESS {
+            return Err(res);
+        }
+        Ok(Self(hkey))
+    }
+
+    /// Names of the direct subkeys (UTF-16, no NUL); unreadable entries are
+    /// skipped
+    pub fn subkeys(&self) -> Vec<Vec<u16>> {
+        let mut names = Vec::new();
+        for index in 0.. {
+            let mut name = [0u16; 256];
+            let mut name_len = name.len() as u32;
+            let res = unsafe {
+                RegEnumKeyExW(
+                    self.0,
+                    index,
+                    Some(PWSTR(name.as_mut_ptr())),
+                    &mut name_len,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+            };
+            if res == ERROR_NO_MORE_ITEMS {
+                break;
+            }
+            if res == ERROR_SUCCESS {
+                names.push(name[..name_len as usize].to_vec());
+            }
+        }
+        names
+    }
+
+    /// REG_SZ `value` under `self\subkey`; None when missing or unreadable
+    pub fn read_sz(&self, subkey: &[u16], value: &str) -> Option<String> {
+        let subkey: Vec<u16> = subkey.iter().copied().chain(std::iter::once(0)).collect();
+        let value = to_wide(value);
+        let mut buf = [0u16; 520];
+        let mut size = (buf.len() * 2) as u32;
+        let res = unsafe {
+            RegGetValueW(
+                self.0,
+                PCWSTR(subkey.as_ptr()),
+                PCWSTR(value.as_ptr()),
+                RRF_RT_REG_SZ,
+                None,
+                Some(buf.as_mut_ptr().cast()),
+                Some(&mut size),
+            )
+        };
+        if res != ERROR_SUCCESS {
+            return None;
+        }
+        // size 含结尾 NUL（字节数）
+        let len = (size as usize / 2).saturating_sub(1);
+        Some(String::from_utf16_lossy(&buf[..len]))
+    }
+}
+
+impl Drop for RegKey {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = RegCloseKey(self.0);
+        }
+    }
+}
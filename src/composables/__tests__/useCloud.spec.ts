@@ -1,5 +1,6 @@
 import { describe, expect, it } from 'vitest'
-import { cloudProgressPercent, formatBytes, summarizeCloudBatchUpload, summarizeCloudDownload } from '../useCloud'
+import { formatBytes } from '@/utils'
+import { cloudProgressPercent, summarizeCloudBatchUpload, summarizeCloudDownload } from '../useCloud'
 
 describe('cloudProgressPercent', () => {
   it('returns rounded percentage from current/total', () => {
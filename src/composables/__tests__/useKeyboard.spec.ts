@@ -1,5 +1,5 @@
 import { describe, expect, it } from 'vitest'
-import { summarizePluginSync } from '../useKeyboard'
+import { summarizeCopy, summarizePluginSync } from '../useKeyboard'
 
 describe('summarizePluginSync', () => {
   it('merges synced dirs into one success message', () => {
@@ -41,3 +41,25 @@ describe('summarizePluginSync', () => {
     expect(result.warnings).toEqual(['my#data: 同步失败: 文件被占用'])
   })
 })
+
+describe('summarizeCopy', () => {
+  const stats = (files: number, skippedSymlinks = 0) => ({ files, dirs: 1, bytes: 2048, skippedSymlinks })
+
+  it('reports counts and size on success', () => {
+    const result = summarizeCopy({ mode: 'replace', backupPath: null, copied: stats(3) })
+    expect(result.success).toBe('键位复制成功: 3 个文件、1 个目录，共 2.0 KB')
+    expect(result.warnings).toEqual([])
+  })
+
+  it('names merge and warns about skipped symlinks', () => {
+    const result = summarizeCopy({ mode: 'merge', backupPath: null, copied: stats(2, 1) })
+    expect(result.success).toBe('键位合并成功: 2 个文件、1 个目录，共 2.0 KB')
+    expect(result.warnings).toEqual(['已跳过 1 个符号链接'])
+  })
+
+  it('only warns when the source was empty', () => {
+    const result = summarizeCopy({ mode: 'replace', backupPath: null, copied: stats(0) })
+    expect(result.success).toBeUndefined()
+    expect(result.warnings[0]).toContain('没有任何文件')
+  })
+})
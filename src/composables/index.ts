@@ -1,3 +1,4 @@
+export { useElevation } from './useElevation'
 export { useKeyboard } from './useKeyboard'
 export { useMac } from './useMac'
 export { useRecentOps } from './useRecentOps'
@@ -3,6 +3,7 @@ import { listen } from '@tauri-apps/api/event'
 import { useMessage } from 'naive-ui'
 import { ref } from 'vue'
 import { cloudService } from '@/services'
+import { getErrorMessage } from '@/utils'
 
 /** 进度百分比（纯函数，便于测试） */
 export function cloudProgressPercent(progress: CloudProgress | null): number {
@@ -11,15 +12,6 @@ export function cloudProgressPercent(progress: CloudProgress | null): number {
   return Math.round((progress.current / progress.total) * 100)
 }
 
-/** 字节数格式化（纯函数，便于测试） */
-export function formatBytes(bytes: number): string {
-  if (bytes < 1024)
-    return `${bytes} B`
-  if (bytes < 1024 * 1024)
-    return `${(bytes / 1024).toFixed(1)} KB`
-  return `${(bytes / 1024 / 1024).toFixed(1)} MB`
-}
-
 function skippedToWarnings(skipped: { dir: string, reason: string }[]): string[] {
   return skipped.map(item => `${item.dir}: ${item.reason}`)
 }
@@ -59,10 +51,6 @@ const progress = ref<CloudProgress | null>(null)
 export function useCloud() {
   const message = useMessage()
 
-  function errorText(error: unknown): string {
-    return error instanceof Error ? error.message : String(error)
-  }
-
   /** 包裹一次云操作：期间监听 cloud://progress 驱动进度条，结束后清理 */
   async function withProgress<T>(label: string, fn: () => Promise<T>): Promise<T> {
     progress.value = { phase: '', current: 0, total: 0, label }
@@ -85,7 +73,7 @@ export function useCloud() {
       config.value = await cloudService.getCloudConfig()
       configLoaded.value = true
     } catch (error: unknown) {
-      message.error(`读取云同步配置失败: ${errorText(error)}`)
+      message.error(`读取云同步配置失败: ${getErrorMessage(error)}`)
       console.error(error)
     }
   }
@@ -98,7 +86,7 @@ export function useCloud() {
       message.success('连接成功，账号可用')
       return true
     } catch (error: unknown) {
-      message.error(`连接失败: ${errorText(error)}`)
+      message.error(`连接失败: ${getErrorMessage(error)}`)
       console.error(error)
       return false
     } finally {
@@ -114,7 +102,7 @@ export function useCloud() {
       message.success('连接验证通过，云同步账号已保存')
       return true
     } catch (error: unknown) {
-      message.error(`保存失败: ${errorText(error)}`)
+      message.error(`保存失败: ${getErrorMessage(error)}`)
       console.error(error)
       return false
     } finally {
@@ -127,7 +115,7 @@ export function useCloud() {
     try {
       roles.value = await cloudService.listRoles()
     } catch (error: unknown) {
-      message.error(`获取云端列表失败: ${errorText(error)}`)
+      message.error(`获取云端列表失败: ${getErrorMessage(error)}`)
       console.error(error)
     } finally {
       listing.value = false
@@ -146,7 +134,7 @@ export function useCloud() {
       await refreshRoles()
       return true
     } catch (error: unknown) {
-      message.error(`上传失败: ${errorText(error)}`)
+      message.error(`上传失败: ${getErrorMessage(error)}`)
       console.error(error)
       return false
     } finally {
@@ -165,7 +153,7 @@ export function useCloud() {
         message.warning(warning)
       return true
     } catch (error: unknown) {
-      message.error(`下载失败: ${errorText(error)}`)
+      message.error(`下载失败: ${getErrorMessage(error)}`)
       console.error(error)
       return false
     } finally {
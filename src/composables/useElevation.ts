@@ -0,0 +1,37 @@
+import { useMessage } from 'naive-ui'
+import { ref } from 'vue'
+import { macService, settingsService } from '@/services'
+import { getErrorMessage } from '@/utils'
+
+// 模块级单例：进程是否提权在运行期间不会变，整个应用只查一次
+const elevated = ref<boolean | null>(null)
+let pending: Promise<void> | null = null
+
+export function useElevation() {
+  const message = useMessage()
+
+  /** 查询是否以管理员身份运行；查询失败时按已提权处理，不拦界面 */
+  function checkElevation(): Promise<void> {
+    pending ??= settingsService.isElevated()
+      .then((value) => {
+        elevated.value = value
+      })
+      .catch((error: unknown) => {
+        console.error('查询管理员权限失败:', error)
+        elevated.value = true
+      })
+    return pending
+  }
+
+  /** 以管理员身份重启本程序（成功则当前实例退出） */
+  async function relaunchAsAdmin() {
+    try {
+      if (!await macService.relaunchAsAdmin())
+        message.warning('请手动以管理员身份运行本程序')
+    } catch (error: unknown) {
+      message.error(getErrorMessage(error))
+    }
+  }
+
+  return { elevated, checkElevation, relaunchAsAdmin }
+}
@@ -1,18 +1,24 @@
-import type { CopyParams, FileEntry, KeyboardTemplate, PluginSyncReport, UserSelect } from '@/types'
+import type { CopyOutcome, CopyParams, FileEntry, KeyboardTemplate, PluginSyncReport, UserSelect } from '@/types'
 import { open } from '@tauri-apps/plugin-dialog'
 import { useStorage } from '@vueuse/core'
 import { useMessage } from 'naive-ui'
 import { ref } from 'vue'
 import { keyboardService } from '@/services'
+import { formatBytes, getErrorMessage } from '@/utils'
 
 // 模块级单例状态（所有组件共享）
 const basePath = useStorage('keyboard-base-path', '')
 const treeData = ref<FileEntry[]>([])
 const loading = ref(false)
 const copyLoading = ref(false)
+// 自动查找到的 userdata 候选目录（多个时由用户挑选）
+const detectedDirs = ref<string[]>([])
+const detecting = ref(false)
 const templates = useStorage<KeyboardTemplate[]>('keyboard-templates', [])
 // 复制键位时是否同时同步插件配置（interface/*#data 下的茗伊/枫影等）
 const syncPluginEnabled = useStorage('keyboard-sync-plugin', false)
+// 复制键位时合并到目标（保留目标独有的文件）而不是整体替换
+const mergeEnabled = useStorage('keyboard-merge-copy', false)
 
 /** 把插件同步结果整理成提示文案（纯函数，便于测试） */
 export function summarizePluginSync(report: PluginSyncReport): { success?: string, warnings: string[] } {
@@ -24,6 +30,16 @@ export function summarizePluginSync(report: PluginSyncReport): { success?: strin
   return { success: `插件配置已同步: ${report.synced.join('、')}`, warnings }
 }
 
+/** 把键位复制结果整理成提示文案：源为空时只给警告（纯函数，便于测试） */
+export function summarizeCopy(outcome: CopyOutcome): { success?: string, warnings: string[] } {
+  const { files, dirs, bytes, skippedSymlinks } = outcome.copied
+  const warnings = skippedSymlinks > 0 ? [`已跳过 ${skippedSymlinks} 个符号链接`] : []
+  if (files === 0)
+    return { warnings: ['源角色目录下没有任何文件，未复制内容（请确认源角色已在游戏中登录过）', ...warnings] }
+  const action = outcome.mode === 'merge' ? '键位合并成功' : '键位复制成功'
+  return { success: `${action}: ${files} 个文件、${dirs} 个目录，共 ${formatBytes(bytes)}`, warnings }
+}
+
 export function useKeyboard() {
   const message = useMessage()
 
@@ -54,10 +70,40 @@ export function useKeyboard() {
       defaultPath: basePath.value || undefined,
     })
 
-    if (path) {
-      basePath.value = path
-      treeData.value = []
-      await loadTree()
+    if (path)
+      await applyDirectory(path)
+  }
+
+  /**
+   * Switch to `path` and reload the tree
+   */
+  async function applyDirectory(path: string) {
+    basePath.value = path
+    detectedDirs.value = []
+    treeData.value = []
+    await loadTree()
+  }
+
+  /**
+   * Search local JX3 installs for userdata dirs; a single hit is applied
+   * directly, several are left in `detectedDirs` for the user to pick
+   */
+  async function detectDirectory() {
+    detecting.value = true
+    try {
+      const dirs = await keyboardService.detectKeybindDirs()
+      if (dirs.length === 0) {
+        message.warning('未找到剑网3键位目录，请手动选择')
+      } else if (dirs.length === 1) {
+        await applyDirectory(dirs[0])
+      } else {
+        detectedDirs.value = dirs
+      }
+    } catch (error: unknown) {
+      message.error(`自动查找失败: ${getErrorMessage(error)}`)
+      console.error(error)
+    } finally {
+      detecting.value = false
     }
   }
 
@@ -99,7 +145,7 @@ export function useKeyboard() {
       for (const warning of warnings)
         message.warning(warning)
     } catch (error: unknown) {
-      const errorMsg = error instanceof Error ? error.message : String(error)
+      const errorMsg = getErrorMessage(error)
       message.warning(`插件配置同步失败（键位复制不受影响）: ${errorMsg}`)
       console.error(error)
     }
@@ -124,25 +170,26 @@ export function useKeyboard() {
       return false
     }
 
-    const params = {
+    const params: CopyParams = {
       source_path: `${basePath.value}/${userSelect.sourcePath}`,
       target_path: `${basePath.value}/${userSelect.targetPath}`,
+      mode: mergeEnabled.value ? 'merge' : 'replace',
     }
 
     copyLoading.value = true
     try {
-      const success = await keyboardService.copySourceToTarget(params)
-      if (success) {
-        message.success('键位复制成功')
-        if (syncPluginEnabled.value)
-          await syncPluginAfterCopy(params)
-        await loadTree()
-      } else {
-        message.error('复制失败')
-      }
-      return success
+      const outcome = await keyboardService.copySourceToTarget(params)
+      const { success, warnings } = summarizeCopy(outcome)
+      if (success)
+        message.success(success)
+      for (const warning of warnings)
+        message.warning(warning)
+      if (syncPluginEnabled.value)
+        await syncPluginAfterCopy(params)
+      await loadTree()
+      return true
     } catch (error: unknown) {
-      const errorMsg = error instanceof Error ? error.message : String(error)
+      const errorMsg = getErrorMessage(error)
       message.error(`复制失败: ${errorMsg}`)
       console.error(error)
       return false
@@ -211,18 +258,14 @@ export function useKeyboard() {
 
     copyLoading.value = true
     try {
-      const success = await keyboardService.copySourceToTarget(params)
-      if (success) {
-        message.success(`已应用键位: ${template.name}`)
-        if (syncPluginEnabled.value)
-          await syncPluginAfterCopy(params)
-        await loadTree()
-      } else {
-        message.error('应用失败')
-      }
-      return success
+      await keyboardService.copySourceToTarget(params)
+      message.success(`已应用键位: ${template.name}`)
+      if (syncPluginEnabled.value)
+        await syncPluginAfterCopy(params)
+      await loadTree()
+      return true
     } catch (error: unknown) {
-      const errorMsg = error instanceof Error ? error.message : String(error)
+      const errorMsg = getErrorMessage(error)
       message.error(`应用失败: ${errorMsg}`)
       console.error(error)
       return false
@@ -238,8 +281,13 @@ export function useKeyboard() {
     copyLoading,
     templates,
     syncPluginEnabled,
+    mergeEnabled,
+    detectedDirs,
+    detecting,
     selectDirectory,
     changeDirectory,
+    applyDirectory,
+    detectDirectory,
     resetDirectory,
     loadTree,
     copyKeyboardConfig,
@@ -1,19 +1,47 @@
-import type { MacInfo } from '@/types/mac'
+import type { AdapterRestartProgress, AdapterRestartStage, MacInfo, RestoreTrigger } from '@/types/mac'
+import { listen } from '@tauri-apps/api/event'
 import { useMessage } from 'naive-ui'
 import { computed, ref } from 'vue'
 import { macService } from '@/services'
+import { ErrorCodes, getErrorMessage, isErrorCode } from '@/utils'
+import { useElevation } from './useElevation'
+
+const RESTART_STAGE_LABELS: Record<AdapterRestartStage, string> = {
+  disabling: '正在禁用网卡…',
+  waiting: '等待网卡释放…',
+  enabling: '正在启用网卡，等待重新连接…',
+  up: '网卡已恢复连接',
+  failed: '网卡重启失败',
+}
 
 export function useMac() {
   const message = useMessage()
+  const { relaunchAsAdmin } = useElevation()
 
   // Backend returns the verified actual adapter state — never display
   // an optimistic value that the driver may have silently rejected
   const info = ref<MacInfo | null>(null)
   const autoRestoreEnabled = ref(false)
+  // 自动还原时机：登录时（默认）或开机时（登录前，以 SYSTEM 运行）
+  const restoreTrigger = ref<RestoreTrigger>('onLogon')
+  // 不重启网卡：避免游戏中断线，新地址在网卡重连或重启后生效
+  const softApply = ref(false)
+  const pendingMac = ref<string | null>(null)
   const loading = ref(false)
   const changing = ref(false)
   const restoring = ref(false)
+  const restarting = ref(false)
+  // 重启网卡当前阶段（mac://adapter-restart 事件驱动），未在重启时为 null
+  const restartStage = ref<AdapterRestartStage | null>(null)
+  const restartLabel = computed(() =>
+    restartStage.value ? RESTART_STAGE_LABELS[restartStage.value] : '',
+  )
   const error = ref<string | null>(null)
+  // 最近一次修改失败是权限不足：Windows 上可一键以管理员身份重启
+  const permissionDenied = ref(false)
+  const canRelaunchAsAdmin = computed(() =>
+    permissionDenied.value && navigator.platform.toLowerCase().includes('win'),
+  )
 
   const adapterName = computed(() => info.value?.adapterName ?? '')
   const currentAddress = computed(() => info.value?.currentMac ?? '')
@@ -30,7 +58,7 @@ export function useMac() {
     try {
       info.value = await macService.getMacInfo()
     } catch (e: unknown) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = getErrorMessage(e)
       error.value = `获取MAC地址失败: ${errorMsg}`
       message.error('获取MAC地址失败')
       info.value = null
@@ -40,6 +68,7 @@ export function useMac() {
 
     try {
       autoRestoreEnabled.value = await macService.getAutoRestoreSetting()
+      restoreTrigger.value = await macService.getAutoRestoreTrigger()
     } catch {
       autoRestoreEnabled.value = false
     }
@@ -53,11 +82,18 @@ export function useMac() {
     error.value = null
 
     try {
-      info.value = await macService.randomizeMacAddress()
-      message.success(`MAC地址已修改为 ${info.value.currentMac}`)
+      const result = await macService.randomizeMacAddress(undefined, softApply.value)
+      info.value = result
+      pendingMac.value = result.pendingMac
+      permissionDenied.value = false
+      if (result.appliedNow)
+        message.success(`MAC地址已修改为 ${result.currentMac}`)
+      else
+        message.warning(`已写入 ${result.pendingMac}，重新连接网络或重启电脑后生效`)
     } catch (e: unknown) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = getErrorMessage(e)
       error.value = errorMsg
+      permissionDenied.value = isErrorCode(e, ErrorCodes.PERMISSION_DENIED)
       message.error(errorMsg)
     } finally {
       changing.value = false
@@ -73,44 +109,110 @@ export function useMac() {
 
     try {
       info.value = await macService.restoreMacAddress()
+      pendingMac.value = null
+      permissionDenied.value = false
       message.success('MAC地址已还原')
     } catch (e: unknown) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = getErrorMessage(e)
       error.value = errorMsg
+      permissionDenied.value = isErrorCode(e, ErrorCodes.PERMISSION_DENIED)
       message.error(errorMsg)
     } finally {
       restoring.value = false
     }
   }
 
+  /**
+   * Restart the adapter (disable → enable) and wait until it is back up,
+   * showing each stage; the backend errors if it doesn't recover in time
+   */
+  async function restartAdapter() {
+    restarting.value = true
+    error.value = null
+    restartStage.value = null
+    const unlisten = await listen<AdapterRestartProgress>('mac://adapter-restart', (e) => {
+      restartStage.value = e.payload.stage
+    })
+
+    try {
+      info.value = await macService.restartNetworkAdapter()
+      permissionDenied.value = false
+      message.success('网卡已重启并恢复连接')
+    } catch (e: unknown) {
+      const errorMsg = getErrorMessage(e)
+      error.value = errorMsg
+      permissionDenied.value = isErrorCode(e, ErrorCodes.PERMISSION_DENIED)
+      message.error(errorMsg)
+    } finally {
+      unlisten()
+      restartStage.value = null
+      restarting.value = false
+    }
+  }
+
   /**
    * Set auto-restore on reboot setting
    */
   async function setAutoRestore(enabled: boolean) {
     try {
-      await macService.setAutoRestoreSetting(enabled)
+      await macService.setAutoRestoreSetting(enabled, restoreTrigger.value)
       autoRestoreEnabled.value = enabled
+      permissionDenied.value = false
       message.success(enabled ? '已开启重启自动还原' : '已关闭重启自动还原')
     } catch (e: unknown) {
-      autoRestoreEnabled.value = !enabled
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      // 以实际计划任务为准，而不是简单取反
+      try {
+        autoRestoreEnabled.value = await macService.getAutoRestoreSetting()
+      } catch {
+        autoRestoreEnabled.value = !enabled
+      }
+      const errorMsg = getErrorMessage(e)
+      permissionDenied.value = isErrorCode(e, ErrorCodes.PERMISSION_DENIED)
       message.error(`更新设置失败: ${errorMsg}`)
     }
   }
 
+  /**
+   * Change when auto-restore runs; an enabled task is re-created right away
+   */
+  async function setRestoreTrigger(trigger: RestoreTrigger) {
+    const previous = restoreTrigger.value
+    restoreTrigger.value = trigger
+    if (!autoRestoreEnabled.value)
+      return
+    try {
+      await macService.setAutoRestoreSetting(true, trigger)
+      permissionDenied.value = false
+      message.success(trigger === 'onStart' ? '已改为开机时自动还原' : '已改为登录时自动还原')
+    } catch (e: unknown) {
+      restoreTrigger.value = previous
+      permissionDenied.value = isErrorCode(e, ErrorCodes.PERMISSION_DENIED)
+      message.error(`更新设置失败: ${getErrorMessage(e)}`)
+    }
+  }
+
   return {
     adapterName,
     originalAddress,
     currentAddress,
     autoRestoreEnabled,
+    restoreTrigger,
+    softApply,
+    pendingMac,
     loading,
     changing,
     restoring,
+    restarting,
+    restartLabel,
     error,
+    canRelaunchAsAdmin,
     isChanged,
     fetchMacAddress,
     changeMacAddress,
     restoreMacAddress,
+    restartAdapter,
     setAutoRestore,
+    setRestoreTrigger,
+    relaunchAsAdmin,
   }
 }
@@ -1,4 +1,18 @@
-import type { HotkeyConfig, HotkeyStatus, WindowInfo } from '@/types'
+import type {
+  HotkeyConfig,
+  HotkeyStatus,
+  InterceptionDevice,
+  KeyGroup,
+  ListenerHealth,
+  ListenerStats,
+  MacroInfo,
+  ProcessInfo,
+  RunnerTransition,
+  SavedConfigInfo,
+  SessionLogInfo,
+  WindowFilter,
+  WindowInfo,
+} from '@/types'
 import { invoke } from '@tauri-apps/api/core'
 
 export const hotkeyService = {
@@ -17,24 +31,174 @@ export const hotkeyService = {
   },
 
   /**
-   * Save hotkey configuration
+   * Check whether the registered start/stop hotkeys are still active
    */
-  async saveConfig(config: HotkeyConfig): Promise<HotkeyConfig> {
-    return invoke<HotkeyConfig>('save_hotkey_config', { config })
+  async getListenerHealth(): Promise<ListenerHealth> {
+    return invoke<ListenerHealth>('get_listener_health')
+  },
+
+  /**
+   * Shortcut press counts (received / debounced / per-hotkey hits) since the
+   * last registration, for diagnosing "keys feel laggy"
+   */
+  async getListenerStats(): Promise<ListenerStats> {
+    return invoke<ListenerStats>('get_listener_stats')
+  },
+
+  /**
+   * Trigger-key labels the backend accepts, grouped for the picker
+   */
+  async supportedTriggerKeys(): Promise<KeyGroup[]> {
+    return invoke<KeyGroup[]>('supported_trigger_keys')
+  },
+
+  /**
+   * JSON Schema of the hotkey config (wire format), for external config tooling
+   */
+  async hotkeyConfigSchema(): Promise<string> {
+    return invoke<string>('hotkey_config_schema')
+  },
+
+  /**
+   * Save hotkey configuration; returns how the key labels were resolved
+   */
+  async saveConfig(config: HotkeyConfig): Promise<SavedConfigInfo> {
+    return invoke<SavedConfigInfo>('save_hotkey_config', { config })
+  },
+
+  /**
+   * Change only the start/stop hotkeys (omitted = unchanged) on the saved
+   * config, so a stale local copy can't overwrite other fields
+   */
+  async updateHotkeys(start?: string, stop?: string): Promise<SavedConfigInfo> {
+    return invoke<SavedConfigInfo>('update_hotkeys', { start: start ?? null, stop: stop ?? null })
+  },
+
+  /**
+   * Re-register start/stop hotkeys from the current config (no save)
+   */
+  async reregisterHotkeys(): Promise<HotkeyStatus> {
+    return invoke<HotkeyStatus>('reregister_hotkeys')
+  },
+
+  /**
+   * Pause or resume hotkey capture without changing the config; while paused
+   * the start/stop keys type normally and only the emergency stop is active
+   */
+  async setListenerEnabled(enabled: boolean): Promise<HotkeyStatus> {
+    return invoke<HotkeyStatus>('set_listener_enabled', { enabled })
+  },
+
+  /**
+   * Dismiss the status error banner (config and registration untouched)
+   */
+  async clearError(): Promise<HotkeyStatus> {
+    return invoke<HotkeyStatus>('clear_hotkey_error')
+  },
+
+  /**
+   * Start the hotkey automation task, same as pressing the start hotkey
+   */
+  async startTask(): Promise<RunnerTransition> {
+    return invoke<RunnerTransition>('start_hotkey_task')
   },
 
   /**
    * Stop running hotkey automation task
    */
-  async stopTask(): Promise<void> {
-    return invoke<void>('stop_hotkey_task')
+  async stopTask(): Promise<RunnerTransition> {
+    return invoke<RunnerTransition>('stop_hotkey_task')
+  },
+
+  /**
+   * Send the configured trigger key once (no runner started)
+   */
+  async testTriggerKey(): Promise<void> {
+    return invoke<void>('test_trigger_key')
+  },
+
+  /**
+   * Post a key to a window once, to check the game accepts window-mode input
+   */
+  async testWindowSend(hwnd: number, key: string): Promise<void> {
+    return invoke<void>('test_window_send', { hwnd, key })
+  },
+
+  /**
+   * List the Interception keyboard devices that opened, for picking deviceFilter. Windows only.
+   */
+  async listInterceptionDevices(): Promise<InterceptionDevice[]> {
+    return invoke<InterceptionDevice[]>('list_interception_devices')
+  },
+
+  /**
+   * Start recording a key macro to be saved as `name` (Windows only)
+   */
+  async startMacroRecord(name: string): Promise<void> {
+    return invoke<void>('start_macro_record', { name })
+  },
+
+  /**
+   * Stop recording and save the captured keys under the name given at start
+   */
+  async stopMacroRecord(): Promise<MacroInfo> {
+    return invoke<MacroInfo>('stop_macro_record')
+  },
+
+  /**
+   * List saved key macros, sorted by name
+   */
+  async listMacros(): Promise<MacroInfo[]> {
+    return invoke<MacroInfo[]>('list_macros')
+  },
+
+  /**
+   * Delete a saved key macro; resolves to whether it existed
+   */
+  async deleteMacro(name: string): Promise<boolean> {
+    return invoke<boolean>('delete_macro', { name })
+  },
+
+  /**
+   * List per-run session logs, newest first
+   */
+  async listSessionLogs(): Promise<SessionLogInfo[]> {
+    return invoke<SessionLogInfo[]>('list_session_logs')
+  },
+
+  /**
+   * Write a per-session CSV summary to `path`; resolves to the session count
+   */
+  async exportSessionStats(path: string): Promise<number> {
+    return invoke<number>('export_session_stats', { path })
+  },
+
+  /**
+   * Open the session log directory in the system file manager
+   */
+  async openSessionLogDir(): Promise<void> {
+    return invoke<void>('open_session_log_dir')
   },
 
   /**
    * Get list of visible windows (Windows only)
    */
-  async listWindows(filter?: string): Promise<WindowInfo[]> {
-    return invoke<WindowInfo[]>('list_windows', { filter: filter || null })
+  async listWindows(filter?: WindowFilter): Promise<WindowInfo[]> {
+    return invoke<WindowInfo[]>('list_windows', { filter: filter ?? null })
+  },
+
+  /**
+   * First visible window with the given class name (Windows only)
+   */
+  async findWindowByClass(className: string): Promise<WindowInfo | null> {
+    return invoke<WindowInfo | null>('find_window_by_class', { className })
+  },
+
+  /**
+   * Running processes whose executable is one of `names` (Windows only)
+   */
+  async listGameProcesses(names: string[]): Promise<ProcessInfo[]> {
+    return invoke<ProcessInfo[]>('list_game_processes', { names })
   },
 
   /**
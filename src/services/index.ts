@@ -2,3 +2,4 @@ export { cloudService } from './cloud.service'
 export { hotkeyService } from './hotkey.service'
 export { keyboardService } from './keyboard.service'
 export { macService } from './mac.service'
+export { settingsService } from './settings.service'
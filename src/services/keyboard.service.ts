@@ -1,19 +1,106 @@
-import type { CopyParams, FileEntry, PluginSyncReport } from '@/types'
+import type { CopyOutcome, CopyParams, CopyPreview, DirDiff, FileEntry, ListOptions, PluginSyncReport, RoleEntry, TargetCopyResult } from '@/types'
 import { invoke } from '@tauri-apps/api/core'
 
 export const keyboardService = {
   /**
    * List directory contents for keyboard config selection
    */
-  async listDirectoryContents(path: string): Promise<FileEntry[]> {
-    return invoke<FileEntry[]>('list_directory_contents', { path })
+  async listDirectoryContents(path: string, options?: ListOptions): Promise<FileEntry[]> {
+    return invoke<FileEntry[]>('list_directory_contents', { path, options: options || null })
+  },
+
+  /**
+   * Flat list of every role directory under `path` (same options as listDirectoryContents)
+   */
+  async listRoles(path: string, options?: ListOptions): Promise<RoleEntry[]> {
+    return invoke<RoleEntry[]>('list_roles', { path, options: options || null })
+  },
+
+  /**
+   * Candidate userdata dirs of local JX3 installs (empty when none are found,
+   * and always empty outside Windows)
+   */
+  async detectKeybindDirs(): Promise<string[]> {
+    return invoke<string[]>('detect_jx3_keybind_dirs')
+  },
+
+  /**
+   * Dry-run preview of a copy: what will be copied and what it replaces
+   */
+  async previewCopy(params: CopyParams): Promise<CopyPreview> {
+    return invoke<CopyPreview>('preview_copy', { params })
+  },
+
+  /**
+   * File-level diff of two dirs: files only in a, only in b, and differing
+   */
+  async diffKeyboardDirs(a: string, b: string): Promise<DirDiff> {
+    return invoke<DirDiff>('diff_keyboard_dirs', { a, b })
   },
 
   /**
    * Copy keyboard config from source to target directory
+   * (listen to `keyboard://copy-progress` for per-file progress).
+   * Resolves with the mode that ran and the backup of the previous target
+   */
+  async copySourceToTarget(params: CopyParams): Promise<CopyOutcome> {
+    return invoke<CopyOutcome>('cp_source_to_target', { params })
+  },
+
+  /**
+   * Copy one source into several targets (e.g. multiple game installs).
+   * Resolves with one result per attempted target; with `stopOnError` the run
+   * ends at the first failure and later targets are omitted; `verify`
+   * hash-checks each copy before it replaces the target
+   */
+  async copySourceToTargets(sourcePath: string, targetPaths: string[], stopOnError = false, verify = false): Promise<TargetCopyResult[]> {
+    return invoke<TargetCopyResult[]>('cp_source_to_targets', { sourcePath, targetPaths, stopOnError, verify })
+  },
+
+  /**
+   * Copy one role into the same account/region/server/role location under
+   * `targetBase` (listen to `keyboard://copy-progress`); resolves with the target role path
+   */
+  async copyRole(sourceRolePath: string, targetBase: string, verify = false): Promise<string> {
+    return invoke<string>('copy_role', { sourceRolePath, targetBase, verify })
+  },
+
+  /**
+   * Copy only the selected subtrees (entry ids from listDirectoryContents on the
+   * same source root, with the same options); resolves with the copied relative paths
+   */
+  async copySelectedEntries(params: CopyParams, selectedIds: number[], options?: ListOptions): Promise<string[]> {
+    return invoke<string[]>('copy_selected_entries', { params, selectedIds, options: options || null })
+  },
+
+  /**
+   * List backups kept by keyboard copies for a target role, newest first
+   */
+  async listKeyboardBackups(targetPath: string): Promise<string[]> {
+    return invoke<string[]>('list_keyboard_backups', { targetPath })
+  },
+
+  /**
+   * Roll a role's keybinds back to a backup (current content is backed up first)
+   */
+  async restoreKeyboardBackup(path: string): Promise<void> {
+    return invoke<void>('restore_keyboard_backup', { path })
+  },
+
+  /**
+   * Zip a keyboard config dir for sharing (symlinks and userpreferences are
+   * left out); resolves with the number of files written
+   */
+  async exportKeyboardZip(source: string, out: string): Promise<number> {
+    return invoke<number>('export_keyboard_zip', { source, out })
+  },
+
+  /**
+   * Extract a shared keyboard zip into a role dir, keeping the old content as a
+   * backup; unsafe or oversized archives are rejected. Resolves with the file count
    */
-  async copySourceToTarget(params: CopyParams): Promise<boolean> {
-    return invoke<boolean>('cp_source_to_target', { params })
+  async importKeyboardZip(archive: string, target: string): Promise<number> {
+    return invoke<number>('import_keyboard_zip', { archive, target })
   },
 
   /**
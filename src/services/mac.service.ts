@@ -1,26 +1,88 @@
-import type { MacInfo } from '@/types/mac'
+import type { AdapterInfo, MacChangeResult, MacDisplayOptions, MacHistoryEntry, MacInfo, MacStatus, RestoreTrigger } from '@/types/mac'
 import { invoke } from '@tauri-apps/api/core'
 
 export const macService = {
   /**
-   * Get the primary adapter's MAC info
+   * Get the selected (or auto-picked) adapter's MAC info
    */
-  async getMacInfo(): Promise<MacInfo> {
-    return invoke<MacInfo>('get_mac_info')
+  async getMacInfo(options: MacDisplayOptions = {}): Promise<MacInfo> {
+    return invoke<MacInfo>('get_mac_info', { ...options })
   },
 
   /**
-   * Change MAC address to a random value; resolves with the verified actual state
+   * Current vs. original MAC of the selected adapter (is a spoof active?)
    */
-  async randomizeMacAddress(): Promise<MacInfo> {
-    return invoke<MacInfo>('randomize_mac_address')
+  async getMacStatus(options: MacDisplayOptions = {}): Promise<MacStatus> {
+    return invoke<MacStatus>('get_mac_status', { ...options })
   },
 
   /**
-   * Restore the original MAC address; resolves with the verified actual state
+   * List every physical adapter, flagging the persisted selection
    */
-  async restoreMacAddress(): Promise<MacInfo> {
-    return invoke<MacInfo>('restore_mac_cmd')
+  async listNetworkAdapters(): Promise<AdapterInfo[]> {
+    return invoke<AdapterInfo[]>('list_network_adapters')
+  },
+
+  /**
+   * Persist the adapter to operate on (null = back to auto-pick)
+   */
+  async selectNetworkAdapter(adapterGuid: string | null): Promise<string | null> {
+    return invoke<string | null>('select_network_adapter', { adapterGuid })
+  },
+
+  /**
+   * Change MAC address to a random value; resolves with the verified actual state.
+   * An explicit adapter GUID becomes the persisted selection. softApply skips
+   * the adapter restart (the result then carries the MAC as pending).
+   */
+  async randomizeMacAddress(adapterGuid?: string, softApply = false): Promise<MacChangeResult> {
+    return invoke<MacChangeResult>('randomize_mac_address', { adapterGuid: adapterGuid || null, softApply })
+  },
+
+  /**
+   * Restore the original MAC address (only the given adapter, else all adapters);
+   * resolves with the verified actual state
+   */
+  async restoreMacAddress(adapterGuid?: string): Promise<MacInfo> {
+    return invoke<MacInfo>('restore_mac_cmd', { adapterGuid: adapterGuid || null })
+  },
+
+  /**
+   * Restart the given adapter (else the selection) in stages; listen to
+   * `mac://adapter-restart` for progress. Rejects unless the adapter is back Up
+   */
+  async restartNetworkAdapter(adapterGuid?: string): Promise<MacInfo> {
+    return invoke<MacInfo>('restart_network_adapter', { adapterGuid: adapterGuid || null })
+  },
+
+  /**
+   * History of applied MACs, most recent first
+   */
+  async getMacHistory(): Promise<MacHistoryEntry[]> {
+    return invoke<MacHistoryEntry[]>('get_mac_history')
+  },
+
+  /**
+   * Re-apply a MAC from the history (0 = most recent); resolves with the verified
+   * actual state, or the pending MAC when softApply
+   */
+  async applyMacFromHistory(index: number, softApply = false): Promise<MacChangeResult> {
+    return invoke<MacChangeResult>('apply_mac_from_history', { index, softApply })
+  },
+
+  /**
+   * Generate a random MAC for preview (optionally keeping a vendor OUI prefix)
+   */
+  async generateRandomMac(oui?: string): Promise<string> {
+    return invoke<string>('generate_random_mac_cmd', { oui: oui || null })
+  },
+
+  /**
+   * Validate a typed MAC without touching any adapter; resolves to the
+   * canonical XX:XX:XX:XX:XX:XX form, rejects with the precise reason
+   */
+  async validateMac(mac: string): Promise<string> {
+    return invoke<string>('validate_mac', { mac })
   },
 
   /**
@@ -31,9 +93,31 @@ export const macService = {
   },
 
   /**
-   * Set auto-restore on reboot setting
+   * Re-check the auto-restore task against the current executable (repairs drift)
+   */
+  async verifyAutoRestoreTask(): Promise<boolean> {
+    return invoke<boolean>('verify_auto_restore_task')
+  },
+
+  /**
+   * Persisted trigger of the auto-restore task (logon or startup)
+   */
+  async getAutoRestoreTrigger(): Promise<RestoreTrigger> {
+    return invoke<RestoreTrigger>('get_auto_restore_trigger')
+  },
+
+  /**
+   * Set auto-restore on reboot setting; trigger picks logon vs. startup and is
+   * remembered (omitted keeps the previous choice)
+   */
+  async setAutoRestoreSetting(autoRestore: boolean, trigger?: RestoreTrigger): Promise<void> {
+    return invoke<void>('set_auto_restore_setting', { autoRestore, trigger: trigger ?? null })
+  },
+  /**
+   * Re-launch the app elevated (UAC) after a permission-denied MAC failure;
+   * this instance exits on success. Resolves false off Windows (no-op)
    */
-  async setAutoRestoreSetting(autoRestore: boolean): Promise<void> {
-    return invoke<void>('set_auto_restore_setting', { autoRestore })
+  async relaunchAsAdmin(): Promise<boolean> {
+    return invoke<boolean>('relaunch_as_admin')
   },
 }
@@ -0,0 +1,81 @@
+import type { AppSettings, CleanupReport, Diagnostics, LogLevel, ResetSummary } from '@/types'
+import { invoke } from '@tauri-apps/api/core'
+
+export const settingsService = {
+  /**
+   * Load app settings (defaults when never saved)
+   */
+  async getSettings(): Promise<AppSettings> {
+    return invoke<AppSettings>('get_settings')
+  },
+
+  /**
+   * Validate and persist app settings
+   */
+  async saveSettings(settings: AppSettings): Promise<void> {
+    return invoke<void>('save_settings', { settings })
+  },
+
+  /**
+   * Change the log level immediately and persist it for the next launch
+   */
+  async setLogLevel(level: LogLevel): Promise<void> {
+    return invoke<void>('set_log_level', { level })
+  },
+
+  /**
+   * Directory holding every config file (JX3_TOOLS_CONFIG_DIR overrides it),
+   * for finding/backing up settings
+   */
+  async getConfigDir(): Promise<string> {
+    return invoke<string>('get_config_dir')
+  },
+
+  /**
+   * Environment readiness report for bug reports (OS, elevation, driver,
+   * config dir, adapter, hotkey config); failed sub-checks carry their own error
+   */
+  async diagnostics(): Promise<Diagnostics> {
+    return invoke<Diagnostics>('diagnostics')
+  },
+
+  /**
+   * Whether the app runs elevated (admin/root); true where it can't be told.
+   * Lets the UI prompt a restart as admin before MAC/driver operations
+   */
+  async isElevated(): Promise<boolean> {
+    return invoke<boolean>('is_elevated')
+  },
+
+  /**
+   * Directory the app log is written to (shown in the UI for bug reports)
+   */
+  async getLogDirPath(): Promise<string> {
+    return invoke<string>('get_log_dir_path')
+  },
+
+  /**
+   * Open the app log directory in the system file manager
+   */
+  async openLogDir(): Promise<void> {
+    return invoke<void>('open_log_dir')
+  },
+
+  /**
+   * Reset every config file to defaults (stops the hotkey task, removes the
+   * auto-restore task); safe to call repeatedly
+   */
+  async resetAllConfig(): Promise<ResetSummary> {
+    return invoke<ResetSummary>('reset_all_config')
+  },
+
+  /**
+   * Delete old hotkey session logs, keyboard `.bak-*` backup dirs under
+   * keyboardBasePath (the userdata dir; omit to skip them) and corrupt-config
+   * backups, keeping the newest ones (backups per backed-up directory);
+   * maxAgeDays 0 = no age limit. Refused while the hotkey task runs
+   */
+  async cleanupAppData(keepSessions: number, keepBackups: number, maxAgeDays: number, keyboardBasePath?: string): Promise<CleanupReport> {
+    return invoke<CleanupReport>('cleanup_app_data', { keyboardBasePath: keyboardBasePath || null, keepSessions, keepBackups, maxAgeDays })
+  },
+}
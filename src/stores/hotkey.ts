@@ -1,10 +1,18 @@
-import type { HotkeyConfig, HotkeyStatus } from '@/types'
+import type { HotkeyConfig, HotkeyStatus, ListenerHealth, RunHeartbeat, RunStarted, RunStopped, SavedConfigInfo } from '@/types'
 import { listen } from '@tauri-apps/api/event'
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
 import { hotkeyService } from '@/services'
 
 const STATUS_EVENT = 'hotkey://status'
+const STARTED_EVENT = 'hotkey://started'
+const STOPPED_EVENT = 'hotkey://stopped'
+const HEARTBEAT_EVENT = 'hotkey://heartbeat'
+
+/** 最近一次启停边沿（status 事件只反映状态，提示类反应看这里） */
+export type RunTransition =
+  | { kind: 'started', payload: RunStarted }
+  | { kind: 'stopped', payload: RunStopped }
 
 export const useHotkeyStore = defineStore('hotkey', () => {
   const config = ref<HotkeyConfig | null>(null)
@@ -15,7 +23,13 @@ export const useHotkeyStore = defineStore('hotkey', () => {
     driverReady: false,
     driverState: 'notInstalled',
     mouseFilterPresent: false,
+    backend: null,
+    listenerEnabled: true,
   })
+  const listenerHealth = ref<ListenerHealth | null>(null)
+  const lastTransition = ref<RunTransition | null>(null)
+  /** 本次运行最近一次心跳；未运行时为 null */
+  const heartbeat = ref<RunHeartbeat | null>(null)
   const loading = ref(false)
   const saving = ref(false)
   const driverBusy = ref(false)
@@ -25,9 +39,23 @@ export const useHotkeyStore = defineStore('hotkey', () => {
     if (stopListener)
       return
 
-    stopListener = await listen<HotkeyStatus>(STATUS_EVENT, (event) => {
-      status.value = event.payload
-    })
+    const unlisteners = await Promise.all([
+      listen<HotkeyStatus>(STATUS_EVENT, (event) => {
+        status.value = event.payload
+      }),
+      listen<RunStarted>(STARTED_EVENT, (event) => {
+        heartbeat.value = null
+        lastTransition.value = { kind: 'started', payload: event.payload }
+      }),
+      listen<RunStopped>(STOPPED_EVENT, (event) => {
+        heartbeat.value = null
+        lastTransition.value = { kind: 'stopped', payload: event.payload }
+      }),
+      listen<RunHeartbeat>(HEARTBEAT_EVENT, (event) => {
+        heartbeat.value = event.payload
+      }),
+    ])
+    stopListener = () => unlisteners.forEach(unlisten => unlisten())
   }
 
   async function disposeListener() {
@@ -45,19 +73,49 @@ export const useHotkeyStore = defineStore('hotkey', () => {
     } finally {
       loading.value = false
     }
+    await checkListenerHealth()
+  }
+
+  async function checkListenerHealth() {
+    listenerHealth.value = await hotkeyService.getListenerHealth()
+  }
+
+  async function saveConfig(next: HotkeyConfig): Promise<SavedConfigInfo> {
+    saving.value = true
+    try {
+      const info = await hotkeyService.saveConfig(next)
+      config.value = info.config
+      return info
+    } finally {
+      saving.value = false
+      void checkListenerHealth()
+    }
   }
 
-  async function saveConfig(next: HotkeyConfig) {
+  async function reregister() {
     saving.value = true
     try {
-      config.value = await hotkeyService.saveConfig(next)
+      status.value = await hotkeyService.reregisterHotkeys()
     } finally {
       saving.value = false
+      void checkListenerHealth()
     }
   }
 
+  async function setListenerEnabled(enabled: boolean) {
+    status.value = await hotkeyService.setListenerEnabled(enabled)
+  }
+
+  async function clearError() {
+    status.value = await hotkeyService.clearError()
+  }
+
+  async function startTask() {
+    return hotkeyService.startTask()
+  }
+
   async function stopTask() {
-    await hotkeyService.stopTask()
+    return hotkeyService.stopTask()
   }
 
   async function installDriver() {
@@ -95,12 +153,20 @@ export const useHotkeyStore = defineStore('hotkey', () => {
   return {
     config,
     status,
+    listenerHealth,
+    lastTransition,
+    heartbeat,
     loading,
     saving,
     driverBusy,
     init,
     fetchConfig,
+    checkListenerHealth,
     saveConfig,
+    reregister,
+    setListenerEnabled,
+    clearError,
+    startTask,
     stopTask,
     installDriver,
     uninstallDriver,
@@ -1,6 +1,23 @@
 /** 按键发送模式 */
 export type KeyMode = 'global' | 'window'
 
+/** 全局模式下按键的注入方式：auto = 驱动就绪用 Interception 否则 SendInput */
+export type SendBackend = 'auto' | 'interception' | 'sendInput'
+
+/** 鼠标按键 */
+export type MouseButton = 'left' | 'right' | 'middle'
+
+/** 每次触发执行的动作：按触发按键、鼠标点击或回放按键宏（后两者忽略触发按键） */
+export type TriggerAction = { type: 'key' } | { type: 'mouseClick', button: MouseButton } | { type: 'macro', name: string }
+
+/** 已保存的按键宏（list_macros） */
+export interface MacroInfo {
+  name: string
+  eventCount: number
+  /** 第一个到最后一个按键的时长（毫秒） */
+  durationMs: number
+}
+
 /** 目标窗口信息 */
 export interface TargetWindow {
   hwnd: number
@@ -15,17 +32,133 @@ export interface WindowInfo {
   title: string
   className: string
   processName: string
+  /** 所属进程 ID（区分多开） */
+  pid: number
   displayName: string
 }
 
+/** 正在运行的进程（list_game_processes） */
+export interface ProcessInfo {
+  pid: number
+  /** 可执行文件名，如 JX3ClientX64.exe */
+  name: string
+  /** 完整路径；权限不足时为空 */
+  path: string
+}
+
+/** 窗口列表过滤条件（只给 keyword 时为子串过滤） */
+export interface WindowFilter {
+  /** 不区分大小写，匹配标题 / 进程名 / 类名 */
+  keyword?: string
+  /** keyword 只匹配进程名 */
+  processOnly?: boolean
+  /** 缺省为 true */
+  visibleOnly?: boolean
+  /** 对标题做不区分大小写的正则匹配 */
+  regex?: string
+}
+
+/** 连发节奏：连按 count 次（每次相隔 burstGapMs），再停 pauseMs，循环 */
+export interface BurstConfig {
+  /** 至少 1 */
+  count: number
+  /** 20-60000 毫秒 */
+  burstGapMs: number
+  /** 20-60000 毫秒 */
+  pauseMs: number
+}
+
 /** Hotkey configuration stored in backend */
 export interface HotkeyConfig {
   triggerKey: string
+  /** 缺省为按键 */
+  triggerAction?: TriggerAction
   intervalMs: number
   startHotkey: string
   stopHotkey: string
+  /** 紧急停止热键：直接打断任务，不等待其退出；默认 Ctrl+Alt+Shift+Esc */
+  emergencyStopHotkey?: string
+  keyMode: KeyMode
+  /** 窗口模式的目标窗口；每次触发依次发给每一个（多开） */
+  targetWindows: TargetWindow[]
+  /** 最长运行时长（秒），到点自动停止；null = 不限制 */
+  maxDurationSecs?: number | null
+  /** 每次触发按住的时长（毫秒），须小于触发周期（连发时为连发间隔/停顿）；0 = 普通点按 */
+  holdMs?: number
+  /** 普通点按时按下与释放的间隔（毫秒），默认 10，须小于触发周期 */
+  pressGapMs?: number
+  /** 是否把每次发送写入会话日志（sessions/*.jsonl） */
+  sessionLogging?: boolean
+  /** 窗口模式下每次发送前把目标窗口切到前台 */
+  focusBeforeSend?: boolean
+  /** 窗口模式启动时目标窗口已失效则本次改用全局模式（配置不变）；默认关闭 = 报错 */
+  fallbackToGlobal?: boolean
+  /** 全局模式下仅当前台窗口属于该进程时发送（如 JX3ClientX64.exe）；null = 不限制，仅 Windows */
+  restrictToProcess?: string | null
+  /** 游戏（限定进程，未设置时为目标窗口进程）离开前台时暂停，超过 5 秒未切回自动停止；仅 Windows */
+  stopOnFocusLoss?: boolean
+  /** 全局模式下整个运行期间按住的修饰键（Ctrl/Shift/Alt），停止时释放；null = 不按住 */
+  holdModifier?: string | null
+  /** 按下开始热键后第一次发送前的等待（毫秒），0-10000，默认 0 */
+  startDelayMs?: number
+  /** 连发模式，设置后忽略 intervalMs；null = 按 intervalMs 均匀触发 */
+  burst?: BurstConfig | null
+  /** 每次触发被跳过的概率（0-1），跳过时照常等待；默认 0 */
+  skipChance?: number
+  /** 全局模式只向该序号（0-9）的 Interception 键盘设备注入；null = 全部设备，仅 Windows */
+  deviceFilter?: number | null
+  /** 全局模式下按键的注入方式；默认 interception（驱动未就绪时报错） */
+  sendBackend?: SendBackend
+}
+
+/** 触发按键选择器的一个分组（supported_trigger_keys） */
+export interface KeyGroup {
+  /** letters / digits / function / numpad / special / custom */
+  id: string
+  name: string
+  keys: string[]
+  /** 分组提示，如 F13–F24 需键盘支持 */
+  note?: string
+}
+
+/** hotkey://started 载荷：每次任务开始只发一次 */
+export interface RunStarted {
+  triggerKey: string
+  triggerAction: TriggerAction
   keyMode: KeyMode
-  targetWindow: TargetWindow | null
+}
+
+/** hotkey://stopped 载荷：每次任务结束只发一次 */
+export interface RunStopped {
+  /** 自动停止/出错的原因；手动停止为 null */
+  reason: string | null
+}
+
+/** hotkey://heartbeat 载荷：运行中约每秒一次；running 仍为 true 却不再收到说明任务卡住 */
+export interface RunHeartbeat {
+  /** 自任务开始（含预热）经过的毫秒数 */
+  elapsedMs: number
+  /** 已成功发送的次数（窗口模式每个窗口各计一次） */
+  sentCount: number
+}
+
+/** 保存配置的回执：各按键标签的解析结果 */
+export interface SavedConfigInfo {
+  config: HotkeyConfig
+  /** 触发按键扫描码；鼠标点击为 null */
+  triggerScancode: number | null
+  /** 开始/结束热键主键的扫描码 */
+  startScancode: number | null
+  stopScancode: number | null
+  /** 实际发送方式，如 Interception / PostMessage */
+  backend: string
+}
+
+/** 会话日志文件 */
+export interface SessionLogInfo {
+  fileName: string
+  path: string
+  bytes: number
 }
 
 /** 按键驱动安装状态 */
@@ -42,4 +175,38 @@ export interface HotkeyStatus {
   driverState: DriverState
   /** 是否残留 interception 鼠标过滤器（旧版安装包遗留，需清理） */
   mouseFilterPresent: boolean
+  /** 最近一次启动实际使用的发送方式；未启动过为 null */
+  backend: string | null
+  /** 热键捕获是否开启；暂停时开始/结束键照常输入，紧急停止仍有效 */
+  listenerEnabled: boolean
+}
+
+/** 热键监听健康度 */
+export interface ListenerHealth {
+  /** 上次注册是否成功 */
+  registered: boolean
+  /** 已注册的开始/结束热键是否仍在生效 */
+  shortcutsAlive: boolean
+}
+
+/** 自上次注册以来的热键按下计数（get_listener_stats，诊断用） */
+export interface ListenerStats {
+  /** 收到的按下总数（含被防抖丢弃的） */
+  received: number
+  /** 防抖窗口内被丢弃的 */
+  debounced: number
+  startHits: number
+  stopHits: number
+  emergencyHits: number
+}
+
+/** 启停请求的结果，区分真正启停与空操作（start/stop_hotkey_task） */
+export type RunnerTransition = 'started' | 'alreadyRunning' | 'stopped' | 'notRunning'
+
+/** 一个已打开的 Interception 键盘设备（list_interception_devices） */
+export interface InterceptionDevice {
+  /** 设备序号 0-9，即 deviceFilter 的取值 */
+  index: number
+  /** 该槽位上真实键盘的硬件 ID；null = 空槽位 */
+  hardwareId: string | null
 }
@@ -3,3 +3,4 @@ export * from './common'
 export * from './hotkey'
 export * from './keyboard'
 export * from './mac'
+export * from './settings'
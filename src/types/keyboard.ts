@@ -2,15 +2,91 @@
 export interface FileEntry {
   id: number
   name: string
+  /** Name wasn't valid UTF-8 and is shown with replacement characters */
+  lossy_name: boolean
   is_dir: boolean
   selected: boolean
   children?: FileEntry[]
 }
 
+/** Directory tree options for list_directory_contents (omitted = current layout) */
+export interface ListOptions {
+  /** Depth of the role directories (1-based), default 4 */
+  maxDepth?: number
+  /** Directory names to skip, default ['userpreferences'] */
+  skipNames?: string[]
+}
+
+/** One role directory, flattened from the tree by list_roles */
+export interface RoleEntry {
+  /** Same id as the role's FileEntry in listDirectoryContents */
+  id: number
+  name: string
+  /** Full path, usable as the copyRole source */
+  path: string
+  /** Path relative to the listed root, '/'-separated (account/region/server/role) */
+  relativePath: string
+}
+
 /** Parameters for copying keyboard config */
 export interface CopyParams {
   source_path: string
   target_path: string
+  /** Hash-compare every copied file before it replaces the target (default false) */
+  verify?: boolean
+  /** replace (default) swaps the whole target; merge overlays the source onto it, keeping target-only files */
+  mode?: CopyMode
+}
+
+export type CopyMode = 'replace' | 'merge'
+
+/** Result of cp_source_to_target */
+export interface CopyOutcome {
+  /** The mode that actually ran */
+  mode: CopyMode
+  /** Where the previous target was backed up; null when the target did not exist */
+  backupPath: string | null
+  /** What was copied from the source (merge excludes files already in the target) */
+  copied: DirStats
+}
+
+/** Per-target outcome of cp_source_to_targets */
+export interface TargetCopyResult {
+  targetPath: string
+  /** Failure reason; null when this target was copied */
+  error: string | null
+}
+
+/** Directory tree counts (root excluded, symlinks skipped and only counted) */
+export interface DirStats {
+  files: number
+  dirs: number
+  bytes: number
+  skippedSymlinks: number
+}
+
+/** Dry-run result of a keyboard copy (preview_copy) */
+export interface CopyPreview {
+  source: DirStats
+  targetExists: boolean
+  /** Current target content that the copy would replace; null if absent */
+  target: DirStats | null
+}
+
+/** File-level diff of two keyboard config dirs (diff_keyboard_dirs); paths are relative, `/`-separated */
+export interface DirDiff {
+  onlyInA: string[]
+  onlyInB: string[]
+  /** Present on both sides with different size or content */
+  different: string[]
+}
+
+/** 键位复制进度（keyboard://copy-progress 事件载荷） */
+export interface CopyProgress {
+  copied: number
+  total: number
+  /** 相对源目录的路径 */
+  currentFile: string
 }
 
 /** User's selection state for keyboard config copy */
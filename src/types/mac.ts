@@ -1,7 +1,79 @@
+/** Separator style of returned MACs: XX:XX…, XX-XX…, or XXXXXXXXXXXX */
+export type MacFormat = 'colon' | 'dash' | 'bare'
+
+/** Hex digit case of returned MACs */
+export type MacCase = 'upper' | 'lower'
+
+/** Output style for getMacInfo/getMacStatus; defaults to colon + upper */
+export interface MacDisplayOptions {
+  format?: MacFormat
+  case?: MacCase
+}
+
 /** Adapter MAC info reported by the backend (verified actual state) */
 export interface MacInfo {
   adapterName: string
+  /** InterfaceGuid of the reported adapter (empty when unknown) */
+  adapterGuid: string
   currentMac: string
   permanentMac: string
   isModified: boolean
 }
+
+/**
+ * Result of a MAC change. With soft apply the adapter isn't restarted: the
+ * MacInfo fields are the unchanged current state and pendingMac takes effect
+ * after a reconnect or reboot
+ */
+export interface MacChangeResult extends MacInfo {
+  /** Whether the adapter was restarted and the new MAC verified just now */
+  appliedNow: boolean
+  /** MAC waiting for a reconnect/reboot; null once applied */
+  pendingMac: string | null
+}
+
+/** When the auto-restore task runs: at user logon (default) or at system startup */
+export type RestoreTrigger = 'onLogon' | 'onStart'
+
+/** Stage of an adapter restart, in order; ends with up or failed */
+export type AdapterRestartStage = 'disabling' | 'waiting' | 'enabling' | 'up' | 'failed'
+
+/** Payload of the mac://adapter-restart event */
+export interface AdapterRestartProgress {
+  stage: AdapterRestartStage
+  /** Why the restart failed; only with failed */
+  error?: string
+}
+
+/** Spoof status summary: current vs. burned-in address */
+export interface MacStatus {
+  current: string
+  /** Registry override present, or the driver reports a non-permanent MAC */
+  isSpoofed: boolean
+  /** Permanent (hardware) address; null when the driver doesn't report one */
+  original: string | null
+}
+
+/** Physical network adapter (adapter picker) */
+export interface AdapterInfo {
+  guid: string
+  name: string
+  description: string
+  status: string
+  currentMac: string
+  permanentMac: string
+  /** Whether this adapter is the persisted selection */
+  selected: boolean
+  /** Whether a spoofed MAC is in effect on this adapter */
+  isSpoofed: boolean
+  /** The spoofed MAC (XX:XX:XX:XX:XX:XX); null when not spoofed */
+  spoofedMac: string | null
+}
+
+/** Previously applied MAC (most recent first) */
+export interface MacHistoryEntry {
+  mac: string
+  /** Unix seconds */
+  timestamp: number
+  adapterGuid?: string | null
+}
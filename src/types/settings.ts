@@ -0,0 +1,61 @@
+import type { HotkeyConfig, HotkeyStatus } from './hotkey'
+import type { MacInfo } from './mac'
+
+/** 界面主题（与 useTheme 的 ThemeMode 一致） */
+export type Theme = 'system' | 'light' | 'dark'
+
+/** 日志级别（运行时可调，见 settingsService.setLogLevel） */
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace'
+
+/** 应用级偏好设置（settings.json） */
+export interface AppSettings {
+  /** BCP 47 语言标记，如 zh-CN */
+  language: string
+  startMinimized: boolean
+  closeToTray: boolean
+  theme: Theme
+  /** 缺省 info */
+  logLevel: LogLevel
+  /** 热键任务检查停止请求的间隔（毫秒，10-1000，默认 50）；越小停得越快、CPU 占用越高 */
+  stopPollMs: number
+}
+
+/** reset_all_config 的结果：实际删除了什么 */
+export interface ResetSummary {
+  /** 被删除的文件名（本来就不存在的不列出） */
+  removedFiles: string[]
+  /** 是否删除了开机自动还原计划任务 */
+  autoRestoreTaskRemoved: boolean
+}
+
+/** cleanup_app_data 的结果 */
+export interface CleanupReport {
+  removedSessions: number
+  /** 删除的键位目录备份与损坏配置备份 */
+  removedBackups: number
+  /** 删除文件的总字节数（备份目录按其中全部文件计） */
+  bytesReclaimed: number
+}
+
+/** diagnostics 单项检查：成功带 value，失败带 error（单项失败不影响整体） */
+export interface DiagnosticCheck<T> {
+  ok: boolean
+  value?: T
+  error?: string
+}
+
+/** diagnostics 环境自检报告，反馈问题时整体复制 */
+export interface Diagnostics {
+  appVersion: string
+  os: string
+  arch: string
+  /** 是否以管理员/root 运行；无法判断时为 null */
+  elevated: boolean | null
+  /** 含按键驱动是否就绪（driverReady / driverState） */
+  hotkeyStatus: HotkeyStatus
+  /** 配置目录路径；不可写时为错误（信息含路径） */
+  configDir: DiagnosticCheck<string>
+  /** 当前网卡的 MAC 概况 */
+  adapter: DiagnosticCheck<MacInfo>
+  hotkeyConfig: HotkeyConfig
+}
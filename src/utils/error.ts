@@ -13,16 +13,27 @@ export interface AppError {
  */
 export const ErrorCodes = {
   UNKNOWN: 'UNKNOWN',
+  MESSAGE: 'MESSAGE',
   IO_ERROR: 'IO_ERROR',
+  JSON_ERROR: 'JSON_ERROR',
   COMMAND_ERROR: 'COMMAND_ERROR',
   HOTKEY_ERROR: 'HOTKEY_ERROR',
+  CONFIG_ERROR: 'CONFIG_ERROR',
+  KEYBOARD_ERROR: 'KEYBOARD_ERROR',
+  PLUGIN_ERROR: 'PLUGIN_ERROR',
+  CLOUD_ERROR: 'CLOUD_ERROR',
   VALIDATION_ERROR: 'VALIDATION_ERROR',
   PLATFORM_NOT_SUPPORTED: 'PLATFORM_NOT_SUPPORTED',
+  PERMISSION_DENIED: 'PERMISSION_DENIED',
+  DRIVER_MISSING: 'DRIVER_MISSING',
   NETWORK_ERROR: 'NETWORK_ERROR',
 } as const
 
 export type ErrorCode = typeof ErrorCodes[keyof typeof ErrorCodes]
 
+// 个别消息（如 DRIVER_MISSING）自带 `[CODE] ` 前缀
+const CODE_PREFIX = /^\[([A-Z_]+)\]\s*/
+
 /**
  * Parse unknown error into structured AppError
  */
@@ -32,6 +43,10 @@ export function parseError(error: unknown): AppError {
   }
 
   if (typeof error === 'string') {
+    // Plain strings come from status fields (e.g. lastError), not from invoke
+    const match = CODE_PREFIX.exec(error)
+    if (match)
+      return { code: match[1], message: error.slice(match[0].length) }
     return { code: ErrorCodes.UNKNOWN, message: error }
   }
 
@@ -42,11 +57,11 @@ export function parseError(error: unknown): AppError {
   if (typeof error === 'object') {
     const obj = error as Record<string, unknown>
 
-    // Handle Tauri IPC errors
+    // Commands reject with `{ code, message }`
     if ('message' in obj && typeof obj.message === 'string') {
       return {
         code: (obj.code as string) || ErrorCodes.UNKNOWN,
-        message: obj.message,
+        message: obj.message.replace(CODE_PREFIX, ''),
         details: obj.details as Record<string, unknown> | undefined,
       }
     }